@@ -8,19 +8,23 @@
 pub mod commandline;
 
 use self::commandline::CommandLine;
-use config_crate::{Config, Environment, File};
+use crate::stopwatch::TimingFormat;
+use config_crate::{Config, Environment, File, FileFormat};
 use serde::Deserialize;
-use std::{collections::HashSet, env, path::PathBuf, sync::RwLock};
+use std::{collections::HashSet, env, path::PathBuf, str::FromStr, sync::RwLock};
+use viper::VerificationBackend;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Optimizations {
     pub inline_constant_functions: bool,
+    pub specialize_functions: bool,
     pub delete_unused_predicates: bool,
     pub optimize_folding: bool,
     pub remove_empty_if: bool,
     pub purify_vars: bool,
     pub fix_quantifiers: bool,
     pub fix_unfoldings: bool,
+    pub fold_constants: bool,
     pub remove_unused_vars: bool,
     pub remove_trivial_assertions: bool,
     pub clean_cfg: bool,
@@ -30,12 +34,14 @@ impl Optimizations {
     fn all_disabled() -> Self {
         Optimizations {
             inline_constant_functions: false,
+            specialize_functions: false,
             delete_unused_predicates: false,
             optimize_folding: false,
             remove_empty_if: false,
             purify_vars: false,
             fix_quantifiers: false,
             fix_unfoldings: false,
+            fold_constants: false,
             remove_unused_vars: false,
             remove_trivial_assertions: false,
             clean_cfg: false,
@@ -45,6 +51,8 @@ impl Optimizations {
     fn all_enabled() -> Self {
         Optimizations {
             inline_constant_functions: true,
+            // Disabled by default, because the pass is experimental.
+            specialize_functions: false,
             delete_unused_predicates: true,
             optimize_folding: true,
             remove_empty_if: true,
@@ -52,6 +60,8 @@ impl Optimizations {
             fix_quantifiers: true,
             // Disabled because https://github.com/viperproject/prusti-dev/issues/892 has been fixed
             fix_unfoldings: false,
+            // Disabled by default, because it is not needed by most programs.
+            fold_constants: false,
             remove_unused_vars: true,
             remove_trivial_assertions: true,
             clean_cfg: true,
@@ -70,6 +80,8 @@ lazy_static! {
         settings.set_default("check_foldunfold_state", false).unwrap();
         settings.set_default("check_overflows", true).unwrap();
         settings.set_default("check_panics", true).unwrap();
+        settings.set_default("check_unchecked_arithmetic", true).unwrap();
+        settings.set_default("assume_proven_assertions", false).unwrap();
         settings.set_default("encode_unsigned_num_constraint", false).unwrap();
         settings.set_default("encode_bitvectors", false).unwrap();
         settings.set_default("simplify_encoding", true).unwrap();
@@ -82,19 +94,27 @@ lazy_static! {
         settings.set_default("dump_path_ctxt_in_debug_info", false).unwrap();
         settings.set_default("dump_reborrowing_dag_in_debug_info", false).unwrap();
         settings.set_default("dump_borrowck_info", false).unwrap();
+        settings.set_default("dump_call_graph", false).unwrap();
+        settings.set_default("dump_obligation_dependencies", false).unwrap();
+        settings.set_default("dump_optimization_changes", false).unwrap();
         settings.set_default("dump_viper_program", false).unwrap();
+        settings.set_default("annotate_viper_with_source", false).unwrap();
         settings.set_default("foldunfold_state_filter", "").unwrap();
         settings.set_default("contracts_lib", "").unwrap();
         settings.set_default::<Vec<String>>("extra_jvm_args", vec![]).unwrap();
         settings.set_default::<Vec<String>>("extra_verifier_args", vec![]).unwrap();
         settings.set_default("quiet", false).unwrap();
         settings.set_default("assert_timeout", 10_000).unwrap();
+        settings.set_default::<Option<i64>>("qi_limit", None).unwrap();
         settings.set_default("use_more_complete_exhale", true).unwrap();
         settings.set_default("skip_unsupported_features", false).unwrap();
         settings.set_default("internal_errors_as_warnings", false).unwrap();
+        settings.set_default("warnings_as_errors", false).unwrap();
         settings.set_default("allow_unreachable_unsupported_code", false).unwrap();
+        settings.set_default("stub_unencodable_std_functions", false).unwrap();
         settings.set_default("no_verify", false).unwrap();
         settings.set_default("no_verify_deps", false).unwrap();
+        settings.set_default("spec_lint", false).unwrap();
         settings.set_default("full_compilation", false).unwrap();
         settings.set_default("json_communication", false).unwrap();
         settings.set_default("optimizations", "all").unwrap();
@@ -109,9 +129,18 @@ lazy_static! {
         settings.set_default("print_typeckd_specs", false).unwrap();
         settings.set_default("print_collected_verification_items", false).unwrap();
         settings.set_default("hide_uuids", false).unwrap();
+        settings.set_default("timing_format", "exact").unwrap();
         settings.set_default("counterexample", false).unwrap();
+        settings.set_default("explain_errors", false).unwrap();
+        settings.set_default("split_conjunctive_assertions", false).unwrap();
+        settings.set_default("report_unused_preconditions", false).unwrap();
+        settings.set_default("report_uncovered_result_variants", false).unwrap();
+        settings.set_default("report_unsupported_features", false).unwrap();
+        settings.set_default("check_trusted_postconditions", false).unwrap();
+        settings.set_default("profile_backend", false).unwrap();
         settings.set_default("print_hash", false).unwrap();
         settings.set_default("enable_cache", true).unwrap();
+        settings.set_default("verification_batching", "none").unwrap();
         settings.set_default("enable_ghost_constraints", false).unwrap();
 
         // Flags for debugging Prusti that can change verification results.
@@ -126,6 +155,10 @@ lazy_static! {
         allowed_keys.insert("server_max_stored_verifiers".to_string());
         allowed_keys.insert("server_max_concurrency".to_string());
         allowed_keys.insert("server_address".to_string());
+        allowed_keys.insert("keep_viper_files".to_string());
+        allowed_keys.insert("save_verification_requests".to_string());
+        allowed_keys.insert("junit_report".to_string());
+        allowed_keys.insert("lsp_diagnostics".to_string());
         allowed_keys.insert("config".to_string());
         allowed_keys.insert("log".to_string());
         allowed_keys.insert("log_style".to_string());
@@ -195,6 +228,26 @@ pub fn dump() -> String {
     format!("{:#?}", SETTINGS.read().unwrap())
 }
 
+/// Serialize the settings as JSON, so that they can be applied again with
+/// `apply_saved_settings`, e.g. when replaying a saved verification request.
+pub fn serialize_settings() -> String {
+    let settings: serde_json::Value = SETTINGS.read().unwrap().clone().try_deserialize().unwrap();
+    settings.to_string()
+}
+
+/// Override the settings with ones serialized by `serialize_settings`. The
+/// environment variables and the command-line arguments still take precedence,
+/// so that the saved settings can be changed when they are applied.
+pub fn apply_saved_settings(json: &str) -> Result<(), String> {
+    let mut settings = SETTINGS.write().unwrap();
+    settings
+        .merge(File::from_str(json, FileFormat::Json))
+        .and_then(|settings| settings.merge(Environment::with_prefix("PRUSTI").ignore_empty(true)))
+        .and_then(|settings| settings.merge(CommandLine::with_prefix("-P").ignore_invalid(true)))
+        .map_err(|error| error.to_string())?;
+    Ok(())
+}
+
 fn read_optional_setting<T>(name: &'static str) -> Option<T>
 where
     T: Deserialize<'static>,
@@ -209,6 +262,26 @@ where
     read_optional_setting(name).unwrap_or_else(|| panic!("Failed to read setting {:?}", name))
 }
 
+/// Check the values of the settings that are not validated when they are
+/// read, returning a message for each invalid one.
+pub fn check_settings() -> Vec<String> {
+    let mut messages = vec![];
+    match read_optional_setting::<Option<i64>>("qi_limit") {
+        Some(None) => {}
+        Some(Some(value)) if value >= 0 => {}
+        _ => messages.push(
+            "invalid value of the QI_LIMIT flag, which must be a non-negative integer".to_string(),
+        ),
+    }
+    if let Err(error) = TimingFormat::from_str(&timing_format()) {
+        messages.push(error.to_string());
+    }
+    if let Err(error) = VerificationBackend::from_str(&viper_backend()) {
+        messages.push(error.to_string());
+    }
+    messages
+}
+
 // The following methods are all convenience wrappers for the actual call to
 // `read_setting` (+ optionally some light sanitisation or processing). Please
 // keep the documentation on each method in sync with `flags.md` in the dev
@@ -243,6 +316,13 @@ pub fn check_panics() -> bool {
     read_setting("check_panics")
 }
 
+/// When enabled, the condition of each `assert!` is assumed after the
+/// assertion has been proven, so that later obligations can use it without the
+/// backend deriving it again. This only has an effect when panics are checked.
+pub fn assume_proven_assertions() -> bool {
+    read_setting("assume_proven_assertions")
+}
+
 /// When enabled, the encoded program is simplified before it is passed to
 /// the Viper backend.
 pub fn simplify_encoding() -> bool {
@@ -287,11 +367,65 @@ pub fn dump_borrowck_info() -> bool {
     read_setting("dump_borrowck_info")
 }
 
+/// When enabled, the calls encoded for the verified functions, together with
+/// the specifications used to encode them, will be output as a DOT graph.
+pub fn dump_call_graph() -> bool {
+    read_setting("dump_call_graph")
+}
+
+/// When enabled, the dependencies between the obligations of each encoded
+/// method will be output as JSON, for schedulers that verify independent
+/// obligations concurrently.
+pub fn dump_obligation_dependencies() -> bool {
+    read_setting("dump_obligation_dependencies")
+}
+
+/// When enabled, the optimizations that changed each encoded method will be
+/// output, one line per method.
+pub fn dump_optimization_changes() -> bool {
+    read_setting("dump_optimization_changes")
+}
+
 /// When enabled, the encoded Viper program will be output.
 pub fn dump_viper_program() -> bool {
     read_setting("dump_viper_program")
 }
 
+/// When set to a directory, the Viper program of each verified function is
+/// kept there as `<program name>.vpr`, so that the backend can be run on it
+/// manually. The statements are annotated with the source location they come
+/// from, as with `ANNOTATE_VIPER_WITH_SOURCE`.
+pub fn keep_viper_files() -> Option<String> {
+    read_optional_setting("keep_viper_files")
+}
+
+/// When set to a directory, the verification request of each verified program
+/// is saved there, so that it can be verified again with
+/// `prusti-server-driver --replay <file>`.
+pub fn save_verification_requests() -> Option<String> {
+    read_optional_setting("save_verification_requests")
+}
+
+/// When set to a file path, the verification outcome of each function is
+/// written there as a JUnit XML report, with one test case per function.
+pub fn junit_report() -> Option<String> {
+    read_optional_setting("junit_report")
+}
+
+/// When set to a file path, the verification errors are written there as a
+/// JSON array of the parameters of Language Server Protocol
+/// `publishDiagnostics` notifications, one per file.
+pub fn lsp_diagnostics() -> Option<String> {
+    read_optional_setting("lsp_diagnostics")
+}
+
+/// When enabled, each top-level statement of the encoded methods is preceded
+/// by a comment with the source location it was generated from. This only
+/// makes the dumped encoding easier to read.
+pub fn annotate_viper_with_source() -> bool {
+    read_setting("annotate_viper_with_source")
+}
+
 /// Filter for `fold`/`unfold` nodes when debug info is dumped.
 pub fn foldunfold_state_filter() -> String {
     read_setting("foldunfold_state_filter")
@@ -316,6 +450,13 @@ pub fn check_overflows() -> bool {
     read_setting("check_overflows")
 }
 
+/// When enabled, calls of unchecked arithmetic operations like
+/// `u32::unchecked_add` are checked not to overflow. Otherwise, they are
+/// encoded like calls of other functions without a specification.
+pub fn check_unchecked_arithmetic() -> bool {
+    read_setting("check_unchecked_arithmetic")
+}
+
 /// When enabled, non-negativity of unsigned integers will be encoded and
 /// checked.
 pub fn encode_unsigned_num_constraint() -> bool {
@@ -357,6 +498,16 @@ pub fn assert_timeout() -> u64 {
     read_setting("assert_timeout")
 }
 
+/// Maximum number of quantifier instantiations of the SMT solver. Functions
+/// that contain a loop annotated with `#[prusti::loop_options(qi_limit = ...)]`
+/// use the limit of the annotation instead. An invalid value, e.g. a negative
+/// number, is reported by `check_settings` and ignored here.
+pub fn qi_limit() -> Option<u64> {
+    read_optional_setting::<Option<i64>>("qi_limit")
+        .flatten()
+        .and_then(|value| value.try_into().ok())
+}
+
 /// When enabled, a more complete `exhale` version is used in the verifier.
 /// See [`consolidate`](https://github.com/viperproject/silicon/blob/f48de7f6e2d90d9020812869c713a5d3e2035995/src/main/scala/rules/StateConsolidator.scala#L29-L46).
 /// Equivalent to the verifier command-line argument
@@ -386,12 +537,89 @@ pub fn hide_uuids() -> bool {
     read_setting("hide_uuids")
 }
 
+/// How durations are printed in the output of Prusti. Possible values:
+///
+/// - `exact` - in seconds, with millisecond precision;
+/// - `rounded` - as a coarse bucket, e.g. `< 10 seconds`;
+/// - `hidden` - as the placeholder `<time>`.
+///
+/// `rounded` and `hidden` make the output stable across runs, e.g. for tests
+/// that compare it with an expected output.
+pub fn timing_format() -> String {
+    read_setting::<String>("timing_format")
+        .to_lowercase()
+        .trim()
+        .to_string()
+}
+
 /// When enabled, Prusti will try to find and print a counterexample for any
 /// failed assertion or specification.
 pub fn counterexample() -> bool {
     read_setting("counterexample")
 }
 
+/// When enabled, Prusti prints the formula of every failed verification
+/// obligation, with the variables named as in the source code where possible.
+pub fn explain_errors() -> bool {
+    read_setting("explain_errors")
+}
+
+/// When enabled, the assertion of a conjunction is split into one assertion
+/// per conjunct, so that a failure is reported at the failing conjunct.
+pub fn split_conjunctive_assertions() -> bool {
+    read_setting("split_conjunctive_assertions")
+}
+
+/// When enabled, Prusti reports the preconditions of successfully verified
+/// functions that are not needed for the proof. Every precondition is checked
+/// by verifying the function again without it, which is expensive.
+pub fn report_unused_preconditions() -> bool {
+    read_setting("report_unused_preconditions")
+}
+
+/// When enabled, Prusti warns about functions that return an enum and whose
+/// postconditions match the result against some of its variants, but not all.
+pub fn report_uncovered_result_variants() -> bool {
+    read_setting("report_uncovered_result_variants")
+}
+
+/// When enabled, Prusti only encodes the crate without verifying it. The
+/// encoding continues past the usages of unsupported features, which are all
+/// reported with their category, and a summary is written as CSV to the log
+/// directory.
+pub fn report_unsupported_features() -> bool {
+    read_setting("report_unsupported_features")
+}
+
+/// When enabled, Prusti measures the backend time spent on each assertion of
+/// the successfully verified functions and reports it as CSV in the log
+/// directory. Every assertion is verified again on its own, which is expensive.
+pub fn profile_backend() -> bool {
+    read_setting("profile_backend")
+}
+
+/// When enabled, Prusti warns about trusted functions whose postcondition is
+/// unsatisfiable under their precondition. Such a postcondition makes every
+/// call to the function unreachable.
+pub fn check_trusted_postconditions() -> bool {
+    read_setting("check_trusted_postconditions")
+}
+
+/// The strategy used to submit the encoded functions to the verifier:
+/// - `none` - every function is verified with a separate backend query;
+/// - `by_backend` - the functions that are verified with the same backend
+///   configuration are merged into a single backend query.
+///
+/// Batching reduces the number of round-trips to the verifier. Errors are
+/// still reported for the individual obligations that failed, but
+/// `REPORT_UNUSED_PRECONDITIONS` skips all functions of a batch that failed.
+pub fn verification_batching() -> String {
+    read_setting::<String>("verification_batching")
+        .to_lowercase()
+        .trim()
+        .to_string()
+}
+
 /// When enabled, prints the hash of a verification request (the hash is used
 /// for caching). This is a debugging option which does not perform
 /// verification -- it is similar to `NO_VERIFY`, except that this flag stops
@@ -483,12 +711,14 @@ pub fn verify_only_basic_block_path() -> Vec<String> {
 /// Possible values in the list are:
 ///
 /// - `"inline_constant_functions"`
+/// - `"specialize_functions"`
 /// - `"delete_unused_predicates"`
 /// - `"optimize_folding"`
 /// - `"remove_empty_if"`
 /// - `"purify_vars"`
 /// - `"fix_quantifiers"`
 /// - `"fix_unfoldings"`
+/// - `"fold_constants"`
 /// - `"remove_unused_vars"`
 /// - `"remove_trivial_assertions"`
 /// - `"clean_cfg"`
@@ -502,12 +732,14 @@ pub fn optimizations() -> Optimizations {
         match trimmed {
             "all" => opt = Optimizations::all_enabled(),
             "inline_constant_functions" => opt.inline_constant_functions = true,
+            "specialize_functions" => opt.specialize_functions = true,
             "delete_unused_predicates" => opt.delete_unused_predicates = true,
             "optimize_folding" => opt.optimize_folding = true,
             "remove_empty_if" => opt.remove_empty_if = true,
             "purify_vars" => opt.purify_vars = true,
             "fix_quantifiers" => opt.fix_quantifiers = true,
             "fix_unfoldings" => opt.fix_unfoldings = true,
+            "fold_constants" => opt.fold_constants = true,
             "remove_unused_vars" => opt.remove_unused_vars = true,
             "remove_trivial_assertions" => opt.remove_trivial_assertions = true,
             "clean_cfg" => opt.clean_cfg = true,
@@ -545,7 +777,10 @@ pub fn unsafe_core_proof() -> bool {
     read_setting("unsafe_core_proof")
 }
 
-/// When enabled, only the core proof is verified.
+/// When enabled, only the core proof is verified: the preconditions and
+/// postconditions of functions are ignored, except for the safety
+/// preconditions of `unsafe` functions and for the functions with the
+/// `#[prusti::functional_verification]` attribute.
 ///
 /// **Note:** This should be used only when `UNSAFE_CORE_PROOF` is enabled.
 pub fn only_memory_safety() -> bool {
@@ -569,12 +804,27 @@ pub fn internal_errors_as_warnings() -> bool {
     read_setting("internal_errors_as_warnings")
 }
 
+/// When enabled, all warnings reported by Prusti are reported as errors
+/// instead, e.g. to fail a CI run on them. Warnings of the compiler are not
+/// affected.
+pub fn warnings_as_errors() -> bool {
+    read_setting("warnings_as_errors")
+}
+
 /// When enabled, unsupported code is encoded as `assert false`. This way error
 /// messages are reported only for unsupported code that is actually reachable.
 pub fn allow_unreachable_unsupported_code() -> bool {
     read_setting("allow_unreachable_unsupported_code")
 }
 
+/// When enabled, calls to `std` functions that have no specification and that
+/// cannot be encoded are treated as calls to trusted functions whose result is
+/// havocked. A warning lists the stubbed functions. When disabled, such calls
+/// are reported as errors.
+pub fn stub_unencodable_std_functions() -> bool {
+    read_setting("stub_unencodable_std_functions")
+}
+
 /// When enabled, verification is skipped altogether.
 pub fn no_verify() -> bool {
     read_setting("no_verify")
@@ -585,6 +835,13 @@ pub fn no_verify_deps() -> bool {
     read_setting("no_verify_deps")
 }
 
+/// When enabled, the specifications are only parsed, type-checked, collected
+/// and checked for well-formedness, without encoding and verifying the
+/// program. The compilation fails if any of these checks fails.
+pub fn spec_lint() -> bool {
+    read_setting("spec_lint")
+}
+
 /// When enabled, compilation will continue and a binary will be generated
 /// after Prusti terminates.
 pub fn full_compilation() -> bool {
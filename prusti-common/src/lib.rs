@@ -30,4 +30,4 @@ mod stopwatch;
 pub mod utils;
 pub mod vir;
 
-pub use stopwatch::Stopwatch;
+pub use stopwatch::{format_duration, Stopwatch};
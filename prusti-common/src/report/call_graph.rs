@@ -0,0 +1,97 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A graph of the calls encoded for the verified functions, recording which
+//! specifications were used to encode each call.
+
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub caller: String,
+    pub callee: String,
+    /// The specification items used to encode the call.
+    pub specs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    calls: Vec<Call>,
+}
+
+impl CallGraph {
+    pub fn add_call(&mut self, caller: String, callee: String, specs: Vec<String>) {
+        let call = Call {
+            caller,
+            callee,
+            specs,
+        };
+        if !self.calls.contains(&call) {
+            self.calls.push(call);
+        }
+    }
+
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Render the graph in the DOT format. Each call is an edge labelled with
+    /// the specifications used to encode it.
+    pub fn to_dot(&self) -> String {
+        fn escape(text: &str) -> String {
+            text.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+        let mut dot = String::from("digraph CallGraph {\n");
+        for call in &self.calls {
+            let label = call
+                .specs
+                .iter()
+                .map(|spec| escape(spec.as_str()))
+                .collect::<Vec<_>>()
+                .join("\\n");
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape(&call.caller),
+                escape(&call.callee),
+                label
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_graph_to_dot() {
+        let mut graph = CallGraph::default();
+        graph.add_call(
+            "client".to_string(),
+            "<Counter as Step>::step".to_string(),
+            vec!["DefId(0:12 ~ test[8787]::Step::prusti_post_item_step_5a4d)".to_string()],
+        );
+        graph.add_call("client".to_string(), "helper".to_string(), vec![]);
+        graph.add_call("client".to_string(), "helper".to_string(), vec![]);
+        assert_eq!(graph.calls().len(), 2);
+        assert_eq!(
+            graph.to_dot(),
+            "digraph CallGraph {\n    \
+             \"client\" -> \"<Counter as Step>::step\" \
+             [label=\"DefId(0:12 ~ test[8787]::Step::prusti_post_item_step_5a4d)\"];\n    \
+             \"client\" -> \"helper\" [label=\"\"];\n\
+             }\n"
+        );
+    }
+}
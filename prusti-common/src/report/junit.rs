@@ -0,0 +1,130 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A JUnit XML report of the verification outcome of each function, for CI
+//! dashboards. Each verified function is a test case.
+
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Success,
+    /// The messages and source locations of the errors of the function.
+    Failure(Vec<(String, String)>),
+    /// The function is not verified, e.g. because it is trusted.
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub outcome: TestOutcome,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Format the test cases as a JUnit XML report with one test suite named
+/// `suite_name`.
+pub fn format_junit_xml(suite_name: &str, test_cases: &[TestCase]) -> String {
+    let count = |predicate: fn(&TestOutcome) -> bool| {
+        test_cases
+            .iter()
+            .filter(|test_case| predicate(&test_case.outcome))
+            .count()
+    };
+    let failures = count(|outcome| matches!(outcome, TestOutcome::Failure(_)));
+    let skipped = count(|outcome| matches!(outcome, TestOutcome::Skipped(_)));
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        xml,
+        "<testsuites>\n  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">",
+        escape(suite_name),
+        test_cases.len(),
+        failures,
+        skipped,
+    )
+    .unwrap();
+    for test_case in test_cases {
+        let name = escape(&test_case.name);
+        let classname = escape(suite_name);
+        match &test_case.outcome {
+            TestOutcome::Success => {
+                writeln!(xml, "    <testcase name=\"{}\" classname=\"{}\"/>", name, classname).unwrap();
+            }
+            TestOutcome::Failure(errors) => {
+                writeln!(xml, "    <testcase name=\"{}\" classname=\"{}\">", name, classname).unwrap();
+                for (message, location) in errors {
+                    writeln!(
+                        xml,
+                        "      <failure message=\"{}\" type=\"verification\">{}: {}</failure>",
+                        escape(message),
+                        escape(location),
+                        escape(message),
+                    )
+                    .unwrap();
+                }
+                xml.push_str("    </testcase>\n");
+            }
+            TestOutcome::Skipped(reason) => {
+                writeln!(
+                    xml,
+                    "    <testcase name=\"{}\" classname=\"{}\">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                    name,
+                    classname,
+                    escape(reason),
+                )
+                .unwrap();
+            }
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_junit_xml_escaping() {
+        let xml = format_junit_xml(
+            "test",
+            &[
+                TestCase {
+                    name: "<T as Trait>::trusted".to_string(),
+                    outcome: TestOutcome::Skipped("trusted".to_string()),
+                },
+                TestCase {
+                    name: "<T as Trait>::failing".to_string(),
+                    outcome: TestOutcome::Failure(vec![(
+                        "\"x\" & y might not hold".to_string(),
+                        "test.rs:3:11".to_string(),
+                    )]),
+                },
+            ],
+        );
+        assert_eq!(
+            xml,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites>\n  \
+             <testsuite name=\"test\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"1\">\n    \
+             <testcase name=\"&lt;T as Trait&gt;::trusted\" classname=\"test\">\n      \
+             <skipped message=\"trusted\"/>\n    \
+             </testcase>\n    \
+             <testcase name=\"&lt;T as Trait&gt;::failing\" classname=\"test\">\n      \
+             <failure message=\"&quot;x&quot; &amp; y might not hold\" type=\"verification\">\
+             test.rs:3:11: &quot;x&quot; &amp; y might not hold</failure>\n    \
+             </testcase>\n  \
+             </testsuite>\n\
+             </testsuites>\n"
+        );
+    }
+}
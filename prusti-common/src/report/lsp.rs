@@ -0,0 +1,258 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The verification errors in the shape of the parameters of the Language
+//! Server Protocol `textDocument/publishDiagnostics` notification, so that an
+//! editor can show them without an adapter.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The severity of a diagnostic, serialized as the number of the protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum DiagnosticSeverity {
+    Error = 1,
+    Warning = 2,
+    Information = 3,
+    Hint = 4,
+}
+
+impl From<DiagnosticSeverity> for u8 {
+    fn from(severity: DiagnosticSeverity) -> Self {
+        severity as u8
+    }
+}
+
+impl TryFrom<u8> for DiagnosticSeverity {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, String> {
+        match value {
+            1 => Ok(DiagnosticSeverity::Error),
+            2 => Ok(DiagnosticSeverity::Warning),
+            3 => Ok(DiagnosticSeverity::Information),
+            4 => Ok(DiagnosticSeverity::Hint),
+            _ => Err(format!("invalid diagnostic severity {}", value)),
+        }
+    }
+}
+
+/// A position in a file. Lines and characters are zero-based, and characters
+/// are counted in UTF-16 code units, as in the protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+impl Position {
+    /// The position of the byte `column` of the one-based `line`, whose text
+    /// is `line_text`. If the column is not at a character boundary of the
+    /// text, e.g. because the text is not available, it is kept as it is.
+    pub fn new(line: usize, line_text: &str, column: usize) -> Self {
+        let character = line_text
+            .get(..column)
+            .map(|prefix| prefix.encode_utf16().count())
+            .unwrap_or(column);
+        Position {
+            line: line - 1,
+            character,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: DiagnosticSeverity,
+    pub code: String,
+    pub source: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(range: Range, severity: DiagnosticSeverity, code: &str, message: &str) -> Self {
+        Diagnostic {
+            range,
+            severity,
+            code: code.to_string(),
+            source: "prusti".to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishDiagnosticsParams {
+    /// The URI of the file, e.g. `file:///home/user/src/lib.rs`.
+    pub uri: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// The URI of a file path. Relative paths are resolved against the current
+/// directory.
+pub fn file_uri(path: &str) -> String {
+    let path = Path::new(path);
+    let path = if path.is_relative() {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    } else {
+        path.to_path_buf()
+    };
+    let path = path.to_string_lossy().replace('\\', "/").replace(' ', "%20");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
+/// Group the diagnostics by the URI of their file, in the order in which the
+/// files first appear.
+pub fn publish_diagnostics(diagnostics: Vec<(String, Diagnostic)>) -> Vec<PublishDiagnosticsParams> {
+    let mut params: Vec<PublishDiagnosticsParams> = vec![];
+    for (uri, diagnostic) in diagnostics {
+        if let Some(file_params) = params.iter_mut().find(|file_params| file_params.uri == uri) {
+            file_params.diagnostics.push(diagnostic);
+        } else {
+            params.push(PublishDiagnosticsParams {
+                uri,
+                diagnostics: vec![diagnostic],
+            });
+        }
+    }
+    params
+}
+
+/// Format the diagnostics as a JSON array of `PublishDiagnosticsParams`, one
+/// per file.
+pub fn format_publish_diagnostics(diagnostics: Vec<(String, Diagnostic)>) -> String {
+    serde_json::to_string_pretty(&publish_diagnostics(diagnostics)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(file_uri("/home/user/my crate/lib.rs"), "file:///home/user/my%20crate/lib.rs");
+        let current_dir = std::env::current_dir().unwrap();
+        assert_eq!(
+            file_uri("src/lib.rs"),
+            file_uri(&current_dir.join("src/lib.rs").to_string_lossy()),
+        );
+        assert!(file_uri("src/lib.rs").starts_with("file:///"));
+    }
+
+    #[test]
+    fn test_position() {
+        // Lines are one-based in the compiler and zero-based in the protocol.
+        assert_eq!(Position::new(1, "fn main() {}", 3), Position { line: 0, character: 3 });
+        // `é` takes two bytes and one UTF-16 code unit, `𝄞` four bytes and
+        // two UTF-16 code units.
+        let line_text = "let s = \"é𝄞\"; assert!(false);";
+        let column = line_text.find("assert").unwrap();
+        assert_eq!(column, 18);
+        assert_eq!(Position::new(7, line_text, column), Position { line: 6, character: 15 });
+        // Without the text of the line, the column stays in bytes.
+        assert_eq!(Position::new(7, "", column), Position { line: 6, character: 18 });
+    }
+
+    #[test]
+    fn test_publish_diagnostics() {
+        // The postcondition `#[ensures(result > 0)]` of a function in line 3
+        // of `lib.rs` might not hold, and a function in `other.rs` uses an
+        // unsupported feature.
+        let range = |start_line, start_character, end_line, end_character| Range {
+            start: Position { line: start_line, character: start_character },
+            end: Position { line: end_line, character: end_character },
+        };
+        let json = format_publish_diagnostics(vec![
+            (
+                "file:///src/lib.rs".to_string(),
+                Diagnostic::new(
+                    range(2, 10, 2, 20),
+                    DiagnosticSeverity::Error,
+                    "postcondition",
+                    "[Prusti: verification error] postcondition might not hold.",
+                ),
+            ),
+            (
+                "file:///src/other.rs".to_string(),
+                Diagnostic::new(
+                    range(0, 0, 1, 1),
+                    DiagnosticSeverity::Warning,
+                    "unsupported",
+                    "\"dyn\" is\tunsupported",
+                ),
+            ),
+            (
+                "file:///src/lib.rs".to_string(),
+                Diagnostic::new(
+                    range(7, 4, 7, 16),
+                    DiagnosticSeverity::Error,
+                    "panic",
+                    "[Prusti: verification error] panic!(..) statement might be reachable",
+                ),
+            ),
+        ]);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {
+                    "uri": "file:///src/lib.rs",
+                    "diagnostics": [
+                        {
+                            "range": {
+                                "start": { "line": 2, "character": 10 },
+                                "end": { "line": 2, "character": 20 },
+                            },
+                            "severity": 1,
+                            "code": "postcondition",
+                            "source": "prusti",
+                            "message": "[Prusti: verification error] postcondition might not hold.",
+                        },
+                        {
+                            "range": {
+                                "start": { "line": 7, "character": 4 },
+                                "end": { "line": 7, "character": 16 },
+                            },
+                            "severity": 1,
+                            "code": "panic",
+                            "source": "prusti",
+                            "message": "[Prusti: verification error] panic!(..) statement might be reachable",
+                        },
+                    ],
+                },
+                {
+                    "uri": "file:///src/other.rs",
+                    "diagnostics": [
+                        {
+                            "range": {
+                                "start": { "line": 0, "character": 0 },
+                                "end": { "line": 1, "character": 1 },
+                            },
+                            "severity": 2,
+                            "code": "unsupported",
+                            "source": "prusti",
+                            "message": "\"dyn\" is\tunsupported",
+                        },
+                    ],
+                },
+            ])
+        );
+    }
+}
@@ -4,12 +4,78 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use crate::config;
 use std::{
-    fmt::Display,
+    fmt::{self, Display},
     marker::PhantomData,
+    str::FromStr,
     time::{Duration, Instant},
 };
 
+/// How durations are printed, as configured by `TIMING_FORMAT`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimingFormat {
+    /// In seconds, with millisecond precision, e.g. `1.234 seconds`.
+    Exact,
+    /// As a coarse bucket, e.g. `< 10 seconds`.
+    Rounded,
+    /// As the placeholder `<time>`.
+    Hidden,
+}
+
+#[derive(Clone, Debug)]
+pub struct UnknownTimingFormatError(String);
+
+impl Display for UnknownTimingFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid value '{}' of the TIMING_FORMAT flag, which must be 'exact', 'rounded' or 'hidden'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for TimingFormat {
+    type Err = UnknownTimingFormatError;
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "exact" => Ok(TimingFormat::Exact),
+            "rounded" => Ok(TimingFormat::Rounded),
+            "hidden" => Ok(TimingFormat::Hidden),
+            _ => Err(UnknownTimingFormatError(format.to_string())),
+        }
+    }
+}
+
+/// Format a duration for the output of Prusti, as configured by
+/// `TIMING_FORMAT`.
+pub fn format_duration(duration: Duration) -> String {
+    // An invalid value is reported by `config::check_settings`.
+    let format = TimingFormat::from_str(&config::timing_format()).unwrap_or(TimingFormat::Exact);
+    format_duration_as(duration, format)
+}
+
+fn format_duration_as(duration: Duration, format: TimingFormat) -> String {
+    match format {
+        TimingFormat::Hidden => "<time>".to_string(),
+        TimingFormat::Rounded => {
+            let bucket = [(1, "< 1 second"), (10, "< 10 seconds"), (60, "< 1 minute")]
+                .iter()
+                .find(|(limit, _)| duration < Duration::from_secs(*limit));
+            match bucket {
+                Some((_, description)) => description.to_string(),
+                None => ">= 1 minute".to_string(),
+            }
+        }
+        TimingFormat::Exact => format!(
+            "{}.{:03} seconds",
+            duration.as_secs(),
+            duration.subsec_millis()
+        ),
+    }
+}
+
 pub trait LogLevel {
     fn log_start(prefix: &str, name: &str);
     fn log_finish(prefix: &str, name: &str, duration: Duration);
@@ -93,11 +159,10 @@ pub mod log_level {
 
                 fn log_finish(prefix: &str, name: &str, duration: Duration) {
                     $level!(
-                        "{}Completed: {} ({}.{:03} seconds)",
+                        "{}Completed: {} ({})",
                         prefix,
                         name,
-                        duration.as_secs(),
-                        duration.subsec_millis()
+                        format_duration(duration)
                     )
                 }
             }
@@ -117,3 +182,29 @@ pub mod log_level {
     log_level!(Debug, debug, start_debug);
     log_level!(Trace, trace, start_trace);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        let duration = Duration::from_millis(4321);
+        assert_eq!(format_duration_as(duration, TimingFormat::Exact), "4.321 seconds");
+        assert_eq!(format_duration_as(duration, TimingFormat::Rounded), "< 10 seconds");
+        assert_eq!(format_duration_as(Duration::from_millis(20), TimingFormat::Rounded), "< 1 second");
+        assert_eq!(format_duration_as(Duration::from_secs(90), TimingFormat::Rounded), ">= 1 minute");
+        assert_eq!(format_duration_as(duration, TimingFormat::Hidden), "<time>");
+        assert_eq!(
+            format_duration_as(Duration::from_secs(90), TimingFormat::Hidden),
+            format_duration_as(duration, TimingFormat::Hidden),
+        );
+    }
+
+    #[test]
+    fn test_parse_timing_format() {
+        assert_eq!(TimingFormat::from_str("Rounded").unwrap(), TimingFormat::Rounded);
+        assert_eq!(TimingFormat::from_str("hidden").unwrap(), TimingFormat::Hidden);
+        assert!(TimingFormat::from_str("approximate").is_err());
+    }
+}
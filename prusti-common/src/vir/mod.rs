@@ -11,7 +11,13 @@ pub use vir::polymorphic as polymorphic_vir;
 pub use vir::high as vir_high;
 
 pub mod fixes;
+pub mod obligation_dependencies;
+pub mod obligation_formulas;
+pub mod obligation_profiling;
 pub mod optimizations;
+pub mod precondition_conjuncts;
+pub mod source_comments;
+pub mod split_assertions;
 mod to_viper;
 mod low_to_viper;
 mod to_graphviz;
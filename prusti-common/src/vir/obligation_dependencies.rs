@@ -0,0 +1,517 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Approximation of the dependencies between the obligations of a method, so
+//! that a scheduler can verify independent obligations concurrently. Two
+//! statements are connected if they mention a common variable, where a
+//! statement also mentions the variables of the branch conditions of its
+//! block. An obligation needs the assumptions it is transitively connected to
+//! and every `inhale false` before it, and depends on the obligations before
+//! it that it is connected to, because a checked obligation is assumed by the
+//! rest of the method.
+
+use super::polymorphic_vir::{
+    ast,
+    cfg::{CfgMethod, Successor},
+    ExprWalker, StmtWalker,
+};
+use fxhash::{FxHashMap, FxHashSet};
+
+/// A statement of a method that is not an obligation, e.g. an `inhale`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assumption {
+    /// The index of the block of the statement.
+    pub block: usize,
+    /// The index of the statement in its block.
+    pub statement: usize,
+    /// The position of the assumed expression, which is the default position
+    /// if the statement has none.
+    pub position: ast::Position,
+}
+
+/// The dependencies of an obligation of a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObligationDependencies {
+    pub position: ast::Position,
+    /// The statements of the method that the obligation needs, in the order
+    /// of the method.
+    pub assumptions: Vec<Assumption>,
+    /// The obligations before this one that it depends on.
+    pub depends_on: Vec<ast::Position>,
+}
+
+/// The variables mentioned by a statement, without the quantified ones.
+struct VariablesCollector {
+    bound: Vec<ast::LocalVar>,
+    variables: FxHashSet<String>,
+}
+
+impl ExprWalker for VariablesCollector {
+    fn walk_local(&mut self, ast::Local { variable, .. }: &ast::Local) {
+        if !self.bound.contains(variable) {
+            self.variables.insert(variable.name.clone());
+        }
+    }
+    fn walk_forall(&mut self, ast::ForAll { variables, body, .. }: &ast::ForAll) {
+        self.bound.extend(variables.iter().cloned());
+        ExprWalker::walk(self, body);
+        self.bound.truncate(self.bound.len() - variables.len());
+    }
+    fn walk_exists(&mut self, ast::Exists { variables, body, .. }: &ast::Exists) {
+        self.bound.extend(variables.iter().cloned());
+        ExprWalker::walk(self, body);
+        self.bound.truncate(self.bound.len() - variables.len());
+    }
+}
+
+impl StmtWalker for VariablesCollector {
+    fn walk_expr(&mut self, expr: &ast::Expr) {
+        ExprWalker::walk(self, expr);
+    }
+    fn walk_local_var(&mut self, var: &ast::LocalVar) {
+        self.variables.insert(var.name.clone());
+    }
+}
+
+fn stmt_variables(stmt: &ast::Stmt) -> FxHashSet<String> {
+    let mut collector = VariablesCollector {
+        bound: vec![],
+        variables: FxHashSet::default(),
+    };
+    StmtWalker::walk(&mut collector, stmt);
+    collector.variables
+}
+
+fn expr_variables(expr: &ast::Expr) -> FxHashSet<String> {
+    let mut collector = VariablesCollector {
+        bound: vec![],
+        variables: FxHashSet::default(),
+    };
+    ExprWalker::walk(&mut collector, expr);
+    collector.variables
+}
+
+/// Whether the statement is an `inhale false`, after which every obligation
+/// holds.
+fn is_inhale_false(stmt: &ast::Stmt) -> bool {
+    matches!(
+        stmt,
+        ast::Stmt::Inhale(ast::Inhale {
+            expr: ast::Expr::Const(ast::ConstExpr {
+                value: ast::Const::Bool(false),
+                ..
+            }),
+        })
+    )
+}
+
+/// The indices of the successors of the block, with the guards of its switch.
+fn successors(successor: &Successor) -> (Vec<&ast::Expr>, Vec<usize>) {
+    match successor {
+        Successor::Undefined | Successor::Return => (vec![], vec![]),
+        Successor::Goto(target) => (vec![], vec![target.block_index]),
+        Successor::GotoSwitch(guarded_targets, default_target) => {
+            let guards = guarded_targets.iter().map(|(guard, _)| guard).collect();
+            let mut targets: Vec<usize> = guarded_targets
+                .iter()
+                .map(|(_, target)| target.block_index)
+                .collect();
+            targets.push(default_target.block_index);
+            (guards, targets)
+        }
+    }
+}
+
+/// The variables of the branch conditions of each block, i.e. of the guards
+/// of the switches on the paths that reach the block. After the branches
+/// join, their conditions are kept, which over-approximates the dependencies.
+fn branch_condition_variables(method: &CfgMethod) -> Vec<FxHashSet<String>> {
+    let blocks = &method.basic_blocks;
+    let mut conditions = vec![FxHashSet::default(); blocks.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (index, block) in blocks.iter().enumerate() {
+            let (guards, targets) = successors(&block.successor);
+            let mut variables = conditions[index].clone();
+            for guard in guards {
+                variables.extend(expr_variables(guard));
+            }
+            for target in targets {
+                let target_conditions = &mut conditions[target];
+                let count = target_conditions.len();
+                target_conditions.extend(variables.iter().cloned());
+                changed |= target_conditions.len() != count;
+            }
+        }
+    }
+    conditions
+}
+
+/// The blocks that can be reached from each block by following at least one
+/// edge.
+fn reachable_blocks(method: &CfgMethod) -> Vec<FxHashSet<usize>> {
+    let blocks = &method.basic_blocks;
+    (0..blocks.len())
+        .map(|start| {
+            let mut reachable = FxHashSet::default();
+            let mut pending = successors(&blocks[start].successor).1;
+            while let Some(index) = pending.pop() {
+                if reachable.insert(index) {
+                    pending.extend(successors(&blocks[index].successor).1);
+                }
+            }
+            reachable
+        })
+        .collect()
+}
+
+struct StmtInfo {
+    block: usize,
+    /// The index of the statement in its block.
+    statement: usize,
+    /// The position of the statement if it is an obligation.
+    position: Option<ast::Position>,
+    /// The position of the expression of the statement if it is an
+    /// assumption.
+    assumed_position: ast::Position,
+    /// The variables of the statement and of the branch conditions of its
+    /// block.
+    variables: FxHashSet<String>,
+    inhales_false: bool,
+}
+
+/// The position of the statement if it is an obligation.
+fn obligation_position(stmt: &ast::Stmt) -> Option<ast::Position> {
+    match stmt {
+        ast::Stmt::Assert(ast::Assert { position, .. })
+        | ast::Stmt::Exhale(ast::Exhale { position, .. })
+            if !position.is_default() =>
+        {
+            Some(*position)
+        }
+        _ => None,
+    }
+}
+
+/// The position of the expression that the statement assumes, or the default
+/// position if it has none.
+fn assumed_position(stmt: &ast::Stmt) -> ast::Position {
+    match stmt {
+        ast::Stmt::Inhale(ast::Inhale { expr }) => expr.pos(),
+        ast::Stmt::Assign(ast::Assign { source, .. }) => source.pos(),
+        _ => ast::Position::default(),
+    }
+}
+
+fn find(parents: &mut FxHashMap<String, String>, var: &str) -> String {
+    let parent = parents
+        .entry(var.to_string())
+        .or_insert_with(|| var.to_string())
+        .clone();
+    if parent == var {
+        return parent;
+    }
+    let root = find(parents, &parent);
+    parents.insert(var.to_string(), root.clone());
+    root
+}
+
+/// Compute the dependencies of the obligations of `method`, in the order of
+/// its blocks. The obligations are the assertions and exhales with a
+/// position.
+pub fn obligation_dependencies(method: &CfgMethod) -> Vec<ObligationDependencies> {
+    let conditions = branch_condition_variables(method);
+    let reachable = reachable_blocks(method);
+    let stmts: Vec<StmtInfo> = method
+        .basic_blocks
+        .iter()
+        .enumerate()
+        .flat_map(|(block, cfg_block)| {
+            cfg_block.stmts.iter().enumerate().map(move |(statement, stmt)| (block, statement, stmt))
+        })
+        .map(|(block, statement, stmt)| {
+            let mut variables = stmt_variables(stmt);
+            variables.extend(conditions[block].iter().cloned());
+            StmtInfo {
+                block,
+                statement,
+                position: obligation_position(stmt),
+                assumed_position: assumed_position(stmt),
+                variables,
+                inhales_false: is_inhale_false(stmt),
+            }
+        })
+        .collect();
+    // Whether the statement `before` can be executed before the statement
+    // `after`.
+    let precedes = |before: usize, after: usize| {
+        let (before_block, after_block) = (stmts[before].block, stmts[after].block);
+        (before_block == after_block && before < after)
+            || reachable[before_block].contains(&after_block)
+    };
+
+    // Connect the variables that are mentioned by the same statement.
+    let mut parents = FxHashMap::default();
+    for stmt in &stmts {
+        let mut variables = stmt.variables.iter();
+        if let Some(first) = variables.next() {
+            let first = find(&mut parents, first);
+            for var in variables {
+                let root = find(&mut parents, var);
+                parents.insert(root, first.clone());
+            }
+        }
+    }
+    // Statements without variables are not connected to any other statement.
+    let components: Vec<Option<String>> = stmts
+        .iter()
+        .map(|stmt| stmt.variables.iter().next().map(|var| find(&mut parents, var)))
+        .collect();
+
+    let mut dependencies: Vec<ObligationDependencies> = vec![];
+    for (index, stmt) in stmts.iter().enumerate() {
+        let position = match stmt.position {
+            Some(position) => position,
+            None => continue,
+        };
+        let component = &components[index];
+        let connected = |other: usize| component.is_some() && &components[other] == component;
+        let assumptions: Vec<Assumption> = (0..stmts.len())
+            .filter(|&other| stmts[other].position.is_none())
+            .filter(|&other| connected(other) || (stmts[other].inhales_false && precedes(other, index)))
+            .map(|other| Assumption {
+                block: stmts[other].block,
+                statement: stmts[other].statement,
+                position: stmts[other].assumed_position,
+            })
+            .collect();
+        let mut depends_on: Vec<ast::Position> = (0..stmts.len())
+            .filter(|&other| connected(other) && precedes(other, index))
+            .filter_map(|other| stmts[other].position)
+            .filter(|other| *other != position)
+            .collect();
+        depends_on.sort_by_key(|position| position.id());
+        depends_on.dedup();
+        if let Some(existing) = dependencies.iter_mut().find(|deps| deps.position == position) {
+            for assumption in assumptions {
+                if !existing.assumptions.contains(&assumption) {
+                    existing.assumptions.push(assumption);
+                }
+            }
+            existing.assumptions.sort_by_key(|assumption| (assumption.block, assumption.statement));
+            continue;
+        }
+        dependencies.push(ObligationDependencies {
+            position,
+            assumptions,
+            depends_on,
+        });
+    }
+    dependencies
+}
+
+/// Group the obligations that can be verified independently of each other.
+pub fn independent_groups(dependencies: &[ObligationDependencies]) -> Vec<Vec<ast::Position>> {
+    let mut groups: Vec<Vec<ast::Position>> = vec![];
+    for deps in dependencies {
+        let mut group = vec![deps.position];
+        groups.retain(|other| {
+            if deps.depends_on.iter().any(|position| other.contains(position)) {
+                group.extend(other.iter().copied());
+                false
+            } else {
+                true
+            }
+        });
+        group.sort_by_key(|position| position.id());
+        groups.push(group);
+    }
+    groups
+}
+
+/// Format the dependencies of the obligations of each method as JSON. The
+/// obligations are identified by the id of their position, and `location`
+/// gives their source location and the one of their assumptions. An
+/// assumption is identified by the indices of its block and statement.
+pub fn format_dependencies_json(
+    methods: Vec<(String, Vec<ObligationDependencies>)>,
+    location: impl Fn(ast::Position) -> String,
+) -> String {
+    let escape = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let ids = |positions: &[ast::Position]| {
+        positions
+            .iter()
+            .map(|position| position.id().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut json = String::from("{\"methods\": [");
+    for (method_index, (name, dependencies)) in methods.iter().enumerate() {
+        if method_index > 0 {
+            json.push(',');
+        }
+        json.push_str(&format!(
+            "\n  {{\"name\": \"{}\", \"obligations\": [",
+            escape(name.clone())
+        ));
+        for (index, deps) in dependencies.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let assumptions = deps.assumptions
+                .iter()
+                .map(|assumption| {
+                    let location = if assumption.position.is_default() {
+                        String::new()
+                    } else {
+                        location(assumption.position)
+                    };
+                    format!(
+                        "{{\"block\": {}, \"statement\": {}, \"location\": \"{}\"}}",
+                        assumption.block,
+                        assumption.statement,
+                        escape(location),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            json.push_str(&format!(
+                "\n    {{\"id\": {}, \"location\": \"{}\", \"assumptions\": [{}], \"depends_on\": [{}]}}",
+                deps.position.id(),
+                escape(location(deps.position)),
+                assumptions,
+                ids(&deps.depends_on),
+            ));
+        }
+        let groups = independent_groups(dependencies)
+            .iter()
+            .map(|group| format!("[{}]", ids(group)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        json.push_str(&format!("\n  ], \"independent_groups\": [{}]}}", groups));
+    }
+    json.push_str("\n]}\n");
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vir::polymorphic_vir::cfg;
+
+    fn local(name: &str) -> ast::Expr {
+        ast::Expr::local(ast::LocalVar::new(name, ast::Type::Bool))
+    }
+
+    fn method(stmts: Vec<ast::Stmt>) -> cfg::CfgMethod {
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let block = method.add_block("start", stmts);
+        method.set_successor(block, cfg::Successor::Return);
+        method
+    }
+
+    #[test]
+    fn test_independent_asserts() {
+        let method = method(vec![
+            ast::Stmt::inhale(local("x")),
+            ast::Stmt::inhale(local("y")),
+            ast::Stmt::Assert(ast::Assert { expr: local("x"), position: ast::Position::new(1, 1, 1) }),
+            ast::Stmt::Assert(ast::Assert { expr: local("y"), position: ast::Position::new(2, 1, 2) }),
+        ]);
+        let dependencies = obligation_dependencies(&method);
+        assert_eq!(dependencies.len(), 2);
+        assert!(dependencies.iter().all(|deps| deps.depends_on.is_empty()));
+        // Each assertion only needs the inhale of its variable.
+        let statements = |deps: &ObligationDependencies| {
+            deps.assumptions.iter().map(|assumption| assumption.statement).collect::<Vec<_>>()
+        };
+        assert_eq!(statements(&dependencies[0]), vec![0]);
+        assert_eq!(statements(&dependencies[1]), vec![1]);
+        assert_eq!(
+            independent_groups(&dependencies),
+            vec![vec![ast::Position::new(1, 1, 1)], vec![ast::Position::new(2, 1, 2)]],
+        );
+    }
+
+    #[test]
+    fn test_dependent_asserts() {
+        let method = method(vec![
+            ast::Stmt::inhale(ast::Expr::and(local("x"), local("y")).set_pos(ast::Position::new(3, 1, 3))),
+            ast::Stmt::Assert(ast::Assert { expr: local("x"), position: ast::Position::new(1, 1, 1) }),
+            ast::Stmt::Assert(ast::Assert { expr: local("y"), position: ast::Position::new(2, 1, 2) }),
+        ]);
+        let dependencies = obligation_dependencies(&method);
+        assert_eq!(dependencies[1].depends_on, vec![ast::Position::new(1, 1, 1)]);
+        assert_eq!(
+            independent_groups(&dependencies),
+            vec![vec![ast::Position::new(1, 1, 1), ast::Position::new(2, 1, 2)]],
+        );
+        let json = format_dependencies_json(
+            vec![("m".to_string(), dependencies)],
+            |position| format!("test.rs:{}", position.id()),
+        );
+        assert!(json.contains(
+            "{\"id\": 2, \"location\": \"test.rs:2\", \"assumptions\": \
+            [{\"block\": 0, \"statement\": 0, \"location\": \"test.rs:3\"}], \"depends_on\": [1]}"
+        ));
+        assert!(json.contains("\"independent_groups\": [[1, 2]]"));
+    }
+
+    #[test]
+    fn test_branch_conditions() {
+        // The assertion in the `then` block is only executed if `c` holds.
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let start = method.add_block("start", vec![
+            ast::Stmt::inhale(local("c")),
+            ast::Stmt::inhale(local("x")),
+        ]);
+        let then = method.add_block("then", vec![
+            ast::Stmt::Assert(ast::Assert { expr: local("x"), position: ast::Position::new(1, 1, 1) }),
+        ]);
+        let end = method.add_block("end", vec![
+            ast::Stmt::Assert(ast::Assert { expr: local("y"), position: ast::Position::new(2, 1, 2) }),
+        ]);
+        let other = method.add_block("other", vec![
+            ast::Stmt::Assert(ast::Assert { expr: local("z"), position: ast::Position::new(3, 1, 3) }),
+        ]);
+        method.set_successor(start, cfg::Successor::GotoSwitch(vec![(local("c"), then)], other));
+        method.set_successor(then, cfg::Successor::Goto(end));
+        method.set_successor(end, cfg::Successor::Return);
+        method.set_successor(other, cfg::Successor::Return);
+        let dependencies = obligation_dependencies(&method);
+        assert_eq!(dependencies.len(), 3);
+        // Both inhales are needed, because `c` is a condition of the block.
+        assert_eq!(dependencies[0].assumptions.len(), 2);
+        // The condition is kept after the branch, so the assertion in `end`
+        // depends on the assertion in `then`.
+        assert_eq!(dependencies[1].depends_on, vec![ast::Position::new(1, 1, 1)]);
+        // The assertion in the other branch cannot be executed after the
+        // first one.
+        assert_eq!(dependencies[2].position, ast::Position::new(3, 1, 3));
+        assert_eq!(dependencies[2].assumptions.len(), 2);
+        assert!(dependencies[2].depends_on.is_empty());
+    }
+
+    #[test]
+    fn test_inhale_false() {
+        let method = method(vec![
+            ast::Stmt::Assert(ast::Assert { expr: local("x"), position: ast::Position::new(1, 1, 1) }),
+            ast::Stmt::inhale(false.into()),
+            ast::Stmt::Assert(ast::Assert { expr: local("x"), position: ast::Position::new(2, 1, 2) }),
+            ast::Stmt::Assert(ast::Assert { expr: local("y"), position: ast::Position::new(3, 1, 3) }),
+        ]);
+        let dependencies = obligation_dependencies(&method);
+        assert!(dependencies[0].assumptions.is_empty());
+        assert_eq!(
+            dependencies[1].assumptions,
+            vec![Assumption { block: 0, statement: 1, position: ast::Position::default() }],
+        );
+        assert_eq!(dependencies[1].depends_on, vec![ast::Position::new(1, 1, 1)]);
+        assert_eq!(dependencies[2].assumptions.len(), 1);
+        assert!(dependencies[2].depends_on.is_empty());
+    }
+}
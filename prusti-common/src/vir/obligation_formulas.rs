@@ -0,0 +1,94 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Render the formulas that the verifier has to prove, so that they can be
+//! shown to the user when the proof fails.
+
+use super::polymorphic_vir::{ast, cfg};
+use std::{collections::HashMap, hash::BuildHasher};
+
+/// Collect the formulas of all assertions and exhales of the method, keyed by
+/// their position. `source_names` maps the names of local variables to the
+/// names they have in the source code.
+pub fn obligation_formulas<S: BuildHasher>(
+    method: &cfg::CfgMethod,
+    source_names: &HashMap<String, String, S>,
+) -> Vec<(ast::Position, String)> {
+    let mut formulas = Vec::new();
+    for block in &method.basic_blocks {
+        for stmt in &block.stmts {
+            match stmt {
+                ast::Stmt::Assert(ast::Assert { expr, position })
+                | ast::Stmt::Exhale(ast::Exhale { expr, position })
+                    if !position.is_default() =>
+                {
+                    formulas.push((*position, render_formula(expr.clone(), source_names)));
+                }
+                _ => {}
+            }
+        }
+    }
+    formulas
+}
+
+/// Render a formula with the local variables renamed according to
+/// `source_names`. The fields that store the value of a primitive type or the
+/// target of a reference (e.g. `x.val_int`) are omitted.
+pub fn render_formula<S: BuildHasher>(
+    expr: ast::Expr,
+    source_names: &HashMap<String, String, S>,
+) -> String {
+    struct Renamer<'a, S> {
+        source_names: &'a HashMap<String, String, S>,
+    }
+    impl<'a, S: BuildHasher> ast::ExprFolder for Renamer<'a, S> {
+        fn fold_local(&mut self, ast::Local { mut variable, position }: ast::Local) -> ast::Expr {
+            if let Some(name) = self.source_names.get(&variable.name) {
+                variable.name = name.clone();
+            }
+            ast::Expr::local_with_pos(variable, position)
+        }
+
+        fn fold_field(&mut self, expr: ast::FieldExpr) -> ast::Expr {
+            if expr.field.name.starts_with("val_") {
+                self.fold(*expr.base)
+            } else {
+                ast::Expr::Field(ast::FieldExpr {
+                    base: self.fold_boxed(expr.base),
+                    ..expr
+                })
+            }
+        }
+    }
+    ast::ExprFolder::fold(&mut Renamer { source_names }, expr).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_formula() {
+        let field = |name: &str| ast::Field::new(name, ast::Type::Int);
+        let result = ast::Expr::local(ast::LocalVar::new("_0", ast::Type::Int));
+        let arg = ast::Expr::local(ast::LocalVar::new("_1", ast::Type::Int));
+        let tmp = ast::Expr::local(ast::LocalVar::new("_3", ast::Type::Int));
+        let expr = ast::Expr::and(
+            ast::Expr::gt_cmp(
+                result.field(field("val_int")),
+                arg.field(field("f$count")).field(field("val_int")).old("pre"),
+            ),
+            ast::Expr::eq_cmp(tmp, 0.into()),
+        );
+        let mut source_names = HashMap::new();
+        source_names.insert("_0".to_string(), "result".to_string());
+        source_names.insert("_1".to_string(), "counter".to_string());
+        assert_eq!(
+            render_formula(expr, &source_names),
+            "((result) > (old[pre](counter.f$count))) && ((_3) == (0))",
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Support for measuring the backend time spent on each obligation of a
+//! program, i.e. on each assertion and exhale. Each obligation is verified on
+//! its own in a variant of the program in which the other obligations are
+//! assumed.
+
+use super::polymorphic_vir::{ast, Program};
+use std::time::Duration;
+
+/// The positions of the assertions and exhales of the program that can be
+/// profiled.
+pub fn obligation_positions(program: &Program) -> Vec<ast::Position> {
+    let mut positions = Vec::new();
+    for method in &program.methods {
+        for block in &method.basic_blocks {
+            for stmt in &block.stmts {
+                if let ast::Stmt::Assert(ast::Assert { position, .. })
+                | ast::Stmt::Exhale(ast::Exhale { position, .. }) = stmt
+                {
+                    if !position.is_default() && !positions.contains(position) {
+                        positions.push(*position);
+                    }
+                }
+            }
+        }
+    }
+    positions
+}
+
+/// Assume all obligations of the program, except for those with the given
+/// position. With `None`, all obligations are assumed. An assertion is
+/// assumed by inhaling it. An exhale still has to remove its permissions, so
+/// it is assumed by only exhaling the access predicates of its top-level
+/// conjunction.
+pub fn isolate_obligation(mut program: Program, position: Option<ast::Position>) -> Program {
+    for method in &mut program.methods {
+        for block in &mut method.basic_blocks {
+            for stmt in &mut block.stmts {
+                match stmt {
+                    ast::Stmt::Assert(ast::Assert { expr, position: pos })
+                        if Some(*pos) != position =>
+                    {
+                        *stmt = ast::Stmt::inhale(expr.clone());
+                    }
+                    ast::Stmt::Exhale(ast::Exhale { expr, position: pos })
+                        if Some(*pos) != position =>
+                    {
+                        *expr = expr.clone().filter_perm_conjunction();
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    program
+}
+
+/// Format the time spent on each obligation as CSV, starting from the slowest
+/// obligation.
+pub fn format_profile_csv(mut rows: Vec<(String, Duration)>) -> String {
+    rows.sort_by(|(name_a, time_a), (name_b, time_b)| {
+        time_b.cmp(time_a).then_with(|| name_a.cmp(name_b))
+    });
+    let mut csv = String::from("obligation,time_ms\n");
+    for (obligation, time) in rows {
+        csv.push_str(&format!(
+            "\"{}\",{:.3}\n",
+            obligation.replace('"', "\"\""),
+            time.as_secs_f64() * 1000.0,
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vir::polymorphic_vir::cfg;
+
+    #[test]
+    fn test_isolate_obligation() {
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let x = ast::Expr::local(ast::LocalVar::new("x", ast::Type::Bool));
+        let y = ast::Expr::local(ast::LocalVar::new("y", ast::Type::Bool));
+        let z = ast::Expr::local(ast::LocalVar::new("z", ast::Type::Bool));
+        let block = method.add_block(
+            "start",
+            vec![
+                ast::Stmt::Assert(ast::Assert { expr: x, position: ast::Position::new(1, 1, 1) }),
+                ast::Stmt::Assert(ast::Assert { expr: y, position: ast::Position::new(2, 1, 2) }),
+                ast::Stmt::Exhale(ast::Exhale { expr: z, position: ast::Position::new(3, 1, 3) }),
+            ],
+        );
+        method.set_successor(block, cfg::Successor::Return);
+        let program = Program {
+            name: "p".to_string(),
+            domains: vec![],
+            fields: vec![],
+            builtin_methods: vec![],
+            methods: vec![method],
+            functions: vec![],
+            viper_predicates: vec![],
+        };
+
+        let positions = obligation_positions(&program);
+        assert_eq!(positions.len(), 3);
+        let isolated_assertion = isolate_obligation(program.clone(), Some(positions[1]));
+        let stmts: Vec<String> = isolated_assertion.methods[0].basic_blocks[0]
+            .stmts
+            .iter()
+            .map(|stmt| stmt.to_string())
+            .collect();
+        assert_eq!(stmts, vec!["inhale x", "assert y", "exhale true"]);
+        let isolated_exhale = isolate_obligation(program, Some(positions[2]));
+        let stmts: Vec<String> = isolated_exhale.methods[0].basic_blocks[0]
+            .stmts
+            .iter()
+            .map(|stmt| stmt.to_string())
+            .collect();
+        assert_eq!(stmts, vec!["inhale x", "inhale y", "exhale z"]);
+    }
+
+    #[test]
+    fn test_format_profile_csv() {
+        let csv = format_profile_csv(vec![
+            ("test.rs:3:5".to_string(), Duration::from_millis(20)),
+            ("test.rs:4:5".to_string(), Duration::from_millis(150)),
+        ]);
+        assert_eq!(
+            csv,
+            "obligation,time_ms\n\"test.rs:4:5\",150.000\n\"test.rs:3:5\",20.000\n",
+        );
+    }
+}
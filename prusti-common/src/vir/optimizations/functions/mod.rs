@@ -8,5 +8,9 @@
 
 mod inliner;
 mod simplifier;
+mod specializer;
 
-pub use self::{inliner::inline_constant_functions, simplifier::Simplifier};
+pub use self::{
+    inliner::inline_constant_functions, simplifier::Simplifier,
+    specializer::specialize_polymorphic_functions,
+};
@@ -0,0 +1,361 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Specialization of polymorphic pure functions.
+
+use crate::vir::polymorphic_vir::{ast, cfg};
+use fxhash::FxHashMap;
+use std::mem;
+
+/// The maximum number of distinct type shapes at which a polymorphic function
+/// is specialized. Functions that are instantiated at more shapes keep being
+/// called through their generic version.
+const MAX_SPECIALIZATIONS: usize = 4;
+
+/// The concrete types of the formal arguments at a call site.
+type Shape = Vec<ast::Type>;
+
+/// The instantiation of the type variables of a function.
+type Substs = FxHashMap<ast::TypeVar, ast::Type>;
+
+/// For every function whose signature mentions type variables, collect the
+/// concrete type shapes at which the methods call it. If there are at most
+/// `MAX_SPECIALIZATIONS` of them, emit a monomorphic copy of the function per
+/// shape and redirect the calls to the copies. The generic function is kept,
+/// because other functions may still call it.
+pub fn specialize_polymorphic_functions(
+    mut methods: Vec<cfg::CfgMethod>,
+    mut functions: Vec<ast::Function>,
+) -> (Vec<cfg::CfgMethod>, Vec<ast::Function>) {
+    trace!("[enter] specialize_polymorphic_functions");
+    let generic_functions: FxHashMap<String, ast::Function> = functions
+        .iter()
+        .filter(|function| is_polymorphic(function))
+        .map(|function| (function.name.clone(), function.clone()))
+        .collect();
+    if generic_functions.is_empty() {
+        return (methods, functions);
+    }
+
+    let mut collector = ShapeCollector {
+        generic_functions: &generic_functions,
+        shapes: FxHashMap::default(),
+    };
+    for method in &methods {
+        method.walk_expressions(|expr| ast::ExprWalker::walk(&mut collector, expr));
+    }
+
+    let mut specializations = FxHashMap::default();
+    let mut shapes: Vec<_> = collector.shapes.into_iter().collect();
+    shapes.sort_by(|(left, _), (right, _)| left.cmp(right));
+    for (function_name, function_shapes) in shapes {
+        if function_shapes.len() > MAX_SPECIALIZATIONS {
+            debug!(
+                "Not specializing {}: instantiated at {} shapes",
+                function_name,
+                function_shapes.len()
+            );
+            continue;
+        }
+        let function = &generic_functions[&function_name];
+        for (index, (shape, substs)) in function_shapes.into_iter().enumerate() {
+            let specialized = specialize(function, &substs, index);
+            specializations.insert(
+                (function_name.clone(), shape),
+                (specialized.name.clone(), substs),
+            );
+            functions.push(specialized);
+        }
+    }
+
+    let mut rewriter = CallRewriter {
+        generic_functions: &generic_functions,
+        specializations: &specializations,
+    };
+    for method in &mut methods {
+        let mut sentinel_stmt = ast::Stmt::comment("moved out stmt");
+        for block in &mut method.basic_blocks {
+            for stmt in &mut block.stmts {
+                mem::swap(&mut sentinel_stmt, stmt);
+                sentinel_stmt = ast::StmtFolder::fold(&mut rewriter, sentinel_stmt);
+                mem::swap(&mut sentinel_stmt, stmt);
+            }
+            if let cfg::Successor::GotoSwitch(targets, _) = &mut block.successor {
+                for (guard, _) in targets {
+                    let old_guard = mem::replace(guard, true.into());
+                    *guard = ast::ExprFolder::fold(&mut rewriter, old_guard);
+                }
+            }
+        }
+    }
+    (methods, functions)
+}
+
+fn is_polymorphic(function: &ast::Function) -> bool {
+    function
+        .formal_args
+        .iter()
+        .any(|arg| contains_type_var(&arg.typ))
+        || contains_type_var(&function.return_type)
+}
+
+fn contains_type_var(typ: &ast::Type) -> bool {
+    match typ {
+        ast::Type::TypeVar(_) => true,
+        ast::Type::Seq(ast::SeqType { box typ }) => contains_type_var(typ),
+        ast::Type::TypedRef(ast::TypedRef { arguments, .. })
+        | ast::Type::Domain(ast::DomainType { arguments, .. })
+        | ast::Type::Snapshot(ast::SnapshotType { arguments, .. }) => {
+            arguments.iter().any(contains_type_var)
+        }
+        _ => false,
+    }
+}
+
+/// Match the type `pattern` of a formal argument against the concrete type
+/// `actual` of the passed argument, extending `substs` with the
+/// instantiations of the type variables.
+fn unify(pattern: &ast::Type, actual: &ast::Type, substs: &mut Substs) -> bool {
+    match (pattern, actual) {
+        (ast::Type::TypeVar(type_var), _) => {
+            if let Some(instantiation) = substs.get(type_var) {
+                instantiation == actual
+            } else {
+                substs.insert(type_var.clone(), actual.clone());
+                true
+            }
+        }
+        (ast::Type::Seq(pattern), ast::Type::Seq(actual)) => {
+            unify(&pattern.typ, &actual.typ, substs)
+        }
+        (ast::Type::TypedRef(pattern), ast::Type::TypedRef(actual)) => {
+            pattern.label == actual.label
+                && unify_all(&pattern.arguments, &actual.arguments, substs)
+        }
+        (ast::Type::Domain(pattern), ast::Type::Domain(actual)) => {
+            pattern.label == actual.label
+                && unify_all(&pattern.arguments, &actual.arguments, substs)
+        }
+        (ast::Type::Snapshot(pattern), ast::Type::Snapshot(actual)) => {
+            pattern.label == actual.label
+                && unify_all(&pattern.arguments, &actual.arguments, substs)
+        }
+        _ => pattern == actual,
+    }
+}
+
+fn unify_all(patterns: &[ast::Type], actuals: &[ast::Type], substs: &mut Substs) -> bool {
+    patterns.len() == actuals.len()
+        && patterns
+            .iter()
+            .zip(actuals)
+            .all(|(pattern, actual)| unify(pattern, actual, substs))
+}
+
+/// Compute the shape of a call to the given generic function. Returns `None`
+/// if the call is itself polymorphic or does not match the signature.
+fn call_shape(function: &ast::Function, arguments: &[ast::Expr]) -> Option<(Shape, Substs)> {
+    if function.formal_args.len() != arguments.len() {
+        return None;
+    }
+    let mut substs = FxHashMap::default();
+    let mut shape = Vec::with_capacity(arguments.len());
+    for (formal_arg, argument) in function.formal_args.iter().zip(arguments) {
+        let actual = argument.get_type();
+        if contains_type_var(actual) || !unify(&formal_arg.typ, actual, &mut substs) {
+            return None;
+        }
+        shape.push(actual.clone());
+    }
+    if contains_type_var(&function.return_type.clone().patch(&substs)) {
+        return None;
+    }
+    Some((shape, substs))
+}
+
+fn specialize(function: &ast::Function, substs: &Substs, index: usize) -> ast::Function {
+    ast::Function {
+        name: format!("{}$spec${}", function.name, index),
+        type_arguments: function
+            .type_arguments
+            .iter()
+            .map(|typ| typ.clone().patch(substs))
+            .collect(),
+        formal_args: function
+            .formal_args
+            .iter()
+            .map(|arg| ast::LocalVar::new(&arg.name, arg.typ.clone().patch(substs)))
+            .collect(),
+        return_type: function.return_type.clone().patch(substs),
+        pres: function
+            .pres
+            .iter()
+            .map(|pre| pre.clone().patch_types(substs))
+            .collect(),
+        posts: function
+            .posts
+            .iter()
+            .map(|post| post.clone().patch_types(substs))
+            .collect(),
+        body: function.body.clone().map(|body| body.patch_types(substs)),
+    }
+}
+
+struct ShapeCollector<'a> {
+    generic_functions: &'a FxHashMap<String, ast::Function>,
+    /// The shapes of the calls of every generic function, in order of
+    /// appearance.
+    shapes: FxHashMap<String, Vec<(Shape, Substs)>>,
+}
+
+impl<'a> ast::ExprWalker for ShapeCollector<'a> {
+    fn walk_func_app(&mut self, func_app: &ast::FuncApp) {
+        for argument in &func_app.arguments {
+            self.walk(argument);
+        }
+        if let Some(function) = self.generic_functions.get(&func_app.function_name) {
+            if let Some((shape, substs)) = call_shape(function, &func_app.arguments) {
+                let shapes = self
+                    .shapes
+                    .entry(func_app.function_name.clone())
+                    .or_default();
+                if !shapes.iter().any(|(known_shape, _)| known_shape == &shape) {
+                    shapes.push((shape, substs));
+                }
+            }
+        }
+    }
+}
+
+/// Redirect calls of generic functions to their specializations.
+struct CallRewriter<'a> {
+    generic_functions: &'a FxHashMap<String, ast::Function>,
+    specializations: &'a FxHashMap<(String, Shape), (String, Substs)>,
+}
+
+impl<'a> ast::StmtFolder for CallRewriter<'a> {
+    fn fold_expr(&mut self, expr: ast::Expr) -> ast::Expr {
+        ast::ExprFolder::fold(self, expr)
+    }
+}
+
+impl<'a> ast::ExprFolder for CallRewriter<'a> {
+    fn fold_func_app(
+        &mut self,
+        ast::FuncApp {
+            function_name,
+            type_arguments,
+            arguments,
+            formal_arguments,
+            return_type,
+            position,
+        }: ast::FuncApp,
+    ) -> ast::Expr {
+        let arguments: Vec<_> = arguments.into_iter().map(|e| self.fold(e)).collect();
+        let specialization = self
+            .generic_functions
+            .get(&function_name)
+            .and_then(|function| call_shape(function, &arguments))
+            .and_then(|(shape, _)| self.specializations.get(&(function_name.clone(), shape)));
+        if let Some((specialized_name, substs)) = specialization {
+            ast::Expr::FuncApp(ast::FuncApp {
+                function_name: specialized_name.clone(),
+                type_arguments: type_arguments
+                    .into_iter()
+                    .map(|typ| typ.patch(substs))
+                    .collect(),
+                arguments,
+                formal_arguments: formal_arguments
+                    .into_iter()
+                    .map(|arg| ast::LocalVar::new(arg.name, arg.typ.patch(substs)))
+                    .collect(),
+                return_type: return_type.patch(substs),
+                position,
+            })
+        } else {
+            ast::Expr::FuncApp(ast::FuncApp {
+                function_name,
+                type_arguments,
+                arguments,
+                formal_arguments,
+                return_type,
+                position,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(argument: ast::Expr) -> ast::Expr {
+        ast::Expr::func_app(
+            "id".to_string(),
+            vec![ast::Type::type_var("T")],
+            vec![argument],
+            vec![ast::LocalVar::new("x", ast::Type::type_var("T"))],
+            ast::Type::type_var("T"),
+            ast::Position::default(),
+        )
+    }
+
+    fn called_functions(method: &cfg::CfgMethod) -> Vec<String> {
+        let mut names = Vec::new();
+        method.walk_expressions(|expr| {
+            if let ast::Expr::FuncApp(ast::FuncApp { function_name, .. }) = expr {
+                names.push(function_name.clone());
+            }
+        });
+        names
+    }
+
+    #[test]
+    fn test_specialize_function_used_at_two_types() {
+        let typ = ast::Type::type_var("T");
+        let function = ast::Function {
+            name: "id".to_string(),
+            type_arguments: vec![typ.clone()],
+            formal_args: vec![ast::LocalVar::new("x", typ.clone())],
+            return_type: typ.clone(),
+            pres: vec![],
+            posts: vec![],
+            body: Some(ast::Expr::local(ast::LocalVar::new("x", typ))),
+        };
+        let int_var = ast::LocalVar::new("i", ast::Type::Int);
+        let bool_var = ast::LocalVar::new("b", ast::Type::Bool);
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let block = method.add_block(
+            "start",
+            vec![
+                ast::Stmt::Assert(ast::Assert {
+                    expr: call(ast::Expr::local(int_var.clone())),
+                    position: ast::Position::default(),
+                }),
+                ast::Stmt::Assert(ast::Assert {
+                    expr: call(ast::Expr::local(bool_var)),
+                    position: ast::Position::default(),
+                }),
+                ast::Stmt::Assert(ast::Assert {
+                    expr: call(ast::Expr::local(int_var)),
+                    position: ast::Position::default(),
+                }),
+            ],
+        );
+        method.set_successor(block, cfg::Successor::Return);
+
+        let (methods, functions) =
+            specialize_polymorphic_functions(vec![method], vec![function]);
+
+        assert_eq!(
+            called_functions(&methods[0]),
+            vec!["id$spec$0", "id$spec$1", "id$spec$0"]
+        );
+        assert_eq!(functions.len(), 3);
+        assert_eq!(functions[1].return_type, ast::Type::Int);
+        assert_eq!(functions[2].return_type, ast::Type::Bool);
+    }
+}
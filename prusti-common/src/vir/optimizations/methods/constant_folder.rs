@@ -0,0 +1,353 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Optimization that folds operations on constants.
+
+use crate::vir::polymorphic_vir::{self as vir, BinaryOpKind, Const, ExprFolder, UnaryOpKind};
+
+/// Simplify the expressions of the method:
+/// * operations on integer and boolean constants, e.g. `2 + 3` to `5` and
+///   `4 < 5` to `true`;
+/// * identities, e.g. `e + 0`, `e * 1`, `e && true` and `e || false` to `e`;
+/// * annihilators, e.g. `e && false` to `false`, if evaluating `e` cannot
+///   fail;
+/// * conditionals with a constant guard;
+/// * double negations.
+///
+/// The integers of VIR are unbounded, because the overflow checks are encoded
+/// as separate assertions, so folding them does not change the semantics.
+/// Division and modulo are only folded on non-negative operands, on which all
+/// their definitions agree. Expressions that might not be well-defined, e.g.
+/// function applications that have preconditions, are never removed.
+pub fn fold_constants(mut method: vir::CfgMethod) -> vir::CfgMethod {
+    let mut folder = ConstantFolder;
+    for block in &mut method.basic_blocks {
+        let stmts = std::mem::take(&mut block.stmts);
+        block.stmts = stmts
+            .into_iter()
+            .map(|stmt| vir::StmtFolder::fold(&mut folder, stmt))
+            .collect();
+        if let vir::Successor::GotoSwitch(targets, _) = &mut block.successor {
+            for (guard, _) in targets.iter_mut() {
+                let expr = std::mem::replace(guard, true.into());
+                *guard = ExprFolder::fold(&mut folder, expr);
+            }
+        }
+    }
+    method
+}
+
+struct ConstantFolder;
+
+impl vir::StmtFolder for ConstantFolder {
+    fn fold_expr(&mut self, expr: vir::Expr) -> vir::Expr {
+        ExprFolder::fold(self, expr)
+    }
+}
+
+impl ExprFolder for ConstantFolder {
+    fn fold_unary_op(&mut self, expr: vir::UnaryOp) -> vir::Expr {
+        let vir::UnaryOp {
+            op_kind,
+            argument,
+            position,
+        } = expr;
+        let argument = self.fold(*argument);
+        let folded = match (op_kind, &argument) {
+            (UnaryOpKind::Not, vir::Expr::Const(vir::ConstExpr { value: Const::Bool(value), .. })) => {
+                Some((!value).into())
+            }
+            (UnaryOpKind::Minus, vir::Expr::Const(vir::ConstExpr { value: Const::Int(value), .. })) => {
+                value.checked_neg().map(vir::Expr::from)
+            }
+            (
+                _,
+                vir::Expr::UnaryOp(vir::UnaryOp {
+                    op_kind: inner_op_kind,
+                    argument: inner_argument,
+                    ..
+                }),
+            ) if *inner_op_kind == op_kind => Some((**inner_argument).clone()),
+            _ => None,
+        };
+        match folded {
+            Some(folded) => with_position(folded, position),
+            None => vir::Expr::UnaryOp(vir::UnaryOp {
+                op_kind,
+                argument: Box::new(argument),
+                position,
+            }),
+        }
+    }
+
+    fn fold_bin_op(&mut self, expr: vir::BinOp) -> vir::Expr {
+        let vir::BinOp {
+            op_kind,
+            left,
+            right,
+            position,
+        } = expr;
+        let left = self.fold(*left);
+        let right = self.fold(*right);
+        match fold_bin_op(op_kind, &left, &right) {
+            Some(folded) => with_position(folded, position),
+            None => vir::Expr::BinOp(vir::BinOp {
+                op_kind,
+                left: Box::new(left),
+                right: Box::new(right),
+                position,
+            }),
+        }
+    }
+
+    fn fold_cond(&mut self, expr: vir::Cond) -> vir::Expr {
+        let vir::Cond {
+            guard,
+            then_expr,
+            else_expr,
+            position,
+        } = expr;
+        let guard = self.fold(*guard);
+        let then_expr = self.fold(*then_expr);
+        let else_expr = self.fold(*else_expr);
+        match bool_const(&guard) {
+            Some(true) => with_position(then_expr, position),
+            Some(false) => with_position(else_expr, position),
+            None => vir::Expr::Cond(vir::Cond {
+                guard: Box::new(guard),
+                then_expr: Box::new(then_expr),
+                else_expr: Box::new(else_expr),
+                position,
+            }),
+        }
+    }
+}
+
+/// Give the folded expression the position of the expression it replaces,
+/// unless it has a position of its own.
+fn with_position(folded: vir::Expr, position: vir::Position) -> vir::Expr {
+    if folded.pos().is_default() {
+        folded.set_pos(position)
+    } else {
+        folded
+    }
+}
+
+fn bool_const(expr: &vir::Expr) -> Option<bool> {
+    match expr {
+        vir::Expr::Const(vir::ConstExpr {
+            value: Const::Bool(value),
+            ..
+        }) => Some(*value),
+        _ => None,
+    }
+}
+
+fn int_const(expr: &vir::Expr) -> Option<i64> {
+    match expr {
+        vir::Expr::Const(vir::ConstExpr {
+            value: Const::Int(value),
+            ..
+        }) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Whether evaluating the expression cannot fail, so that it can be removed
+/// without removing a check.
+fn is_well_defined(expr: &vir::Expr) -> bool {
+    match expr {
+        vir::Expr::Local(_) | vir::Expr::Const(_) => true,
+        vir::Expr::UnaryOp(vir::UnaryOp { argument, .. }) => is_well_defined(argument),
+        vir::Expr::BinOp(vir::BinOp {
+            op_kind: BinaryOpKind::Div | BinaryOpKind::Mod,
+            ..
+        }) => false,
+        vir::Expr::BinOp(vir::BinOp { left, right, .. }) => {
+            is_well_defined(left) && is_well_defined(right)
+        }
+        vir::Expr::Cond(vir::Cond {
+            guard,
+            then_expr,
+            else_expr,
+            ..
+        }) => is_well_defined(guard) && is_well_defined(then_expr) && is_well_defined(else_expr),
+        _ => false,
+    }
+}
+
+fn fold_bin_op(op_kind: BinaryOpKind, left: &vir::Expr, right: &vir::Expr) -> Option<vir::Expr> {
+    if let (Some(left), Some(right)) = (int_const(left), int_const(right)) {
+        return match op_kind {
+            BinaryOpKind::Add => left.checked_add(right).map(vir::Expr::from),
+            BinaryOpKind::Sub => left.checked_sub(right).map(vir::Expr::from),
+            BinaryOpKind::Mul => left.checked_mul(right).map(vir::Expr::from),
+            BinaryOpKind::Div if left >= 0 && right > 0 => Some((left / right).into()),
+            BinaryOpKind::Mod if left >= 0 && right > 0 => Some((left % right).into()),
+            BinaryOpKind::EqCmp => Some((left == right).into()),
+            BinaryOpKind::NeCmp => Some((left != right).into()),
+            BinaryOpKind::LtCmp => Some((left < right).into()),
+            BinaryOpKind::LeCmp => Some((left <= right).into()),
+            BinaryOpKind::GtCmp => Some((left > right).into()),
+            BinaryOpKind::GeCmp => Some((left >= right).into()),
+            _ => None,
+        };
+    }
+    if let (Some(left), Some(right)) = (bool_const(left), bool_const(right)) {
+        return match op_kind {
+            BinaryOpKind::And => Some((left && right).into()),
+            BinaryOpKind::Or => Some((left || right).into()),
+            BinaryOpKind::Implies => Some((!left || right).into()),
+            BinaryOpKind::EqCmp => Some((left == right).into()),
+            BinaryOpKind::NeCmp => Some((left != right).into()),
+            _ => None,
+        };
+    }
+    let (left_int, right_int) = (int_const(left), int_const(right));
+    let (left_bool, right_bool) = (bool_const(left), bool_const(right));
+    match op_kind {
+        BinaryOpKind::Add if right_int == Some(0) => Some(left.clone()),
+        BinaryOpKind::Add if left_int == Some(0) => Some(right.clone()),
+        BinaryOpKind::Sub if right_int == Some(0) => Some(left.clone()),
+        BinaryOpKind::Mul if right_int == Some(1) => Some(left.clone()),
+        BinaryOpKind::Mul if left_int == Some(1) => Some(right.clone()),
+        BinaryOpKind::Mul if right_int == Some(0) && is_well_defined(left) => Some(0.into()),
+        BinaryOpKind::Mul if left_int == Some(0) && is_well_defined(right) => Some(0.into()),
+        BinaryOpKind::And if right_bool == Some(true) => Some(left.clone()),
+        BinaryOpKind::And if left_bool == Some(true) => Some(right.clone()),
+        // The right operand is not evaluated.
+        BinaryOpKind::And if left_bool == Some(false) => Some(false.into()),
+        BinaryOpKind::And if right_bool == Some(false) && is_well_defined(left) => {
+            Some(false.into())
+        }
+        BinaryOpKind::Or if right_bool == Some(false) => Some(left.clone()),
+        BinaryOpKind::Or if left_bool == Some(false) => Some(right.clone()),
+        BinaryOpKind::Or if left_bool == Some(true) => Some(true.into()),
+        BinaryOpKind::Or if right_bool == Some(true) && is_well_defined(left) => Some(true.into()),
+        BinaryOpKind::Implies if left_bool == Some(true) => Some(right.clone()),
+        BinaryOpKind::Implies if left_bool == Some(false) => Some(true.into()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vir::polymorphic_vir::{ast, cfg};
+
+    fn int(name: &str) -> vir::Expr {
+        vir::Expr::local(vir::LocalVar::new(name, vir::Type::Int))
+    }
+
+    fn boolean(name: &str) -> vir::Expr {
+        vir::Expr::local(vir::LocalVar::new(name, vir::Type::Bool))
+    }
+
+    fn fold(expr: vir::Expr) -> vir::Expr {
+        ExprFolder::fold(&mut ConstantFolder, expr)
+    }
+
+    fn call() -> vir::Expr {
+        vir::Expr::func_app(
+            "f".to_string(),
+            vec![],
+            vec![],
+            vec![],
+            vir::Type::Bool,
+            vir::Position::default(),
+        )
+    }
+
+    #[test]
+    fn test_fold_literals() {
+        assert_eq!(fold(vir::Expr::add(2.into(), 3.into())), vir::Expr::from(5));
+        assert_eq!(fold(vir::Expr::mul(vir::Expr::sub(7.into(), 3.into()), 2.into())), vir::Expr::from(8));
+        assert_eq!(fold(vir::Expr::lt_cmp(4.into(), 5.into())), vir::Expr::from(true));
+        assert_eq!(fold(vir::Expr::eq_cmp(4.into(), 5.into())), vir::Expr::from(false));
+        assert_eq!(fold(vir::Expr::and(true.into(), false.into())), vir::Expr::from(false));
+        assert_eq!(fold(vir::Expr::implies(false.into(), boolean("b"))), vir::Expr::from(true));
+        assert_eq!(fold(vir::Expr::minus(3.into())), vir::Expr::from(-3));
+        // Overflowing literals and division of negative numbers are kept.
+        let overflowing = vir::Expr::add(i64::MAX.into(), vir::Expr::from(1));
+        assert_eq!(fold(overflowing.clone()), overflowing);
+        let division = vir::Expr::div((-7).into(), vir::Expr::from(2));
+        assert_eq!(fold(division.clone()), division);
+        assert_eq!(fold(vir::Expr::div(7.into(), 2.into())), vir::Expr::from(3));
+    }
+
+    #[test]
+    fn test_fold_identities() {
+        assert_eq!(fold(vir::Expr::add(int("x"), 0.into())), int("x"));
+        assert_eq!(fold(vir::Expr::add(0.into(), int("x"))), int("x"));
+        assert_eq!(fold(vir::Expr::mul(1.into(), int("x"))), int("x"));
+        assert_eq!(fold(vir::Expr::mul(int("x"), 1.into())), int("x"));
+        assert_eq!(fold(vir::Expr::and(boolean("b"), true.into())), boolean("b"));
+        assert_eq!(fold(vir::Expr::or(boolean("b"), false.into())), boolean("b"));
+        assert_eq!(
+            fold(vir::Expr::ite(true.into(), int("x"), int("y"))),
+            int("x")
+        );
+    }
+
+    #[test]
+    fn test_fold_annihilators() {
+        assert_eq!(fold(vir::Expr::and(boolean("b"), false.into())), vir::Expr::from(false));
+        assert_eq!(fold(vir::Expr::or(boolean("b"), true.into())), vir::Expr::from(true));
+        assert_eq!(fold(vir::Expr::mul(int("x"), 0.into())), vir::Expr::from(0));
+        // The function application might not be well-defined.
+        let conjunction = vir::Expr::and(call(), vir::Expr::from(false));
+        assert_eq!(fold(conjunction.clone()), conjunction);
+        assert_eq!(fold(vir::Expr::and(false.into(), call())), vir::Expr::from(false));
+    }
+
+    #[test]
+    fn test_fold_double_negation() {
+        assert_eq!(fold(vir::Expr::not(vir::Expr::not(boolean("b")))), boolean("b"));
+        assert_eq!(fold(vir::Expr::minus(vir::Expr::minus(int("x")))), int("x"));
+    }
+
+    #[test]
+    fn test_keep_position() {
+        let position = vir::Position::new(1, 2, 3);
+        let folded = fold(vir::Expr::add(2.into(), 3.into()).set_pos(position));
+        assert_eq!(folded.pos(), position);
+        let folded = fold(vir::Expr::add(int("x"), 0.into()).set_pos(position));
+        assert_eq!(folded, int("x"));
+        assert_eq!(folded.pos(), position);
+    }
+
+    #[test]
+    fn test_fold_method() {
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let position = vir::Position::new(1, 2, 3);
+        let block = method.add_block(
+            "start",
+            vec![
+                ast::Stmt::inhale(vir::Expr::and(boolean("b"), vir::Expr::not(vir::Expr::not(true.into())))),
+                ast::Stmt::Assert(ast::Assert {
+                    expr: vir::Expr::eq_cmp(vir::Expr::add(int("x"), vir::Expr::mul(0.into(), int("y"))), int("x")),
+                    position,
+                }),
+            ],
+        );
+        method.set_successor(block, cfg::Successor::Return);
+        let folded = fold_constants(method);
+        assert_eq!(
+            folded.basic_blocks[0].stmts,
+            vec![
+                ast::Stmt::inhale(boolean("b")),
+                ast::Stmt::Assert(ast::Assert {
+                    expr: vir::Expr::eq_cmp(int("x"), int("x")),
+                    position,
+                }),
+            ]
+        );
+        // Folding again does not change the method.
+        let stmts = folded.basic_blocks[0].stmts.clone();
+        let folded = fold_constants(folded);
+        assert_eq!(folded.basic_blocks[0].stmts, stmts);
+    }
+}
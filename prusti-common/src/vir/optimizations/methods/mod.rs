@@ -8,6 +8,7 @@
 
 mod assert_remover;
 mod cfg_cleaner;
+mod constant_folder;
 mod empty_if_remover;
 mod purifier;
 mod quantifier_fixer;
@@ -19,20 +20,27 @@ use crate::{config::Optimizations, vir::polymorphic_vir::cfg::CfgMethod};
 
 use self::{
     assert_remover::remove_trivial_assertions, cfg_cleaner::clean_cfg,
-    empty_if_remover::remove_empty_if, purifier::purify_vars, quantifier_fixer::fix_quantifiers,
-    unfolding_fixer::fix_unfoldings, var_remover::remove_unused_vars,
+    constant_folder::fold_constants, empty_if_remover::remove_empty_if, purifier::purify_vars,
+    quantifier_fixer::fix_quantifiers, unfolding_fixer::fix_unfoldings,
+    var_remover::remove_unused_vars,
 };
 
+/// Apply the enabled optimizations to the method. If `changes` is given, the
+/// names of the optimizations that modified the method are appended to it.
 #[allow(clippy::let_and_return)]
 pub fn optimize_method_encoding(
     cfg: CfgMethod,
     source_file_name: &str,
     optimizations: &Optimizations,
+    mut changes: Option<&mut Vec<&'static str>>,
 ) -> CfgMethod {
     macro_rules! apply {
         ($optimization: ident, $cfg: ident) => {
             if optimizations.$optimization {
                 log_method(source_file_name, &$cfg, stringify!($optimization), false);
+                let original = changes
+                    .as_ref()
+                    .map(|_| ($cfg.basic_blocks.clone(), $cfg.local_vars.clone()));
                 let optimized_cfg = $optimization($cfg);
                 log_method(
                     source_file_name,
@@ -40,6 +48,15 @@ pub fn optimize_method_encoding(
                     stringify!($optimization),
                     true,
                 );
+                if let (Some(changes), Some((basic_blocks, local_vars))) =
+                    (changes.as_mut(), original)
+                {
+                    if basic_blocks != optimized_cfg.basic_blocks
+                        || local_vars != optimized_cfg.local_vars
+                    {
+                        changes.push(stringify!($optimization));
+                    }
+                }
                 optimized_cfg
             } else {
                 $cfg
@@ -49,6 +66,7 @@ pub fn optimize_method_encoding(
     let cfg = apply!(purify_vars, cfg);
     let cfg = apply!(fix_unfoldings, cfg);
     let cfg = apply!(fix_quantifiers, cfg);
+    let cfg = apply!(fold_constants, cfg);
     let cfg = apply!(remove_empty_if, cfg);
     let cfg = apply!(remove_unused_vars, cfg);
     let cfg = apply!(remove_trivial_assertions, cfg);
@@ -56,3 +74,50 @@ pub fn optimize_method_encoding(
 
     cfg
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vir::polymorphic_vir::{ast, cfg};
+
+    #[test]
+    fn test_optimization_changes() {
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        method.add_local_var("unused", ast::Type::Int);
+        let x = ast::Expr::local(ast::LocalVar::new("x", ast::Type::Bool));
+        let block = method.add_block(
+            "start",
+            vec![
+                ast::Stmt::inhale(x),
+                ast::Stmt::Assert(ast::Assert {
+                    expr: true.into(),
+                    position: ast::Position::default(),
+                }),
+            ],
+        );
+        method.set_successor(block, cfg::Successor::Return);
+        let optimizations = Optimizations {
+            inline_constant_functions: false,
+            specialize_functions: false,
+            delete_unused_predicates: false,
+            optimize_folding: false,
+            remove_empty_if: true,
+            purify_vars: false,
+            fix_quantifiers: false,
+            fix_unfoldings: false,
+            fold_constants: false,
+            remove_unused_vars: true,
+            remove_trivial_assertions: true,
+            clean_cfg: false,
+        };
+
+        let mut changes = vec![];
+        let method =
+            optimize_method_encoding(method, "test.rs", &optimizations, Some(&mut changes));
+        assert_eq!(
+            changes,
+            vec!["remove_unused_vars", "remove_trivial_assertions"]
+        );
+        assert!(method.local_vars.is_empty());
+    }
+}
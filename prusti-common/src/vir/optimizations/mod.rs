@@ -98,6 +98,24 @@ pub fn optimize_program(p: Program, source_file_name: &str) -> Program {
             true,
         );
     }
+    if optimizations.specialize_functions {
+        log_methods(
+            source_file_name,
+            &program.methods,
+            "specialize_functions",
+            false,
+        );
+        let (new_methods, new_functions) =
+            functions::specialize_polymorphic_functions(program.methods, program.functions);
+        program.methods = new_methods;
+        program.functions = new_functions;
+        log_methods(
+            source_file_name,
+            &program.methods,
+            "specialize_functions",
+            true,
+        );
+    }
     if optimizations.optimize_folding {
         log_methods(source_file_name, &program.methods, "folding", false);
         program.methods = program
@@ -112,11 +130,32 @@ pub fn optimize_program(p: Program, source_file_name: &str) -> Program {
             .collect();
         log_methods(source_file_name, &program.methods, "folding", true);
     }
+    let dump_changes = config::dump_optimization_changes();
+    let mut changes_summary = String::new();
     program.methods = program
         .methods
         .into_iter()
-        .map(|method| methods::optimize_method_encoding(method, source_file_name, &optimizations))
+        .map(|method| {
+            let mut changes = vec![];
+            let method = methods::optimize_method_encoding(
+                method,
+                source_file_name,
+                &optimizations,
+                if dump_changes { Some(&mut changes) } else { None },
+            );
+            if dump_changes {
+                changes_summary.push_str(&format!("{}: {}\n", method.name(), changes.join(", ")));
+            }
+            method
+        })
         .collect();
+    if dump_changes {
+        crate::report::log::report(
+            "optimization_changes",
+            format!("{}.{}.txt", source_file_name, program.name),
+            changes_summary,
+        );
+    }
     if optimizations.delete_unused_predicates {
         program.viper_predicates = predicates::delete_unused_predicates(
             &program.methods,
@@ -0,0 +1,92 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Support for checking whether the preconditions of a method are needed for
+//! its proof. Each precondition is inhaled by a separate statement that
+//! follows a marker comment, so that it can be dropped from the program
+//! without re-encoding the method.
+
+use super::polymorphic_vir::{ast, Program};
+
+/// The comment that precedes the inhale of the precondition with the given
+/// index.
+pub fn precondition_conjunct_marker(index: usize) -> String {
+    format!("Precondition conjunct {}", index)
+}
+
+/// Replace the inhale of the precondition with the given index by
+/// `inhale true` in all methods of the program.
+pub fn drop_precondition_conjunct(mut program: Program, index: usize) -> Program {
+    let marker = precondition_conjunct_marker(index);
+    for method in &mut program.methods {
+        for block in &mut method.basic_blocks {
+            let mut after_marker = false;
+            for stmt in &mut block.stmts {
+                match stmt {
+                    ast::Stmt::Comment(ast::Comment { comment }) if comment == &marker => {
+                        after_marker = true;
+                    }
+                    ast::Stmt::Inhale(ast::Inhale { expr }) if after_marker => {
+                        *expr = true.into();
+                        after_marker = false;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    program
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vir::polymorphic_vir::cfg;
+
+    #[test]
+    fn test_drop_precondition_conjunct() {
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let x = ast::Expr::local(ast::LocalVar::new("x", ast::Type::Bool));
+        let y = ast::Expr::local(ast::LocalVar::new("y", ast::Type::Bool));
+        let block = method.add_block(
+            "start",
+            vec![
+                ast::Stmt::comment(precondition_conjunct_marker(0)),
+                ast::Stmt::inhale(x),
+                ast::Stmt::comment(precondition_conjunct_marker(1)),
+                ast::Stmt::comment("source: test.rs:3:1"),
+                ast::Stmt::inhale(y),
+            ],
+        );
+        method.set_successor(block, cfg::Successor::Return);
+        let program = Program {
+            name: "p".to_string(),
+            domains: vec![],
+            fields: vec![],
+            builtin_methods: vec![],
+            methods: vec![method],
+            functions: vec![],
+            viper_predicates: vec![],
+        };
+
+        let program = drop_precondition_conjunct(program, 1);
+        let stmts: Vec<String> = program.methods[0].basic_blocks[0]
+            .stmts
+            .iter()
+            .map(|stmt| stmt.to_string())
+            .collect();
+        assert_eq!(
+            stmts,
+            vec![
+                "// Precondition conjunct 0",
+                "inhale x",
+                "// Precondition conjunct 1",
+                "// source: test.rs:3:1",
+                "inhale true",
+            ]
+        );
+    }
+}
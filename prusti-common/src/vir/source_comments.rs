@@ -0,0 +1,111 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Annotate the statements of a method with the source location they were
+//! generated from. This is only used to make the dumped encoding easier to
+//! read and does not change the verification result.
+
+use super::polymorphic_vir::{ast, cfg};
+
+/// Insert a `// source: <location>` comment in front of every top-level
+/// statement that has a position. `get_source_location` translates a
+/// position to a human-readable source location, e.g. `file.rs:3:5`.
+///
+/// To keep the output readable, a comment is emitted only when the location
+/// differs from the one of the previous annotated statement of the same block.
+pub fn add_source_comments<F>(mut method: cfg::CfgMethod, get_source_location: F) -> cfg::CfgMethod
+where
+    F: Fn(ast::Position) -> Option<String>,
+{
+    for block in &mut method.basic_blocks {
+        let mut last_location = None;
+        let mut stmts = Vec::with_capacity(block.stmts.len());
+        for stmt in block.stmts.drain(..) {
+            let location = stmt_position(&stmt).and_then(&get_source_location);
+            if location.is_some() && location != last_location {
+                stmts.push(ast::Stmt::comment(format!(
+                    "source: {}",
+                    location.as_ref().unwrap()
+                )));
+                last_location = location;
+            }
+            stmts.push(stmt);
+        }
+        block.stmts = stmts;
+    }
+    method
+}
+
+/// The position that identifies the source of a statement, if any.
+fn stmt_position(stmt: &ast::Stmt) -> Option<ast::Position> {
+    let position = match stmt {
+        ast::Stmt::Assert(ast::Assert { position, .. })
+        | ast::Stmt::Exhale(ast::Exhale { position, .. })
+        | ast::Stmt::Fold(ast::Fold { position, .. })
+        | ast::Stmt::PackageMagicWand(ast::PackageMagicWand { position, .. })
+        | ast::Stmt::ApplyMagicWand(ast::ApplyMagicWand { position, .. }) => *position,
+        ast::Stmt::Inhale(ast::Inhale { expr }) => expr.pos(),
+        ast::Stmt::Assign(ast::Assign { target, source, .. }) => {
+            if target.pos().is_default() {
+                source.pos()
+            } else {
+                target.pos()
+            }
+        }
+        _ => return None,
+    };
+    if position.is_default() {
+        None
+    } else {
+        Some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_source_comments() {
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let assert = |line| {
+            ast::Stmt::Assert(ast::Assert {
+                expr: true.into(),
+                position: ast::Position::new(line, 1, line as u64),
+            })
+        };
+        let block = method.add_block(
+            "start",
+            vec![
+                ast::Stmt::comment("no position"),
+                assert(3),
+                assert(3),
+                assert(4),
+            ],
+        );
+        method.set_successor(block, cfg::Successor::Return);
+
+        let method = add_source_comments(method, |position| {
+            Some(format!("test.rs:{}:{}", position.line(), position.column()))
+        });
+        let stmts: Vec<String> = method.basic_blocks[0]
+            .stmts
+            .iter()
+            .map(|stmt| stmt.to_string())
+            .collect();
+        assert_eq!(
+            stmts,
+            vec![
+                "// no position",
+                "// source: test.rs:3:1",
+                "assert true",
+                "assert true",
+                "// source: test.rs:4:1",
+                "assert true",
+            ]
+        );
+    }
+}
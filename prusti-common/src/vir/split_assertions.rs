@@ -0,0 +1,141 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Split the assertion of a conjunction into one assertion per conjunct, so
+//! that a failure is reported at the failing conjunct. The backend checks the
+//! assertions in order and assumes every conjunct that it has checked, so the
+//! later conjuncts can rely on the earlier ones as with `&&`.
+
+use super::polymorphic_vir::{ast, cfg};
+
+/// Replace every `assert c1 && ... && cn` of the method by the sequence
+/// `assert c1; ...; assert cn`. A conditional `c ? e : false`, which is how
+/// a `&&` of a specification is encoded, is treated as the conjunction
+/// `c && e`. `split_position` is called with the position of the original
+/// assertion and the position of a conjunct, and returns the position of the
+/// assertion of the conjunct.
+pub fn split_conjunctive_assertions<F>(mut method: cfg::CfgMethod, mut split_position: F) -> cfg::CfgMethod
+where
+    F: FnMut(ast::Position, ast::Position) -> ast::Position,
+{
+    for block in &mut method.basic_blocks {
+        let mut stmts = Vec::with_capacity(block.stmts.len());
+        for stmt in block.stmts.drain(..) {
+            match stmt {
+                ast::Stmt::Assert(ast::Assert { expr, position }) if is_conjunction(&expr) => {
+                    let mut conjuncts = vec![];
+                    collect_conjuncts(expr, &mut conjuncts);
+                    for conjunct in conjuncts {
+                        let conjunct_position = if position.is_default() {
+                            position
+                        } else {
+                            split_position(position, conjunct.pos())
+                        };
+                        stmts.push(ast::Stmt::Assert(ast::Assert {
+                            expr: conjunct,
+                            position: conjunct_position,
+                        }));
+                    }
+                }
+                stmt => stmts.push(stmt),
+            }
+        }
+        block.stmts = stmts;
+    }
+    method
+}
+
+fn is_conjunction(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::BinOp(ast::BinOp {
+            op_kind: ast::BinaryOpKind::And,
+            ..
+        }) => true,
+        ast::Expr::Cond(ast::Cond { else_expr, .. }) => matches!(
+            **else_expr,
+            ast::Expr::Const(ast::ConstExpr {
+                value: ast::Const::Bool(false),
+                ..
+            })
+        ),
+        _ => false,
+    }
+}
+
+fn collect_conjuncts(expr: ast::Expr, conjuncts: &mut Vec<ast::Expr>) {
+    if !is_conjunction(&expr) {
+        conjuncts.push(expr);
+        return;
+    }
+    match expr {
+        ast::Expr::BinOp(ast::BinOp { left, right, .. }) => {
+            collect_conjuncts(*left, conjuncts);
+            collect_conjuncts(*right, conjuncts);
+        }
+        ast::Expr::Cond(ast::Cond {
+            guard, then_expr, ..
+        }) => {
+            collect_conjuncts(*guard, conjuncts);
+            collect_conjuncts(*then_expr, conjuncts);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_conjunctive_assertions() {
+        let mut method = cfg::CfgMethod::new("m".to_string(), 0, vec![], vec![], vec![]);
+        let var = |name: &str, id| {
+            ast::Expr::local_with_pos(
+                ast::LocalVar::new(name, ast::Type::Bool),
+                ast::Position::new(id as i32, 1, id),
+            )
+        };
+        let block = method.add_block(
+            "start",
+            vec![
+                ast::Stmt::Assert(ast::Assert {
+                    expr: ast::Expr::and(
+                        ast::Expr::and(var("a", 1), var("b", 2)),
+                        ast::Expr::ite(var("c", 3), var("d", 4), false.into()),
+                    ),
+                    position: ast::Position::new(10, 1, 10),
+                }),
+                ast::Stmt::Assert(ast::Assert {
+                    expr: ast::Expr::or(var("e", 5), var("f", 6)),
+                    position: ast::Position::new(11, 1, 11),
+                }),
+            ],
+        );
+        method.set_successor(block, cfg::Successor::Return);
+
+        let mut split = vec![];
+        let method = split_conjunctive_assertions(method, |position, conjunct_position| {
+            split.push((position.id(), conjunct_position.id()));
+            ast::Position::new(position.line(), 1, 100 + conjunct_position.id())
+        });
+        let stmts: Vec<String> = method.basic_blocks[0]
+            .stmts
+            .iter()
+            .map(|stmt| stmt.to_string())
+            .collect();
+        assert_eq!(
+            stmts,
+            vec![
+                "assert a",
+                "assert b",
+                "assert c",
+                "assert d",
+                "assert (e) || (f)",
+            ]
+        );
+        assert_eq!(split, vec![(10, 1), (10, 2), (10, 3), (10, 4)]);
+    }
+}
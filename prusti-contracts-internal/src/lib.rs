@@ -33,9 +33,24 @@ pub fn pure(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     rewrite_prusti_attributes(SpecAttributeKind::Pure, attr.into(), tokens.into()).into()
 }
 
+#[proc_macro_attribute]
+pub fn opaque_spec(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::OpaqueSpec, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn terminates(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::Terminates, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn case(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::Case, attr.into(), tokens.into()).into()
+}
+
 #[proc_macro_attribute]
 pub fn trusted(attr: TokenStream, tokens: TokenStream) -> TokenStream {
-    rewrite_prusti_attributes(SpecAttributeKind::Trusted, attr.into(), tokens.into()).into()
+    prusti_specs::trusted(attr.into(), tokens.into()).into()
 }
 
 #[proc_macro]
@@ -43,6 +58,11 @@ pub fn body_invariant(tokens: TokenStream) -> TokenStream {
     prusti_specs::body_invariant(tokens.into()).into()
 }
 
+#[proc_macro]
+pub fn break_ensures(tokens: TokenStream) -> TokenStream {
+    prusti_specs::break_ensures(tokens.into()).into()
+}
+
 #[proc_macro]
 pub fn closure(tokens: TokenStream) -> TokenStream {
     prusti_specs::closure(tokens.into(), false).into()
@@ -53,6 +73,11 @@ pub fn refine_trait_spec(attr: TokenStream, tokens: TokenStream) -> TokenStream
     prusti_specs::refine_trait_spec(attr.into(), tokens.into()).into()
 }
 
+#[proc_macro_attribute]
+pub fn assumptions(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::assumptions(attr.into(), tokens.into()).into()
+}
+
 #[proc_macro_attribute]
 pub fn extern_spec(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     prusti_specs::extern_spec(attr.into(), tokens.into()).into()
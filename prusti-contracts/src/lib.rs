@@ -20,9 +20,21 @@ mod private {
     /// A macro for marking a function as trusted.
     pub use prusti_contracts_impl::trusted;
 
+    /// A macro for exposing a weaker contract to callers outside of the module.
+    pub use prusti_contracts_impl::opaque_spec;
+
+    /// A macro for the termination measure of a pure function.
+    pub use prusti_contracts_impl::terminates;
+
+    /// A macro for a precondition and postcondition pair of a procedure.
+    pub use prusti_contracts_impl::case;
+
     /// A macro for writing a loop body invariant.
     pub use prusti_contracts_impl::body_invariant;
 
+    /// A macro for writing what holds when a loop is left with a `break`.
+    pub use prusti_contracts_impl::break_ensures;
+
     /// A macro for defining a closure with a specification.
     /// Note: this is a declarative macro defined in this crate
     /// because declarative macros can't be exported from
@@ -41,6 +53,9 @@ mod private {
     /// A macro for impl blocks that refine trait specifications.
     pub use prusti_contracts_impl::refine_trait_spec;
 
+    /// A macro for assertions that are assumed by all functions of a module.
+    pub use prusti_contracts_impl::assumptions;
+
     /// A macro for specifying external functions.
     pub use prusti_contracts_impl::extern_spec;
 
@@ -76,15 +91,30 @@ mod private {
     /// A macro for marking a function as trusted.
     pub use prusti_contracts_internal::trusted;
 
+    /// A macro for exposing a weaker contract to callers outside of the module.
+    pub use prusti_contracts_internal::opaque_spec;
+
+    /// A macro for the termination measure of a pure function.
+    pub use prusti_contracts_internal::terminates;
+
+    /// A macro for a precondition and postcondition pair of a procedure.
+    pub use prusti_contracts_internal::case;
+
     /// A macro for writing a loop body invariant.
     pub use prusti_contracts_internal::body_invariant;
 
+    /// A macro for writing what holds when a loop is left with a `break`.
+    pub use prusti_contracts_internal::break_ensures;
+
     /// A macro for defining a closure with a specification.
     pub use prusti_contracts_internal::closure;
 
     /// A macro for impl blocks that refine trait specifications.
     pub use prusti_contracts_internal::refine_trait_spec;
 
+    /// A macro for assertions that are assumed by all functions of a module.
+    pub use prusti_contracts_internal::assumptions;
+
     /// A macro for specifying external functions.
     pub use prusti_contracts_internal::extern_spec;
 
@@ -124,4 +154,54 @@ pub fn exists<T, F>(_trigger_set: T, _closure: F) -> bool {
     true
 }
 
+/// This function is used to count the integers `i` for which the condition
+/// of `count(|i: usize| condition)` holds. The condition must bound `i`, e.g.
+/// `0 <= i && i < n`.
+pub fn count<F>(_closure: F) -> usize {
+    0
+}
+
+/// This function is used to specify that the array or slice `a` is a
+/// permutation of `b`, i.e. that they are equal as multisets.
+pub fn is_permutation<T: ?Sized>(_a: &T, _b: &T) -> bool {
+    true
+}
+
+/// This function is used to specify that `a` and `b` are snapshot-equal,
+/// i.e. that they have the same value in every field, without calling their
+/// `PartialEq` implementation.
+pub fn snap_eq<T>(_a: T, _b: T) -> bool {
+    true
+}
+
+/// This function is used to specify that the flag `flag` is set in `value`,
+/// i.e. that `value & flag != 0`. The value can be an integer or a struct
+/// whose only field is an integer, as used by `bitflags`-style types.
+pub fn has_flag<T, F>(_value: T, _flag: F) -> bool {
+    true
+}
+
+/// This function is used to specify in a postcondition that the returned
+/// reference `result` borrows from the argument `arg`, i.e. that the type of
+/// the result shares a lifetime with the type of the argument. This is
+/// checked against the signature of the function, so the call can only be a
+/// conjunct of a postcondition.
+pub fn borrows_from<R: ?Sized, A: ?Sized>(_result: &R, _arg: &A) -> bool {
+    true
+}
+
+/// This function is used in the specifications of a loop to refer to the
+/// number of iterations of the loop that have been completed, e.g. the number
+/// of elements that a `for` loop has processed before the current one.
+pub fn loop_iterations() -> usize {
+    0
+}
+
+/// This function is used by the projection `e.Variant.field` on the field of
+/// an enum variant, as the value of the projection when `e` is of another
+/// variant.
+pub fn inactive_variant_field<T>() -> T {
+    unreachable!()
+}
+
 pub use private::*;
@@ -0,0 +1,55 @@
+use rustc_ast::ast;
+use rustc_hir::intravisit::{Visitor, walk_expr, walk_local};
+use rustc_hir as hir;
+use rustc_middle::hir::map::Map;
+use rustc_span::Span;
+
+use crate::utils::has_prusti_attr;
+
+/// Collects the expressions and `let` statements of a body that carry a
+/// `prusti::<attr_name>` attribute.
+pub struct CollectAttributedCodeVisitor<'a, 'tcx> {
+    map: Map<'tcx>,
+    attr_name: &'a str,
+    result: Vec<(Span, &'tcx [ast::Attribute])>,
+}
+
+impl<'a, 'tcx> CollectAttributedCodeVisitor<'a, 'tcx> {
+    pub fn new(map: Map<'tcx>, attr_name: &'a str) -> Self {
+        CollectAttributedCodeVisitor {
+            map,
+            attr_name,
+            result: Vec::new(),
+        }
+    }
+
+    pub fn get_attributed_code(self) -> Vec<(Span, &'tcx [ast::Attribute])> {
+        self.result
+    }
+
+    fn check_attrs(&mut self, hir_id: hir::HirId, span: Span) {
+        let attrs = self.map.attrs(hir_id);
+        if has_prusti_attr(attrs, self.attr_name) {
+            self.result.push((span, attrs));
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for CollectAttributedCodeVisitor<'a, 'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = rustc_middle::hir::nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.map
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        self.check_attrs(expr.hir_id, expr.span);
+        walk_expr(self, expr)
+    }
+
+    fn visit_local(&mut self, local: &'tcx hir::Local<'tcx>) {
+        self.check_attrs(local.hir_id, local.span);
+        walk_local(self, local)
+    }
+}
@@ -11,7 +11,7 @@
 use rustc_middle::mir;
 use rustc_hir::hir_id::HirId;
 use rustc_hir::def_id::{DefId, LocalDefId};
-use rustc_middle::ty::{self, TyCtxt};
+use rustc_middle::ty::{self, DefIdTree, TyCtxt};
 use rustc_middle::ty::subst::{Subst, SubstsRef};
 use rustc_trait_selection::infer::{InferCtxtExt, TyCtxtInferExt};
 use std::path::PathBuf;
@@ -27,6 +27,7 @@ use std::cell::RefCell;
 pub mod borrowck;
 mod collect_prusti_spec_visitor;
 mod collect_closure_defs_visitor;
+mod collect_attributed_code_visitor;
 mod dump_borrowck_info;
 mod loops;
 mod loops_utils;
@@ -41,6 +42,7 @@ mod traits;
 
 use self::collect_prusti_spec_visitor::CollectPrustiSpecVisitor;
 use self::collect_closure_defs_visitor::CollectClosureDefsVisitor;
+use self::collect_attributed_code_visitor::CollectAttributedCodeVisitor;
 use rustc_hir::intravisit::Visitor;
 pub use self::loops::{PlaceAccess, PlaceAccessKind, ProcedureLoops};
 pub use self::loops_utils::*;
@@ -58,6 +60,9 @@ struct CachedBody<'tcx> {
     base_body: Rc<mir::Body<'tcx>>,
     /// Copies of the MIR body with the given substs applied.
     monomorphised_bodies: HashMap<SubstsRef<'tcx>, Rc<mir::Body<'tcx>>>,
+    /// Monomorphised copies of the MIR body, normalized in the parameter
+    /// environment of a caller.
+    normalized_bodies: HashMap<(SubstsRef<'tcx>, DefId), Rc<mir::Body<'tcx>>>,
     /// Cached borrowck information.
     borrowck_facts: Rc<BorrowckFacts>,
 }
@@ -204,15 +209,137 @@ impl<'tcx> Environment<'tcx> {
 
         let mut result: Vec<_> = visitor.get_annotated_procedures();
         result.extend(cl_visitor.get_closure_defs());
+        result.retain(|def_id| !self.is_ignored(*def_id));
         result
     }
 
+    /// Get the spans and attributes of the expressions and `let` statements in
+    /// the body of the procedure that are annotated with `prusti::<name>`.
+    pub fn get_attributed_code(
+        &self,
+        def_id: LocalDefId,
+        name: &str,
+    ) -> Vec<(Span, &'tcx [rustc_ast::ast::Attribute])> {
+        let map = self.tcx.hir();
+        let body_id = map.body_owned_by(map.local_def_id_to_hir_id(def_id));
+        let mut visitor = CollectAttributedCodeVisitor::new(map, name);
+        visitor.visit_body(map.body(body_id));
+        visitor.get_attributed_code()
+    }
+
     /// Find whether the procedure has a particular `prusti::<name>` attribute.
     pub fn has_prusti_attribute(&self, def_id: ProcedureDefId, name: &str) -> bool {
         let tcx = self.tcx();
         crate::utils::has_prusti_attr(tcx.get_attrs(def_id), name)
     }
 
+    /// Find whether the procedure is defined in a module that has a particular
+    /// `#[prusti::<name>]` attribute, either directly or in one of the
+    /// enclosing modules.
+    pub fn has_prusti_module_attribute(&self, def_id: ProcedureDefId, name: &str) -> bool {
+        let mut current = if let Some(local_def_id) = def_id.as_local() {
+            local_def_id
+        } else {
+            return false;
+        };
+        loop {
+            let module = self.tcx.parent_module_from_def_id(current);
+            if crate::utils::has_prusti_attr(self.tcx.get_attrs(module.to_def_id()), name) {
+                return true;
+            }
+            if module == rustc_hir::def_id::CRATE_DEF_ID {
+                return false;
+            }
+            current = module;
+        }
+    }
+
+    /// Find whether the procedure has an ABI other than the Rust one, e.g. an
+    /// `extern "C"` function that is exported with `#[no_mangle]`. A panic
+    /// must not unwind out of such a procedure.
+    pub fn has_foreign_abi(&self, def_id: ProcedureDefId) -> bool {
+        use rustc_target::spec::abi::Abi;
+        if self.tcx.is_closure(def_id) {
+            return false;
+        }
+        !matches!(
+            self.tcx.fn_sig(def_id).abi(),
+            Abi::Rust | Abi::RustCall | Abi::RustIntrinsic | Abi::PlatformIntrinsic
+        )
+    }
+
+    /// Find whether `caller_def_id` is defined outside of the module that
+    /// contains the procedure `def_id`, including in its nested modules.
+    pub fn is_outside_module_of(&self, caller_def_id: DefId, def_id: ProcedureDefId) -> bool {
+        match def_id.as_local() {
+            Some(local_def_id) => {
+                let module = self.tcx.parent_module_from_def_id(local_def_id);
+                !self.tcx.is_descendant_of(caller_def_id, module.to_def_id())
+            }
+            None => true,
+        }
+    }
+
+    /// Find whether the procedure is ignored, i.e. neither encoded nor
+    /// verified, because it is defined in a module with the
+    /// `#[prusti::ignored]` attribute. The `#[prusti::trusted_boundary]`
+    /// functions of such a module are trusted instead.
+    pub fn is_ignored(&self, def_id: ProcedureDefId) -> bool {
+        self.has_prusti_module_attribute(def_id, "ignored")
+            && !self.has_prusti_attribute(def_id, "trusted_boundary")
+    }
+
+    /// The paths of the contract functions declared by the
+    /// `#[prusti::fn_contract(path)]` attributes on the parameters of the local
+    /// function `def_id`, with the index and the span of the parameter.
+    pub fn get_fn_pointer_contracts(
+        &self,
+        def_id: ProcedureDefId,
+    ) -> Vec<(usize, Span, Result<String, String>)> {
+        let local_def_id = match def_id.as_local() {
+            Some(local_def_id) => local_def_id,
+            None => return vec![],
+        };
+        let hir = self.tcx.hir();
+        let body_id = match hir.maybe_body_owned_by(hir.local_def_id_to_hir_id(local_def_id)) {
+            Some(body_id) => body_id,
+            None => return vec![],
+        };
+        hir.body(body_id).params
+            .iter()
+            .enumerate()
+            .filter_map(|(index, param)| {
+                crate::utils::read_prusti_attr_path("fn_contract", hir.attrs(param.hir_id))
+                    .map(|path| (index, param.span, path))
+            })
+            .collect()
+    }
+
+    /// Resolve the path of a local function, as written in the module that
+    /// contains `scope`. Only paths that are relative to that module or to the
+    /// crate root are supported.
+    pub fn resolve_local_function_path(&self, scope: DefId, path: &str) -> Option<ProcedureDefId> {
+        let path = path.trim_start_matches("crate::").trim_start_matches("self::");
+        let mut candidates = vec![path.to_string()];
+        if let Some(local_scope) = scope.as_local() {
+            let module = self.tcx.parent_module_from_def_id(local_scope);
+            let module_path = self.tcx.def_path_str(module.to_def_id());
+            if !module_path.is_empty() {
+                candidates.insert(0, format!("{}::{}", module_path, path));
+            }
+        }
+        candidates.into_iter().find_map(|candidate| {
+            self.tcx.hir().body_owners()
+                .map(LocalDefId::to_def_id)
+                .find(|&def_id| {
+                    matches!(
+                        self.tcx.def_kind(def_id),
+                        rustc_hir::def::DefKind::Fn | rustc_hir::def::DefKind::AssocFn
+                    ) && self.tcx.def_path_str(def_id) == candidate
+                })
+        })
+    }
+
     /// Dump various information from the borrow checker.
     ///
     /// Mostly used for experiments and debugging.
@@ -259,6 +386,15 @@ impl<'tcx> Environment<'tcx> {
         Procedure::new(self, proc_def_id)
     }
 
+    /// Get the instance of a generic procedure with the given substitutions.
+    pub fn get_procedure_instance(
+        &self,
+        proc_def_id: ProcedureDefId,
+        substs: SubstsRef<'tcx>,
+    ) -> Procedure<'tcx> {
+        Procedure::new_with_substs(self, proc_def_id, substs)
+    }
+
     /// Get the MIR body of a local procedure, monomorphised with the given
     /// type substitutions.
     pub fn local_mir(
@@ -284,6 +420,7 @@ impl<'tcx> Environment<'tcx> {
                 CachedBody {
                     base_body: Rc::new(body),
                     monomorphised_bodies: HashMap::new(),
+                    normalized_bodies: HashMap::new(),
                     borrowck_facts: Rc::new(facts),
                 }
             });
@@ -294,6 +431,41 @@ impl<'tcx> Environment<'tcx> {
             .clone()
     }
 
+    /// Get the MIR body of a local procedure monomorphised with the given
+    /// substitutions, with its associated types normalized in the parameter
+    /// environment of `caller_def_id`. This resolves the projections that the
+    /// substitution makes resolvable, e.g. `<T as Iterator>::Item` with
+    /// `T = std::vec::IntoIter<u32>` or with a caller bound
+    /// `S: Iterator<Item = U>`. The regions of a normalized body are erased.
+    pub fn local_mir_in_caller_env(
+        &self,
+        def_id: LocalDefId,
+        substs: SubstsRef<'tcx>,
+        caller_def_id: DefId,
+    ) -> Rc<mir::Body<'tcx>> {
+        use crate::rustc_middle::ty::TypeFoldable;
+        let body = self.local_mir(def_id, substs);
+        if !body.has_projections() {
+            return body;
+        }
+        let mut bodies = self.bodies.borrow_mut();
+        let cached_body = bodies.get_mut(&def_id).unwrap();
+        cached_body
+            .normalized_bodies
+            .entry((substs, caller_def_id))
+            .or_insert_with(|| {
+                let param_env = self.tcx.param_env(caller_def_id);
+                match self.tcx.try_normalize_erasing_regions(param_env, body.clone()) {
+                    Ok(normalized) => normalized,
+                    Err(err) => {
+                        debug!("Error while normalizing the body of {:?}: {:?}", def_id, err);
+                        body
+                    }
+                }
+            })
+            .clone()
+    }
+
     /// Get Polonius facts of a local procedure.
     pub fn local_mir_borrowck_facts(&self, def_id: LocalDefId) -> Rc<BorrowckFacts> {
         self.try_get_local_mir_borrowck_facts(def_id).unwrap()
@@ -361,6 +533,71 @@ impl<'tcx> Environment<'tcx> {
             .is_some()
     }
 
+    /// Returns true iff `def_id` is an `unsafe fn`
+    pub fn is_unsafe_function(&self, def_id: ProcedureDefId) -> bool {
+        matches!(
+            self.tcx.def_kind(def_id),
+            rustc_hir::def::DefKind::Fn | rustc_hir::def::DefKind::AssocFn
+        ) && self.tcx.fn_sig(def_id).unsafety() == rustc_hir::Unsafety::Unsafe
+    }
+
+    /// If the closure `def_id` is immediately invoked where it is defined, as
+    /// in `(|| { ... })()`, returns the procedure that contains the invocation
+    /// together with, for each parameter of that procedure, the index of the
+    /// closure field that captures it and whether it is captured by reference.
+    ///
+    /// Returns `None` if the values of the parameters inside the closure might
+    /// differ from their values at the entry of the procedure, i.e. if some
+    /// parameter is a mutable binding, has a type with mutable references, or
+    /// is not captured as a whole.
+    pub fn get_iife_caller(
+        &self,
+        def_id: ProcedureDefId,
+    ) -> Option<(ProcedureDefId, Vec<(mir::Field, bool)>)> {
+        let map = self.tcx.hir();
+        let hir_id = map.local_def_id_to_hir_id(def_id.as_local()?);
+        match map.find(map.get_parent_node(hir_id)) {
+            Some(rustc_hir::Node::Expr(rustc_hir::Expr {
+                kind: rustc_hir::ExprKind::Call(callee, _),
+                ..
+            })) if callee.hir_id == hir_id => {}
+            _ => return None,
+        }
+        let caller_def_id = self.tcx.typeck_root_def_id(def_id);
+        if !matches!(
+            self.tcx.def_kind(caller_def_id),
+            rustc_hir::def::DefKind::Fn | rustc_hir::def::DefKind::AssocFn
+        ) {
+            return None;
+        }
+        let has_mutable_ref = self.tcx.fn_sig(caller_def_id).skip_binder().inputs().iter()
+            .any(|ty| ty.walk().any(|arg| matches!(
+                arg.unpack(),
+                ty::subst::GenericArgKind::Type(ty) if ty.is_mutable_ptr()
+            )));
+        if has_mutable_ref {
+            return None;
+        }
+        let caller_hir_id = map.local_def_id_to_hir_id(caller_def_id.as_local()?);
+        let caller_body = map.body(map.body_owned_by(caller_hir_id));
+        let captures: Vec<_> = self.tcx.closure_min_captures_flattened(def_id).collect();
+        caller_body.params.iter().map(|param| {
+            let param_hir_id = match param.pat.kind {
+                rustc_hir::PatKind::Binding(rustc_hir::BindingAnnotation::Unannotated, param_hir_id, _, None) => {
+                    param_hir_id
+                }
+                _ => return None,
+            };
+            let index = captures.iter().position(|captured_place| {
+                captured_place.get_root_variable() == param_hir_id
+                    && captured_place.place.projections.is_empty()
+            })?;
+            let by_ref = matches!(captures[index].info.capture_kind, ty::UpvarCapture::ByRef(_));
+            Some((mir::Field::from_usize(index), by_ref))
+        }).collect::<Option<Vec<_>>>()
+            .map(|captures| (caller_def_id, captures))
+    }
+
     /// Returns the `DefId` of the corresponding trait method, if any.
     /// This should not be used to resolve calls (where substs are known): use
     /// `find_trait_method_substs` instead!
@@ -486,11 +723,41 @@ impl<'tcx> Environment<'tcx> {
         }
 
         let param_env = self.tcx.param_env(caller_def_id);
-        traits::resolve_instance(self.tcx, param_env.and((called_def_id, call_substs)))
+        let (resolved_def_id, resolved_substs) = traits::resolve_instance(self.tcx, param_env.and((called_def_id, call_substs)))
             .map(|opt_instance| opt_instance
                 .map(|instance| (instance.def_id(), instance.substs))
                 .unwrap_or((called_def_id, call_substs)))
-            .unwrap_or((called_def_id, call_substs))
+            .unwrap_or((called_def_id, call_substs));
+        if let Some(from_call) = self.blanket_into_as_from(resolved_def_id, resolved_substs) {
+            return self.resolve_method_call(caller_def_id, from_call.0, from_call.1);
+        }
+        (resolved_def_id, resolved_substs)
+    }
+
+    /// The blanket implementation `impl<T, U: From<T>> Into<U> for T` only
+    /// calls `U::from`. If `def_id` is its `into`, returns `<U as From<T>>::from`
+    /// instead, so that a call to `into` uses the specification of `from`.
+    fn blanket_into_as_from(
+        &self,
+        def_id: ProcedureDefId,
+        substs: SubstsRef<'tcx>,
+    ) -> Option<(ProcedureDefId, SubstsRef<'tcx>)> {
+        let impl_def_id = self.tcx.impl_of_method(def_id)?;
+        let trait_def_id = self.tcx.trait_id_of_impl(impl_def_id)?;
+        if Some(trait_def_id) != self.tcx.get_diagnostic_item(rustc_span::sym::Into)
+            || !matches!(self.tcx.type_of(impl_def_id).kind(), ty::TyKind::Param(_))
+            || substs.len() != 2
+        {
+            return None;
+        }
+        let from_trait = self.tcx.get_diagnostic_item(rustc_span::sym::From)?;
+        let from_fn = self.tcx.associated_items(from_trait)
+            .in_definition_order()
+            .find(|item| item.kind == ty::AssocKind::Fn)?
+            .def_id;
+        // The substitutions of the blanket implementation are `[T, U]`, the
+        // ones of `From::from` are `[Self = U, T]`.
+        Some((from_fn, self.tcx.intern_substs(&[substs[1], substs[0]])))
     }
 
     /// Checks whether `ty` is copy.
@@ -543,6 +810,20 @@ impl<'tcx> Environment<'tcx> {
         })
     }
 
+    /// Returns the type of a field of an ADT instantiated with `substs`, with
+    /// its associated types resolved where possible. For example, the field of
+    /// `Cow::<str>::Owned` has type `String` rather than `<str as ToOwned>::Owned`,
+    /// which is the type that the MIR of a function uses for its values.
+    pub fn field_ty(&self, field: &ty::FieldDef, substs: SubstsRef<'tcx>) -> ty::Ty<'tcx> {
+        use crate::rustc_middle::ty::TypeFoldable;
+        let field_ty = field.ty(self.tcx, substs);
+        if field_ty.has_projections() {
+            self.resolve_assoc_types(field_ty, ty::ParamEnv::reveal_all())
+        } else {
+            field_ty
+        }
+    }
+
     /// Normalizes associated types in foldable types,
     /// i.e. this resolves projection types ([ty::TyKind::Projection]s)
     /// **Important:** Regions while be erased during this process
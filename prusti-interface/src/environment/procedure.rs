@@ -8,7 +8,7 @@ use super::loops;
 use crate::data::ProcedureDefId;
 use rustc_middle::mir::{self, Body as Mir, Rvalue, AggregateKind};
 use rustc_middle::mir::{BasicBlock, BasicBlockData};
-use rustc_middle::ty::{Ty, TyCtxt};
+use rustc_middle::ty::{subst::SubstsRef, Ty, TyCtxt};
 
 use std::rc::Rc;
 use std::collections::{HashSet, HashMap};
@@ -28,6 +28,9 @@ pub type BasicBlockIndex = mir::BasicBlock;
 pub struct Procedure<'tcx> {
     tcx: TyCtxt<'tcx>,
     proc_def_id: ProcedureDefId,
+    /// The substitutions with which the procedure is monomorphised, the
+    /// identity unless the procedure is an instance of a generic procedure.
+    substs: SubstsRef<'tcx>,
     mir: Rc<Mir<'tcx>>,
     real_edges: RealEdges,
     loop_info: loops::ProcedureLoops,
@@ -39,10 +42,18 @@ impl<'tcx> Procedure<'tcx> {
     /// Builds an implementation of the Procedure interface, given a typing context and the
     /// identifier of a procedure
     pub fn new(env: &Environment<'tcx>, proc_def_id: ProcedureDefId) -> Self {
-        trace!("Encoding procedure {:?}", proc_def_id);
+        Self::new_with_substs(env, proc_def_id, env.identity_substs(proc_def_id))
+    }
+
+    /// Builds the instance of a generic procedure with the given substitutions.
+    pub fn new_with_substs(
+        env: &Environment<'tcx>,
+        proc_def_id: ProcedureDefId,
+        substs: SubstsRef<'tcx>,
+    ) -> Self {
+        trace!("Encoding procedure {:?} with substs {:?}", proc_def_id, substs);
         let tcx = env.tcx();
-        // TOOD(tymap): add substs to procedure? check usages
-        let mir = env.local_mir(proc_def_id.expect_local(), env.identity_substs(proc_def_id));
+        let mir = env.local_mir(proc_def_id.expect_local(), substs);
         let real_edges = RealEdges::new(&mir);
         let reachable_basic_blocks = build_reachable_basic_blocks(&mir, &real_edges);
         let nonspec_basic_blocks = build_nonspec_basic_blocks(&mir, &real_edges, &tcx);
@@ -51,6 +62,7 @@ impl<'tcx> Procedure<'tcx> {
         Self {
             tcx,
             proc_def_id,
+            substs,
             mir,
             real_edges,
             loop_info,
@@ -96,6 +108,11 @@ impl<'tcx> Procedure<'tcx> {
         self.proc_def_id
     }
 
+    /// Get the substitutions with which the procedure is monomorphised.
+    pub fn get_substs(&self) -> SubstsRef<'tcx> {
+        self.substs
+    }
+
     /// Get the MIR of the procedure
     pub fn get_mir(&self) -> &Mir<'tcx> {
         &self.mir
@@ -129,6 +129,14 @@ impl PrustiError {
         self.is_error
     }
 
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> &MultiSpan {
+        &self.span
+    }
+
     // FIXME: This flag is a temporary workaround for having duplicate errors
     // coming from verifying functions multiple times. We should verify each
     // function only once.
@@ -152,9 +160,18 @@ impl PrustiError {
         self.notes.push((message.to_string(), opt_span));
     }
 
-    /// Report the encoding error using the compiler's interface
-    pub fn emit(self, env: &Environment) {
+    /// Report the encoding error using the compiler's interface. With
+    /// `WARNINGS_AS_ERRORS`, a warning is reported as an error.
+    pub fn emit(mut self, env: &Environment) {
         assert!(!self.is_disabled);
+        if !self.is_error && config::warnings_as_errors() {
+            self.is_error = true;
+            self.notes.push((
+                "this warning is reported as an error because `WARNINGS_AS_ERRORS` is enabled"
+                    .to_string(),
+                None,
+            ));
+        }
         if self.is_error {
             env.span_err_with_help_and_notes(
                 self.span,
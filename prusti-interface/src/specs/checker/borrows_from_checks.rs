@@ -0,0 +1,156 @@
+use rustc_hir::{self as hir, def_id::LocalDefId, intravisit};
+use rustc_middle::{
+    hir::map::Map,
+    ty::{self, subst::GenericArgKind, TyCtxt},
+};
+use rustc_span::Span;
+use rustc_errors::MultiSpan;
+use super::common::*;
+use crate::{environment::Environment, PrustiError};
+use log::debug;
+use std::collections::HashSet;
+
+/// Checks that the result of a function borrows from the arguments named in
+/// the `borrows_from(result, arg)` of its postconditions, i.e. that in the
+/// signature of the function the type of the result shares a lifetime with the
+/// type of the argument. Since this is all that is checked, and the call is
+/// encoded as `true`, it is only allowed as a conjunct of a postcondition.
+pub struct BorrowsFromChecker;
+
+impl<'tcx> SpecCheckerStrategy<'tcx> for BorrowsFromChecker {
+    fn check(&self, env: &Environment<'tcx>) -> Vec<PrustiError> {
+        let mut collect = CollectPostconditionsVisitor::new(env.tcx());
+        env.tcx().hir().walk_toplevel_module(&mut collect);
+
+        let mut errors = vec![];
+        for (spec_id, fn_def_id) in collect.postconditions {
+            if let Some(&(spec_def_id, body_id)) = collect.spec_functions.get(&spec_id) {
+                let body = env.tcx().hir().body(body_id);
+                let conjunct_ids = conjuncts(&body.value).iter().map(|conjunct| conjunct.hir_id).collect();
+                let mut visitor = BorrowsFromVisitor {
+                    tcx: env.tcx(),
+                    fn_def_id,
+                    spec_def_id,
+                    conjuncts: conjunct_ids,
+                    errors: vec![],
+                };
+                intravisit::Visitor::visit_body(&mut visitor, body);
+                errors.extend(visitor.errors);
+            }
+        }
+        errors
+    }
+}
+
+/// Checks the `borrows_from` calls in the postcondition of a function.
+struct BorrowsFromVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The function whose postcondition is checked.
+    fn_def_id: LocalDefId,
+    /// The specification function that encodes the postcondition.
+    spec_def_id: LocalDefId,
+    /// The conjuncts of the postcondition.
+    conjuncts: HashSet<hir::HirId>,
+    errors: Vec<PrustiError>,
+}
+
+impl<'tcx> BorrowsFromVisitor<'tcx> {
+    /// The name of the variable `expr`, if it is one.
+    fn variable_name(&self, expr: &hir::Expr<'tcx>) -> Option<String> {
+        match expr.kind {
+            hir::ExprKind::Path(hir::QPath::Resolved(None, path)) => match path.res {
+                hir::def::Res::Local(hir_id) => Some(self.tcx.hir().name(hir_id).to_string()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The lifetimes in the type of the argument `name` of the function, or in
+    /// the type of its result.
+    fn lifetimes(&self, name: &str) -> Option<Vec<ty::Region<'tcx>>> {
+        let fn_sig = self.tcx.fn_sig(self.fn_def_id.to_def_id()).skip_binder();
+        let ty = if name == "result" {
+            fn_sig.output()
+        } else {
+            let index = self.tcx
+                .fn_arg_names(self.fn_def_id.to_def_id())
+                .iter()
+                .position(|ident| ident.name.as_str() == name)?;
+            fn_sig.inputs()[index]
+        };
+        Some(ty.walk()
+            .filter_map(|arg| match arg.unpack() {
+                GenericArgKind::Lifetime(region) => Some(region),
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn check_call(&mut self, span: Span, args: &'tcx [hir::Expr<'tcx>]) {
+        let names = (self.variable_name(&args[0]), self.variable_name(&args[1]));
+        let (result, arg) = match names {
+            (Some(result), Some(arg)) if result == "result" => (result, arg),
+            _ => {
+                self.errors.push(PrustiError::incorrect(
+                    "the arguments of `borrows_from` must be `result` and an argument of the function",
+                    MultiSpan::from_span(span),
+                ));
+                return;
+            }
+        };
+        let (result_lifetimes, arg_lifetimes) = match (self.lifetimes(&result), self.lifetimes(&arg)) {
+            (Some(result_lifetimes), Some(arg_lifetimes)) => (result_lifetimes, arg_lifetimes),
+            _ => {
+                self.errors.push(PrustiError::incorrect(
+                    format!("`{}` is not an argument of the function", arg),
+                    MultiSpan::from_span(args[1].span),
+                ));
+                return;
+            }
+        };
+        debug!("Lifetimes of the result: {:?}, of `{}`: {:?}", result_lifetimes, arg, arg_lifetimes);
+        if !result_lifetimes.iter().any(|region| arg_lifetimes.contains(region)) {
+            self.errors.push(PrustiError::incorrect(
+                format!("the result does not borrow from `{}`", arg),
+                MultiSpan::from_span(span),
+            ).set_help(format!(
+                "the type of the result must share a lifetime with the type of `{}`",
+                arg,
+            )));
+        }
+    }
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for BorrowsFromVisitor<'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = rustc_middle::hir::nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        if let hir::ExprKind::Call(func, args) = ex.kind {
+            if let hir::ExprKind::Path(ref path) = func.kind {
+                let typeck_results = self.tcx.typeck(self.spec_def_id);
+                if let hir::def::Res::Def(_, def_id) = typeck_results.qpath_res(path, func.hir_id) {
+                    if self.tcx.def_path_str(def_id) == "prusti_contracts::borrows_from" {
+                        if self.conjuncts.contains(&ex.hir_id) {
+                            self.check_call(ex.span, args);
+                        } else {
+                            self.errors.push(PrustiError::incorrect(
+                                "`borrows_from` can only be used as a conjunct of a postcondition",
+                                MultiSpan::from_span(ex.span),
+                            ).set_help(
+                                "`borrows_from` is checked against the signature of the function, \
+                                and holds trivially in assertions"
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        intravisit::walk_expr(self, ex);
+    }
+}
@@ -0,0 +1,113 @@
+use rustc_hir::{self as hir, intravisit};
+use rustc_middle::{hir::map::Map, ty::{self, TyCtxt}};
+use rustc_span::Span;
+use rustc_errors::MultiSpan;
+use super::common::*;
+use crate::{
+    environment::Environment,
+    utils::{has_prusti_attr, read_prusti_attr},
+    PrustiError,
+};
+use log::debug;
+
+/// Checks that closures with a `#[modifies(...)]` declaration only mutate the
+/// captured variables listed in it, and that the listed variables are captured.
+pub struct ClosureModifiesChecker;
+
+impl<'tcx> SpecCheckerStrategy<'tcx> for ClosureModifiesChecker {
+    fn check(&self, env: &Environment<'tcx>) -> Vec<PrustiError> {
+        let mut visitor = ClosureModifiesVisitor {
+            tcx: env.tcx(),
+            errors: Vec::new(),
+        };
+        env.tcx().hir().walk_toplevel_module(&mut visitor);
+        visitor.errors
+    }
+}
+
+struct ClosureModifiesVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    errors: Vec<PrustiError>,
+}
+
+impl<'tcx> ClosureModifiesVisitor<'tcx> {
+    fn check_closure(
+        &mut self,
+        closure: &'tcx hir::Expr<'tcx>,
+        modifies: &[&str],
+        span: Span,
+    ) {
+        let closure_def_id = self.tcx.hir().local_def_id(closure.hir_id);
+        let mut captured_names = vec![];
+        for captured_place in self.tcx.closure_min_captures_flattened(closure_def_id.to_def_id()) {
+            let var_name = self.tcx.hir().name(captured_place.get_root_variable());
+            captured_names.push(var_name);
+            if !mutates_caller_state(captured_place) {
+                continue;
+            }
+            debug!("Closure {:?} mutates capture {}", closure_def_id, var_name);
+            if !modifies.contains(&var_name.as_str()) {
+                self.errors.push(PrustiError::incorrect(
+                    format!(
+                        "the closure mutates `{}`, which is not declared in its `modifies` attribute",
+                        var_name,
+                    ),
+                    MultiSpan::from_span(captured_place.get_path_span(self.tcx)),
+                ));
+            }
+        }
+        for name in modifies {
+            if !captured_names.iter().any(|captured_name| captured_name.as_str() == *name) {
+                self.errors.push(PrustiError::incorrect(
+                    format!(
+                        "`{}` is declared in the `modifies` attribute, but it is not captured \
+                        by the closure",
+                        name,
+                    ),
+                    MultiSpan::from_span(span),
+                ));
+            }
+        }
+    }
+}
+
+/// Whether the closure can mutate the state of its caller through the
+/// captured place: it is captured by a mutable (or unique) borrow, or it is
+/// a mutable reference captured by value. Mutating a place that is moved into
+/// the closure only mutates the copy of the closure.
+fn mutates_caller_state(captured_place: &ty::CapturedPlace<'_>) -> bool {
+    match captured_place.info.capture_kind {
+        ty::UpvarCapture::ByRef(borrow_kind) => borrow_kind != ty::BorrowKind::ImmBorrow,
+        ty::UpvarCapture::ByValue => matches!(
+            captured_place.place.ty().kind(),
+            ty::Ref(_, _, hir::Mutability::Mut)
+        ),
+    }
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for ClosureModifiesVisitor<'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = rustc_middle::hir::nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_stmt(&mut self, stmt: &'tcx hir::Stmt<'tcx>) {
+        if let hir::StmtKind::Local(local) = stmt.kind {
+            let attrs = self.tcx.hir().attrs(local.hir_id);
+            if has_prusti_attr(attrs, "closure") {
+                if let (Some(modifies), Some(init_expr)) =
+                    (read_prusti_attr("closure_modifies", attrs), local.init)
+                {
+                    let modifies: Vec<&str> = modifies
+                        .split(',')
+                        .filter(|name| !name.is_empty())
+                        .collect();
+                    self.check_closure(init_expr, &modifies, local.span);
+                }
+            }
+        }
+        intravisit::walk_stmt(self, stmt);
+    }
+}
@@ -1,14 +1,16 @@
 use rustc_hir::{
     self as hir,
+    def_id::LocalDefId,
     intravisit::{self, Visitor},
 };
 use rustc_middle::{hir::map::Map, ty::TyCtxt};
 use rustc_span::Span;
 use crate::{
     environment::Environment,
-    utils::has_spec_only_attr,
+    utils::{has_spec_only_attr, read_prusti_attr, read_prusti_attrs},
     PrustiError,
 };
+use std::collections::HashMap;
 
 /// A strategy to check specifications
 pub trait SpecCheckerStrategy<'tcx> {
@@ -86,4 +88,75 @@ impl<'tcx, T: NonSpecExprVisitor<'tcx>> Visitor<'tcx> for NonSpecExprVisitorWrap
 
         intravisit::walk_fn(self, fk, fd, b, s, id);
     }
-}
\ No newline at end of file
+}
+
+/// The conjuncts of the assertion `expr` of a specification function, looking
+/// through its `!!((...) : bool)` wrapper.
+pub fn conjuncts<'a, 'tcx>(expr: &'a hir::Expr<'tcx>) -> Vec<&'a hir::Expr<'tcx>> {
+    match expr.kind {
+        hir::ExprKind::Block(hir::Block { stmts: [], expr: Some(inner), .. }, None)
+        | hir::ExprKind::Unary(hir::UnOp::Not, hir::Expr {
+            kind: hir::ExprKind::Unary(hir::UnOp::Not, inner), ..
+        })
+        | hir::ExprKind::Type(inner, _)
+        | hir::ExprKind::DropTemps(inner) => conjuncts(inner),
+        hir::ExprKind::Binary(op, lhs, rhs) if op.node == hir::BinOpKind::And => {
+            let mut result = conjuncts(lhs);
+            result.extend(conjuncts(rhs));
+            result
+        }
+        _ => vec![expr],
+    }
+}
+
+/// Collects the functions with preconditions and postconditions, and the
+/// specification functions, keyed by the specification IDs.
+pub struct CollectPostconditionsVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub preconditions: HashMap<String, LocalDefId>,
+    pub postconditions: HashMap<String, LocalDefId>,
+    pub spec_functions: HashMap<String, (LocalDefId, hir::BodyId)>,
+}
+
+impl<'tcx> CollectPostconditionsVisitor<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            preconditions: HashMap::new(),
+            postconditions: HashMap::new(),
+            spec_functions: HashMap::new(),
+        }
+    }
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for CollectPostconditionsVisitor<'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = rustc_middle::hir::nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_fn(
+        &mut self,
+        fk: intravisit::FnKind<'tcx>,
+        fd: &'tcx hir::FnDecl<'tcx>,
+        b: hir::BodyId,
+        s: Span,
+        id: hir::HirId,
+    ) {
+        let attrs = self.tcx.hir().attrs(id);
+        let def_id = self.tcx.hir().local_def_id(id);
+        for spec_id in read_prusti_attrs("pre_spec_id_ref", attrs) {
+            self.preconditions.insert(spec_id, def_id);
+        }
+        for spec_id in read_prusti_attrs("post_spec_id_ref", attrs) {
+            self.postconditions.insert(spec_id, def_id);
+        }
+        if let Some(spec_id) = read_prusti_attr("spec_id", attrs) {
+            self.spec_functions.insert(spec_id, (def_id, b));
+        }
+
+        intravisit::walk_fn(self, fk, fd, b, s, id);
+    }
+}
@@ -0,0 +1,66 @@
+use rustc_hir::{self as hir, def::Res, intravisit};
+use rustc_middle::{hir::map::Map, ty::TyCtxt};
+use rustc_errors::MultiSpan;
+use super::common::*;
+use crate::{environment::Environment, utils::has_prusti_attr, PrustiError};
+
+/// Checks that `loop_iterations()` is only used in the specifications of a
+/// loop, i.e. in `body_invariant!` and `break_ensures!`.
+pub struct LoopIterationsChecker;
+
+impl<'tcx> SpecCheckerStrategy<'tcx> for LoopIterationsChecker {
+    fn check(&self, env: &Environment<'tcx>) -> Vec<PrustiError> {
+        let mut visitor = LoopIterationsVisitor {
+            tcx: env.tcx(),
+            loop_spec_depth: 0,
+            errors: vec![],
+        };
+        env.tcx().hir().walk_toplevel_module(&mut visitor);
+        visitor.errors
+    }
+}
+
+struct LoopIterationsVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The number of loop specifications that enclose the visited expression.
+    loop_spec_depth: usize,
+    errors: Vec<PrustiError>,
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for LoopIterationsVisitor<'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = rustc_middle::hir::nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        if let hir::ExprKind::Closure(..) = ex.kind {
+            let attrs = self.tcx.hir().attrs(ex.hir_id);
+            if has_prusti_attr(attrs, "loop_body_invariant_spec")
+                || has_prusti_attr(attrs, "loop_break_ensures_spec")
+            {
+                self.loop_spec_depth += 1;
+                intravisit::walk_expr(self, ex);
+                self.loop_spec_depth -= 1;
+                return;
+            }
+        }
+        if let hir::ExprKind::Call(func, _) = ex.kind {
+            if let hir::ExprKind::Path(hir::QPath::Resolved(None, path)) = func.kind {
+                if let Res::Def(_, def_id) = path.res {
+                    if self.loop_spec_depth == 0
+                        && self.tcx.def_path_str(def_id) == "prusti_contracts::loop_iterations"
+                    {
+                        self.errors.push(PrustiError::incorrect(
+                            "`loop_iterations()` can only be used in the specifications of a loop",
+                            MultiSpan::from_span(ex.span),
+                        ).set_help("use it in `body_invariant!` or `break_ensures!`"));
+                    }
+                }
+            }
+        }
+        intravisit::walk_expr(self, ex);
+    }
+}
@@ -0,0 +1,92 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use rustc_hir::{self as hir, intravisit};
+use rustc_middle::{hir::map::Map, ty::TyCtxt};
+use rustc_errors::MultiSpan;
+use super::common::*;
+use crate::{environment::Environment, utils::has_prusti_attr, PrustiError};
+
+/// Checks that `body_invariant!` and `break_ensures!` are only used in the
+/// body of a loop. Otherwise, they would not belong to any loop and would be
+/// silently ignored.
+pub struct LoopSpecPlacementChecker;
+
+impl<'tcx> SpecCheckerStrategy<'tcx> for LoopSpecPlacementChecker {
+    fn check(&self, env: &Environment<'tcx>) -> Vec<PrustiError> {
+        let mut visitor = LoopSpecPlacementVisitor {
+            tcx: env.tcx(),
+            loop_depth: 0,
+            errors: vec![],
+        };
+        env.tcx().hir().walk_toplevel_module(&mut visitor);
+        visitor.errors
+    }
+}
+
+struct LoopSpecPlacementVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The number of loops of the current function or closure that enclose
+    /// the visited expression.
+    loop_depth: usize,
+    errors: Vec<PrustiError>,
+}
+
+impl<'tcx> LoopSpecPlacementVisitor<'tcx> {
+    /// Visit the body of a function or closure, which is not in the loops
+    /// that enclose its definition.
+    fn with_new_body(&mut self, visit: impl FnOnce(&mut Self)) {
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        visit(self);
+        self.loop_depth = loop_depth;
+    }
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for LoopSpecPlacementVisitor<'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = rustc_middle::hir::nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_item(&mut self, item: &'tcx hir::Item<'tcx>) {
+        self.with_new_body(|visitor| intravisit::walk_item(visitor, item));
+    }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        match ex.kind {
+            hir::ExprKind::Loop(..) => {
+                self.loop_depth += 1;
+                intravisit::walk_expr(self, ex);
+                self.loop_depth -= 1;
+            }
+            hir::ExprKind::Closure(..) => {
+                let attrs = self.tcx.hir().attrs(ex.hir_id);
+                let macro_name = if has_prusti_attr(attrs, "loop_body_invariant_spec") {
+                    Some("body_invariant!")
+                } else if has_prusti_attr(attrs, "loop_break_ensures_spec") {
+                    Some("break_ensures!")
+                } else {
+                    None
+                };
+                match macro_name {
+                    Some(macro_name) => {
+                        if self.loop_depth == 0 {
+                            self.errors.push(PrustiError::incorrect(
+                                format!("`{}` can only be used in the body of a loop", macro_name),
+                                MultiSpan::from_span(ex.span),
+                            ).set_help("move it to the beginning of the body of the loop that it specifies"));
+                        }
+                        intravisit::walk_expr(self, ex);
+                    }
+                    None => self.with_new_body(|visitor| intravisit::walk_expr(visitor, ex)),
+                }
+            }
+            _ => intravisit::walk_expr(self, ex),
+        }
+    }
+}
@@ -3,11 +3,24 @@
 mod common;
 mod type_model_checks;
 mod predicate_checks;
+mod closure_checks;
+mod borrows_from_checks;
+mod result_variants_checks;
+mod loop_iterations_checks;
+mod loop_spec_placement_checks;
+mod trivial_assertion_checks;
 
 use common::*;
 use type_model_checks::{IllegalModelUsagesChecker, ModelDefinedOnTypeWithoutFields};
 use predicate_checks::IllegalPredicateUsagesChecker;
+use closure_checks::ClosureModifiesChecker;
+use borrows_from_checks::BorrowsFromChecker;
+use result_variants_checks::ResultVariantsChecker;
+use loop_iterations_checks::LoopIterationsChecker;
+use loop_spec_placement_checks::LoopSpecPlacementChecker;
+use trivial_assertion_checks::TrivialAssertionChecker;
 use crate::environment::Environment;
+use prusti_common::config;
 
 /// Checker visitor for the specifications.
 /// Checks are implemented in various [SpecCheckerStrategy]s
@@ -23,13 +36,23 @@ impl<'tcx> Default for SpecChecker<'tcx> {
 
 impl<'tcx> SpecChecker<'tcx> {
     pub fn new() -> Self {
-        Self {
-            checks: vec![
-                Box::new(IllegalPredicateUsagesChecker {}),
-                Box::new(IllegalModelUsagesChecker {}),
-                Box::new(ModelDefinedOnTypeWithoutFields {})
-            ]
+        let mut checks: Vec<Box<dyn SpecCheckerStrategy<'tcx>>> = vec![
+            Box::new(IllegalPredicateUsagesChecker {}),
+            Box::new(IllegalModelUsagesChecker {}),
+            Box::new(ModelDefinedOnTypeWithoutFields {}),
+            Box::new(ClosureModifiesChecker {}),
+            Box::new(BorrowsFromChecker {}),
+            Box::new(ResultVariantsChecker {}),
+            Box::new(LoopIterationsChecker {}),
+            Box::new(LoopSpecPlacementChecker {}),
+        ];
+        // Trivial assertions are legitimate in some cases, e.g. to mark an
+        // unreachable function with `#[requires(false)]`, so they are only
+        // reported when linting the specifications.
+        if config::spec_lint() {
+            checks.push(Box::new(TrivialAssertionChecker {}));
         }
+        Self { checks }
     }
 
     /// Executes all checks and emits errors
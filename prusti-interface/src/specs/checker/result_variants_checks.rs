@@ -0,0 +1,184 @@
+use rustc_hir::{
+    self as hir,
+    def::{CtorOf, DefKind, Res},
+    def_id::{DefId, LocalDefId},
+    intravisit,
+};
+use rustc_middle::{
+    hir::map::Map,
+    ty::{self, TyCtxt},
+};
+use rustc_errors::MultiSpan;
+use prusti_common::config;
+use super::common::*;
+use crate::{environment::Environment, PrustiError};
+use std::collections::{HashMap, HashSet};
+use log::debug;
+
+/// Warns about functions that return an enum and whose postconditions match
+/// the result against some of its variants, but not all of them.
+pub struct ResultVariantsChecker;
+
+impl<'tcx> SpecCheckerStrategy<'tcx> for ResultVariantsChecker {
+    fn check(&self, env: &Environment<'tcx>) -> Vec<PrustiError> {
+        if !config::report_uncovered_result_variants() {
+            return vec![];
+        }
+        let tcx = env.tcx();
+        let mut collect = CollectPostconditionsVisitor::new(tcx);
+        tcx.hir().walk_toplevel_module(&mut collect);
+
+        let mut spec_ids: HashMap<LocalDefId, Vec<String>> = HashMap::new();
+        for (spec_id, fn_def_id) in collect.postconditions {
+            spec_ids.entry(fn_def_id).or_default().push(spec_id);
+        }
+
+        let mut warnings = vec![];
+        for (fn_def_id, spec_ids) in spec_ids {
+            let result_ty = tcx.fn_sig(fn_def_id.to_def_id()).skip_binder().output().peel_refs();
+            let adt_def = match result_ty.kind() {
+                ty::TyKind::Adt(adt_def, _) if adt_def.is_enum() => *adt_def,
+                _ => continue,
+            };
+            let mut visitor = ResultVariantsVisitor {
+                tcx,
+                typeck_results: None,
+                adt_def,
+                covered: HashSet::new(),
+                covers_all: false,
+            };
+            for spec_id in spec_ids {
+                if let Some(&(spec_def_id, body_id)) = collect.spec_functions.get(&spec_id) {
+                    visitor.typeck_results = Some(tcx.typeck(spec_def_id));
+                    intravisit::Visitor::visit_body(&mut visitor, tcx.hir().body(body_id));
+                }
+            }
+            debug!("Variants of the result of {:?} covered: {:?}", fn_def_id, visitor.covered);
+            if visitor.covered.is_empty() || visitor.covers_all {
+                continue;
+            }
+            let uncovered: Vec<String> = adt_def.variants()
+                .iter()
+                .filter(|variant| !visitor.covered.contains(&variant.def_id))
+                .map(|variant| format!("`{}`", variant.name))
+                .collect();
+            if uncovered.is_empty() {
+                continue;
+            }
+            let variants = if uncovered.len() == 1 { "variant" } else { "variants" };
+            let mut warning = PrustiError::incorrect(
+                format!(
+                    "the postconditions do not mention the {} {} of the result",
+                    variants,
+                    uncovered.join(", "),
+                ),
+                MultiSpan::from_span(tcx.def_span(fn_def_id.to_def_id())),
+            ).set_help("the specification might have forgotten a case");
+            warning.set_warning();
+            warnings.push(warning);
+        }
+        // Report the warnings in the order of the functions.
+        warnings.sort();
+        warnings
+    }
+}
+
+/// Collects the variants of the result enum that are matched in the
+/// postconditions, e.g. by `matches!(result, ...)` or `if let ... = result`.
+struct ResultVariantsVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The type-checking results of the specification function being visited.
+    typeck_results: Option<&'tcx ty::TypeckResults<'tcx>>,
+    adt_def: ty::AdtDef<'tcx>,
+    covered: HashSet<DefId>,
+    /// Whether the result is matched with a wildcard arm, which covers the
+    /// remaining variants.
+    covers_all: bool,
+}
+
+impl<'tcx> ResultVariantsVisitor<'tcx> {
+    /// Whether `expr` is the `result` variable, possibly borrowed or
+    /// dereferenced.
+    fn is_result(&self, expr: &hir::Expr<'tcx>) -> bool {
+        match expr.kind {
+            hir::ExprKind::AddrOf(_, _, inner) | hir::ExprKind::Unary(hir::UnOp::Deref, inner) => {
+                self.is_result(inner)
+            }
+            hir::ExprKind::Path(hir::QPath::Resolved(None, path)) => match path.res {
+                Res::Local(hir_id) => self.tcx.hir().name(hir_id).as_str() == "result",
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn collect_variants(&mut self, pat: &hir::Pat<'tcx>) {
+        match pat.kind {
+            hir::PatKind::Path(ref qpath)
+            | hir::PatKind::TupleStruct(ref qpath, _, _)
+            | hir::PatKind::Struct(ref qpath, _, _) => {
+                let res = self.typeck_results.unwrap().qpath_res(qpath, pat.hir_id);
+                let variant = match res {
+                    Res::Def(DefKind::Variant, variant_def_id) => self.adt_def.variants()
+                        .iter()
+                        .find(|variant| variant.def_id == variant_def_id),
+                    Res::Def(DefKind::Ctor(CtorOf::Variant, _), ctor_def_id) => self.adt_def.variants()
+                        .iter()
+                        .find(|variant| variant.ctor_def_id == Some(ctor_def_id)),
+                    _ => None,
+                };
+                if let Some(variant) = variant {
+                    self.covered.insert(variant.def_id);
+                }
+            }
+            hir::PatKind::Or(pats) => {
+                for pat in pats {
+                    self.collect_variants(pat);
+                }
+            }
+            hir::PatKind::Binding(_, _, _, Some(inner))
+            | hir::PatKind::Ref(inner, _)
+            | hir::PatKind::Box(inner) => self.collect_variants(inner),
+            _ => {}
+        }
+    }
+}
+
+/// Whether `expr` is the literal `false`, as in the last arm of `matches!`.
+fn is_false_literal(expr: &hir::Expr) -> bool {
+    match expr.kind {
+        hir::ExprKind::Lit(ref lit) => matches!(lit.node, rustc_ast::LitKind::Bool(false)),
+        _ => false,
+    }
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for ResultVariantsVisitor<'tcx> {
+    type Map = Map<'tcx>;
+    type NestedFilter = rustc_middle::hir::nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        match ex.kind {
+            hir::ExprKind::Match(scrutinee, arms, _) if self.is_result(scrutinee) => {
+                for arm in arms {
+                    match arm.pat.kind {
+                        hir::PatKind::Wild | hir::PatKind::Binding(_, _, _, None) => {
+                            if !is_false_literal(arm.body) {
+                                self.covers_all = true;
+                            }
+                        }
+                        _ => self.collect_variants(arm.pat),
+                    }
+                }
+            }
+            hir::ExprKind::Let(let_expr) if self.is_result(let_expr.init) => {
+                self.collect_variants(let_expr.pat);
+            }
+            _ => {}
+        }
+        intravisit::walk_expr(self, ex);
+    }
+}
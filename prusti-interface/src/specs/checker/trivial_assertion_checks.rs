@@ -0,0 +1,83 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use rustc_ast::LitKind;
+use rustc_hir::{self as hir, def_id::LocalDefId};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+use rustc_errors::MultiSpan;
+use super::common::*;
+use crate::{environment::Environment, PrustiError};
+
+/// Checks that no conjunct of a precondition or postcondition always holds
+/// or never holds, i.e. that it is neither a boolean literal nor a comparison
+/// of an expression with itself. Such a conjunct is most likely a typo, e.g.
+/// `result == result` instead of `result == x`.
+pub struct TrivialAssertionChecker;
+
+impl<'tcx> SpecCheckerStrategy<'tcx> for TrivialAssertionChecker {
+    fn check(&self, env: &Environment<'tcx>) -> Vec<PrustiError> {
+        let mut collect = CollectPostconditionsVisitor::new(env.tcx());
+        env.tcx().hir().walk_toplevel_module(&mut collect);
+
+        let mut errors = vec![];
+        let assertions = collect.preconditions.keys().map(|spec_id| ("precondition", spec_id))
+            .chain(collect.postconditions.keys().map(|spec_id| ("postcondition", spec_id)));
+        for (kind, spec_id) in assertions {
+            if let Some(&(spec_def_id, body_id)) = collect.spec_functions.get(spec_id) {
+                let body = env.tcx().hir().body(body_id);
+                for conjunct in conjuncts(&body.value) {
+                    if let Some(holds) = trivial_value(env.tcx(), spec_def_id, conjunct) {
+                        errors.push(trivial_assertion_error(kind, holds, conjunct.span));
+                    }
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// The value of `expr` if it is a boolean literal or a comparison of an
+/// expression with itself. Floats are excluded, since `x != x` holds for NaN.
+fn trivial_value<'tcx>(tcx: TyCtxt<'tcx>, spec_def_id: LocalDefId, expr: &hir::Expr<'tcx>) -> Option<bool> {
+    match expr.kind {
+        hir::ExprKind::Lit(ref lit) => match lit.node {
+            LitKind::Bool(value) => Some(value),
+            _ => None,
+        },
+        hir::ExprKind::Binary(op, lhs, rhs) => {
+            let holds = match op.node {
+                hir::BinOpKind::Eq | hir::BinOpKind::Le | hir::BinOpKind::Ge => true,
+                hir::BinOpKind::Ne | hir::BinOpKind::Lt | hir::BinOpKind::Gt => false,
+                _ => return None,
+            };
+            if tcx.typeck(spec_def_id).expr_ty(lhs).is_floating_point() {
+                return None;
+            }
+            let source_map = tcx.sess.source_map();
+            match (source_map.span_to_snippet(lhs.span), source_map.span_to_snippet(rhs.span)) {
+                (Ok(lhs), Ok(rhs)) if !lhs.is_empty() && lhs == rhs => Some(holds),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The error for a conjunct of a precondition or postcondition (`kind`) that
+/// always holds or never holds.
+fn trivial_assertion_error(kind: &str, holds: bool, span: Span) -> PrustiError {
+    let (message, help) = match (kind, holds) {
+        ("precondition", true) => ("always holds", "the precondition does not restrict the callers"),
+        ("precondition", false) => ("never holds", "the function can never be called"),
+        (_, true) => ("always holds", "the postcondition does not say anything about the function"),
+        (_, false) => ("never holds", "the function can never return"),
+    };
+    PrustiError::incorrect(
+        format!("this {} {}", kind, message),
+        MultiSpan::from_span(span),
+    ).set_help(help)
+}
@@ -9,7 +9,7 @@ use rustc_errors::MultiSpan;
 use crate::{environment::Environment, PrustiError};
 use std::collections::HashMap;
 use prusti_specs::ExternSpecKind;
-use rustc_middle::ty::subst::SubstsRef;
+use rustc_middle::ty::{self, subst::{Subst, SubstsRef}};
 use std::cmp::{Eq, PartialEq};
 
 pub enum ExternSpecResolverError {
@@ -34,6 +34,18 @@ pub enum ExternSpecResolverError {
 
     /// Occurs when a trait impl extern spec resolves to the trait method.
     ResolvedToDefault(DefId, Span),
+
+    /// Occurs when the signature of the extern spec differs from the one of
+    /// the target method, e.g. in its return type.
+    ///
+    /// # Example:
+    /// ```
+    /// #[extern_spec]
+    /// impl<T> std::vec::Vec<T> {
+    ///     fn len(&self) -> u32;
+    /// }
+    /// ```
+    MismatchedSignature(DefId, Span),
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
@@ -163,6 +175,10 @@ impl<'v, 'tcx: 'v> ExternSpecResolver<'v, 'tcx> {
                         self.errors.push(
                             ExternSpecResolverError::InvalidGenerics(resolved_def_id, span),
                         );
+                    } else if !self.signatures_match(current_def_id, target_def_id, substs) {
+                        self.errors.push(
+                            ExternSpecResolverError::MismatchedSignature(target_def_id, span),
+                        );
                     }
                 }
             }
@@ -176,6 +192,23 @@ impl<'v, 'tcx: 'v> ExternSpecResolver<'v, 'tcx> {
         }
     }
 
+    /// Checks that the signature of the extern spec `spec_def_id` is the one
+    /// of `target_def_id` with the given substitutions. The call of the target
+    /// in the body of the extern spec does not ensure this, since its
+    /// arguments can be coerced and its result is discarded.
+    fn signatures_match(&self, spec_def_id: DefId, target_def_id: DefId, substs: SubstsRef<'tcx>) -> bool {
+        let tcx = self.env.tcx();
+        let param_env = tcx.param_env(spec_def_id);
+        let normalize = |sig: ty::PolyFnSig<'tcx>| {
+            let sig = tcx.erase_late_bound_regions(sig);
+            tcx.try_normalize_erasing_regions(param_env, sig)
+                .unwrap_or_else(|_| tcx.erase_regions(sig))
+        };
+        let spec_sig = normalize(tcx.fn_sig(spec_def_id));
+        let target_sig = normalize(tcx.fn_sig(target_def_id).subst(tcx, substs));
+        spec_sig.inputs() == target_sig.inputs() && spec_sig.output() == target_sig.output()
+    }
+
     fn register_duplicate_spec(&mut self, decl_def_id: DefId, dup_spec_def_id: DefId, span: Span) {
         self
             .spec_duplicates
@@ -223,6 +256,15 @@ impl<'v, 'tcx: 'v> ExternSpecResolver<'v, 'tcx> {
                     ).add_note(err_note, None)
                         .emit(env);
                 }
+                ExternSpecResolverError::MismatchedSignature(def_id, span) => {
+                    let function_name = env.get_item_name(*def_id);
+                    let err_note = format!("The types of the arguments and of the result must be the ones of method '{}'.", function_name);
+                    PrustiError::incorrect(
+                        "Invalid external specification",
+                        MultiSpan::from_span(*span),
+                    ).add_note(err_note, None)
+                        .emit(env);
+                }
                 ExternSpecResolverError::ResolvedToDefault(def_id, span) => {
                     let function_name = env.get_item_name(*def_id);
                     let err_note = format!("Specified method ('{}') resolved to the trait's implementation. Add specification to the trait instead.", function_name);
@@ -30,6 +30,7 @@ struct ProcedureSpecRefs {
     pure: bool,
     abstract_predicate: bool,
     trusted: bool,
+    opaque: bool,
 }
 
 /// Specification collector, intended to be applied as a visitor over the crate
@@ -46,7 +47,7 @@ pub struct SpecCollector<'a, 'tcx: 'a> {
 
     /// Map from functions/loops and their specifications.
     procedure_specs: HashMap<LocalDefId, ProcedureSpecRefs>,
-    loop_specs: Vec<LocalDefId>,
+    loop_specs: Vec<typed::LoopSpecification>,
 }
 
 impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
@@ -104,10 +105,32 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
                     SpecIdRef::Predicate(spec_id) => {
                         kind = ProcedureSpecificationKind::Predicate(Some(*self.spec_functions.get(spec_id).unwrap()));
                     }
+                    SpecIdRef::Assumption(spec_id) => {
+                        spec.add_assumption(*self.spec_functions.get(spec_id).unwrap());
+                    }
+                    SpecIdRef::OpaquePostcondition(spec_id) => {
+                        spec.add_opaque_postcondition(*self.spec_functions.get(spec_id).unwrap());
+                    }
+                    SpecIdRef::Measure(spec_id) => {
+                        spec.add_measure(*self.spec_functions.get(spec_id).unwrap());
+                    }
+                    SpecIdRef::Case { pre, post } => {
+                        let pre = *self.spec_functions.get(pre).unwrap();
+                        let post = *self.spec_functions.get(post).unwrap();
+                        spec.add_postcondition(post, self.env);
+                        spec.add_case(typed::ProcedureCase {
+                            name: read_prusti_attr("case_name", self.tcx.get_attrs(pre.to_def_id())),
+                            pre,
+                            post,
+                        });
+                    }
                 }
             }
 
             spec.set_trusted(refs.trusted);
+            if refs.opaque {
+                spec.set_opaque();
+            }
 
             // We do not want to create an empty kind.
             // This would lead to refinement inheritance if there is a trait involved.
@@ -151,10 +174,8 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
     }
 
     fn determine_loop_specs(&self, def_spec: &mut typed::DefSpecificationMap) {
-        for local_id in self.loop_specs.iter() {
-            def_spec.loop_specs.insert(local_id.to_def_id(),typed::LoopSpecification {
-                invariant: *local_id,
-            });
+        for loop_spec in self.loop_specs.iter() {
+            def_spec.loop_specs.insert(loop_spec.spec_def_id().to_def_id(), loop_spec.clone());
         }
     }
 
@@ -203,18 +224,46 @@ fn get_procedure_spec_ids(def_id: DefId, attrs: &[ast::Attribute]) -> Option<Pro
             |raw_spec_id| SpecIdRef::Predicate(parse_spec_id(raw_spec_id, def_id))
         )
     );
+    spec_id_refs.extend(
+        read_prusti_attrs("assumption_spec_id_ref", attrs).into_iter().map(
+            |raw_spec_id| SpecIdRef::Assumption(parse_spec_id(raw_spec_id, def_id))
+        )
+    );
+    spec_id_refs.extend(
+        read_prusti_attrs("opaque_post_spec_id_ref", attrs).into_iter().map(
+            |raw_spec_id| SpecIdRef::OpaquePostcondition(parse_spec_id(raw_spec_id, def_id))
+        )
+    );
+    spec_id_refs.extend(
+        read_prusti_attrs("measure_spec_id_ref", attrs).into_iter().map(
+            |raw_spec_id| SpecIdRef::Measure(parse_spec_id(raw_spec_id, def_id))
+        )
+    );
+    spec_id_refs.extend(
+        read_prusti_attrs("case_spec_id_ref", attrs).into_iter().map(|raw_spec_ids| {
+            let (pre, post) = raw_spec_ids.split_once(':').unwrap_or_else(||
+                panic!("cannot parse the case spec_ids attached to {:?}", def_id)
+            );
+            SpecIdRef::Case {
+                pre: parse_spec_id(pre.to_string(), def_id),
+                post: parse_spec_id(post.to_string(), def_id),
+            }
+        })
+    );
     debug!("Function {:?} has specification ids {:?}", def_id, spec_id_refs);
 
     let pure = has_prusti_attr(attrs, "pure");
     let trusted = has_prusti_attr(attrs, "trusted");
     let abstract_predicate = has_abstract_predicate_attr(attrs);
+    let opaque = has_prusti_attr(attrs, "opaque_spec");
 
-    if abstract_predicate || pure || trusted || !spec_id_refs.is_empty() {
+    if abstract_predicate || pure || trusted || opaque || !spec_id_refs.is_empty() {
         Some(ProcedureSpecRefs {
             spec_id_refs,
             pure,
             abstract_predicate,
             trusted,
+            opaque,
         })
     } else {
         None
@@ -267,7 +316,10 @@ impl<'a, 'tcx> intravisit::Visitor<'tcx> for SpecCollector<'a, 'tcx> {
 
             // Collect loop specifications
             if has_prusti_attr(attrs, "loop_body_invariant_spec") {
-                self.loop_specs.push(local_id);
+                self.loop_specs.push(typed::LoopSpecification::Invariant(local_id));
+            }
+            if has_prusti_attr(attrs, "loop_break_ensures_spec") {
+                self.loop_specs.push(typed::LoopSpecification::BreakEnsures(local_id));
             }
         } else {
             // Don't collect specs "for" spec items
@@ -37,6 +37,19 @@ pub struct ProcedureSpecification {
     pub posts: SpecificationItem<Vec<LocalDefId>>,
     pub pledges: SpecificationItem<Vec<Pledge>>,
     pub trusted: SpecificationItem<bool>,
+    /// Assertions of the enclosing `#[assumptions(...)]` module, which are
+    /// assumed when verifying the body but are not part of the contract.
+    pub assumptions: SpecificationItem<Vec<LocalDefId>>,
+    /// The postconditions of an `#[opaque_spec(...)]` attribute, which replace
+    /// `posts` for callers outside of the module of the procedure. `None` if
+    /// the procedure has no such attribute.
+    pub opaque_posts: Option<Vec<LocalDefId>>,
+    /// The components of the lexicographic termination measure of a
+    /// `#[terminates(...)]` attribute.
+    pub measures: SpecificationItem<Vec<LocalDefId>>,
+    /// The `#[case(...)]` attributes. The postcondition of each case is also
+    /// in `posts`, and the disjunction of their preconditions is in `pres`.
+    pub cases: SpecificationItem<Vec<ProcedureCase>>,
 }
 
 impl ProcedureSpecification {
@@ -50,10 +63,25 @@ impl ProcedureSpecification {
             posts: SpecificationItem::Empty,
             pledges: SpecificationItem::Empty,
             trusted: SpecificationItem::Inherent(false),
+            assumptions: SpecificationItem::Empty,
+            opaque_posts: None,
+            measures: SpecificationItem::Empty,
+            cases: SpecificationItem::Empty,
         }
     }
 }
 
+/// A `#[case(name = "...", pre = ..., post = ...)]` attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcedureCase {
+    pub name: Option<String>,
+    /// The precondition of the case.
+    pub pre: LocalDefId,
+    /// The postcondition `old(pre) ==> post` of the case, without `old` on a
+    /// pure function.
+    pub post: LocalDefId,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ProcedureSpecificationKind {
     Impure,
@@ -79,8 +107,22 @@ impl Display for ProcedureSpecificationKind {
 }
 
 #[derive(Debug, Clone)]
-pub struct LoopSpecification {
-    pub invariant: LocalDefId,
+pub enum LoopSpecification {
+    /// A `body_invariant!(..)` of the loop.
+    Invariant(LocalDefId),
+    /// A `break_ensures!(..)` of the loop, which has to hold whenever the
+    /// loop is left.
+    BreakEnsures(LocalDefId),
+}
+
+impl LoopSpecification {
+    pub fn spec_def_id(&self) -> LocalDefId {
+        match self {
+            LoopSpecification::Invariant(def_id) | LoopSpecification::BreakEnsures(def_id) => {
+                *def_id
+            }
+        }
+    }
 }
 
 /// The base container to store a contract of a procedure.
@@ -220,6 +262,51 @@ impl SpecGraph<ProcedureSpecification> {
             .for_each(|s| s.pledges.push(pledge.clone()));
     }
 
+    /// Attaches the module assumption `assumption` to the base spec and all
+    /// constrained specs.
+    pub fn add_assumption(&mut self, assumption: LocalDefId) {
+        self.base_spec.assumptions.push(assumption);
+        self.specs_with_constraints
+            .values_mut()
+            .for_each(|s| s.assumptions.push(assumption));
+    }
+
+    /// Marks the base spec and all constrained specs as exposing only their
+    /// opaque postconditions to callers outside of the module.
+    pub fn set_opaque(&mut self) {
+        self.base_spec.opaque_posts.get_or_insert_with(Vec::new);
+        self.specs_with_constraints
+            .values_mut()
+            .for_each(|s| { s.opaque_posts.get_or_insert_with(Vec::new); });
+    }
+
+    /// Attaches the opaque postcondition `post` to the base spec and all
+    /// constrained specs.
+    pub fn add_opaque_postcondition(&mut self, post: LocalDefId) {
+        self.base_spec.opaque_posts.get_or_insert_with(Vec::new).push(post);
+        self.specs_with_constraints
+            .values_mut()
+            .for_each(|s| s.opaque_posts.get_or_insert_with(Vec::new).push(post));
+    }
+
+    /// Attaches the component `measure` of the termination measure to the base
+    /// spec and all constrained specs.
+    pub fn add_measure(&mut self, measure: LocalDefId) {
+        self.base_spec.measures.push(measure);
+        self.specs_with_constraints
+            .values_mut()
+            .for_each(|s| s.measures.push(measure));
+    }
+
+    /// Attaches the `#[case(...)]` attribute `case` to the base spec and all
+    /// constrained specs.
+    pub fn add_case(&mut self, case: ProcedureCase) {
+        self.base_spec.cases.push(case.clone());
+        self.specs_with_constraints
+            .values_mut()
+            .for_each(|s| s.cases.push(case.clone()));
+    }
+
     /// Sets the trusted flag for the base spec and all constrained specs.
     pub fn set_trusted(&mut self, trusted: bool) {
         self.base_spec.trusted.set(trusted);
@@ -520,8 +607,15 @@ impl Refinable for ProcedureSpecification {
             pres: self.pres.refine(&other.pres),
             posts: self.posts.refine(&other.posts),
             pledges: self.pledges.refine(&other.pledges),
+            cases: self.cases.refine(&other.cases),
             kind: self.kind.refine(&other.kind),
             trusted: self.trusted.refine(&other.trusted),
+            // Module assumptions are never inherited from a trait.
+            assumptions: self.assumptions,
+            // Neither are the exposed contract of an opaque spec and the
+            // termination measure.
+            opaque_posts: self.opaque_posts,
+            measures: self.measures,
         }
     }
 }
@@ -338,7 +338,7 @@ pub fn read_prusti_attrs(attr_name: &str, attrs: &[ast::Attribute]) -> Vec<Strin
                         segments,
                         tokens: _,
                     },
-                args: ast::MacArgs::Eq(_, token),
+                args,
                 tokens: _,
             },
             _,
@@ -352,19 +352,142 @@ pub fn read_prusti_attrs(attr_name: &str, attrs: &[ast::Attribute]) -> Vec<Strin
                 continue;
             }
             use rustc_ast::token::{Lit, Token, TokenKind};
+            use rustc_ast::tokenstream::TokenTree;
+            let token = match args {
+                ast::MacArgs::Eq(_, token) => token.clone(),
+                // Also accept the `prusti::<attr_name>("...")` form.
+                ast::MacArgs::Delimited(_, _, tokens) => match tokens.trees().next() {
+                    Some(TokenTree::Token(token)) if matches!(token.kind, TokenKind::Literal(_)) => {
+                        token
+                    }
+                    _ => continue,
+                },
+                ast::MacArgs::Empty => continue,
+            };
             fn extract_string(token: &Token) -> String {
                 force_matches!(&token.kind, TokenKind::Literal(Lit { symbol, .. }) => {
                         symbol.as_str().replace("\\\"", "\"")
                     }
                 )
             }
-            strings.push(extract_string(token));
+            strings.push(extract_string(&token));
         };
     }
     strings
 }
 
-/// Read the value stored in a single Prusti attribute (e.g. `prusti::<attr_name>="...")`.
+/// Read the value stored in a single Prusti attribute (e.g. `prusti::<attr_name>="..."`
+/// or `prusti::<attr_name>("...")`).
 pub fn read_prusti_attr(attr_name: &str, attrs: &[ast::Attribute]) -> Option<String> {
     read_prusti_attrs(attr_name, attrs).pop()
 }
+
+/// Read the `key = value` pairs stored in a Prusti attribute of the form
+/// `prusti::<attr_name>(key1 = value1, key2 = value2)`. Returns an error
+/// message if the arguments of the attribute do not have this form.
+pub fn read_prusti_attr_options(
+    attr_name: &str,
+    attrs: &[ast::Attribute],
+) -> Option<Result<Vec<(String, String)>, String>> {
+    use rustc_ast::token::{Lit, TokenKind};
+    use rustc_ast::tokenstream::TokenTree;
+    let args = attrs.iter().rev().find_map(|attr| match &attr.kind {
+        ast::AttrKind::Normal(ast::AttrItem { path, args, .. }, _)
+            if path.segments.len() == 2
+                && path.segments[0].ident.as_str() == "prusti"
+                && path.segments[1].ident.as_str() == attr_name =>
+        {
+            Some(args)
+        }
+        _ => None,
+    })?;
+    let tokens = match args {
+        ast::MacArgs::Delimited(_, _, tokens) => tokens,
+        ast::MacArgs::Empty => return Some(Ok(vec![])),
+        ast::MacArgs::Eq(..) => {
+            return Some(Err(format!(
+                "expected `#[prusti::{}(key = value, ...)]`",
+                attr_name
+            )));
+        }
+    };
+    let trees: Vec<_> = tokens.trees().collect();
+    let mut options = vec![];
+    for option in trees.split(|tree| {
+        matches!(tree, TokenTree::Token(token) if token.kind == TokenKind::Comma)
+    }) {
+        match option {
+            [] => {}
+            [TokenTree::Token(key), TokenTree::Token(eq), TokenTree::Token(value)]
+                if eq.kind == TokenKind::Eq =>
+            {
+                let key = match key.kind {
+                    TokenKind::Ident(symbol, _) => symbol.to_string(),
+                    _ => return Some(Err("expected the name of an option".to_string())),
+                };
+                let value = match value.kind {
+                    TokenKind::Ident(symbol, _) | TokenKind::Literal(Lit { symbol, .. }) => {
+                        symbol.to_string()
+                    }
+                    _ => return Some(Err(format!("expected a value for option `{}`", key))),
+                };
+                options.push((key, value));
+            }
+            _ => {
+                return Some(Err(format!(
+                    "expected `#[prusti::{}(key = value, ...)]`",
+                    attr_name
+                )));
+            }
+        }
+    }
+    Some(Ok(options))
+}
+
+/// Read the path stored in a Prusti attribute of the form
+/// `prusti::<attr_name>(path::to::item)`. Returns an error message if the
+/// argument of the attribute is not a path.
+pub fn read_prusti_attr_path(
+    attr_name: &str,
+    attrs: &[ast::Attribute],
+) -> Option<Result<String, String>> {
+    use rustc_ast::token::TokenKind;
+    use rustc_ast::tokenstream::TokenTree;
+    let args = attrs.iter().rev().find_map(|attr| match &attr.kind {
+        ast::AttrKind::Normal(ast::AttrItem { path, args, .. }, _)
+            if path.segments.len() == 2
+                && path.segments[0].ident.as_str() == "prusti"
+                && path.segments[1].ident.as_str() == attr_name =>
+        {
+            Some(args)
+        }
+        _ => None,
+    })?;
+    let error = || Err(format!("expected `#[prusti::{}(path)]`", attr_name));
+    let tokens = match args {
+        ast::MacArgs::Delimited(_, _, tokens) => tokens,
+        _ => return Some(error()),
+    };
+    let mut path = String::new();
+    let mut expect_ident = true;
+    for tree in tokens.trees() {
+        match tree {
+            TokenTree::Token(token) => match token.kind {
+                TokenKind::Ident(symbol, _) if expect_ident => {
+                    path.push_str(symbol.as_str());
+                    expect_ident = false;
+                }
+                TokenKind::ModSep if !expect_ident || path.is_empty() => {
+                    path.push_str("::");
+                    expect_ident = true;
+                }
+                _ => return Some(error()),
+            },
+            TokenTree::Delimited(..) => return Some(error()),
+        }
+    }
+    if expect_ident {
+        return Some(error());
+    }
+    Some(Ok(path))
+}
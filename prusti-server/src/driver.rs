@@ -5,6 +5,10 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use clap::Parser;
+use prusti_common::{config, report::lsp::format_publish_diagnostics};
+use prusti_server::{replay_verification_request, SavedVerificationRequest};
+use std::path::PathBuf;
+use viper::{VerificationResult, Viper};
 
 /// A verification server to handle Prusti verification requests.
 #[derive(Parser, Debug)]
@@ -14,6 +18,11 @@ struct Args {
     /// Pass 0 to get a free one assigned by the OS.
     #[clap(short, long, value_name = "PORT", default_value_t = 0)]
     port: u16,
+
+    /// Verifies a verification request saved with
+    /// `PRUSTI_SAVE_VERIFICATION_REQUESTS` instead of starting the server.
+    #[clap(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
 }
 
 fn main() {
@@ -21,5 +30,64 @@ fn main() {
 
     let args = Args::parse();
 
+    if let Some(path) = args.replay {
+        std::process::exit(replay(path));
+    }
+
     prusti_server::start_server_on_port(args.port);
 }
+
+/// Verify a saved verification request and return the exit code.
+fn replay(path: PathBuf) -> i32 {
+    let saved = match SavedVerificationRequest::load(&path) {
+        Ok(saved) => saved,
+        Err(error) => {
+            eprintln!("Could not load {}: {}", path.display(), error);
+            return 2;
+        }
+    };
+    // Verify with the configuration of the original run, e.g. the same JVM
+    // arguments, unless the flags are overridden for the replay.
+    if let Err(error) = config::apply_saved_settings(&saved.configuration) {
+        eprintln!("Could not apply the configuration of {}: {}", path.display(), error);
+        return 2;
+    }
+
+    let viper = Viper::new_with_args(config::extra_jvm_args());
+    let verification_context = viper.attach_current_thread();
+    let result = replay_verification_request(&verification_context, saved.clone());
+    if let Some(lsp_path) = config::lsp_diagnostics() {
+        let errors: &[_] = match &result {
+            VerificationResult::Failure(errors) => &errors[..],
+            _ => &[],
+        };
+        let json = format_publish_diagnostics(saved.lsp_diagnostics(errors));
+        if let Err(error) = std::fs::write(&lsp_path, json) {
+            eprintln!("Could not write the LSP diagnostics to {}: {}", lsp_path, error);
+        }
+    }
+    match result {
+        VerificationResult::Success => {
+            println!("Verification successful");
+            0
+        }
+        VerificationResult::Failure(errors) => {
+            println!("Verification failed with {} errors:", errors.len());
+            for error in &errors {
+                println!("  {}", saved.describe_error(error));
+            }
+            1
+        }
+        VerificationResult::ConsistencyErrors(errors) => {
+            println!("The program is not consistent:");
+            for error in &errors {
+                println!("  {}", error);
+            }
+            1
+        }
+        VerificationResult::JavaException(exception) => {
+            println!("Verification failed with an exception: {}", exception);
+            1
+        }
+    }
+}
@@ -6,11 +6,13 @@
 
 mod client;
 mod process_verification;
+mod replay;
 mod server;
 mod verification_request;
 
 pub use client::*;
 pub use process_verification::*;
+pub use replay::*;
 pub use server::*;
 pub use verification_request::*;
 
@@ -5,21 +5,54 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::{VerificationRequest, ViperBackendConfig};
-use log::info;
+use log::{info, warn};
 use prusti_common::{config, report::log::report, vir::ToViper, Stopwatch};
-use std::{fs::create_dir_all, path::PathBuf};
-use viper::{Cache, VerificationBackend, VerificationContext};
+use std::{
+    fs::{create_dir_all, write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use viper::{Cache, VerificationBackend, VerificationContext, VerificationResult};
+
+/// A cache that never contains the result of a request, so that a request is
+/// always verified.
+pub struct NoCache;
+
+impl Cache for NoCache {
+    fn get(&self, _request: u64) -> Option<VerificationResult> {
+        None
+    }
+    fn insert(self, _request: u64, _result: VerificationResult) -> Option<VerificationResult> {
+        None
+    }
+}
 
 pub fn process_verification_request<'v, 't: 'v>(
     verification_context: &'v VerificationContext<'t>,
     request: VerificationRequest,
     cache: impl Cache,
 ) -> viper::VerificationResult {
+    process_verification_request_with_time(verification_context, request, cache).0
+}
+
+/// Like `process_verification_request`, but also returns the time that the
+/// backend spent verifying the program, which excludes the construction of
+/// the JVM objects and the startup of the verifier. The time is zero if the
+/// result comes from the cache or if verification is skipped.
+pub fn process_verification_request_with_time<'v, 't: 'v>(
+    verification_context: &'v VerificationContext<'t>,
+    request: VerificationRequest,
+    cache: impl Cache,
+) -> (viper::VerificationResult, Duration) {
     let ast_utils = verification_context.new_ast_utils();
 
     let hash = request.get_hash();
     info!("Verification request hash: {}", hash);
 
+    let keep_viper_files = config::keep_viper_files();
+    let dump_or_keep_viper_program =
+        config::dump_viper_program() || keep_viper_files.is_some();
+
     let build_or_dump_viper_program = || {
         let mut stopwatch = Stopwatch::start("prusti-server", "construction of JVM objects");
         let ast_factory = verification_context.new_ast_factory();
@@ -30,6 +63,16 @@ pub fn process_verification_request<'v, 't: 'v>(
             dump_viper_program(&ast_utils, viper_program, request.program.get_name());
         }
 
+        if let Some(dir) = &keep_viper_files {
+            stopwatch.start_next("keeping viper program");
+            keep_viper_program(
+                &ast_utils,
+                viper_program,
+                Path::new(dir),
+                request.program.get_name(),
+            );
+        }
+
         viper_program
     };
 
@@ -41,23 +84,23 @@ pub fn process_verification_request<'v, 't: 'v>(
         );
         println!("Hash of the request is: {}", hash);
         // Some tests need the dump to report a diff of the Viper programs.
-        if config::dump_viper_program() {
+        if dump_or_keep_viper_program {
             ast_utils.with_local_frame(16, || {
                 let _ = build_or_dump_viper_program();
             });
         }
-        return viper::VerificationResult::Success;
+        return (viper::VerificationResult::Success, Duration::ZERO);
     }
 
     // Early return in case of cache hit
     if config::enable_cache() {
         if let Some(result) = cache.get(hash) {
-            if config::dump_viper_program() {
+            if dump_or_keep_viper_program {
                 ast_utils.with_local_frame(16, || {
                     let _ = build_or_dump_viper_program();
                 });
             }
-            return result;
+            return (result, Duration::ZERO);
         }
     };
 
@@ -70,13 +113,15 @@ pub fn process_verification_request<'v, 't: 'v>(
         let verifier = new_viper_verifier(verification_context, request.backend_config);
 
         stopwatch.start_next("verification");
+        let start = Instant::now();
         let result = verifier.verify(viper_program);
+        let time = start.elapsed();
 
         if config::enable_cache() {
             cache.insert(hash, result.clone());
         }
 
-        result
+        (result, time)
     })
 }
 
@@ -87,6 +132,20 @@ fn dump_viper_program(ast_utils: &viper::AstUtils, program: viper::Program, prog
     report(namespace, filename, ast_utils.pretty_print(program));
 }
 
+fn keep_viper_program(
+    ast_utils: &viper::AstUtils,
+    program: viper::Program,
+    dir: &Path,
+    program_name: &str,
+) {
+    let path = dir.join(format!("{}.vpr", program_name));
+    info!("Keeping Viper program in '{}'", path.display());
+    let result = create_dir_all(dir).and_then(|()| write(&path, ast_utils.pretty_print(program)));
+    if let Err(error) = result {
+        warn!("Failed to keep Viper program in '{}': {}", path.display(), error);
+    }
+}
+
 fn new_viper_verifier<'v, 't: 'v>(
     verification_context: &'v viper::VerificationContext<'t>,
     backend_config: ViperBackendConfig,
@@ -0,0 +1,85 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{process_verification_request, NoCache, VerificationRequest};
+use prusti_common::report::lsp::Diagnostic;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{create_dir_all, File},
+    io::{self, BufReader, BufWriter},
+    path::Path,
+};
+use viper::{VerificationContext, VerificationError, VerificationResult};
+
+/// A verification request saved to a file, so that it can be verified again
+/// without running the compiler front end, e.g. to reproduce a failure on
+/// another machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedVerificationRequest {
+    pub request: VerificationRequest,
+    /// The source location and the kind of obligation of each position of
+    /// the program, by position id.
+    pub positions: Vec<(u64, String)>,
+    /// The URI of the file and the LSP diagnostic of each position, by
+    /// position id. The messages are those of the verification errors.
+    pub diagnostics: Vec<(u64, String, Diagnostic)>,
+    /// The Prusti configuration with which the program was encoded, as
+    /// serialized by `config::serialize_settings`.
+    pub configuration: String,
+}
+
+impl SavedVerificationRequest {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, self)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        bincode::deserialize_from(reader)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Describe a verification error with the saved description of its
+    /// position.
+    pub fn describe_error(&self, error: &VerificationError) -> String {
+        let position = error
+            .pos_id
+            .as_ref()
+            .and_then(|pos_id| pos_id.parse::<u64>().ok())
+            .and_then(|pos_id| self.positions.iter().find(|(id, _)| *id == pos_id))
+            .map(|(_, description)| description.as_str())
+            .unwrap_or("unknown position");
+        format!("[{}] {}: {}", error.full_id, position, error.message)
+    }
+
+    /// The URI of the file and the LSP diagnostic of each verification error
+    /// at a saved position.
+    pub fn lsp_diagnostics(&self, errors: &[VerificationError]) -> Vec<(String, Diagnostic)> {
+        errors
+            .iter()
+            .filter_map(|error| {
+                let pos_id = error.pos_id.as_ref()?.parse::<u64>().ok()?;
+                let (_, uri, diagnostic) = self.diagnostics.iter().find(|(id, _, _)| *id == pos_id)?;
+                let mut diagnostic = diagnostic.clone();
+                diagnostic.message = error.message.clone();
+                Some((uri.clone(), diagnostic))
+            })
+            .collect()
+    }
+}
+
+/// Verify a saved verification request again, without using the cache.
+pub fn replay_verification_request<'v, 't: 'v>(
+    verification_context: &'v VerificationContext<'t>,
+    saved: SavedVerificationRequest,
+) -> VerificationResult {
+    process_verification_request(verification_context, saved.request, NoCache)
+}
@@ -38,13 +38,31 @@ pub struct ViperBackendConfig {
 
 impl Default for ViperBackendConfig {
     fn default() -> Self {
-        let backend = VerificationBackend::from_str(&config::viper_backend()).unwrap();
+        Self::new(VerificationBackend::from_str(&config::viper_backend()).unwrap())
+    }
+}
+
+impl ViperBackendConfig {
+    /// The configuration for the given backend, with the verifier arguments
+    /// derived from the Prusti configuration.
+    pub fn new(backend: VerificationBackend) -> Self {
+        Self::with_qi_limit(backend, config::qi_limit())
+    }
+
+    /// Like `new`, but with the given limit on the number of quantifier
+    /// instantiations instead of the one from the Prusti configuration.
+    pub fn with_qi_limit(backend: VerificationBackend, qi_limit: Option<u64>) -> Self {
         let mut verifier_args = config::extra_verifier_args();
+        let qi_limit_option = qi_limit.map(|limit| format!("smt.qi.max_instances={}", limit));
         match backend {
             VerificationBackend::Silicon => {
                 if config::use_more_complete_exhale() {
                     verifier_args.push("--enableMoreCompleteExhale".to_string());
                 }
+                if let Some(option) = qi_limit_option {
+                    verifier_args.push("--z3ConfigArgs".to_string());
+                    verifier_args.push(option);
+                }
                 if config::counterexample() {
                     verifier_args.push("--counterexample".to_string());
                     verifier_args.push("mapped".to_string());
@@ -59,6 +77,10 @@ impl Default for ViperBackendConfig {
             }
             VerificationBackend::Carbon => {
                 verifier_args.extend(vec!["--disableAllocEncoding".to_string()]);
+                if let Some(option) = qi_limit_option {
+                    verifier_args.push("--boogieOpt".to_string());
+                    verifier_args.push(format!("/proverOpt:O:{}", option));
+                }
             }
         }
         Self {
@@ -0,0 +1,44 @@
+use prusti_common::vir::*;
+use prusti_server::{spawn_server_thread, tokio::runtime::Builder, PrustiClient, VerificationRequest};
+use std::{env, fs};
+use viper::VerificationResult;
+
+/// test that the Viper program of a request is kept in the given directory
+#[test]
+fn keeps_viper_program() {
+    let dir = env::temp_dir().join("prusti-server-kept-viper-files");
+    let _ = fs::remove_dir_all(&dir);
+    // The configuration is read lazily, so this has to happen before the
+    // server starts.
+    env::set_var("PRUSTI_KEEP_VIPER_FILES", &dir);
+    let server_address = spawn_server_thread().to_string();
+    let client = PrustiClient::new(server_address).expect("Could not connect to server!");
+
+    let program = Program {
+        name: "kept_program".to_string(),
+        domains: vec![],
+        fields: vec![],
+        builtin_methods: vec![],
+        methods: vec![],
+        functions: vec![],
+        viper_predicates: vec![],
+    };
+    let request = VerificationRequest {
+        program: prusti_common::vir::program::Program::Legacy(program),
+        backend_config: Default::default(),
+    };
+    let result = Builder::new()
+        .basic_scheduler()
+        .enable_all()
+        .build()
+        .expect("failed to construct Tokio runtime")
+        .block_on(client.verify(request))
+        .expect("Verification request failed");
+
+    assert!(matches!(result, VerificationResult::Success));
+    assert!(
+        dir.join("kept_program.vpr").is_file(),
+        "the Viper program was not kept in {}",
+        dir.display()
+    );
+}
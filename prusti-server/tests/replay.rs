@@ -0,0 +1,129 @@
+use lazy_static::lazy_static;
+use prusti_common::{
+    config,
+    report::lsp::{self, Diagnostic, DiagnosticSeverity},
+    vir::*,
+};
+use prusti_server::{
+    process_verification_request, replay_verification_request, SavedVerificationRequest,
+    VerificationRequest,
+};
+use std::{env, path::PathBuf};
+use viper::{PersistentCache, VerificationError, VerificationResult, Viper};
+
+lazy_static! {
+    // only start the jvm once
+    static ref VIPER: Viper = Viper::new_with_args(config::extra_jvm_args());
+}
+
+#[test]
+fn replay_consistency_error() {
+    let (direct, replayed) = verify_and_replay("replay_consistency_error", |program| {
+        program.fields.push(Field {
+            name: "field".to_string(), // reserved identifier
+            typ: Type::Int,
+        });
+    });
+
+    assert!(matches!(direct, VerificationResult::ConsistencyErrors(_)));
+    assert_eq!(direct, replayed);
+}
+
+#[test]
+fn replay_empty_program() {
+    let (direct, replayed) = verify_and_replay("replay_empty_program", |_| ());
+
+    assert!(matches!(direct, VerificationResult::Success));
+    assert_eq!(direct, replayed);
+}
+
+#[test]
+fn replay_lsp_diagnostics() {
+    let range = lsp::Range {
+        start: lsp::Position { line: 2, character: 4 },
+        end: lsp::Position { line: 2, character: 18 },
+    };
+    let saved = SavedVerificationRequest {
+        request: VerificationRequest {
+            program: prusti_common::vir::program::Program::Legacy(empty_program("replay_lsp_diagnostics")),
+            backend_config: Default::default(),
+        },
+        positions: vec![],
+        diagnostics: vec![(
+            3,
+            "file:///src/lib.rs".to_string(),
+            Diagnostic::new(range, DiagnosticSeverity::Error, "panic", ""),
+        )],
+        configuration: config::serialize_settings(),
+    };
+    let error = |pos_id: &str| {
+        VerificationError::new(
+            "assert.failed:assertion.false".to_string(),
+            Some(pos_id.to_string()),
+            None,
+            "Assert might fail. Assertion false might not hold.".to_string(),
+            None,
+        )
+    };
+
+    // The error at an unknown position has no diagnostic.
+    let diagnostics = saved.lsp_diagnostics(&[error("3"), error("4")]);
+    assert_eq!(
+        diagnostics,
+        vec![(
+            "file:///src/lib.rs".to_string(),
+            Diagnostic::new(
+                range,
+                DiagnosticSeverity::Error,
+                "panic",
+                "Assert might fail. Assertion false might not hold.",
+            ),
+        )]
+    );
+}
+
+fn empty_program(name: &str) -> Program {
+    Program {
+        name: name.to_string(),
+        domains: vec![],
+        fields: vec![],
+        builtin_methods: vec![],
+        methods: vec![],
+        functions: vec![],
+        viper_predicates: vec![],
+    }
+}
+
+/// Verify a program directly, then save its request to a file, load it and
+/// verify it again.
+fn verify_and_replay<F>(name: &str, configure: F) -> (VerificationResult, VerificationResult)
+where
+    F: FnOnce(&mut Program),
+{
+    let mut program = empty_program(name);
+    configure(&mut program);
+    let request = VerificationRequest {
+        program: prusti_common::vir::program::Program::Legacy(program),
+        backend_config: Default::default(),
+    };
+
+    let verification_context = VIPER.attach_current_thread();
+    // An empty path disables loading and saving the cache.
+    let mut cache = PersistentCache::load_cache(PathBuf::new());
+    let direct = process_verification_request(&verification_context, request.clone(), &mut cache);
+
+    let path = env::temp_dir()
+        .join("prusti-server-replay")
+        .join(format!("{}.request", name));
+    let saved = SavedVerificationRequest {
+        request,
+        positions: vec![],
+        diagnostics: vec![],
+        configuration: config::serialize_settings(),
+    };
+    saved.save(&path).expect("could not save the request");
+    let loaded = SavedVerificationRequest::load(&path).expect("could not load the request");
+    let replayed = replay_verification_request(&verification_context, loaded);
+
+    (direct, replayed)
+}
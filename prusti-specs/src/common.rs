@@ -241,6 +241,13 @@ mod receiver_rewriter {
     ///     _self.qux()
     /// }
     /// ```
+    ///
+    /// A receiver with an explicit type such as `self: Pin<&mut Self>` keeps
+    /// its type (with `Self` already rewritten), so a pinned receiver is still
+    /// pinned in the specifications:
+    /// ```ignore
+    /// fn foo(_self: Pin<&mut T>, arg1: Bar) -> Baz
+    /// ```
     pub(crate) trait RewritableReceiver {
         fn rewrite_receiver(&mut self, new_ty: &TypePath);
     }
@@ -276,8 +283,11 @@ mod receiver_rewriter {
             let tokens_span = tokens.span();
             let rewritten = TokenStream::from_iter(tokens.into_iter().map(|token| match token {
                 TokenTree::Group(group) => {
-                    let new_group =
+                    let mut new_group =
                         proc_macro2::Group::new(group.delimiter(), self.rewrite_tokens(group.stream()));
+                    // Keep the span, so that errors in the group are reported
+                    // on the tokens written by the user.
+                    new_group.set_span(group.span());
                     TokenTree::Group(new_group)
                 }
                 TokenTree::Ident(ident) if ident == "self" => {
@@ -308,6 +318,8 @@ mod receiver_rewriter {
             };
                 *fn_arg = new_fn_arg;
             } else {
+                // A typed receiver (e.g. `self: Pin<&mut Self>`) is renamed
+                // by `visit_ident_mut` and keeps its type.
                 syn::visit_mut::visit_fn_arg_mut(self, fn_arg);
             }
         }
@@ -523,6 +535,30 @@ mod tests {
         }
     }
 
+    mod receiver {
+        use super::*;
+
+        #[test]
+        fn rewrite_pinned_receiver() {
+            let self_type: syn::TypePath = parse_quote!(Foo);
+            let mut method: syn::ImplItemMethod = parse_quote!(
+                fn poll(self: Pin<&mut Self>, other: &Self) -> bool {
+                    self.bar(other)
+                }
+            );
+
+            method.rewrite_self_type(&self_type, None);
+            method.rewrite_receiver(&self_type);
+
+            let expected: syn::ImplItemMethod = parse_quote!(
+                fn poll(_self: Pin<&mut Foo>, other: &Foo) -> bool {
+                    _self.bar(other)
+                }
+            );
+            assert_eq!(expected, method);
+        }
+    }
+
     mod phantom_data {
         use super::*;
         use quote::ToTokens;
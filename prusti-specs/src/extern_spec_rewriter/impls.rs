@@ -32,7 +32,9 @@ fn rewrite_extern_spec_internal(item_impl: &syn::ItemImpl) -> syn::Result<Rewrit
 
     if item_impl.trait_.is_some() {
         let (_, trait_path, _) = item_impl.trait_.as_ref().unwrap();
-        if has_generic_arguments(trait_path) {
+        // Concrete generic arguments of the trait, as in `impl TryFrom<i32> for u8`,
+        // select one of its implementations and are supported.
+        if has_generic_arguments(trait_path) && !item_impl.generics.params.is_empty() {
             return Err(syn::Error::new(
                 item_impl.generics.params.span(),
                 "Generics for extern trait impls are not supported",
@@ -329,10 +331,36 @@ mod tests {
             assert_eq_tokenizable(rewritten.generated_impl.clone(), expected_impl);
         }
 
+        #[test]
+        fn concrete_generic_arguments() {
+            let mut inp_impl: syn::ItemImpl = parse_quote!(
+                impl MyTrait<i32> for MyStruct {
+                    fn foo(arg1: i32) -> Result<Self, Self::Error>;
+                }
+            );
+
+            let rewritten = rewrite_extern_spec_internal(&mut inp_impl).unwrap();
+
+            let newtype_ident = &rewritten.generated_struct.ident;
+            let expected_impl: syn::ItemImpl = parse_quote! {
+                impl #newtype_ident <> {
+                    #[prusti::extern_spec = "trait_impl"]
+                    #[allow(unused, dead_code)]
+                    #[prusti::trusted]
+                    fn foo(arg1: i32) -> Result<MyStruct, <MyStruct as MyTrait<i32> > :: Error> {
+                        <MyStruct as MyTrait<i32> > :: foo(arg1);
+                        unimplemented!()
+                    }
+                }
+            };
+
+            assert_eq_tokenizable(rewritten.generated_impl.clone(), expected_impl);
+        }
+
         #[test]
         fn generics_not_supported() {
             let mut inp_impl: syn::ItemImpl = parse_quote!(
-                impl MyTrait<I> for MyStruct {
+                impl<I> MyTrait<I> for MyStruct {
                     fn foo(&mut self, arg1: I);
                 }
             );
@@ -57,7 +57,9 @@ fn extract_prusti_attributes(
                     | SpecAttributeKind::Ensures
                     | SpecAttributeKind::AfterExpiry
                     | SpecAttributeKind::AssertOnExpiry
-                    | SpecAttributeKind::GhostConstraint => {
+                    | SpecAttributeKind::GhostConstraint
+                    | SpecAttributeKind::Terminates
+                    | SpecAttributeKind::Case => {
                         // We need to drop the surrounding parenthesis to make the
                         // tokens identical to the ones passed by the native procedural
                         // macro call.
@@ -73,6 +75,17 @@ fn extract_prusti_attributes(
                         assert!(attr.tokens.is_empty(), "Unexpected shape of an attribute.");
                         attr.tokens
                     }
+                    // The arguments are optional.
+                    SpecAttributeKind::OpaqueSpec => {
+                        let mut iter = attr.tokens.into_iter();
+                        let tokens = match iter.next() {
+                            Some(TokenTree::Group(group)) => group.stream(),
+                            None => TokenStream::new(),
+                            _ => unreachable!("Unexpected shape of an attribute."),
+                        };
+                        assert!(iter.next().is_none(), "Unexpected shape of an attribute.");
+                        tokens
+                    }
                 };
                 prusti_attributes.push((attr_kind, tokens));
             } else {
@@ -86,6 +99,18 @@ fn extract_prusti_attributes(
     prusti_attributes
 }
 
+/// Check whether any of the attributes is a Prusti specification attribute
+/// (e.g. `#[requires(...)]` or `#[pure]`).
+fn has_prusti_attributes(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if attr.path.segments.len() != 1 {
+            return false;
+        }
+        let attr_kind: Result<SpecAttributeKind, _> = attr.path.segments[0].ident.to_string().try_into();
+        attr_kind.is_ok()
+    })
+}
+
 /// Rewrite an item as required by *all* its specification attributes.
 ///
 /// The first attribute (the outer one) needs to be passed via `attr_kind` and `attr` because
@@ -136,6 +161,8 @@ fn generate_spec_and_assertions(
 ) -> GeneratedResult {
     let mut generated_items = vec![];
     let mut generated_attributes = vec![];
+    let mut cases = vec![];
+    let is_pure = prusti_attributes.iter().any(|(attr_kind, _)| attr_kind == &SpecAttributeKind::Pure);
 
     for (attr_kind, attr_tokens) in prusti_attributes.drain(..) {
         let rewriting_result = match attr_kind {
@@ -150,11 +177,24 @@ fn generate_spec_and_assertions(
             // `check_incompatible_attrs`; so we'll never reach here.
             SpecAttributeKind::Predicate => unreachable!(),
             SpecAttributeKind::GhostConstraint => ghost_constraints::generate(attr_tokens, item),
+            SpecAttributeKind::OpaqueSpec => generate_for_opaque_spec(attr_tokens, item),
+            SpecAttributeKind::Terminates => generate_for_terminates(attr_tokens, item),
+            // The cases are handled together below, because their
+            // preconditions are combined into one precondition.
+            SpecAttributeKind::Case => {
+                cases.push(attr_tokens);
+                continue;
+            }
         };
         let (new_items, new_attributes) = rewriting_result?;
         generated_items.extend(new_items);
         generated_attributes.extend(new_attributes);
     }
+    if !cases.is_empty() {
+        let (new_items, new_attributes) = generate_for_cases(cases, is_pure, item)?;
+        generated_items.extend(new_items);
+        generated_attributes.extend(new_attributes);
+    }
 
     Ok((generated_items, generated_attributes))
 }
@@ -264,6 +304,270 @@ fn generate_for_trusted(attr: TokenStream, item: &untyped::AnyFnItem) -> Generat
     ))
 }
 
+/// Generate spec items and attributes to typecheck and later retrieve the
+/// postconditions of an `#[opaque_spec(ensures(...), ...)]` attribute, which
+/// are exposed to callers outside of the module instead of the full contract.
+fn generate_for_opaque_spec(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let mut generated_items = vec![];
+    let mut generated_attributes: Vec<syn::Attribute> = vec![parse_quote_spanned! {item.span()=>
+        #[prusti::opaque_spec]
+    }];
+    let mut iter = attr.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        let assertion = match (&token, iter.next()) {
+            (TokenTree::Ident(ident), Some(TokenTree::Group(group)))
+                if ident == "ensures" && group.delimiter() == proc_macro2::Delimiter::Parenthesis =>
+            {
+                group.stream()
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    token.span(),
+                    "expected `ensures(...)` in the `#[opaque_spec(...)]` attribute",
+                ));
+            }
+        };
+        let mut rewriter = rewriter::AstRewriter::new();
+        let spec_id = rewriter.generate_spec_id();
+        let spec_id_str = spec_id.to_string();
+        generated_items.push(rewriter.process_assertion(
+            rewriter::SpecItemType::Postcondition,
+            spec_id,
+            assertion,
+            item,
+        )?);
+        generated_attributes.push(parse_quote_spanned! {item.span()=>
+            #[prusti::opaque_post_spec_id_ref = #spec_id_str]
+        });
+        if let Some(TokenTree::Punct(punct)) = iter.peek() {
+            if punct.as_char() == ',' {
+                iter.next();
+            }
+        }
+    }
+    Ok((generated_items, generated_attributes))
+}
+
+/// Generate spec items and attributes to typecheck and later retrieve the
+/// lexicographic termination measure of a `#[terminates(m1, m2, ...)]`
+/// attribute, and its optional `group = "name"` of mutually recursive
+/// functions.
+fn generate_for_terminates(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let parser = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+    let arguments = syn::parse::Parser::parse2(parser, attr.clone())?;
+    let mut generated_items: Vec<syn::Item> = vec![];
+    let mut generated_attributes: Vec<syn::Attribute> = vec![];
+    for argument in arguments {
+        match argument {
+            syn::Expr::Assign(syn::ExprAssign { left, right, .. })
+                if matches!(&*left, syn::Expr::Path(path) if path.path.is_ident("group")) =>
+            {
+                let group = match *right {
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(group), .. }) => group.value(),
+                    right => {
+                        return Err(syn::Error::new(
+                            right.span(),
+                            "the group of a `#[terminates(...)]` attribute must be a string literal",
+                        ));
+                    }
+                };
+                generated_attributes.push(parse_quote_spanned! {item.span()=>
+                    #[prusti::termination_group = #group]
+                });
+            }
+            measure => {
+                let mut rewriter = rewriter::AstRewriter::new();
+                let spec_id = rewriter.generate_spec_id();
+                let spec_id_str = spec_id.to_string();
+                generated_items.push(rewriter.process_measure(spec_id, measure, item)?);
+                generated_attributes.push(parse_quote_spanned! {item.span()=>
+                    #[prusti::measure_spec_id_ref = #spec_id_str]
+                });
+            }
+        }
+    }
+    if generated_items.is_empty() {
+        return Err(syn::Error::new(
+            attr.span(),
+            "the `#[terminates(...)]` attribute requires a termination measure",
+        ));
+    }
+    Ok((generated_items, generated_attributes))
+}
+
+/// The name, precondition and postcondition of a
+/// `#[case(name = "...", pre = ..., post = ...)]` attribute.
+struct ParsedCase {
+    span: Span,
+    name: Option<String>,
+    pre: TokenStream,
+    post: TokenStream,
+}
+
+/// Parse the arguments of a `#[case(...)]` attribute. The assertions are not
+/// Rust expressions, so the arguments are split at the commas that are neither
+/// nested in a group nor in the generic arguments of a turbofish, e.g. in
+/// `pre = f::<u32, u32>(x)`.
+fn parse_case(attr: TokenStream) -> syn::Result<ParsedCase> {
+    let span = attr.clone().into_iter().next().map_or_else(Span::call_site, |token| token.span());
+    let mut case = ParsedCase { span, name: None, pre: TokenStream::new(), post: TokenStream::new() };
+    let mut has_pre = false;
+    let mut has_post = false;
+    let mut iter = attr.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        let key = match (&token, iter.next()) {
+            (TokenTree::Ident(key), Some(TokenTree::Punct(punct)))
+                if punct.as_char() == '=' && punct.spacing() == proc_macro2::Spacing::Alone =>
+            {
+                key.to_string()
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    token.span(),
+                    "expected `name = \"...\"`, `pre = ...` or `post = ...` in the `#[case(...)]` attribute",
+                ));
+            }
+        };
+        let mut value = TokenStream::new();
+        let mut generics_depth = 0;
+        let mut previous_char = None;
+        while let Some(token) = iter.next_if(|token| {
+            generics_depth > 0 || !matches!(token, TokenTree::Punct(punct) if punct.as_char() == ',')
+        }) {
+            let current_char = match &token {
+                TokenTree::Punct(punct) => Some(punct.as_char()),
+                _ => None,
+            };
+            match current_char {
+                // A `<` opens generic arguments only after the `::` of a
+                // turbofish or inside other generic arguments; otherwise it
+                // is a comparison.
+                Some('<') if generics_depth > 0 || previous_char == Some(':') => generics_depth += 1,
+                // The `>` of `->` does not close generic arguments.
+                Some('>') if generics_depth > 0 && previous_char != Some('-') => generics_depth -= 1,
+                _ => {}
+            }
+            previous_char = current_char;
+            value.extend(std::iter::once(token));
+        }
+        iter.next();
+        if value.is_empty() {
+            return Err(syn::Error::new(token.span(), format!("expected a value for `{}`", key)));
+        }
+        match key.as_str() {
+            "name" => {
+                let name: syn::LitStr = syn::parse2(value)?;
+                case.name = Some(name.value());
+            }
+            "pre" if !has_pre => {
+                has_pre = true;
+                case.pre = value;
+            }
+            "post" if !has_post => {
+                has_post = true;
+                case.post = value;
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    token.span(),
+                    format!("unexpected argument `{}` in the `#[case(...)]` attribute", key),
+                ));
+            }
+        }
+    }
+    if !has_pre || !has_post {
+        return Err(syn::Error::new(
+            span,
+            "the `#[case(...)]` attribute requires a `pre` and a `post` assertion",
+        ));
+    }
+    Ok(case)
+}
+
+/// Generate spec items and attributes to typecheck and later retrieve the
+/// `#[case(...)]` attributes of `item`. The procedure requires that the
+/// precondition of one of the cases holds, and ensures the postcondition of
+/// every case whose precondition held on entry. A pure function cannot
+/// mutate its arguments, so there its preconditions are not wrapped in `old`.
+fn generate_for_cases(cases: Vec<TokenStream>, is_pure: bool, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let mut rewriter = rewriter::AstRewriter::new();
+    let mut generated_items = vec![];
+    let mut generated_attributes: Vec<syn::Attribute> = vec![];
+    let mut case_pres = vec![];
+    let mut pres_span = item.span();
+    for case in cases {
+        let ParsedCase { span, name, pre, post } = parse_case(case)?;
+        if case_pres.is_empty() {
+            pres_span = span;
+        }
+        let pre_spec_id = rewriter.generate_spec_id();
+        let post_spec_id = rewriter.generate_spec_id();
+        let mut pre_item = rewriter.process_assertion(
+            rewriter::SpecItemType::Precondition,
+            pre_spec_id,
+            pre.clone(),
+            item,
+        )?;
+        if let (syn::Item::Fn(pre_item), Some(name)) = (&mut pre_item, name) {
+            pre_item.attrs.push(parse_quote_spanned! {span=>
+                #[prusti::case_name = #name]
+            });
+        }
+        generated_items.push(pre_item);
+        generated_items.push(rewriter.process_assertion(
+            rewriter::SpecItemType::Postcondition,
+            post_spec_id,
+            if is_pure {
+                quote_spanned! {span=> (#pre) ==> (#post)}
+            } else {
+                quote_spanned! {span=> old(#pre) ==> (#post)}
+            },
+            item,
+        )?);
+        let case_spec_id_str = format!("{}:{}", pre_spec_id, post_spec_id);
+        generated_attributes.push(parse_quote_spanned! {item.span()=>
+            #[prusti::case_spec_id_ref = #case_spec_id_str]
+        });
+        case_pres.push(quote_spanned! {span=> (#pre)});
+    }
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    generated_items.push(rewriter.process_assertion(
+        rewriter::SpecItemType::Precondition,
+        spec_id,
+        quote_spanned! {pres_span=> #(#case_pres)||*},
+        item,
+    )?);
+    generated_attributes.push(parse_quote_spanned! {item.span()=>
+        #[prusti::pre_spec_id_ref = #spec_id_str]
+    });
+    Ok((generated_items, generated_attributes))
+}
+
+/// Rewrite a `#[trusted]` attribute. On an `impl` block, the attribute marks
+/// every method of the block as trusted, but neither the implemented trait nor
+/// its other impls.
+pub fn trusted(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    let mut impl_block: syn::ItemImpl = match syn::parse2(tokens.clone()) {
+        Ok(impl_block) => impl_block,
+        Err(_) => return rewrite_prusti_attributes(SpecAttributeKind::Trusted, attr, tokens),
+    };
+    if !attr.is_empty() {
+        return syn::Error::new(
+            attr.span(),
+            "the `#[trusted]` attribute does not take parameters"
+        ).to_compile_error();
+    }
+    for item in impl_block.items.iter_mut() {
+        if let syn::ImplItem::Method(method) = item {
+            method.attrs.push(parse_quote_spanned! {method.span()=>
+                #[prusti::trusted]
+            });
+        }
+    }
+    impl_block.into_token_stream()
+}
+
 pub fn body_invariant(tokens: TokenStream) -> TokenStream {
     let mut rewriter = rewriter::AstRewriter::new();
     let spec_id = rewriter.generate_spec_id();
@@ -277,6 +581,19 @@ pub fn body_invariant(tokens: TokenStream) -> TokenStream {
     }
 }
 
+pub fn break_ensures(tokens: TokenStream) -> TokenStream {
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let postcondition = handle_result!(rewriter.process_loop_break_ensures(spec_id, tokens));
+    let callsite_span = Span::call_site();
+    quote_spanned! {callsite_span=>
+        #[allow(unused_must_use, unused_variables, unused_braces, unused_parens)]
+        if false {
+            #postcondition
+        }
+    }
+}
+
 /// Unlike the functions above, which are only called from
 /// prusti-contracts-internal, this function also needs to be called
 /// from prusti-contracts-impl, because we still need to parse the
@@ -325,6 +642,16 @@ pub fn closure(tokens: TokenStream, drop_spec: bool) -> TokenStream {
         });
     }
 
+    if let Some(modifies) = cl_spec.modifies {
+        let modifies_str = modifies.iter()
+            .map(|ident| ident.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        cl_annotations.extend(quote_spanned! {callsite_span=>
+            #[prusti::closure_modifies = #modifies_str]
+        });
+    }
+
     let syn::ExprClosure {
         attrs, asyncness, movability, capture, or1_token,
         inputs, or2_token, output, body
@@ -393,7 +720,9 @@ pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream
     let mut generated_spec_items = Vec::new();
     for item in impl_block.items {
         match item {
-            syn::ImplItem::Method(method) => {
+            // Methods without specifications (e.g. generated by other macros)
+            // are kept as they are.
+            syn::ImplItem::Method(method) if has_prusti_attributes(&method.attrs) => {
                 let mut method_item = untyped::AnyFnItem::ImplMethod(method);
                 let prusti_attributes: Vec<_> = extract_prusti_attributes(&mut method_item);
 
@@ -454,6 +783,93 @@ pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream
     }
 }
 
+/// Attach the assertion of an `#[assumptions(...)]` attribute to all
+/// functions of the module, including the methods of inherent impls and the
+/// functions of nested modules.
+pub fn assumptions(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    let mut item_mod: syn::ItemMod = handle_result!(syn::parse2(tokens));
+    handle_result!(rewrite_module_assumptions(&attr, &mut item_mod));
+    item_mod.into_token_stream()
+}
+
+fn rewrite_module_assumptions(attr: &TokenStream, item_mod: &mut syn::ItemMod) -> syn::Result<()> {
+    let items = match &mut item_mod.content {
+        Some((_, items)) => items,
+        None => {
+            return Err(syn::Error::new(
+                item_mod.span(),
+                "`#[assumptions(...)]` can only be attached to inline modules",
+            ));
+        }
+    };
+    let mut new_items = Vec::new();
+    for item in items.drain(..) {
+        match item {
+            syn::Item::Fn(item_fn) => {
+                let mut fn_item = untyped::AnyFnItem::Fn(item_fn);
+                let (spec_item, attribute) = generate_for_assumption(attr.clone(), &fn_item)?;
+                fn_item.attrs_mut().push(attribute);
+                new_items.push(spec_item);
+                new_items.push(force_matches!(fn_item, untyped::AnyFnItem::Fn(item_fn) =>
+                    syn::Item::Fn(item_fn)));
+            }
+            // Methods of trait impls cannot get additional spec items in the
+            // same impl block, so only inherent impls are rewritten.
+            syn::Item::Impl(mut item_impl) if item_impl.trait_.is_none() => {
+                let mut new_impl_items = Vec::new();
+                for impl_item in item_impl.items.drain(..) {
+                    match impl_item {
+                        syn::ImplItem::Method(method) => {
+                            let mut method_item = untyped::AnyFnItem::ImplMethod(method);
+                            let (spec_item, attribute) =
+                                generate_for_assumption(attr.clone(), &method_item)?;
+                            method_item.attrs_mut().push(attribute);
+                            let spec_item_fn = force_matches!(spec_item, syn::Item::Fn(item_fn) => item_fn);
+                            new_impl_items.push(parse_quote_spanned! {spec_item_fn.span()=>
+                                #spec_item_fn
+                            });
+                            new_impl_items.push(syn::ImplItem::Method(method_item.expect_impl_item()));
+                        }
+                        impl_item => new_impl_items.push(impl_item),
+                    }
+                }
+                item_impl.items = new_impl_items;
+                new_items.push(syn::Item::Impl(item_impl));
+            }
+            syn::Item::Mod(mut nested_mod) if nested_mod.content.is_some() => {
+                rewrite_module_assumptions(attr, &mut nested_mod)?;
+                new_items.push(syn::Item::Mod(nested_mod));
+            }
+            item => new_items.push(item),
+        }
+    }
+    *items = new_items;
+    Ok(())
+}
+
+/// Generate a spec item and an attribute to typecheck and later retrieve a
+/// module assumption of `item`.
+fn generate_for_assumption(
+    attr: TokenStream,
+    item: &untyped::AnyFnItem,
+) -> syn::Result<(syn::Item, syn::Attribute)> {
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    let spec_item = rewriter.process_assertion(
+        rewriter::SpecItemType::Precondition,
+        spec_id,
+        attr,
+        item,
+    )?;
+    Ok((
+        spec_item,
+        parse_quote_spanned! {item.span()=>
+            #[prusti::assumption_spec_id_ref = #spec_id_str]
+        },
+    ))
+}
+
 pub fn extern_spec(_attr: TokenStream, tokens:TokenStream) -> TokenStream {
     let item: syn::Item = handle_result!(syn::parse2(tokens));
     match item {
@@ -1,8 +1,11 @@
 use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 
 pub(crate) struct ClosureWithSpec {
     pub pres: Vec<syn::Expr>,
     pub posts: Vec<syn::Expr>,
+    /// The captured variables the closure may mutate, if declared.
+    pub modifies: Option<Vec<syn::Ident>>,
     pub cl: syn::ExprClosure
 }
 
@@ -13,6 +16,7 @@ impl Parse for ClosureWithSpec {
 
         let mut pres: Vec<syn::Result<syn::Expr>> = vec![];
         let mut posts: Vec<syn::Result<syn::Expr>> = vec![];
+        let mut modifies: Option<syn::Result<Vec<syn::Ident>>> = None;
 
         // collect and remove any specification attributes
         // leave other attributes intact
@@ -21,6 +25,10 @@ impl Parse for ClosureWithSpec {
                 match id.to_string().as_ref() {
                     "requires" => pres.push(syn::parse2(attr.tokens.clone())),
                     "ensures" => posts.push(syn::parse2(attr.tokens.clone())),
+                    "modifies" => modifies = Some(
+                        attr.parse_args_with(Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated)
+                            .map(|idents| idents.into_iter().collect())
+                    ),
                     _ => return false
                 }
                 true
@@ -33,6 +41,7 @@ impl Parse for ClosureWithSpec {
         Ok(Self {
             pres: pres.into_iter().collect::<syn::Result<Vec<_>>>()?,
             posts: posts.into_iter().collect::<syn::Result<Vec<_>>>()?,
+            modifies: modifies.transpose()?,
             cl,
         })
     }
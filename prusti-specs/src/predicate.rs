@@ -5,6 +5,8 @@ use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
 use syn::{parse::Parse, parse_quote_spanned, spanned::Spanned};
 use crate::common::{HasMacro, HasSignature};
+use crate::SpecAttributeKind;
+use std::convert::TryFrom;
 
 #[derive(Debug)]
 pub struct PredicateWithBody<T: ToTokens> {
@@ -82,7 +84,8 @@ fn parse_predicate_internal(
     let input: PredicateFnInput = syn::parse2(tokens).map_err(|e| {
         syn::Error::new(
             e.span(),
-            "`predicate!` can only be used on function definitions. it supports no attributes.",
+            "`predicate!` can only be used on function definitions. it supports only `requires` \
+            and `ensures` attributes.",
         )
     })?;
 
@@ -111,9 +114,11 @@ fn parse_predicate_internal(
             }))
         }
     } else {
+        let attributes = input.attributes;
         let signature = input.fn_sig;
         let patched_function = parse_quote_spanned!(span=>
             #[prusti::abstract_predicate]
+            #(#attributes)*
             #signature;
         );
 
@@ -128,6 +133,7 @@ fn patch_predicate_macro_body<R: Parse>(
     input_span: Span,
     spec_id: SpecificationId,
 ) -> R {
+    let attributes = &predicate.attributes;
     let visibility = &predicate.visibility;
     let signature = &predicate.fn_sig;
     let spec_id_str = spec_id.to_string();
@@ -135,6 +141,7 @@ fn patch_predicate_macro_body<R: Parse>(
     parse_quote_spanned!(input_span=>
         #[allow(unused_must_use, unused_variables, dead_code)]
         #[prusti::pred_spec_id_ref = #spec_id_str]
+        #(#attributes)*
         #visibility #signature {
             unimplemented!("predicate")
         }
@@ -157,6 +164,8 @@ fn generate_spec_function<T: HasSignature + Spanned>(
 
 #[derive(Debug)]
 struct PredicateFnInput {
+    /// Specification attributes (`requires` and `ensures`) of the predicate
+    attributes: Vec<syn::Attribute>,
     visibility: Option<syn::Visibility>,
     fn_sig: syn::Signature,
     body: Option<TokenStream>,
@@ -164,6 +173,10 @@ struct PredicateFnInput {
 
 impl syn::parse::Parse for PredicateFnInput {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attributes = input.call(syn::Attribute::parse_outer)?;
+        if let Some(attribute) = attributes.iter().find(|attr| !is_predicate_spec_attribute(attr)) {
+            return Err(syn::Error::new(attribute.pound_token.span, "unsupported attribute"));
+        }
         let visibility = input.parse().ok();
         let fn_sig = input.parse()?;
 
@@ -178,9 +191,19 @@ impl syn::parse::Parse for PredicateFnInput {
         };
 
         Ok(PredicateFnInput {
+            attributes,
             visibility,
             fn_sig,
             body,
         })
     }
 }
+
+/// Predicates can carry their own pre- and postconditions.
+fn is_predicate_spec_attribute(attribute: &syn::Attribute) -> bool {
+    attribute.path.segments.len() == 1
+        && matches!(
+            SpecAttributeKind::try_from(attribute.path.segments[0].ident.to_string()),
+            Ok(SpecAttributeKind::Requires) | Ok(SpecAttributeKind::Ensures)
+        )
+}
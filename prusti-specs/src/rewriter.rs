@@ -151,6 +151,35 @@ impl AstRewriter {
         )
     }
 
+    /// Turn a component of a termination measure into a function that
+    /// computes it from the arguments of `item`.
+    pub fn process_measure<T: HasSignature + Spanned>(
+        &mut self,
+        spec_id: SpecificationId,
+        expr: syn::Expr,
+        item: &T,
+    ) -> syn::Result<syn::Item> {
+        let item_span = expr.span();
+        let item_name = syn::Ident::new(
+            &format!("prusti_measure_item_{}_{}", item.sig().ident, spec_id),
+            item_span,
+        );
+        let spec_id_str = spec_id.to_string();
+        // The measure is converted to `i128` so that the measures of the
+        // functions of a group can be compared with each other.
+        let mut spec_item: syn::ItemFn = parse_quote_spanned! {item_span=>
+            #[allow(unused_must_use, unused_parens, unused_variables, dead_code)]
+            #[prusti::spec_only]
+            #[prusti::spec_id = #spec_id_str]
+            fn #item_name() -> i128 {
+                (#expr) as i128
+            }
+        };
+        spec_item.sig.generics = item.sig().generics.clone();
+        spec_item.sig.inputs = item.sig().inputs.clone();
+        Ok(syn::Item::Fn(spec_item))
+    }
+
     /// Parse a pledge with lhs into a Rust expression
     pub fn process_pledge(
         &mut self,
@@ -210,6 +239,26 @@ impl AstRewriter {
         })
     }
 
+    /// Parse the postcondition of the `break`s of a loop into a Rust expression
+    pub fn process_loop_break_ensures(
+        &mut self,
+        spec_id: SpecificationId,
+        tokens: TokenStream,
+    ) -> syn::Result<TokenStream> {
+        let expr = parse_prusti(tokens)?;
+        let spec_id_str = spec_id.to_string();
+        Ok(quote_spanned! {expr.span()=>
+            {
+                #[prusti::spec_only]
+                #[prusti::loop_break_ensures_spec]
+                #[prusti::spec_id = #spec_id_str]
+                || -> bool {
+                    #expr
+                };
+            }
+        })
+    }
+
     /// Parse a closure with specifications into a Rust expression
     /// TODO: arguments, result (types are typically not known yet after parsing...)
     pub fn process_closure(
@@ -11,6 +11,9 @@ pub enum SpecAttributeKind {
     Trusted,
     Predicate,
     GhostConstraint,
+    OpaqueSpec,
+    Terminates,
+    Case,
 }
 
 impl TryFrom<String> for SpecAttributeKind {
@@ -26,6 +29,9 @@ impl TryFrom<String> for SpecAttributeKind {
             "trusted" => Ok(SpecAttributeKind::Trusted),
             "predicate" => Ok(SpecAttributeKind::Predicate),
             "ghost_constraint" => Ok(SpecAttributeKind::GhostConstraint),
+            "opaque_spec" => Ok(SpecAttributeKind::OpaqueSpec),
+            "terminates" => Ok(SpecAttributeKind::Terminates),
+            "case" => Ok(SpecAttributeKind::Case),
             _ => Err(name),
         }
     }
@@ -60,6 +60,13 @@ pub enum SpecIdRef {
         rhs: SpecificationId,
     },
     Predicate(SpecificationId),
+    Assumption(SpecificationId),
+    OpaquePostcondition(SpecificationId),
+    Measure(SpecificationId),
+    Case {
+        pre: SpecificationId,
+        post: SpecificationId,
+    },
 }
 
 impl Display for SpecificationId {
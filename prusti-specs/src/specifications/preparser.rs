@@ -71,6 +71,177 @@ Preparsing consists of two stages:
    Prusti AST, as we reuse the Rust AST (i.e. [TokenTree] and [TokenStream]).
 */
 
+/// Translates the arguments of `matches!(expr, pattern)` into comparisons of
+/// `expr` with the bounds of the pattern, if the pattern consists only of
+/// integer range patterns (e.g. `0..10`, `0..=10`, `5..` or `..10`), possibly
+/// combined with `|`. Returns `None` for all other patterns, which are left to
+/// the `matches!` macro.
+fn translate_range_matches(args: TokenStream) -> Option<TokenStream> {
+    let is_punct = |token: &TokenTree, c: char| {
+        matches!(token, TokenTree::Punct(punct) if punct.as_char() == c)
+    };
+    let mut args = args.into_iter().collect::<Vec<_>>();
+    if args.last().map_or(false, |token| is_punct(token, ',')) {
+        args.pop();
+    }
+    // The scrutinee might contain commas (e.g. in a turbofish), the pattern
+    // cannot.
+    let comma = args.iter().rposition(|token| is_punct(token, ','))?;
+    // As with the default binding modes of `match`, a range pattern matches a
+    // reference to an integer by the value of the integer, e.g. in
+    // `matches!(&self.len, 0..10)`.
+    let mut scrutinee = &args[..comma];
+    while let [first, rest @ ..] = scrutinee {
+        if !is_punct(first, '&') {
+            break;
+        }
+        scrutinee = match rest {
+            [TokenTree::Ident(ident), rest @ ..] if ident == "mut" => rest,
+            _ => rest,
+        };
+    }
+    let scrutinee = TokenStream::from_iter(scrutinee.iter().cloned());
+    if scrutinee.is_empty() {
+        return None;
+    }
+    let alternatives = args[comma + 1..]
+        .split(|token| is_punct(token, '|'))
+        .map(|alternative| {
+            // Find the `..` or `..=` operator
+            let start = alternative.windows(2).position(|tokens| {
+                is_punct(&tokens[0], '.') && is_punct(&tokens[1], '.')
+            })?;
+            let inclusive = alternative.get(start + 2).map_or(false, |token| is_punct(token, '='));
+            let end = if inclusive { start + 3 } else { start + 2 };
+            let (lower, upper) = (&alternative[..start], &alternative[end..]);
+            // The bounds are literals, possibly negated, or paths to constants
+            let is_bound = |tokens: &[TokenTree]| tokens.iter().all(|token| match token {
+                TokenTree::Literal(_) | TokenTree::Ident(_) => true,
+                TokenTree::Punct(punct) => matches!(punct.as_char(), '-' | ':'),
+                TokenTree::Group(_) => false,
+            });
+            if !is_bound(lower) || !is_bound(upper) || (lower.is_empty() && upper.is_empty())
+                || (inclusive && upper.is_empty())
+            {
+                return None;
+            }
+            let lower = TokenStream::from_iter(lower.iter().cloned());
+            let upper = TokenStream::from_iter(upper.iter().cloned());
+            let mut comparisons = vec![];
+            if !lower.is_empty() {
+                comparisons.push(quote! { (#lower) <= (#scrutinee) });
+            }
+            if inclusive {
+                comparisons.push(quote! { (#scrutinee) <= (#upper) });
+            } else if !upper.is_empty() {
+                comparisons.push(quote! { (#scrutinee) < (#upper) });
+            }
+            Some(quote! { (#(#comparisons)&&*) })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(quote! { #(#alternatives)||* })
+}
+
+/// Translates the projections `e.Variant.0` and `e.Variant.field` on the fields
+/// of enum variants into `match` expressions. Variants are recognized by their
+/// capitalized name and need to be in scope, as for example `Some` and `Ok`.
+/// If `e` is of another variant, the value of the projection is
+/// `inactive_variant_field()`, which the verifier reports as an error when it
+/// cannot prove that this case is unreachable.
+fn translate_variant_projections(source: Vec<TokenTree>) -> Vec<TokenTree> {
+    let is_dot = |token: Option<&TokenTree>| {
+        matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == '.' && punct.spacing() == Alone)
+    };
+    let mut result: Vec<TokenTree> = Vec::with_capacity(source.len());
+    let mut pos = 0;
+    while pos < source.len() {
+        if let (true, Some(TokenTree::Ident(variant)), true, Some(field)) = (
+            is_dot(source.get(pos)),
+            source.get(pos + 1),
+            is_dot(source.get(pos + 2)),
+            source.get(pos + 3),
+        ) {
+            let is_variant = variant.to_string().starts_with(char::is_uppercase);
+            let pattern = match field {
+                TokenTree::Literal(index) if is_variant => index
+                    .to_string()
+                    .parse::<usize>()
+                    .ok()
+                    .map(|index| {
+                        let wildcards = (0..index).map(|_| quote! { _, });
+                        quote! { #variant(#(#wildcards)* __prusti_field, ..) }
+                    }),
+                TokenTree::Ident(field) if is_variant =>
+                    Some(quote! { #variant { #field: __prusti_field, .. } }),
+                _ => None,
+            };
+            if let (Some(pattern), Some(start)) = (pattern, projection_receiver_start(&result)) {
+                let receiver = TokenStream::from_iter(result.drain(start..));
+                let span = variant.span();
+                let projection = quote_spanned! { span => match #receiver {
+                    #pattern => __prusti_field,
+                    #[allow(unreachable_patterns)]
+                    _ => inactive_variant_field(),
+                } };
+                let mut group = proc_macro2::Group::new(Delimiter::Parenthesis, projection);
+                group.set_span(span);
+                result.push(TokenTree::Group(group));
+                pos += 4;
+                continue;
+            }
+        }
+        result.push(source[pos].clone());
+        pos += 1;
+    }
+    result
+}
+
+/// Finds where the receiver of a projection starts at the end of `tokens`. The
+/// receiver consists of paths, field accesses, calls and indexing, e.g.
+/// `self.items[0].value()`.
+fn projection_receiver_start(tokens: &[TokenTree]) -> Option<usize> {
+    const KEYWORDS: &[&str] = &[
+        "as", "box", "break", "else", "if", "in", "let", "match", "move", "mut", "ref", "return",
+    ];
+    let is_atom = |token: &TokenTree| match token {
+        TokenTree::Ident(ident) => !KEYWORDS.contains(&ident.to_string().as_str()),
+        TokenTree::Literal(_) => true,
+        TokenTree::Group(group) => group.delimiter() != Delimiter::Brace,
+        TokenTree::Punct(_) => false,
+    };
+    // Scan backwards. An identifier or a literal cannot be directly followed
+    // by another one, and connectors need to be followed by one of them.
+    let mut start = tokens.len();
+    while start > 0 {
+        let token = &tokens[start - 1];
+        let next_is_name = matches!(
+            tokens.get(start),
+            Some(TokenTree::Ident(_) | TokenTree::Literal(_))
+        );
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '.' && punct.spacing() == Alone
+                && next_is_name && start >= 2 && is_atom(&tokens[start - 2]) =>
+            {
+                start -= 1;
+            }
+            TokenTree::Punct(punct) if punct.as_char() == ':' && start >= 2 && next_is_name
+                && matches!(&tokens[start - 2], TokenTree::Punct(p) if p.as_char() == ':' && p.spacing() == Joint) =>
+            {
+                start -= 2;
+            }
+            _ if is_atom(token) && !next_is_name => {
+                start -= 1;
+            }
+            _ => break,
+        }
+    }
+    // The receiver cannot start with a connector
+    match tokens.get(start) {
+        Some(token) if is_atom(token) => Some(start),
+        _ => None,
+    }
+}
+
 /// The preparser reuses [syn::Result] to integrate with the rest of the specs
 /// library, even though syn is not used here.
 fn error<T>(span: Span, msg: &str) -> syn::Result<T> {
@@ -87,7 +258,7 @@ impl PrustiTokenStream {
     /// Constructs a stream of Prusti tokens from a stream of Rust tokens.
     fn new(source: TokenStream) -> Self {
         let source_span = source.span();
-        let source = source.into_iter().collect::<Vec<_>>();
+        let source = translate_variant_projections(source.into_iter().collect());
 
         let mut pos = 0;
         let mut tokens = VecDeque::new();
@@ -118,12 +289,38 @@ impl PrustiTokenStream {
                     pos += 1;
                     op
                 }
+                (
+                    TokenTree::Ident(ident),
+                    Some(TokenTree::Punct(bang)),
+                    Some(TokenTree::Group(group)),
+                ) if let Some(comparisons) = (ident == "matches"
+                    && bang.as_char() == '!'
+                    && group.delimiter() == Delimiter::Parenthesis)
+                    .then(|| translate_range_matches(group.stream()))
+                    .flatten() => {
+                    // `matches!` with integer range patterns, consume the
+                    // `!` and the arguments
+                    pos += 2;
+                    PrustiToken::Group(
+                        group.span(),
+                        Delimiter::Parenthesis,
+                        box Self::new(comparisons),
+                    )
+                }
                 (TokenTree::Ident(ident), _, _) if ident == "outer" =>
                     PrustiToken::Outer(ident.span()),
                 (TokenTree::Ident(ident), _, _) if ident == "forall" =>
                     PrustiToken::Quantifier(ident.span(), Quantifier::Forall),
                 (TokenTree::Ident(ident), _, _) if ident == "exists" =>
                     PrustiToken::Quantifier(ident.span(), Quantifier::Exists),
+                (TokenTree::Ident(ident), Some(TokenTree::Group(group)), _)
+                    if ident == "count"
+                        && group.delimiter() == Delimiter::Parenthesis
+                        && matches!(group.stream().into_iter().next(),
+                            Some(TokenTree::Punct(p)) if p.as_char() == '|')
+                        && !matches!(pos.checked_sub(2).and_then(|prev| source.get(prev)),
+                            Some(TokenTree::Punct(p)) if p.as_char() == '.') =>
+                    PrustiToken::Count(ident.span()),
                 (TokenTree::Punct(punct), _, _)
                     if punct.as_char() == ',' && punct.spacing() == Alone =>
                     PrustiToken::BinOp(punct.span(), PrustiBinaryOp::Rust(RustOp::Comma)),
@@ -300,6 +497,8 @@ impl PrustiTokenStream {
                 kind.translate(span, triggers, args, body)
             }
 
+            Some(PrustiToken::Count(span)) => self.pop_count(span)?,
+
             Some(PrustiToken::SpecEnt(span, _))
             | Some(PrustiToken::CallDesc(span, _)) =>
                 return error(span, "unexpected operator"),
@@ -310,6 +509,8 @@ impl PrustiTokenStream {
 
             Some(PrustiToken::BinOp(span, _)) =>
                 return error(span, "unexpected binary operator"),
+            Some(PrustiToken::Token(TokenTree::Ident(ident))) if ident == "if" =>
+                self.pop_conditional(ident.span())?,
             Some(PrustiToken::Token(token)) => token.to_token_stream(),
             None => return Ok(TokenStream::new()),
         };
@@ -329,11 +530,25 @@ impl PrustiTokenStream {
                     self.tokens.pop_front();
                     continue;
                 }
+                Some(PrustiToken::Token(TokenTree::Ident(ident))) if ident == "if" => {
+                    let span = ident.span();
+                    self.tokens.pop_front();
+                    lhs.extend(self.pop_conditional(span)?);
+                    continue;
+                }
                 Some(PrustiToken::Token(token)) => {
                     lhs.extend(token.to_token_stream());
                     self.tokens.pop_front();
                     continue;
                 }
+                // `count` is an integer, so it can be an operand of Rust
+                // operators, e.g. `result == count(...)`
+                Some(PrustiToken::Count(span)) => {
+                    let span = *span;
+                    self.tokens.pop_front();
+                    lhs.extend(self.pop_count(span)?);
+                    continue;
+                }
 
                 Some(PrustiToken::SpecEnt(span, once)) => {
                     let span = *span;
@@ -389,6 +604,68 @@ impl PrustiTokenStream {
         }
     }
 
+    /// Processes a conditional expression `if c { a } else { b }`, whose `if`
+    /// keyword was already popped. The condition and the branches may use
+    /// Prusti syntax. The result is parenthesized, so that it can be used as an
+    /// operand of binary operators.
+    fn pop_conditional(&mut self, span: Span) -> syn::Result<TokenStream> {
+        let conditional = self.pop_conditional_branches(span)?;
+        Ok(quote_spanned! { span => (if #conditional) })
+    }
+
+    fn pop_conditional_branches(&mut self, span: Span) -> syn::Result<TokenStream> {
+        let mut condition = VecDeque::new();
+        while !matches!(self.tokens.front(), Some(PrustiToken::Group(_, Delimiter::Brace, _))) {
+            condition.push_back(self.tokens.pop_front()
+                .ok_or_else(|| syn::parse::Error::new(span, "expected block after condition"))?);
+        }
+        if condition.is_empty() {
+            return error(span, "expected condition");
+        }
+        let condition = Self { tokens: condition, source_span: self.source_span }.parse()?;
+        let then_branch = self.pop_group(Delimiter::Brace).unwrap().parse()?;
+        match self.tokens.pop_front() {
+            Some(PrustiToken::Token(TokenTree::Ident(ident))) if ident == "else" => {}
+            _ => return error(span, "conditional expressions in specifications must have an `else` branch"),
+        }
+        let else_branch = match self.tokens.pop_front() {
+            Some(PrustiToken::Token(TokenTree::Ident(ident))) if ident == "if" => {
+                let branches = self.pop_conditional_branches(ident.span())?;
+                quote_spanned! { ident.span() => if #branches }
+            }
+            Some(PrustiToken::Group(group_span, Delimiter::Brace, box stream)) => {
+                let mut group = proc_macro2::Group::new(Delimiter::Brace, stream.parse()?);
+                group.set_span(group_span);
+                TokenTree::Group(group).to_token_stream()
+            }
+            _ => return error(span, "expected block after `else`"),
+        };
+        Ok(quote_spanned! { span => #condition { #then_branch } else #else_branch })
+    }
+
+    /// Processes `count(|i: usize| condition)`, whose `count` was already
+    /// popped. The condition may use Prusti syntax.
+    fn pop_count(&mut self, span: Span) -> syn::Result<TokenStream> {
+        let mut stream = self.pop_group(Delimiter::Parenthesis)
+            .ok_or_else(|| syn::parse::Error::new(span, "expected parenthesized expression after count"))?;
+        let args = stream.pop_closure_args()
+            .ok_or_else(|| syn::parse::Error::new(span, "expected closure after count"))?;
+        {
+            // the counted integer must have an explicit type
+            let cl_args = args.clone().parse_rust_only()?;
+            let check_cl = quote! { | #cl_args | 0 };
+            let parsed_cl = syn::parse2::<syn::ExprClosure>(check_cl)?;
+            if !matches!(parsed_cl.inputs.iter().collect::<Vec<_>>().as_slice(), [syn::Pat::Type(_)]) {
+                return error(span, "count must have exactly one argument with an explicit type");
+            }
+        }
+        let args = args.parse()?;
+        let body = stream.parse()?;
+        Ok(quote_spanned! { body.span() => count(
+            #[prusti::spec_only] | #args | -> bool { ((#body): bool) }
+        ) })
+    }
+
     fn pop_closure_args(&mut self) -> Option<Self> {
         let mut tokens = VecDeque::new();
 
@@ -532,6 +809,7 @@ enum PrustiToken {
     // TODO: add note about unops not sharing a variant, descriptions ...
     Outer(Span),
     Quantifier(Span, Quantifier),
+    Count(Span),
     SpecEnt(Span, bool),
     CallDesc(Span, bool),
 }
@@ -686,6 +964,7 @@ impl PrustiToken {
             | Self::BinOp(span, _)
             | Self::Outer(span)
             | Self::Quantifier(span, _)
+            | Self::Count(span)
             | Self::SpecEnt(span, _)
             | Self::CallDesc(span, _) => *span,
             Self::Token(tree) => tree.span(),
@@ -897,6 +1176,10 @@ mod tests {
             parse_prusti(quote! { forall(|x: i32| a ==> b) }).unwrap().to_string(),
             "forall (() , # [prusti :: spec_only] | x : i32 | -> bool { (((! (a) || (b))) : bool) })",
         );
+        assert_eq!(
+            parse_prusti(quote! { result == count(|i: usize| i < n && a ==> b) }).unwrap().to_string(),
+            "result == count (# [prusti :: spec_only] | i : usize | -> bool { (((! (i < n && a) || (b))) : bool) })",
+        );
         assert_eq!(
             parse_prusti(quote! { exists(|x: i32| a === b) }).unwrap().to_string(),
             "exists (() , # [prusti :: spec_only] | x : i32 | -> bool { ((snapshot_equality (a , b)) : bool) })",
@@ -907,6 +1190,79 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_conditional() {
+        assert_eq!(
+            parse_prusti(quote! { result == if a && b { c ==> d } else { e } }).unwrap().to_string(),
+            "result == (if a && b { (! (c) || (d)) } else { e })",
+        );
+        assert_eq!(
+            parse_prusti(quote! { if x { a } else if y { b } else { c } ==> d }).unwrap().to_string(),
+            "(! ((if x { a } else if y { b } else { c })) || (d))",
+        );
+        assert_error!(
+            parse_prusti(quote! { result == if a { b } }),
+            "conditional expressions in specifications must have an `else` branch"
+        );
+    }
+
+    #[test]
+    fn test_range_matches() {
+        assert_eq!(
+            parse_prusti(quote! { matches!(result, 0..10) }).unwrap().to_string(),
+            "(((0) <= (result) && (result) < (10)))",
+        );
+        assert_eq!(
+            parse_prusti(quote! { matches!(result, 0..=10) }).unwrap().to_string(),
+            "(((0) <= (result) && (result) <= (10)))",
+        );
+        assert_eq!(
+            parse_prusti(quote! { matches!(x.f, 5..) }).unwrap().to_string(),
+            "(((5) <= (x . f)))",
+        );
+        assert_eq!(
+            parse_prusti(quote! { matches!(x, ..=-1 | 1..=i32::MAX) }).unwrap().to_string(),
+            "(((x) <= (- 1)) || ((1) <= (x) && (x) <= (i32 :: MAX)))",
+        );
+        assert_eq!(
+            parse_prusti(quote! { matches!(&self.len, 0..10) }).unwrap().to_string(),
+            "(((0) <= (self . len) && (self . len) < (10)))",
+        );
+        assert_eq!(
+            parse_prusti(quote! { matches!(&&mut x, 1..) }).unwrap().to_string(),
+            "(((1) <= (x)))",
+        );
+        // Other patterns are left to the `matches!` macro
+        assert!(
+            parse_prusti(quote! { matches!(x, Some(0..=10)) }).unwrap().to_string()
+                .starts_with("matches !")
+        );
+    }
+
+    #[test]
+    fn test_variant_projections() {
+        assert_eq!(
+            parse_prusti(quote! { result.Ok.0 > 0 }).unwrap().to_string(),
+            "(match result { Ok (__prusti_field , ..) => __prusti_field , \
+                # [allow (unreachable_patterns)] _ => inactive_variant_field () , }) > 0",
+        );
+        assert_eq!(
+            parse_prusti(quote! { self.get(i).Pair.1 }).unwrap().to_string(),
+            "(match self . get (i) { Pair (_ , __prusti_field , ..) => __prusti_field , \
+                # [allow (unreachable_patterns)] _ => inactive_variant_field () , })",
+        );
+        assert_eq!(
+            parse_prusti(quote! { s.Circle.radius }).unwrap().to_string(),
+            "(match s { Circle { radius : __prusti_field , .. } => __prusti_field , \
+                # [allow (unreachable_patterns)] _ => inactive_variant_field () , })",
+        );
+        // Field accesses are left unchanged
+        assert_eq!(
+            parse_prusti(quote! { x.field.0 }).unwrap().to_string(),
+            "x . field . 0",
+        );
+    }
+
     mod ghost_constraints {
         use super::*;
 
@@ -0,0 +1,46 @@
+use prusti_contracts::*;
+
+// Only memory safety is checked, so the postcondition is ignored.
+#[ensures(result == 0)]
+pub fn identity(a: u32) -> u32 {
+    a
+}
+
+// The `prusti` tool attribute is only registered when running Prusti.
+#[cfg_attr(feature = "prusti", prusti::functional_verification)]
+#[requires(a < 100)]
+#[ensures(result == a + 1)]
+pub fn increment(a: u32) -> u32 {
+    a + 1
+}
+
+#[cfg_attr(feature = "prusti", prusti::functional_verification)]
+#[ensures(result == 6)]
+pub fn increment_five() -> u32 {
+    increment(5)
+}
+
+// The precondition of `increment` is not checked at calls from functions
+// that are only checked for memory safety.
+pub fn increment_large() -> u32 {
+    increment(500)
+}
+
+pub fn same_address() {
+    let a = 4u32;
+    let x = std::ptr::addr_of!(a);
+    let y = std::ptr::addr_of!(a);
+    assert!(x == y);
+}
+
+// The safety precondition of an `unsafe fn` is assumed in its body, so it can
+// call functions with a weaker safety precondition.
+#[requires(index < 10)]
+pub unsafe fn read(index: usize) -> usize {
+    index
+}
+
+#[requires(index < 5)]
+pub unsafe fn read_even(index: usize) -> usize {
+    read(index * 2)
+}
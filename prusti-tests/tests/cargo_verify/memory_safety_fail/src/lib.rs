@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+#[requires(a < 100)]
+pub fn below_hundred(a: u32) -> u32 {
+    a
+}
+
+// The precondition of `below_hundred` is checked at calls from functions that
+// are verified against their specification.
+#[cfg_attr(feature = "prusti", prusti::functional_verification)]
+pub fn large() -> u32 {
+    below_hundred(500)
+}
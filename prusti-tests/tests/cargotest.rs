@@ -11,17 +11,25 @@ use std::{
 };
 
 fn cargo_prusti_path() -> PathBuf {
+    executable_path("cargo-prusti")
+}
+
+fn prusti_server_driver_path() -> PathBuf {
+    executable_path("prusti-server-driver")
+}
+
+fn executable_path(name: &str) -> PathBuf {
     let target_directory = if cfg!(debug_assertions) {
         "debug"
     } else {
         "release"
     };
     let executable_name = if cfg!(windows) {
-        "cargo-prusti.exe"
+        format!("{}.exe", name)
     } else {
-        "cargo-prusti"
+        name.to_string()
     };
-    let local_prusti_rustc_path: PathBuf = ["target", target_directory, executable_name]
+    let local_prusti_rustc_path: PathBuf = ["target", target_directory, &executable_name]
         .iter()
         .collect();
     if local_prusti_rustc_path.exists() {
@@ -32,7 +40,7 @@ fn cargo_prusti_path() -> PathBuf {
             )
         });
     }
-    let workspace_prusti_rustc_path: PathBuf = ["..", "target", target_directory, executable_name]
+    let workspace_prusti_rustc_path: PathBuf = ["..", "target", target_directory, &executable_name]
         .iter()
         .collect();
     if workspace_prusti_rustc_path.exists() {
@@ -44,9 +52,9 @@ fn cargo_prusti_path() -> PathBuf {
         });
     }
     panic!(
-        "Could not find the {:?} cargo-prusti binary to be used in tests. \
+        "Could not find the {:?} {} binary to be used in tests. \
         It might be that Prusti has not been compiled correctly.",
-        target_directory
+        target_directory, name
     );
 }
 
@@ -82,6 +90,366 @@ error: could not compile `foo` due to previous error
         .run();
 }
 
+#[cargo_test]
+fn lsp_diagnostics_of_failing_function() {
+    // The string literal takes 6 bytes and 3 UTF-16 code units.
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn main() {}\nfn fails() { let _ = \"é𝄞\"; assert!(false); }\n",
+        )
+        .build();
+    let diagnostics_path = p.root().join("diagnostics.json");
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_LSP_DIAGNOSTICS", &diagnostics_path)
+        .with_status(101)
+        .run();
+
+    let json = fs::read_to_string(&diagnostics_path).unwrap();
+    let params: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let params = params.as_array().unwrap();
+    assert_eq!(params.len(), 1);
+    let uri = params[0]["uri"].as_str().unwrap();
+    assert!(uri.starts_with("file:///"), "{}", uri);
+    assert!(uri.ends_with("/src/main.rs"), "{}", uri);
+    assert_eq!(
+        params[0]["diagnostics"],
+        serde_json::json!([{
+            "range": {
+                "start": { "line": 1, "character": 28 },
+                "end": { "line": 1, "character": 42 },
+            },
+            "severity": 1,
+            "code": "panic",
+            "source": "prusti",
+            "message": "[Prusti: verification error] the asserted expression might not hold",
+        }])
+    );
+}
+
+#[cargo_test]
+fn replay_with_saved_configuration() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}\nfn fails() { assert!(false); }\n")
+        .build();
+    let requests_dir = p.root().join("requests");
+    // The hash is printed instead of verifying the program.
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_SAVE_VERIFICATION_REQUESTS", &requests_dir)
+        .env("PRUSTI_PRINT_HASH", "true")
+        .run();
+
+    let requests: Vec<_> = fs::read_dir(&requests_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert!(!requests.is_empty());
+    let replay = |print_hash: Option<&str>| {
+        let mut stdout = String::new();
+        for request in &requests {
+            let mut command = std::process::Command::new(prusti_server_driver_path());
+            command.arg("--replay").arg(request).env_remove("PRUSTI_PRINT_HASH");
+            if let Some(print_hash) = print_hash {
+                command.env("PRUSTI_PRINT_HASH", print_hash);
+            }
+            let output = command.output().unwrap();
+            stdout.push_str(&String::from_utf8_lossy(&output.stdout));
+        }
+        stdout
+    };
+
+    // The saved configuration is applied...
+    let stdout = replay(None);
+    assert!(stdout.contains("Hash of the request is:"), "{}", stdout);
+    assert!(!stdout.contains("Verification failed"), "{}", stdout);
+    // ...unless a flag is overridden for the replay.
+    let stdout = replay(Some("false"));
+    assert!(!stdout.contains("Hash of the request is:"), "{}", stdout);
+    assert!(stdout.contains("Verification failed with 1 errors:"), "{}", stdout);
+}
+
+#[cargo_test]
+fn junit_report_of_failing_and_passing_function() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn main() {}\nfn passes() { assert!(true); }\nfn fails() { assert!(false); }\n",
+        )
+        .build();
+    let report_path = p.root().join("report.xml");
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_JUNIT_REPORT", &report_path)
+        .with_status(101)
+        .run();
+
+    let xml = fs::read_to_string(&report_path).unwrap();
+    assert!(
+        xml.contains(
+            "<testsuite name=\"foo\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"0\">"
+        ),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains("<testcase name=\"passes\" classname=\"foo\"/>"),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains(
+            "<testcase name=\"fails\" classname=\"foo\">\n      \
+             <failure message=\"[Prusti: verification error] the asserted expression might not hold\" \
+             type=\"verification\">src/main.rs:3:14: \
+             [Prusti: verification error] the asserted expression might not hold</failure>\n    \
+             </testcase>"
+        ),
+        "{}",
+        xml
+    );
+    assert_eq!(xml.matches("<failure ").count(), 1, "{}", xml);
+}
+
+#[cargo_test]
+fn profile_backend_of_assertions() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+fn main() {}
+fn several_asserts(x: u32, y: u32) {
+    if x < y {
+        assert!(x < y);
+        assert!(y > x);
+    }
+}
+",
+        )
+        .build();
+    let log_dir = p.root().join("log");
+    // The second run profiles the variants again, even though the results of
+    // the first run are in the cache.
+    for _ in 0..2 {
+        p.process(cargo_prusti_path())
+            .env("PRUSTI_PROFILE_BACKEND", "true")
+            .env("PRUSTI_LOG_DIR", &log_dir)
+            .env("PRUSTI_CACHE_PATH", p.root().join("cache.bin"))
+            .run();
+    }
+
+    let csv = fs::read_to_string(log_dir.join("backend_profile").join("foo.csv")).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("obligation,time_ms"));
+    let rows: Vec<(&str, f64)> = lines
+        .map(|line| {
+            let (obligation, time) = line.rsplit_once(',').unwrap();
+            (obligation, time.parse().unwrap())
+        })
+        .collect();
+    for location in ["\"src/main.rs:4:9\"", "\"src/main.rs:5:9\""] {
+        assert!(
+            rows.iter().any(|&(obligation, _)| obligation == location),
+            "{}",
+            csv
+        );
+    }
+    assert!(rows.iter().any(|&(_, time)| time > 0.0), "{}", csv);
+}
+
+#[cargo_test]
+fn dump_call_graph_of_caller_and_callees() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+use prusti_contracts::*;
+
+#[ensures(result == x)]
+fn callee(x: u32) -> u32 {
+    x
+}
+fn helper() {}
+fn caller() {
+    helper();
+    let y = callee(2);
+    assert!(y == 2);
+}
+fn main() {}
+",
+        )
+        .build();
+    let log_dir = p.root().join("log");
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_DUMP_CALL_GRAPH", "true")
+        .env("PRUSTI_LOG_DIR", &log_dir)
+        .run();
+
+    let dot = fs::read_to_string(log_dir.join("call_graph").join("foo.dot")).unwrap();
+    assert!(dot.starts_with("digraph CallGraph {\n"), "{}", dot);
+    let edges: Vec<&str> = dot
+        .lines()
+        .filter(|line| line.contains(" -> "))
+        .map(str::trim)
+        .collect();
+    assert_eq!(edges.len(), 2, "{}", dot);
+    // The call of `callee` is encoded with its postcondition.
+    let callee_edge = edges
+        .iter()
+        .find(|edge| edge.starts_with("\"caller\" -> \"callee\" "))
+        .unwrap_or_else(|| panic!("{}", dot));
+    assert!(callee_edge.contains("[label=\"DefId("), "{}", dot);
+    assert!(callee_edge.contains("prusti_post_item_callee_"), "{}", dot);
+    assert!(
+        edges.contains(&"\"caller\" -> \"helper\" [label=\"\"];"),
+        "{}",
+        dot
+    );
+}
+
+#[cargo_test]
+fn report_invalid_qi_limit() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+use prusti_contracts::*;
+#[ensures(result == x)]
+fn identity(x: i32) -> i32 {
+    x
+}
+fn main() {
+    identity(1);
+}
+",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_QI_LIMIT", "-1")
+        .with_stderr_contains("[..]invalid value of the QI_LIMIT flag, which must be a non-negative integer")
+        .with_status(101)
+        .run();
+}
+
+#[cargo_test]
+fn report_unknown_timing_format() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+use prusti_contracts::*;
+#[ensures(result == x)]
+fn identity(x: i32) -> i32 {
+    x
+}
+fn main() {
+    identity(1);
+}
+",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_TIMING_FORMAT", "approximate")
+        .env("PRUSTI_LOG", "info")
+        .with_stderr_contains("[..]invalid value 'approximate' of the TIMING_FORMAT flag[..]")
+        .with_status(101)
+        .run();
+}
+
+#[cargo_test]
+fn report_unknown_viper_backend() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+use prusti_contracts::*;
+#[ensures(result == x)]
+fn identity(x: i32) -> i32 {
+    x
+}
+fn main() {
+    identity(1);
+}
+",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_VIPER_BACKEND", "Silicium")
+        .with_stderr_contains("[..]Invalid verification backend: 'silicium'[..]")
+        .with_status(101)
+        .run();
+}
+
+#[cargo_test]
+fn report_unknown_verification_batching() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+use prusti_contracts::*;
+#[ensures(result == x)]
+fn identity(x: i32) -> i32 {
+    x
+}
+fn main() {
+    identity(1);
+}
+",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_VERIFICATION_BATCHING", "by_crate")
+        .with_stderr_contains("[..]unknown verification batching strategy 'by_crate'")
+        .with_status(101)
+        .run();
+}
+
+#[cargo_test]
+fn report_unsupported_features_summary() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "\
+union Foo {
+    a: [i32; 1],
+    b: [i32; 1],
+}
+fn test_union() {
+    let _ = Foo { a: [0] };
+}
+fn test_raw_pointer(p: *const i32) -> bool {
+    p.is_null()
+}
+fn main() {
+    let _: *const i32 = std::ptr::null_mut();
+}
+",
+        )
+        .build();
+    let log_dir = p.root().join("log");
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_REPORT_UNSUPPORTED_FEATURES", "true")
+        .env("PRUSTI_LOG_DIR", &log_dir)
+        .with_stderr_contains(
+            "[..]found 3 usages of unsupported features: raw pointers (2), unions (1)",
+        )
+        .with_status(101)
+        .run();
+
+    let csv = fs::read_to_string(log_dir.join("unsupported_features").join("foo.csv")).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("category,location,message"));
+    let rows: Vec<&str> = lines.collect();
+    assert_eq!(rows.len(), 3, "{}", csv);
+    for prefix in [
+        "\"raw pointers\",\"src/main.rs:9:5\",",
+        "\"raw pointers\",\"src/main.rs:12:25\",",
+        "\"unions\",\"src/main.rs:6:13\",",
+    ] {
+        assert!(rows.iter().any(|row| row.starts_with(prefix)), "{}", csv);
+    }
+    // The rows are sorted by category.
+    assert!(rows[2].starts_with("\"unions\""), "{}", csv);
+}
+
 /// Test `cargo-prusti` on one of the crates in `test/cargo_verify`.
 ///
 /// Special files and folders in the root of the test crate:
@@ -227,4 +595,14 @@ fn test_overflow_checks() {
     test_local_project("overflow_checks");
 }
 
+#[cargo_test]
+fn test_memory_safety() {
+    test_local_project("memory_safety");
+}
+
+#[cargo_test]
+fn test_memory_safety_fail() {
+    test_local_project("memory_safety_fail");
+}
+
 // TODO: automatically create a test for each folder in `test/cargo_verify`.
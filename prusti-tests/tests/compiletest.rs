@@ -121,6 +121,16 @@ fn run_no_verification(group_name: &str, filter: &Option<String>) {
     run_prusti_tests(group_name, filter, None);
 }
 
+fn run_spec_lint(group_name: &str, filter: &Option<String>) {
+    let _temporary_env_vars = (
+        TemporaryEnvVar::set("PRUSTI_FULL_COMPILATION", "true"),
+        TemporaryEnvVar::set("PRUSTI_SPEC_LINT", "true"),
+        TemporaryEnvVar::set("PRUSTI_QUIET", "true"),
+    );
+
+    run_prusti_tests(group_name, filter, None);
+}
+
 fn run_verification_base(group_name: &str, filter: &Option<String>) {
     let _temporary_env_vars = (
         TemporaryEnvVar::set("PRUSTI_FULL_COMPILATION", "true"),
@@ -176,6 +186,10 @@ fn test_runner(_tests: &[&()]) {
     println!("[typecheck]");
     run_no_verification("typecheck", &filter);
 
+    // Test the well-formedness checks of specifications. This doesn't run the verifier.
+    println!("[spec_lint]");
+    run_spec_lint("spec_lint", &filter);
+
     // Test the verifier.
     println!("[verify]");
     run_verification_no_overflow("verify", &filter);
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+fn sum(n: u32) -> u32 {
+    let mut i = 0;
+    let mut total = 0;
+    body_invariant!(i <= n); //~ ERROR `body_invariant!` can only be used in the body of a loop
+    while i < n {
+        i += 1;
+        total += i;
+    }
+    break_ensures!(i == n); //~ ERROR `break_ensures!` can only be used in the body of a loop
+    total
+}
+
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        body_invariant!(i < n);
+        let check = || {
+            body_invariant!(i < n); //~ ERROR `body_invariant!` can only be used in the body of a loop
+            i
+        };
+        i = check() + 1;
+    }
+    i
+}
+
+fn main() {}
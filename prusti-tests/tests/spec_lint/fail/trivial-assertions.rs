@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+#[requires(x == x)] //~ ERROR this precondition always holds
+fn reflexive(x: u32) -> u32 {
+    x
+}
+
+#[requires(x > 0 && false)] //~ ERROR this precondition never holds
+fn unreachable(x: u32) -> u32 {
+    x
+}
+
+#[ensures(result < result)] //~ ERROR this postcondition never holds
+fn irreflexive(x: u32) -> u32 {
+    x
+}
+
+#[ensures(result == x && result >= result)] //~ ERROR this postcondition always holds
+fn identity(x: u32) -> u32 {
+    x
+}
+
+#[ensures(result != result)]
+fn not_a_number(x: f64) -> f64 {
+    x
+}
+
+fn main() {}
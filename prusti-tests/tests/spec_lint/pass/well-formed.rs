@@ -0,0 +1,19 @@
+use prusti_contracts::*;
+
+// The specification lint does not verify the program, so the postcondition,
+// which does not hold, is not reported.
+#[ensures(result == n + 1)]
+fn sum(n: u32) -> u32 {
+    let mut i = 0;
+    let mut total = 0;
+    while i < n {
+        body_invariant!(i < n);
+        i += 1;
+        total += i;
+    }
+    total
+}
+
+fn main() {
+    sum(0);
+}
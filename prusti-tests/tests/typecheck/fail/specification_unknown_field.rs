@@ -0,0 +1,34 @@
+// This test checks that a misspelled field in a specification is reported on
+// the field, with a suggestion of the field that was meant.
+
+use prusti_contracts::*;
+
+struct Buffer {
+    length: usize,
+}
+
+impl Buffer {
+    #[ensures(self.lenght > 0)] //~ ERROR no field `lenght` on type `&Buffer`
+    //~| HELP a field with a similar name exists
+    fn check(&self) {}
+}
+
+#[requires(matches!(buffer.lenght, 1 | 2))] //~ ERROR no field `lenght` on type `&Buffer`
+//~| HELP a field with a similar name exists
+fn small(buffer: &Buffer) {}
+
+trait Len {
+    fn len(&self) -> usize;
+}
+
+#[refine_trait_spec]
+impl Len for Buffer {
+    // The tokens of macros in refined specifications are rewritten.
+    #[ensures(matches!(self.lenght, 0) ==> result == 0)] //~ ERROR no field `lenght` on type `&Buffer`
+    //~| HELP a field with a similar name exists
+    fn len(&self) -> usize {
+        self.length
+    }
+}
+
+fn main() {}
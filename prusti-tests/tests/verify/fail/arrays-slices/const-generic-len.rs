@@ -0,0 +1,19 @@
+use prusti_contracts::*;
+
+#[ensures(result.len() == N)]
+fn zeros<const N: usize>() -> [u8; N] { //~ WARNING this function is verified only for the instances called in this crate
+    [0u8; N]
+}
+
+// Only fails for the instance with `N == 0`.
+fn first<const N: usize>() -> u8 { //~ WARNING this function is verified only for the instances called in this crate
+    let a = zeros::<N>();
+    a[0] //~ ERROR the array or slice index may be out of bounds
+}
+
+fn main() {
+    let a = zeros::<4>();
+    assert!(a.len() == 5); //~ ERROR asserted expression might not hold
+    first::<2>();
+    first::<0>();
+}
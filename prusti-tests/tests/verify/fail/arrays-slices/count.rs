@@ -0,0 +1,21 @@
+use prusti_contracts::*;
+
+#[requires(s.len() == 2 && s[0] > 0 && s[1] > 0)]
+#[ensures(result == count(|i: usize| i < s.len() && s[i] > 0))] //~ ERROR postcondition might not hold
+fn two_positive(s: &[i32]) -> usize {
+    1
+}
+
+#[ensures(result == count(|i: usize| i < s.len() && s[i] > 0))] //~ ERROR postcondition might not hold
+fn count_positive(s: &[i32]) -> usize {
+    s.len()
+}
+
+// Only `i < n` delimits the range, `i < m` must still be counted.
+#[requires(n == 2 && m == 5)]
+#[ensures(result == count(|i: usize| i < n && i < m))] //~ ERROR postcondition might not hold
+fn two_upper_bounds(n: usize, m: usize) -> usize {
+    5
+}
+
+fn main() {}
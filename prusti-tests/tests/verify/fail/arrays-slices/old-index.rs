@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(s.len() > 1)]
+#[requires(s[1] < 100)]
+#[ensures(s[0] == old(s[0]) + 1)] //~ ERROR postcondition might not hold
+fn inc_second(s: &mut [i32]) {
+    s[1] += 1;
+}
+
+#[requires(i < s.len())]
+#[ensures(forall(|j: usize| j < s.len() ==> s[j] == old(s[j])))] //~ ERROR postcondition might not hold
+fn reset_at(s: &mut [i32], i: usize) {
+    s[i] = 0;
+}
+
+#[requires(s.len() > 0)]
+#[requires(forall(|i: usize| i < s.len() ==> s[i] < 1000))]
+#[ensures(forall(|i: usize| i < s.len() ==> s[i] == old(s[i]) + 1))] //~ ERROR postcondition might not hold
+fn increment_all_but_first(s: &mut [i32]) {
+    let mut k = 1;
+    while k < s.len() {
+        body_invariant!(1 <= k && k < s.len());
+        body_invariant!(s[0] == old(s[0]));
+        body_invariant!(forall(|i: usize| 1 <= i && i < k ==> s[i] == old(s[i]) + 1));
+        body_invariant!(forall(|i: usize| k <= i && i < s.len() ==> s[i] == old(s[i])));
+        s[k] += 1;
+        k += 1;
+    }
+}
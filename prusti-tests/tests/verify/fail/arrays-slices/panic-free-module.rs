@@ -0,0 +1,27 @@
+// compile-flags: -Pcheck_panics=false
+
+use prusti_contracts::*;
+
+#[prusti::panic_free]
+mod checked {
+    pub fn first(s: &[i32]) -> i32 {
+        s[0]  //~ ERROR the array or slice index may be out of bounds
+    }
+
+    pub fn first_or_zero(s: &[i32]) -> i32 {
+        if s.len() > 0 { s[0] } else { 0 }
+    }
+
+    pub mod nested {
+        pub fn get(s: &[i32], i: usize) -> i32 {
+            s[i]  //~ ERROR the array or slice index may be out of bounds
+        }
+    }
+}
+
+// Panics outside of the module are not checked.
+fn first(s: &[i32]) -> i32 {
+    s[0]
+}
+
+fn main() {}
@@ -0,0 +1,44 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+// The loop overwrites the element at index `hi`, which must be preserved.
+#[requires(lo <= hi && hi < s.len())]
+#[ensures(s.len() == old(s.len()))]
+#[ensures(forall(|i: usize| (lo <= i && i < hi) ==> s[i] == v))]
+#[ensures(forall(|i: usize| (i < s.len() && (i < lo || hi <= i)) ==> s[i] == old(s[i])))] //~ ERROR postcondition might not hold
+fn fill_off_by_one(s: &mut [i32], lo: usize, hi: usize, v: i32) {
+    let mut k = lo;
+    while k <= hi {
+        body_invariant!(lo <= k && k <= hi && hi < s.len());
+        body_invariant!(s.len() == old(s.len()));
+        body_invariant!(forall(|i: usize| (lo <= i && i < k) ==> s[i] == v));
+        body_invariant!(forall(|i: usize| (i < s.len() && (i < lo || k <= i)) ==> s[i] == old(s[i])));
+        s[k] = v;
+        k += 1;
+    }
+}
+
+#[requires(s.len() == 5)]
+fn client(s: &mut [i32]) {
+    let last = s[4];
+    fill(s, 1, 4, 7);
+    assert!(s[4] == 7); //~ ERROR the asserted expression might not hold
+    assert!(s[4] == last);
+}
+
+#[requires(lo <= hi && hi <= s.len())]
+#[ensures(s.len() == old(s.len()))]
+#[ensures(forall(|i: usize| (lo <= i && i < hi) ==> s[i] == v))]
+#[ensures(forall(|i: usize| (i < s.len() && (i < lo || hi <= i)) ==> s[i] == old(s[i])))]
+fn fill(s: &mut [i32], lo: usize, hi: usize, v: i32) {
+    let mut k = lo;
+    while k < hi {
+        body_invariant!(lo <= k && k < hi && hi <= s.len());
+        body_invariant!(s.len() == old(s.len()));
+        body_invariant!(forall(|i: usize| (lo <= i && i < k) ==> s[i] == v));
+        body_invariant!(forall(|i: usize| (i < s.len() && (i < lo || k <= i)) ==> s[i] == old(s[i])));
+        s[k] = v;
+        k += 1;
+    }
+}
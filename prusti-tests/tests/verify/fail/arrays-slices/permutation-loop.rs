@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[ensures(is_permutation(s, old(s)))]
+fn overwrite(s: &mut [i32]) {
+    let len = s.len();
+    let mut i = 0;
+    while i < len / 2 {
+        body_invariant!(s.len() == len);
+        body_invariant!(i < len / 2);
+        body_invariant!(is_permutation(s, old(s))); //~ ERROR loop invariant might not hold after a loop iteration
+        s[i] = s[len - 1 - i];
+        i += 1;
+    }
+}
+
+#[requires(s.len() == 2)]
+#[ensures(s[0] == old(s[0]))] //~ ERROR postcondition might not hold
+fn swap_pair(s: &mut [i32]) {
+    s.swap(0, 1);
+}
@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[ensures(is_permutation(&result, &a))] //~ ERROR postcondition might not hold
+fn duplicate_first(a: [i32; 3]) -> [i32; 3] {
+    [a[0], a[0], a[1]]
+}
+
+#[ensures(is_permutation(&result, &a))] //~ ERROR postcondition might not hold
+fn drop_last(a: [i32; 3]) -> [i32; 3] {
+    [a[0], a[1], 0]
+}
@@ -0,0 +1,21 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(s.len() > 3)]
+fn not_descending(s: &mut [i32]) {
+    s.sort();
+    assert!(s[3] <= s[0]); //~ ERROR the asserted expression might not hold
+}
+
+#[requires(s.len() > 0)]
+fn unknown_elements(s: &mut [i32]) {
+    s.sort();
+    assert!(s[0] >= 0); //~ ERROR the asserted expression might not hold
+}
+
+#[requires(s.len() == 3 && s[0] == 2 && s[1] == 1 && s[2] == 1)]
+fn duplicates_are_kept(s: &mut [i32]) {
+    s.sort();
+    assert!(s[1] == 2); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(s.len() == 2)]
+fn swap_past_the_end(s: &mut [i32]) {
+    s.swap(0, 2); //~ ERROR the array or slice index may be out of bounds
+}
+
+fn swap_in_unknown_slice(s: &mut [i32], i: usize) {
+    s.swap(i, 0); //~ ERROR the array or slice index may be out of bounds
+}
+
+#[requires(s.len() > 1)]
+#[ensures(s[0] == old(s[1]))]
+fn swap_in_bounds(s: &mut [i32]) {
+    s.swap(0, 1);
+}
@@ -0,0 +1,36 @@
+use prusti_contracts::*;
+
+trait Length {
+    #[pure]
+    fn length(&self) -> usize;
+}
+
+impl<U> Length for [U] {
+    #[pure]
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Length for str {
+    #[pure]
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[requires(x.length() > 0)]
+#[ensures(result < x.length())]
+fn last_index<T: Length + ?Sized>(x: &T) -> usize {
+    x.length() - 1
+}
+
+fn slices(s: &[i32]) {
+    last_index(s); //~ ERROR precondition might not hold
+}
+
+fn strings(s: &str) {
+    assert!(s.len() > 0); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
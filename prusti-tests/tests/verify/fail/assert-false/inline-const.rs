@@ -0,0 +1,22 @@
+#![feature(inline_const)]
+
+use prusti_contracts::*;
+
+const LEN: usize = 4;
+
+fn too_short(a: [i32; LEN]) -> i32 {
+    const { assert!(LEN > 8) }; //~ ERROR the inline const block might panic
+    a[0]
+}
+
+#[requires(x > 100)]
+fn const_block_ignores_precondition(x: u32) -> u32 {
+    if x > 100 {
+        return x;
+    }
+    // Unreachable at runtime, but still evaluated at compile time.
+    const { assert!(2 + 2 == 5) }; //~ ERROR the inline const block might panic
+    x
+}
+
+fn main() {}
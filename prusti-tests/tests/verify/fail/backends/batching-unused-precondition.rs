@@ -0,0 +1,18 @@
+// compile-flags: -Pverification_batching=by_backend -Preport_unused_preconditions=true
+
+use prusti_contracts::*;
+
+// The failure of this function does not prevent the unused preconditions of
+// the other functions of the batch from being reported.
+fn wrong_assert(x: u32) {
+    assert!(x > 0); //~ ERROR the asserted expression might not hold
+}
+
+#[requires(x > 0)]
+#[requires(y == 42)] //~ WARNING this precondition is not needed to verify the function
+#[ensures(result > 0)]
+fn first(x: u32, y: u32) -> u32 {
+    x
+}
+
+fn main() {}
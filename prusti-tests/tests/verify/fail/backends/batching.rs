@@ -0,0 +1,30 @@
+// compile-flags: -Pverification_batching=by_backend
+
+use prusti_contracts::*;
+
+#[ensures(result > x)] //~ ERROR postcondition might not hold
+fn wrong_inc(x: u32) -> u32 {
+    x
+}
+
+#[requires(x < 100)]
+#[ensures(result == x + 1)]
+fn inc(x: u32) -> u32 {
+    x + 1
+}
+
+fn wrong_assert(x: u32) {
+    assert!(x > 0); //~ ERROR the asserted expression might not hold
+}
+
+fn calls_inc(x: u32) -> u32 {
+    inc(x) //~ ERROR precondition might not hold
+}
+
+#[prusti::backend("carbon")]
+#[ensures(result == x)] //~ ERROR postcondition might not hold
+fn verified_by_carbon(x: u32) -> u32 {
+    0
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+
+#[prusti::backend("carbon")]
+#[ensures(result == x)] //~ ERROR postcondition might not hold
+fn verified_by_carbon(x: u32) -> u32 {
+    0
+}
+
+#[prusti::backend("silicon")]
+#[ensures(result == x)] //~ ERROR postcondition might not hold
+fn verified_by_silicon(x: u32) -> u32 {
+    0
+}
+
+#[prusti::backend("boogie")]
+fn unknown_backend() {} //~ ERROR Invalid verification backend
+
+fn main() {}
@@ -0,0 +1,33 @@
+use prusti_contracts::*;
+
+#[trusted]
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result > x)]
+fn increasing(x: i32) -> i32 {
+    unimplemented!()
+}
+
+#[requires(x > 0 && x < 1000)]
+#[ensures(result == x + 1)]
+fn add_one_positive(x: i32) -> i32 {
+    x + 1
+}
+
+#[requires(x > -1000 && x < 1000)]
+#[ensures(result == x - 1)]
+fn sub_one(x: i32) -> i32 {
+    x - 1
+}
+
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result == x + 1)] //~ ERROR postcondition might not hold
+fn apply(#[prusti::fn_contract(increasing)] f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn client() {
+    apply(add_one_positive, 5); //~ ERROR the function passed as argument might not satisfy the contract `increasing`
+    apply(sub_one, 5); //~ ERROR the function passed as argument might not satisfy the contract `increasing`
+}
+
+fn main() {}
@@ -0,0 +1,44 @@
+use prusti_contracts::*;
+
+#[trusted]
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result > x)]
+fn increasing(x: i32) -> i32 {
+    unimplemented!()
+}
+
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result == x + 1)]
+fn add_one(x: i32) -> i32 {
+    x + 1
+}
+
+#[requires(x > -1000 && x < 1000)]
+#[ensures(result == x - 1)]
+fn sub_one(x: i32) -> i32 {
+    x - 1
+}
+
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result > x)]
+fn apply(#[prusti::fn_contract(increasing)] f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn call_reassigned(b: bool) -> i32 {
+    let mut f: fn(i32) -> i32 = add_one;
+    if b {
+        f = sub_one;
+    }
+    f(5) //~ ERROR calls of function pointers are supported only if the pointer is assigned exactly once
+}
+
+fn pass_reassigned(b: bool) -> i32 {
+    let mut f: fn(i32) -> i32 = add_one;
+    if b {
+        f = sub_one;
+    }
+    apply(f, 5) //~ ERROR cannot determine the function passed for a function pointer with a contract
+}
+
+fn main() {}
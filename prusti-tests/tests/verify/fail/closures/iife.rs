@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+
+#[requires(x > 0)]
+fn positive(x: u32) {
+    (|| {
+        assert!(x > 1); //~ ERROR the asserted expression might not hold
+    })();
+}
+
+fn unrelated(x: u32) {
+    let cl = || {
+        assert!(x > 0); //~ ERROR the asserted expression might not hold
+    };
+    cl();
+}
+
+fn main() {}
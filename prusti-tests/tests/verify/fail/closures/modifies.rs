@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+
+fn main() {
+    let mut a = 0;
+    let mut b = 0;
+    let _cl = closure!(
+        #[modifies(a)]
+        || -> () {
+            a += 1;
+            b += 1; //~ ERROR the closure mutates `b`, which is not declared in its `modifies` attribute
+        }
+    );
+
+    let mut c = 0;
+    let r = &mut c;
+    let _cl = closure!(
+        #[modifies()]
+        move || -> () {
+            *r += 1; //~ ERROR the closure mutates `r`, which is not declared in its `modifies` attribute
+        }
+    );
+
+    let mut d = 0;
+    let _cl = closure!( //~ ERROR `e` is declared in the `modifies` attribute, but it is not captured by the closure
+        #[modifies(d, e)]
+        || -> () {
+            d += 1;
+        }
+    );
+}
@@ -0,0 +1,48 @@
+use prusti_contracts::*;
+
+#[prusti::clone_equal]
+struct Interval {
+    start: u32,
+    end: u32,
+}
+
+impl Clone for Interval {
+    fn clone(&self) -> Self { //~ ERROR the `Clone` implementation of `Interval` might not return a value equal to the original
+        Interval { start: self.start, end: self.start }
+    }
+}
+
+#[prusti::clone_equal]
+struct Entry {
+    id: u64,
+    valid: bool,
+}
+
+impl Clone for Entry {
+    fn clone(&self) -> Self { //~ ERROR the `Clone` implementation of `Entry` might not return a value equal to the original
+        Entry { id: self.id, valid: true }
+    }
+}
+
+#[prusti::clone_equal]
+struct Pair {
+    first: u32,
+    second: u32,
+}
+
+impl Clone for Pair {
+    #[pure]
+    fn clone(&self) -> Self { //~ ERROR the `Clone` implementation of `Pair` might not return a value equal to the original
+        Pair { first: self.first, second: 0 }
+    }
+}
+
+#[ensures(snap_eq(x.clone(), x))] //~ ERROR postcondition might not hold
+fn clone_is_equal(x: Pair) {}
+
+#[ensures(snap_eq(result, x.clone()))]
+fn duplicate(x: &Pair) -> Pair {
+    Pair { first: x.first, second: 0 }
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+
+#[pure]
+fn naive_max(a: i32, b: i32) -> i32 {
+    if a >= b { a } else { b }
+}
+
+#[prusti::equivalent_to(naive_max)]
+fn wrong_max(a: i32, b: i32) -> i32 { //~ ERROR the function might not be equivalent to `naive_max`
+    if a > b { b } else { a }
+}
+
+#[requires(a > 0)]
+#[prusti::equivalent_to(naive_max)]
+fn max_with_positive(a: i32, b: i32) -> i32 { //~ ERROR the function might not be equivalent to `naive_max`
+    if b > 0 { b } else { a }
+}
+
+#[prusti::equivalent_to(missing)]
+fn unknown_function(a: i32) -> i32 { //~ ERROR cannot find the function `missing`
+    a
+}
+
+fn first(a: &mut i32, _b: i32) -> i32 {
+    *a
+}
+
+#[prusti::equivalent_to(first)]
+fn set_first(a: &mut i32, b: i32) -> i32 { //~ ERROR the `equivalent_to` attribute is supported only on functions whose arguments do not contain mutable references
+    *a = b;
+    b
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+use std::hash::{Hash, Hasher};
+
+#[prusti::hash_eq_consistent]
+struct Key {
+    id: u32,
+    cached_len: usize,
+}
+
+impl PartialEq for Key {
+    #[pure]
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.cached_len.hash(state); //~ ERROR the `Hash` implementation of `Key` might not be consistent with its `PartialEq` implementation
+    }
+}
+
+#[prusti::hash_eq_consistent]
+struct Name {
+    first: u32,
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.first == other.first
+    }
+}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) { //~ ERROR requires the `eq` method of the `PartialEq` implementation of `Name` to be pure
+        self.first.hash(state);
+    }
+}
+
+fn main() {}
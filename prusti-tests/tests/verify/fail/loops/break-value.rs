@@ -0,0 +1,16 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(s.len() > 0)]
+fn find(s: &[i32], n: i32) -> usize {
+    let mut i = 0;
+    loop {
+        body_invariant!(i < s.len());
+        break_ensures!(s[i] == n); //~ ERROR the postcondition of the loop might not hold
+        if s[i] == n || i + 1 == s.len() {
+            break i;
+        }
+        i += 1;
+    }
+}
@@ -0,0 +1,49 @@
+// compile-flags: -Pcheck_overflows=false
+use prusti_contracts::*;
+
+struct Indices {
+    position: usize,
+    end: usize,
+}
+
+#[refine_trait_spec]
+impl Iterator for Indices {
+    type Item = usize;
+
+    #[ensures(match result {
+        Some(index) => index == old(self.position),
+        None => true,
+    })]
+    fn next(&mut self) -> Option<usize> {
+        if self.position < self.end {
+            let index = self.position;
+            self.position += 1;
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+#[requires(forall(|k: usize| k < s.len() ==> s[k] <= 100))]
+fn sum(s: &[usize], indices: Indices) -> usize {
+    let mut total = 0;
+    for i in indices {
+        body_invariant!(total < 100 * loop_iterations()); //~ ERROR loop invariant might not hold
+        if i < s.len() {
+            total += s[i];
+        }
+    }
+    total
+}
+
+fn count(indices: Indices) -> usize {
+    let mut steps = 0;
+    for _ in indices {
+        body_invariant!(steps == loop_iterations()); //~ ERROR loop invariant might not hold
+        steps += 2;
+    }
+    steps
+}
+
+fn main() {}
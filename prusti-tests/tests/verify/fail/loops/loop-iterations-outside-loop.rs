@@ -0,0 +1,8 @@
+use prusti_contracts::*;
+
+#[ensures(result == loop_iterations())] //~ ERROR `loop_iterations()` can only be used in the specifications of a loop
+fn zero() -> usize {
+    0
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+fn unknown_option(n: u32) {
+    let mut i = 0;
+    #[prusti::loop_options(trigger_strategy = greedy)]
+    while i < n { //~ ERROR unknown loop option `trigger_strategy`
+        body_invariant!(i < n);
+        i += 1;
+    }
+}
+
+fn no_loop(n: u32) {
+    #[prusti::loop_options(qi_limit = 1000)]
+    let m = n + 1; //~ ERROR the `loop_options` attribute must be attached to a loop
+}
@@ -0,0 +1,20 @@
+// compile-flags: -Poptimizations=all,fold_constants
+use prusti_contracts::*;
+
+macro_rules! scaled {
+    ($e:expr) => { 1 * $e + 0 };
+}
+
+#[ensures(result == x + 2 + 3)]
+fn add_five(x: u32) -> u32 {
+    scaled!(x) + 5 //~ ERROR attempt to add with overflow
+}
+
+#[ensures(result == (x > 0 || false))] //~ ERROR postcondition might not hold
+fn positive(x: i32) -> bool {
+    x >= 0 && true
+}
+
+fn main() {
+    assert!(2 + 3 == 6); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+
+predicate! {
+    fn in_bounds(i: usize, len: usize) -> bool {
+        0 <= i && i < len
+    }
+}
+
+#[requires(in_bounds(i, len))]
+#[ensures(result < len)]
+fn next_index(i: usize, len: usize) -> usize {
+    i
+}
+
+#[requires(in_bounds(i, len))]
+#[ensures(in_bounds(result, len))] //~ ERROR postcondition might not hold
+fn wrong_next_index(i: usize, len: usize) -> usize {
+    i + 1
+}
+
+fn client() {
+    next_index(3, 10);
+    next_index(10, 10); //~ ERROR precondition might not hold
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use prusti_contracts::*;
+
+predicate! {
+    #[requires(len <= 1000)]
+    fn in_bounds(i: usize, len: usize) -> bool {
+        0 <= i && i < len
+    }
+}
+
+// The precondition of the predicate is checked where it is used.
+#[requires(in_bounds(i, len))] //~ ERROR precondition
+fn unbounded_index(i: usize, len: usize) -> usize {
+    i
+}
+
+#[requires(len <= 1000 && in_bounds(i, len))]
+fn bounded_index(i: usize, len: usize) -> usize {
+    i
+}
+
+fn main() {}
@@ -0,0 +1,45 @@
+use prusti_contracts::*;
+use std::ops::Deref;
+
+#[derive(Clone, Copy)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+struct Wrapper {
+    inner: Point,
+}
+
+impl Deref for Wrapper {
+    type Target = Point;
+
+    #[pure]
+    fn deref(&self) -> &Point {
+        &self.inner
+    }
+}
+
+#[ensures(result == w.y)] //~ ERROR postcondition might not hold
+fn get_x(w: &Wrapper) -> i32 {
+    w.inner.x
+}
+
+struct ImpureWrapper {
+    inner: Point,
+}
+
+impl Deref for ImpureWrapper {
+    type Target = Point;
+
+    fn deref(&self) -> &Point {
+        &self.inner
+    }
+}
+
+#[ensures(result == w.x)] //~ ERROR the `Deref` implementation of `ImpureWrapper` must be `#[pure]`
+fn get_impure_x(w: &ImpureWrapper) -> i32 {
+    w.inner.x
+}
+
+fn main() {}
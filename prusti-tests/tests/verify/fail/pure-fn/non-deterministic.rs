@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+use std::cell::Cell;
+
+static mut COUNTER: u32 = 0;
+
+#[pure]
+fn read_counter() -> u32 {
+    unsafe { COUNTER } //~ ERROR pure functions cannot read the mutable static
+}
+
+#[pure]
+fn read_cell(cell: &Cell<u32>) -> u32 { //~ ERROR pure functions cannot use values with interior mutability
+    0
+}
+
+#[ensures(result == read_counter())]
+fn client() -> u32 {
+    0
+}
+
+#[ensures(result == read_cell(cell))]
+fn cell_client(cell: &Cell<u32>) -> u32 {
+    0
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use prusti_contracts::*;
+
+// Calls with other arguments in the postcondition are checked against the
+// body, see `verify/pass/issues/issue-769.rs`.
+
+#[pure]
+#[ensures(result == circular(n) + 1)]
+fn circular(n: u32) -> u32 { //~ ERROR only trusted functions can call themselves in their preconditions, or with their own arguments in their postconditions
+    n
+}
+
+#[pure]
+#[requires(n == 0 || self_bounded(n - 1) < 10)]
+fn self_bounded(n: u32) -> u32 { //~ ERROR only trusted functions can call themselves in their preconditions, or with their own arguments in their postconditions
+    0
+}
+
+#[pure]
+#[ensures(n > 0 ==> result == wrong_recursion(n - 1) + 2)] //~ ERROR postcondition might not hold
+fn wrong_recursion(n: u32) -> u32 {
+    if n == 0 { 0 } else { 1 }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+#[pure]
+#[terminates(n, group = "parity")]
+fn is_even(n: u64) -> bool {
+    if n == 0 { true } else { is_odd(n) } //~ ERROR the termination measure might not decrease
+}
+
+#[pure]
+#[terminates(n, group = "parity")]
+fn is_odd(n: u64) -> bool {
+    if n == 0 { false } else { is_even(n - 1) }
+}
+
+#[pure]
+#[terminates(n)]
+#[requires(limit < 1000)]
+fn count_up(n: u64, limit: u64) -> u64 {
+    if n >= limit { n } else { count_up(n + 1, limit) } //~ ERROR the termination measure might not decrease
+}
+
+fn main() {}
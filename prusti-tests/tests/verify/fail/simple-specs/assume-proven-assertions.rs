@@ -0,0 +1,21 @@
+// compile-flags: -Passume_proven_assertions=true
+use prusti_contracts::*;
+
+// The asserted expression is only assumed on the path of the assertion.
+fn conditional(x: i32, check: bool) -> i32 {
+    if check {
+        assert!(x > 10);
+    }
+    assert!(x > 10); //~ ERROR the asserted expression might not hold
+    x
+}
+
+// A failing assertion does not make the later ones hold twice: each failing
+// assertion is reported.
+fn failing(x: i32) -> i32 {
+    assert!(x > 10); //~ ERROR the asserted expression might not hold
+    assert!(x > 20); //~ ERROR the asserted expression might not hold
+    x
+}
+
+fn main() {}
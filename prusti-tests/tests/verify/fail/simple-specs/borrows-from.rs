@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+struct Pair {
+    first: i32,
+    second: i32,
+}
+
+#[ensures(borrows_from(result, b))] //~ ERROR the result does not borrow from `b`
+fn pick<'a, 'b>(a: &'a Pair, b: &'b Pair) -> &'a i32 {
+    &a.first
+}
+
+#[ensures(borrows_from(result, &a.second))] //~ ERROR the arguments of `borrows_from` must be `result` and an argument of the function
+fn first(a: &Pair) -> &i32 {
+    &a.first
+}
+
+#[ensures(!borrows_from(result, a))] //~ ERROR `borrows_from` can only be used as a conjunct of a postcondition
+fn negated(a: &Pair) -> &i32 {
+    &a.first
+}
+
+#[ensures(a.first > 0 ==> borrows_from(result, a))] //~ ERROR `borrows_from` can only be used as a conjunct of a postcondition
+fn conditional(a: &Pair) -> &i32 {
+    &a.first
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+#[requires(lo <= hi)]
+#[case(name = "below range", pre = x < lo, post = result == lo)]
+#[case(name = "in range", pre = lo <= x && x <= hi, post = result == x)]
+#[case(name = "above range", pre = x > hi, post = result == hi)] //~ ERROR postcondition of case `above range` might not hold
+fn clamp(x: i32, lo: i32, hi: i32) -> i32 {
+    if x < lo {
+        lo
+    } else if x > hi {
+        lo
+    } else {
+        x
+    }
+}
+
+#[case(name = "positive", pre = x > 0, post = result == x)]
+#[case(name = "negative", pre = x < 0, post = result == -x)]
+fn abs(x: i32) -> i32 {
+    if x < 0 { -x } else { x }
+}
+
+fn client() {
+    assert!(abs(-3) == 3);
+    abs(0); //~ ERROR precondition might not hold
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use prusti_contracts::*;
+
+#[pure]
+fn is_max(a: i32, b: i32, result: i32) -> bool {
+    result >= a && result >= b && (result == a || result == b)
+}
+
+#[ensures(result >= a && result >= b)]
+#[prusti::check_implies(is_max)]
+fn max(a: i32, b: i32) -> i32 { //~ ERROR the postcondition might not imply `is_max`
+    if a >= b { a } else { b }
+}
+
+#[prusti::check_implies(missing)]
+fn unknown_function(a: i32) -> i32 { //~ ERROR cannot find the function `missing`
+    a
+}
+
+fn main() {}
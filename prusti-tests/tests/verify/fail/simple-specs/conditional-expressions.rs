@@ -0,0 +1,8 @@
+use prusti_contracts::*;
+
+#[ensures(result == if x >= 0 { x } else { -x })] //~ ERROR postcondition might not hold
+fn wrong_abs(x: i32) -> i32 {
+    x
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+#[ensures(matches!(result, 0..10))] //~ ERROR postcondition might not hold
+fn last_digit_plus_one(x: u32) -> u32 {
+    x % 10 + 1
+}
+
+#[ensures(matches!(result, 5..))] //~ ERROR postcondition might not hold
+fn at_least_five(x: u32) -> u32 {
+    x
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use prusti_contracts::*;
+
+enum State {
+    Open,
+    Closed(u32),
+}
+
+struct Connection {
+    state: State,
+}
+
+impl Connection {
+    #[requires(matches!(&self.state, State::Closed(code) if *code > 0))]
+    fn reopen(&mut self) {
+        self.state = State::Open;
+    }
+}
+
+fn main() {
+    let mut closed = Connection { state: State::Closed(0) };
+    closed.reopen(); //~ ERROR precondition might not hold
+    let mut open = Connection { state: State::Open };
+    open.reopen(); //~ ERROR precondition might not hold
+}
@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+
+mod counter {
+    use prusti_contracts::*;
+
+    #[requires(x < 1000)]
+    #[ensures(result == x + 1)]
+    #[opaque_spec(ensures(result > x))]
+    pub fn next(x: u32) -> u32 {
+        x + 1
+    }
+
+    #[ensures(result == 42)]
+    #[opaque_spec]
+    pub fn answer() -> u32 {
+        42
+    }
+
+    #[ensures(result >= x)]
+    #[opaque_spec(ensures(result > x))] //~ ERROR the postcondition might not imply the exposed postcondition
+    pub fn weak(x: u32) -> u32 {
+        x
+    }
+}
+
+mod sibling {
+    fn client() {
+        // The same crate, but another module.
+        assert!(super::counter::next(3) == 4); //~ ERROR the asserted expression might not hold
+    }
+}
+
+fn client_in_other_module() {
+    let y = counter::next(3);
+    assert!(y > 3);
+    assert!(y == 4); //~ ERROR the asserted expression might not hold
+}
+
+fn answer_in_other_module() {
+    assert!(counter::answer() == 42); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+// compile-flags: -Psplit_conjunctive_assertions=true
+use prusti_contracts::*;
+
+#[requires(x < 50)]
+#[ensures(result > x)]
+#[ensures(result > 10)] //~ ERROR postcondition might not hold
+#[ensures(result < 100)]
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+// The failure is reported at the second conjunct of the `&&`.
+#[requires(x < 50)]
+#[ensures(
+    result > x
+    && result > 10 //~ ERROR postcondition might not hold
+)]
+fn increment_conjunction(x: u32) -> u32 {
+    x + 1
+}
+
+#[requires(x < 50)]
+#[ensures(result > x)]
+#[ensures(result > 10 || x <= 10)]
+#[ensures(result < 100)]
+fn increment_correct(x: u32) -> u32 {
+    x + 1
+}
+
+fn main() {}
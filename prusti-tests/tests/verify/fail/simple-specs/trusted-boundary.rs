@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+#[prusti::ignored]
+mod buffer {
+    pub fn raw_len(len: usize) -> usize {
+        len
+    }
+
+    #[prusti::trusted_boundary]
+    #[ensures(result <= 4096)]
+    pub fn checked_len(len: usize) -> usize {
+        if len > 4096 { 4096 } else { raw_len(len) }
+    }
+}
+
+fn bypass(len: usize) -> usize {
+    buffer::raw_len(len) //~ ERROR can only be used through the trusted boundary of the module
+}
+
+fn wrong_bound(len: usize) {
+    let len = buffer::checked_len(len);
+    assert!(len < 4096); //~ ERROR asserted expression might not hold
+}
+
+fn main() {}
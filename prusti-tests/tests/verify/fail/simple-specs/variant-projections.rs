@@ -0,0 +1,14 @@
+use prusti_contracts::*;
+
+#[ensures(result.Ok.0 == x)] //~ ERROR the enum might not be of the variant whose field is accessed
+fn unknown_variant(x: i32) -> Result<i32, i32> {
+    if x > 0 { Ok(x) } else { Err(x) }
+}
+
+#[ensures(matches!(result, Ok(_)) ==> result.Ok.0 == x)]
+#[ensures(result.Err.0 == x)] //~ ERROR the enum might not be of the variant whose field is accessed
+fn guarded_variant(x: i32) -> Result<i32, i32> {
+    if x > 0 { Ok(x) } else { Err(x) }
+}
+
+fn main() {}
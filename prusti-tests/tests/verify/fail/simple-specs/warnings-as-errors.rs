@@ -0,0 +1,22 @@
+// compile-flags: -Preport_uncovered_result_variants=true -Pwarnings_as_errors=true
+use prusti_contracts::*;
+
+pub enum Ordering {
+    Less,
+    Equal,
+    Greater,
+}
+
+#[ensures(matches!(result, Ordering::Less) ==> a < b)]
+#[ensures(matches!(result, Ordering::Greater) ==> a > b)]
+fn compare(a: i32, b: i32) -> Ordering { //~ ERROR the postconditions do not mention the variant `Equal` of the result
+    if a < b {
+        Ordering::Less
+    } else if a > b {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use prusti_contracts::*;
+
+use std::mem;
+
+#[ensures(result == y)] //~ ERROR postcondition might not hold
+fn replace_value(x: &mut i32, y: i32) -> i32 {
+    mem::replace(x, y)
+}
+
+#[ensures(*a == old(*a))] //~ ERROR postcondition might not hold
+fn swap_values(a: &mut u32, b: &mut u32) {
+    mem::swap(a, b)
+}
+
+fn main() {}
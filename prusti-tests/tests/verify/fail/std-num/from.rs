@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+#[extern_spec]
+impl From<i32> for i64 {
+    #[pure]
+    #[ensures(result == value as i64)]
+    fn from(value: i32) -> i64;
+}
+
+#[ensures(result == a as i64 + b as i64)] //~ ERROR postcondition might not hold
+fn add(a: i32, b: i32) -> i64 {
+    i64::from(a) - i64::from(b)
+}
+
+#[ensures(result == x as i64)] //~ ERROR postcondition might not hold
+fn into_successor(x: i32) -> i64 {
+    let x: i64 = x.into();
+    x + 1
+}
+
+fn client() {
+    assert!(i64::from(-1i32) == 1); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+
+use std::num::NonZeroU32;
+
+fn from_zero() -> NonZeroU32 {
+    unsafe { NonZeroU32::new_unchecked(0) } //~ ERROR precondition might not hold
+}
+
+fn from_unknown(n: u32) -> NonZeroU32 {
+    unsafe { NonZeroU32::new_unchecked(n) } //~ ERROR precondition might not hold
+}
+
+#[ensures(result > 1)] //~ ERROR postcondition might not hold
+fn not_greater_than_one(n: NonZeroU32) -> u32 {
+    n.get()
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+use std::convert::TryFrom;
+
+#[extern_spec]
+impl TryFrom<i32> for u8 {
+    #[ensures(matches!(result, Ok(_)) == (0 <= value && value <= 255))]
+    #[ensures(match result { Ok(byte) => byte as i32 == value, Err(_) => true })]
+    fn try_from(value: i32) -> Result<Self, Self::Error>;
+}
+
+#[requires(x >= 0)]
+fn to_byte(x: i32) -> u8 {
+    match u8::try_from(x) {
+        Ok(byte) => byte,
+        Err(_) => unreachable!(), //~ ERROR unreachable!(..) statement might be reachable
+    }
+}
+
+fn client() {
+    let converted = u8::try_from(256i32);
+    assert!(matches!(converted, Ok(_))); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+use std::borrow::Cow;
+
+#[ensures(matches!(result, Cow::Borrowed(_)))] //~ ERROR postcondition might not hold
+fn shorten(s: &str) -> Cow<str> {
+    if s.len() <= 3 {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(String::new())
+    }
+}
+
+#[ensures(if let Cow::Borrowed(b) = result { b.len() == s.len() + 1 } else { true })] //~ ERROR postcondition might not hold
+fn keep(s: &str) -> Cow<str> {
+    Cow::Borrowed(s)
+}
+
+fn main() {}
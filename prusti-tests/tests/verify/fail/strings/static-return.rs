@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+
+#[ensures(result.len() == 4)] //~ ERROR postcondition might not hold
+fn greeting() -> &'static str {
+    "hello"
+}
+
+#[ensures(result.len() == 5)]
+fn other_greeting() -> &'static str {
+    "hallo"
+}
+
+fn client() {
+    let s = other_greeting();
+    assert!(s.len() == 4); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
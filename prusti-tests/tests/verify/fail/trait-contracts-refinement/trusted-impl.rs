@@ -0,0 +1,40 @@
+use prusti_contracts::*;
+
+trait Sensor {
+    #[ensures(result <= 100)] //~ ERROR postcondition might not hold
+    fn read(&self) -> u32;
+
+    #[ensures(result <= 100)]
+    fn read_twice(&self) -> u32 {
+        let first = self.read();
+        let second = self.read();
+        if first < second { second } else { first }
+    }
+}
+
+/// Backed by a foreign function whose result is known to be in range.
+struct Hardware;
+
+#[trusted]
+impl Sensor for Hardware {
+    fn read(&self) -> u32 {
+        200 // not verified
+    }
+}
+
+struct Simulated {
+    value: u32,
+}
+
+impl Sensor for Simulated {
+    fn read(&self) -> u32 {
+        self.value
+    }
+}
+
+fn client(hardware: &Hardware) {
+    let value = hardware.read();
+    assert!(value <= 100);
+}
+
+fn main() {}
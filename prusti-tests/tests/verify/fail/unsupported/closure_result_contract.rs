@@ -0,0 +1,8 @@
+use prusti_contracts::*;
+
+#[ensures(result |= |x: i32| [ensures(result == x + n)])] //~ ERROR specification entailments (e.g. contracts of returned closures) are not supported yet
+fn adder(n: i32) -> impl Fn(i32) -> i32 {
+    move |x| x + n
+}
+
+fn main() {}
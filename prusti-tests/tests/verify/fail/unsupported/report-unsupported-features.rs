@@ -0,0 +1,18 @@
+// compile-flags: -Preport_unsupported_features=true
+
+union Foo {
+    a: [i32; 1],
+    b: [i32; 1],
+}
+
+fn test_union() {
+    let _ = Foo { a: [0] }; //~ ERROR unions are not supported
+}
+
+fn test_raw_pointer(p: *const i32) -> bool {
+    p.is_null() //~ ERROR raw pointers are not supported
+}
+
+fn main() {
+    let _: *const i32 = std::ptr::null_mut(); //~ ERROR raw pointers are not supported
+}
@@ -0,0 +1,16 @@
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+
+struct SelfReferential {
+    value: u32,
+    _pinned: PhantomPinned,
+}
+
+impl SelfReferential {
+    fn replace(self: Pin<&mut Self>, value: u32) {
+        let this = unsafe { self.get_unchecked_mut() }; //~ ERROR accessing the pointee of a `Pin` without pinning it is not supported
+        this.value = value;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+
+const SIZE: usize = 2 * 4;
+
+#[ensures(result.len() == N)]
+fn zeros<const N: usize>() -> [u8; N] {
+    [0u8; N]
+}
+
+#[ensures(result.len() == SIZE)]
+fn zeros_of_size() -> [u8; SIZE] {
+    [0u8; SIZE]
+}
+
+// Calls the instance of `zeros` with the length of its own instance.
+#[ensures(result.len() == M)]
+fn forward<const M: usize>() -> [u8; M] {
+    zeros::<M>()
+}
+
+fn main() {
+    let a = zeros::<4>();
+    assert!(a.len() == 4);
+    assert!(a[3] == 0);
+
+    let b: [u8; 16] = zeros();
+    assert!(b.len() == 16);
+
+    let c = zeros_of_size();
+    assert!(c.len() == 8);
+
+    let d = forward::<3>();
+    assert!(d.len() == 3);
+}
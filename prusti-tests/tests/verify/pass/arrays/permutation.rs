@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[ensures(is_permutation(&result, &a))]
+fn reverse(a: [i32; 3]) -> [i32; 3] {
+    [a[2], a[1], a[0]]
+}
+
+#[ensures(is_permutation(&result, &a))]
+fn rotate(a: [i32; 3]) -> [i32; 3] {
+    [a[1], a[2], a[0]]
+}
+
+#[ensures(is_permutation(s, old(s)))]
+fn unchanged(s: &mut [i32]) {}
+
+#[ensures(is_permutation(&result, &result))]
+fn reflexive() -> [i32; 2] {
+    [1, 2]
+}
+
+#[requires(b[0] == a[1] && b[1] == a[0] && b[2] == a[0])]
+#[ensures(is_permutation(&result, &b))]
+fn duplicates(a: [i32; 2], b: [i32; 3]) -> [i32; 3] {
+    [a[0], a[1], a[0]]
+}
+
+#[requires(is_permutation(&a, &b))]
+#[ensures(is_permutation(&b, &a))]
+fn symmetric(a: [i32; 3], b: [i32; 3]) {}
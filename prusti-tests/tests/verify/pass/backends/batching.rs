@@ -0,0 +1,29 @@
+// compile-flags: -Pverification_batching=by_backend
+
+use prusti_contracts::*;
+
+#[pure]
+#[requires(x < 100)]
+fn double(x: u32) -> u32 {
+    2 * x
+}
+
+#[requires(x < 100)]
+#[ensures(result == double(x) + 1)]
+fn double_inc(x: u32) -> u32 {
+    2 * x + 1
+}
+
+#[requires(x < 10)]
+#[ensures(result > x)]
+fn client(x: u32) -> u32 {
+    double_inc(x)
+}
+
+#[prusti::backend("carbon")]
+#[ensures(result == x)]
+fn verified_by_carbon(x: u32) -> u32 {
+    x
+}
+
+fn main() {}
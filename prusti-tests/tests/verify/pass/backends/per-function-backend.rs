@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+#[prusti::backend("carbon")]
+#[requires(x < 100)]
+#[ensures(result == x + 1)]
+fn verified_by_carbon(x: u32) -> u32 {
+    x + 1
+}
+
+#[prusti::backend("silicon")]
+#[requires(x < 100)]
+#[ensures(result == x + 2)]
+fn verified_by_silicon(x: u32) -> u32 {
+    verified_by_carbon(verified_by_carbon(x) - 1) + 1
+}
+
+#[ensures(result == 4)]
+fn verified_by_default() -> u32 {
+    verified_by_silicon(2)
+}
+
+fn main() {}
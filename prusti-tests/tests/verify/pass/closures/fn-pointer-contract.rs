@@ -0,0 +1,46 @@
+use prusti_contracts::*;
+
+#[trusted]
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result > x)]
+fn increasing(x: i32) -> i32 {
+    unimplemented!()
+}
+
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result == x + 1)]
+fn add_one(x: i32) -> i32 {
+    x + 1
+}
+
+#[requires(x >= -10 && x < 1000)]
+#[ensures(result >= x + 2)]
+fn add_two(x: i32) -> i32 {
+    x + 2
+}
+
+#[requires(x >= 0 && x < 1000)]
+#[ensures(result > x)]
+fn apply(#[prusti::fn_contract(increasing)] f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+#[requires(x >= 0 && x < 100)]
+#[ensures(result > x)]
+fn apply_forward(#[prusti::fn_contract(increasing)] f: fn(i32) -> i32, x: i32) -> i32 {
+    apply(f, x)
+}
+
+fn client() {
+    let a = apply(add_one, 5);
+    assert!(a > 5);
+    let b = apply(add_two, 7);
+    assert!(b > 7);
+    let c = apply_forward(add_one, 0);
+    assert!(c > 0);
+    let g: fn(i32) -> i32 = add_one;
+    let d = g(3);
+    assert!(d == 4);
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+
+#[requires(x > 0)]
+fn positive(x: u32) {
+    (|| {
+        assert!(x > 0);
+    })();
+}
+
+#[requires(x < y)]
+fn ordered(x: i32, y: i32) -> i32 {
+    (move || {
+        assert!(x != y);
+        y - x
+    })()
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+
+fn main() {
+    let mut a = 0;
+    let b = 0;
+    let _cl = closure!(
+        #[modifies(a)]
+        || -> () {
+            a += b;
+        }
+    );
+
+    // Only reading a mutable variable, or mutating a variable that is moved
+    // into the closure, does not mutate the state of the caller.
+    let mut c = 0;
+    let mut d = 0;
+    c += 1;
+    d += 1;
+    let _cl = closure!(
+        #[modifies()]
+        move || -> i32 {
+            d += c;
+            d
+        }
+    );
+}
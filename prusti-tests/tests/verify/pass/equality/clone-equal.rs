@@ -0,0 +1,52 @@
+use prusti_contracts::*;
+
+#[prusti::clone_equal]
+struct Interval {
+    start: u32,
+    end: u32,
+}
+
+impl Clone for Interval {
+    fn clone(&self) -> Self {
+        Interval { end: self.end, start: self.start }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Id(u64);
+
+#[prusti::clone_equal]
+struct Entry {
+    id: Id,
+    valid: bool,
+}
+
+impl Clone for Entry {
+    fn clone(&self) -> Self {
+        let valid = if self.valid { true } else { false };
+        Entry { id: Id(self.id.0), valid }
+    }
+}
+
+#[prusti::clone_equal]
+struct Pair {
+    first: u32,
+    second: u32,
+}
+
+impl Clone for Pair {
+    #[pure]
+    fn clone(&self) -> Self {
+        Pair { first: self.first, second: self.second }
+    }
+}
+
+#[ensures(snap_eq(x.clone(), x))]
+fn clone_is_equal(x: Pair) {}
+
+#[ensures(snap_eq(result, x.clone()))]
+fn duplicate(x: &Pair) -> Pair {
+    Pair { first: x.first, second: x.second }
+}
+
+fn main() {}
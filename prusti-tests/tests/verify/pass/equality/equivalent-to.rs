@@ -0,0 +1,36 @@
+use prusti_contracts::*;
+
+#[pure]
+fn naive_max(a: i32, b: i32) -> i32 {
+    if a >= b { a } else { b }
+}
+
+#[prusti::equivalent_to(naive_max)]
+fn max(a: i32, b: i32) -> i32 {
+    let mut result = a;
+    if b > result {
+        result = b;
+    }
+    result
+}
+
+#[pure]
+#[requires(n <= 1000)]
+fn naive_sum(n: u64) -> u64 {
+    if n == 0 { 0 } else { n + naive_sum(n - 1) }
+}
+
+#[requires(n <= 1000)]
+#[prusti::equivalent_to(naive_sum)]
+fn iterative_sum(n: u64) -> u64 {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < n {
+        body_invariant!(i < n && sum == naive_sum(i));
+        i += 1;
+        sum += i;
+    }
+    sum
+}
+
+fn main() {}
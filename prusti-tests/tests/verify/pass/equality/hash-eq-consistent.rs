@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+use std::hash::{Hash, Hasher};
+
+#[prusti::hash_eq_consistent]
+struct Key {
+    id: u32,
+    version: u32,
+    cached_len: usize,
+}
+
+impl PartialEq for Key {
+    #[pure]
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.version == other.version
+    }
+}
+
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.version.hash(state);
+    }
+}
+
+#[prusti::hash_eq_consistent]
+#[derive(PartialEq, Eq, Hash)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {}
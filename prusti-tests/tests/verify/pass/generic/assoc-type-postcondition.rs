@@ -0,0 +1,61 @@
+use prusti_contracts::*;
+
+struct Countdown {
+    n: u32,
+}
+
+impl Iterator for Countdown {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.n == 0 {
+            None
+        } else {
+            self.n -= 1;
+            Some(self.n)
+        }
+    }
+}
+
+#[pure]
+#[trusted]
+fn weight<I: Copy>(_item: I) -> u32 {
+    0
+}
+
+#[trusted]
+#[ensures(weight(result) == weight(item))]
+fn pass_through<T>(_iter: &T, item: <T as Iterator>::Item) -> <T as Iterator>::Item
+where
+    T: Iterator,
+    <T as Iterator>::Item: Copy,
+{
+    item
+}
+
+#[trusted]
+#[ensures(result == default as <T as Iterator>::Item)]
+fn default_item<T>(_iter: &T, default: u32) -> <T as Iterator>::Item
+where
+    T: Iterator<Item = u32>,
+{
+    default
+}
+
+#[requires(x < 100)]
+#[ensures(result == x + 1)]
+fn generic_client<S>(iter: &S, x: u32) -> u32
+where
+    S: Iterator<Item = u32>,
+{
+    default_item(iter, x) + 1
+}
+
+fn concrete_client(countdown: &Countdown) {
+    let item = pass_through(countdown, 3);
+    assert!(weight(item) == weight(3u32));
+    let item = default_item(countdown, 5);
+    assert!(item == 5);
+}
+
+fn main() {}
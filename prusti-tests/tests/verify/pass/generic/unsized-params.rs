@@ -0,0 +1,54 @@
+use prusti_contracts::*;
+
+trait Length {
+    #[pure]
+    fn length(&self) -> usize;
+}
+
+impl<U> Length for [U] {
+    #[pure]
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Length for str {
+    #[pure]
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[ensures(result == x.length())]
+fn measure<T: Length + ?Sized>(x: &T) -> usize {
+    x.length()
+}
+
+#[requires(x.length() > 0)]
+#[ensures(result < x.length())]
+fn last_index<T: Length + ?Sized>(x: &T) -> usize {
+    x.length() - 1
+}
+
+#[requires(s.len() > 2)]
+fn slices(s: &[i32]) {
+    let n = measure(s);
+    assert!(n == s.len());
+    let i = last_index(s);
+    assert!(i < s.len());
+}
+
+#[ensures(result == s.len())]
+fn str_len(s: &str) -> usize {
+    s.len()
+}
+
+#[requires(s.len() > 0)]
+fn strings(s: &str) {
+    let n = measure(s);
+    assert!(n == s.len());
+    let i = last_index(s);
+    assert!(i + 1 == n);
+}
+
+fn main() {}
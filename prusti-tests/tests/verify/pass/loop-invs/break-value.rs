@@ -0,0 +1,19 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(s.len() > 0 && s[s.len() - 1] == n)]
+#[ensures(result < s.len() && s[result] == n)]
+fn find(s: &[i32], n: i32) -> usize {
+    let mut i = 0;
+    let index = loop {
+        body_invariant!(i < s.len());
+        body_invariant!(s[s.len() - 1] == n);
+        break_ensures!(i < s.len() && s[i] == n);
+        if s[i] == n {
+            break i;
+        }
+        i += 1;
+    };
+    index
+}
@@ -0,0 +1,57 @@
+// compile-flags: -Pcheck_overflows=false
+use prusti_contracts::*;
+
+struct Indices {
+    position: usize,
+    end: usize,
+}
+
+impl Indices {
+    #[ensures(result.position == 0 && result.end == end)]
+    fn new(end: usize) -> Self {
+        Indices { position: 0, end }
+    }
+}
+
+#[refine_trait_spec]
+impl Iterator for Indices {
+    type Item = usize;
+
+    #[ensures(self.end == old(self.end))]
+    #[ensures(match result {
+        Some(index) => index == old(self.position) && index < self.end,
+        None => true,
+    })]
+    fn next(&mut self) -> Option<usize> {
+        if self.position < self.end {
+            let index = self.position;
+            self.position += 1;
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+#[requires(forall(|k: usize| k < s.len() ==> s[k] <= 100))]
+fn sum(s: &[usize]) -> usize {
+    let mut total = 0;
+    for i in Indices::new(s.len()) {
+        body_invariant!(total <= 100 * loop_iterations());
+        if i < s.len() {
+            total += s[i];
+        }
+    }
+    total
+}
+
+fn count(n: usize) -> usize {
+    let mut steps = 0;
+    for _ in Indices::new(n) {
+        body_invariant!(steps == loop_iterations());
+        steps += 1;
+    }
+    steps
+}
+
+fn main() {}
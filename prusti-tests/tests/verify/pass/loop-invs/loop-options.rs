@@ -0,0 +1,17 @@
+// compile-flags: -Pqi_limit=0
+
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(forall(|k: usize| k < s.len() ==> s[k] > 0))]
+fn check_positive(s: &[i32]) {
+    let mut i = 0;
+    #[prusti::loop_options(qi_limit = 100000)]
+    while i < s.len() {
+        body_invariant!(i < s.len());
+        body_invariant!(forall(|k: usize| k < s.len() ==> s[k] > 0));
+        assert!(s[i] > 0);
+        i += 1;
+    }
+}
@@ -0,0 +1,25 @@
+#![feature(inline_const)]
+
+use prusti_contracts::*;
+
+const LEN: usize = 4;
+
+fn last(a: [i32; LEN]) -> i32 {
+    const { assert!(LEN > 0) };
+    a[LEN - 1]
+}
+
+#[requires(x > 100)]
+fn independent_of_precondition(x: u32) -> u32 {
+    const { assert!(2 + 2 == 4) };
+    if x > 0 {
+        const {
+            if LEN > 2 {
+                assert!(LEN - 2 > 0)
+            }
+        };
+    }
+    x
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+// compile-flags: -Poptimizations=all,fold_constants
+use prusti_contracts::*;
+
+macro_rules! scaled {
+    ($e:expr) => { 1 * $e + 0 };
+}
+
+#[requires(x < 100)]
+#[ensures(result == x + 2 + 3)]
+fn add_five(x: u32) -> u32 {
+    let mut y = scaled!(x);
+    if true && 4 < 5 {
+        y += 2 + 3;
+    }
+    y
+}
+
+#[ensures(!!result == (x > 0 || false))]
+fn positive(x: i32) -> bool {
+    x > 0 && true
+}
+
+fn main() {
+    assert!(add_five(1) == 6);
+    assert!(positive(scaled!(2)));
+}
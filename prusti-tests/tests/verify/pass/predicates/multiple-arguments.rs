@@ -0,0 +1,44 @@
+use prusti_contracts::*;
+
+predicate! {
+    #[requires(len <= 1000)]
+    fn in_bounds(i: usize, len: usize) -> bool {
+        0 <= i && i < len
+    }
+}
+
+#[requires(len <= 1000)]
+#[requires(in_bounds(i, len))]
+#[ensures(result < len)]
+fn next_index(i: usize, len: usize) -> usize {
+    if i + 1 < len {
+        i + 1
+    } else {
+        i
+    }
+}
+
+#[requires(len <= 1000)]
+#[requires(in_bounds(i, len))]
+#[ensures(in_bounds(result, len))]
+fn previous_index(i: usize, len: usize) -> usize {
+    if i > 0 {
+        i - 1
+    } else {
+        i
+    }
+}
+
+#[requires(len <= 1000)]
+#[requires(in_bounds(i, len))]
+fn client(i: usize, len: usize) {
+    // The precondition of `client` is assumed and then checked as an
+    // obligation of the calls.
+    let j = previous_index(i, len);
+    let k = next_index(j, len);
+    assert!(k < len);
+}
+
+fn main() {
+    client(3, 10);
+}
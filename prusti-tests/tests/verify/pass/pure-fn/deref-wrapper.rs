@@ -0,0 +1,71 @@
+use prusti_contracts::*;
+use std::ops::Deref;
+
+#[derive(Clone, Copy)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    #[pure]
+    fn is_origin(&self) -> bool {
+        self.x == 0 && self.y == 0
+    }
+}
+
+struct Wrapper {
+    inner: Point,
+}
+
+impl Deref for Wrapper {
+    type Target = Point;
+
+    #[pure]
+    fn deref(&self) -> &Point {
+        &self.inner
+    }
+}
+
+#[ensures(result == w.x)]
+fn get_x(w: &Wrapper) -> i32 {
+    w.inner.x
+}
+
+#[ensures(result.x == x && result.y == y)]
+fn wrap(x: i32, y: i32) -> Wrapper {
+    Wrapper { inner: Point { x, y } }
+}
+
+#[ensures(result == w.is_origin())]
+fn at_origin(w: &Wrapper) -> bool {
+    w.inner.x == 0 && w.inner.y == 0
+}
+
+struct TrustedWrapper {
+    inner: Point,
+}
+
+impl Deref for TrustedWrapper {
+    type Target = Point;
+
+    #[pure]
+    #[trusted]
+    #[ensures(*result == self.inner)]
+    fn deref(&self) -> &Point {
+        &self.inner
+    }
+}
+
+#[ensures(result == w.y)]
+fn get_y(w: &TrustedWrapper) -> i32 {
+    w.inner.y
+}
+
+fn client() {
+    let w = wrap(0, 0);
+    assert!(at_origin(&w));
+    assert!(get_x(&w) == 0);
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[pure]
+fn manhattan(p: &Point) -> i32 {
+    let x = if p.x < 0 { -p.x } else { p.x };
+    let y = if p.y < 0 { -p.y } else { p.y };
+    x + y
+}
+
+#[pure]
+fn max(a: i32, b: i32) -> i32 {
+    if a > b { a } else { b }
+}
+
+#[requires(p.x >= 0 && p.y >= 0)]
+#[ensures(result == manhattan(p))]
+fn sum(p: &Point) -> i32 {
+    p.x + p.y
+}
+
+fn main() {
+    assert!(max(1, 2) == 2);
+}
@@ -0,0 +1,65 @@
+use prusti_contracts::*;
+use std::ops::Index;
+
+pub struct VecWrapper {
+    v: Vec<i32>,
+}
+
+impl VecWrapper {
+    #[trusted]
+    #[pure]
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    #[trusted]
+    #[pure]
+    #[requires(index < self.len())]
+    pub fn lookup(&self, index: usize) -> i32 {
+        self.v[index]
+    }
+
+    #[trusted]
+    #[requires(index < self.len())]
+    #[ensures(self.len() == old(self.len()))]
+    #[ensures(self.lookup(index) == value)]
+    #[ensures(forall(|i: usize| (i < self.len() && i != index) ==> self.lookup(i) == old(self.lookup(i))))]
+    pub fn store(&mut self, index: usize, value: i32) {
+        self.v[index] = value;
+    }
+}
+
+impl Index<usize> for VecWrapper {
+    type Output = i32;
+
+    #[trusted]
+    #[pure]
+    #[requires(index < self.len())]
+    #[ensures(*result == self.lookup(index))]
+    fn index(&self, index: usize) -> &i32 {
+        &self.v[index]
+    }
+}
+
+#[requires(c.len() > 0)]
+#[ensures(result == c[0])]
+fn first(c: &VecWrapper) -> i32 {
+    c[0]
+}
+
+#[requires(c.len() > 1)]
+#[ensures(c.len() == old(c.len()))]
+#[ensures(c[0] == 42)]
+#[ensures(c[1] == old(c[1]))]
+fn set_first(c: &mut VecWrapper) {
+    c.store(0, 42);
+}
+
+#[requires(c.len() > 1)]
+fn client(c: &mut VecWrapper) {
+    set_first(c);
+    assert!(first(c) == 42);
+    assert!(c[0] == 42);
+}
+
+fn main() {}
@@ -0,0 +1,37 @@
+use prusti_contracts::*;
+
+#[pure]
+#[terminates(n, group = "parity")]
+fn is_even(n: u64) -> bool {
+    if n == 0 { true } else { is_odd(n - 1) }
+}
+
+#[pure]
+#[terminates(n, group = "parity")]
+fn is_odd(n: u64) -> bool {
+    if n == 0 { false } else { is_even(n - 1) }
+}
+
+#[pure]
+#[terminates(n)]
+fn all_small(n: u64) -> bool {
+    n == 0 || (n < 100 && all_small(n - 1))
+}
+
+#[pure]
+#[terminates(m, n)]
+#[requires(m < 10 && n < 10)]
+fn ackermann_like(m: u32, n: u32) -> u32 {
+    if m == 0 {
+        n
+    } else if n == 0 {
+        ackermann_like(m - 1, 9)
+    } else {
+        ackermann_like(m, n - 1)
+    }
+}
+
+#[requires(is_even(4))]
+fn client() {}
+
+fn main() {}
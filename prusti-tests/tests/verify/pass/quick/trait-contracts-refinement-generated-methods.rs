@@ -0,0 +1,48 @@
+use prusti_contracts::*;
+
+trait Counter {
+    #[ensures(result >= 0)]
+    fn get(&self) -> i32;
+
+    fn name(&self) -> &'static str;
+
+    fn increment(&mut self);
+}
+
+macro_rules! impl_name {
+    ($name:expr) => {
+        fn name(&self) -> &'static str {
+            $name
+        }
+    };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Simple {
+    value: i32,
+}
+
+#[refine_trait_spec]
+impl Counter for Simple {
+    #[ensures(result >= 0)]
+    #[ensures(self.value >= 0 ==> result == self.value)]
+    fn get(&self) -> i32 {
+        if self.value < 0 { 0 } else { self.value }
+    }
+
+    impl_name!("simple");
+
+    #[inline]
+    fn increment(&mut self) {
+        if self.value >= 0 && self.value < 100 {
+            self.value += 1;
+        }
+    }
+}
+
+fn main() {
+    let mut counter = Simple { value: 0 };
+    counter.increment();
+    assert!(counter.get() >= 0);
+    assert!(Simple { value: 3 }.get() == 3);
+}
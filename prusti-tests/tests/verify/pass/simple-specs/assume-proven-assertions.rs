@@ -0,0 +1,22 @@
+// compile-flags: -Passume_proven_assertions=true
+use prusti_contracts::*;
+
+#[pure]
+fn is_even(x: u32) -> bool {
+    x % 2 == 0
+}
+
+#[ensures(is_even(result))]
+fn next_even(x: u32) -> u32 {
+    let y = x + x;
+    assert!(is_even(y));
+    y
+}
+
+#[ensures(result > 5)]
+fn above_five(x: i32) -> i32 {
+    assert!(x > 10);
+    x - 5
+}
+
+fn main() {}
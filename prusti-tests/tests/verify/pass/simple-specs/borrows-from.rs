@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+struct Pair {
+    first: i32,
+    second: i32,
+}
+
+impl Pair {
+    #[ensures(borrows_from(result, self))]
+    #[ensures(*result == self.first)]
+    fn first(&self) -> &i32 {
+        &self.first
+    }
+}
+
+#[ensures(borrows_from(result, a) && *result == a.first)]
+fn pick<'a, 'b>(a: &'a Pair, _b: &'b Pair) -> &'a i32 {
+    a.first()
+}
+
+fn main() {
+    let pair = Pair { first: 1, second: 2 };
+    let first = pair.first();
+    assert!(*first == 1);
+}
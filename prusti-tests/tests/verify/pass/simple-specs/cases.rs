@@ -0,0 +1,54 @@
+use prusti_contracts::*;
+
+#[requires(lo <= hi)]
+#[case(name = "below range", pre = x < lo, post = result == lo)]
+#[case(name = "in range", pre = lo <= x && x <= hi, post = result == x)]
+#[case(name = "above range", pre = x > hi, post = result == hi)]
+fn clamp(x: i32, lo: i32, hi: i32) -> i32 {
+    if x < lo {
+        lo
+    } else if x > hi {
+        hi
+    } else {
+        x
+    }
+}
+
+#[case(pre = x >= 0, post = result == x)]
+#[case(pre = x <= 0, post = result == -x)]
+#[ensures(result >= 0)]
+fn abs(x: i64) -> i64 {
+    if x < 0 { -x } else { x }
+}
+
+#[pure]
+#[case(name = "positive", pre = x > 0, post = result)]
+#[case(name = "negative", pre = x < 0, post = !result)]
+fn is_positive(x: i32) -> bool {
+    x > 0
+}
+
+#[pure]
+fn first<A: Copy, B: Copy>(a: A, _b: B) -> A {
+    a
+}
+
+// The comma of the turbofish does not separate the arguments of the case.
+#[case(pre = first::<i32, bool>(x, true) > 0, post = result == x)]
+#[case(pre = x <= 0, post = result == 0)]
+fn positive_part(x: i32) -> i32 {
+    if x > 0 { x } else { 0 }
+}
+
+fn client() {
+    assert!(clamp(-5, 0, 10) == 0);
+    assert!(clamp(5, 0, 10) == 5);
+    assert!(clamp(15, 0, 10) == 10);
+    assert!(abs(-3) == 3);
+    assert!(abs(0) == 0);
+    assert!(is_positive(3));
+    assert!(!is_positive(-3));
+    assert!(positive_part(-3) == 0);
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use prusti_contracts::*;
+
+#[pure]
+fn is_upper_bound(a: i32, b: i32, result: i32) -> bool {
+    result >= a && result >= b
+}
+
+#[ensures(result >= a && result >= b)]
+#[ensures(result == a || result == b)]
+#[prusti::check_implies(is_upper_bound)]
+fn max(a: i32, b: i32) -> i32 {
+    if a >= b { a } else { b }
+}
+
+#[pure]
+fn is_positive(_x: u32, result: u32) -> bool {
+    result > 0
+}
+
+#[requires(x < 1000)]
+#[ensures(result == x + 1)]
+#[prusti::check_implies(is_positive)]
+fn inc(x: u32) -> u32 {
+    x + 1
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+
+#[ensures(result == if x >= 0 { x } else { -x })]
+fn abs(x: i32) -> i32 {
+    if x < 0 {
+        -x
+    } else {
+        x
+    }
+}
+
+#[ensures(result == if x < lo { lo } else if x > hi { hi } else { x })]
+fn clamp(x: i32, lo: i32, hi: i32) -> i32 {
+    if x < lo {
+        lo
+    } else if x > hi {
+        hi
+    } else {
+        x
+    }
+}
+
+#[requires(if flag { x > 0 } else { x < 0 })]
+#[ensures(if flag { result } else { !result })]
+fn sign(flag: bool, x: i32) -> bool {
+    x > 0
+}
+
+fn client() {
+    assert!(abs(-3) == 3);
+    assert!(clamp(15, 0, 10) == 10);
+    assert!(sign(false, -1) == false);
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+
+const READABLE: u8 = 0b001;
+const WRITABLE: u8 = 0b010;
+const EXECUTABLE: u8 = 0b100;
+
+struct Permissions {
+    bits: u8,
+}
+
+#[ensures(has_flag(result, READABLE))]
+#[ensures(!has_flag(result, WRITABLE))]
+fn read_only() -> Permissions {
+    Permissions { bits: READABLE }
+}
+
+#[ensures(has_flag(result, READABLE))]
+#[ensures(has_flag(result, WRITABLE))]
+#[ensures(has_flag(result, EXECUTABLE))]
+fn all() -> Permissions {
+    Permissions { bits: 7 }
+}
+
+#[requires(mode < 8)]
+#[ensures(has_flag(result, EXECUTABLE))]
+#[ensures(has_flag(mode, READABLE) ==> has_flag(result, READABLE))]
+fn make_executable(mode: u8) -> u8 {
+    if mode >= EXECUTABLE {
+        mode
+    } else {
+        mode + EXECUTABLE
+    }
+}
+
+fn main() {}
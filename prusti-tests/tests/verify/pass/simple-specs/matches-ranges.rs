@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+
+#[ensures(matches!(result, 0..10))]
+fn last_digit(x: u32) -> u32 {
+    x % 10
+}
+
+#[ensures(matches!(result, 0..=10))]
+fn clamp(x: i32) -> i32 {
+    if x < 0 {
+        0
+    } else if x > 10 {
+        10
+    } else {
+        x
+    }
+}
+
+#[requires(x < 100)]
+#[ensures(matches!(result, 5..))]
+fn at_least_five(x: u32) -> u32 {
+    x + 5
+}
+
+#[ensures(matches!(result, ..=-1 | 1..))]
+fn non_zero(x: i32) -> i32 {
+    if x == 0 {
+        1
+    } else {
+        x
+    }
+}
+
+fn main() {}
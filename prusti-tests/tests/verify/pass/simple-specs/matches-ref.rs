@@ -0,0 +1,46 @@
+use prusti_contracts::*;
+
+enum State {
+    Open,
+    Closed(u32),
+}
+
+struct Connection {
+    state: State,
+    len: u32,
+}
+
+impl Connection {
+    #[pure]
+    fn is_open(&self) -> bool {
+        matches!(&self.state, State::Open)
+    }
+
+    #[requires(matches!(&self.state, State::Closed(code) if *code > 0))]
+    #[ensures(self.is_open())]
+    fn reopen(&mut self) {
+        match &self.state {
+            State::Closed(code) => assert!(*code > 0),
+            State::Open => unreachable!(),
+        }
+        self.state = State::Open;
+    }
+
+    #[requires(matches!(&self.state, State::Closed(ref code) if *code == 1))]
+    #[ensures(matches!(&self.len, 0..10))]
+    fn reset(&mut self) -> u32 {
+        self.len = 0;
+        match self.state {
+            State::Closed(ref code) => *code,
+            State::Open => unreachable!(),
+        }
+    }
+}
+
+fn main() {
+    let mut connection = Connection { state: State::Closed(1), len: 42 };
+    connection.reset();
+    connection.reopen();
+    assert!(connection.is_open());
+    assert!(connection.len < 10);
+}
@@ -0,0 +1,33 @@
+use prusti_contracts::*;
+
+#[assumptions(capacity() > 0)]
+mod ring {
+    use prusti_contracts::*;
+
+    #[pure]
+    #[trusted]
+    pub fn capacity() -> usize {
+        unimplemented!()
+    }
+
+    #[ensures(result < capacity())]
+    pub fn slot(index: usize) -> usize {
+        index % capacity()
+    }
+
+    pub struct Ring {
+        pub head: usize,
+    }
+
+    impl Ring {
+        #[ensures(self.head < capacity())]
+        pub fn advance(&mut self) {
+            self.head = (self.head % capacity() + 1) % capacity();
+        }
+    }
+}
+
+fn main() {
+    let mut r = ring::Ring { head: 0 };
+    r.advance();
+}
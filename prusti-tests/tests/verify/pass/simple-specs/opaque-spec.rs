@@ -0,0 +1,38 @@
+use prusti_contracts::*;
+
+mod counter {
+    use prusti_contracts::*;
+
+    #[requires(x < 1000)]
+    #[ensures(result == x + 1)]
+    #[opaque_spec(ensures(result > x))]
+    pub fn next(x: u32) -> u32 {
+        x + 1
+    }
+
+    #[ensures(result == 42)]
+    #[opaque_spec]
+    pub fn answer() -> u32 {
+        42
+    }
+
+    fn internal_client() {
+        let y = next(3);
+        assert!(y == 4);
+        assert!(answer() == 42);
+    }
+
+    mod nested {
+        fn nested_client() {
+            assert!(super::next(3) == 4);
+        }
+    }
+}
+
+fn client_in_other_module() {
+    let y = counter::next(3);
+    assert!(y > 3);
+    let _ = counter::answer();
+}
+
+fn main() {}
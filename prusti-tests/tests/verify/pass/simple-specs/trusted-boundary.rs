@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+// Nothing in this module is encoded, except for the contract of its trusted
+// boundary.
+#[prusti::ignored]
+mod buffer {
+    pub struct Buffer {
+        len: usize,
+    }
+
+    // Raw pointers are not supported by the verifier.
+    fn raw_len(buffer: &Buffer) -> usize {
+        let ptr = &buffer.len as *const usize;
+        unsafe { *ptr }
+    }
+
+    #[prusti::trusted_boundary]
+    #[ensures(result <= 4096)]
+    pub fn checked_len(buffer: &Buffer) -> usize {
+        let len = raw_len(buffer);
+        if len > 4096 { 4096 } else { len }
+    }
+}
+
+fn padded_len(buffer: &buffer::Buffer) -> usize {
+    let len = buffer::checked_len(buffer);
+    assert!(len <= 4096);
+    len + 1
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+use prusti_contracts::*;
+
+enum Shape {
+    Circle { radius: u32 },
+    Rectangle(u32, u32),
+}
+
+use Shape::*;
+
+#[requires(x > 0)]
+#[ensures(result.Ok.0 == x)]
+fn positive(x: i32) -> Result<i32, i32> {
+    if x > 0 { Ok(x) } else { Err(x) }
+}
+
+#[ensures(x >= 0 ==> result.Some.0 == x)]
+#[ensures(matches!(result, Some(_)) ==> result.Some.0 >= 0)]
+fn non_negative(x: i32) -> Option<i32> {
+    if x >= 0 { Some(x) } else { None }
+}
+
+#[ensures(result.Circle.radius == radius)]
+fn circle(radius: u32) -> Shape {
+    Circle { radius }
+}
+
+#[ensures(matches!(result, Rectangle(..)) && result.Rectangle.0 == side && result.Rectangle.1 == side)]
+fn square(side: u32) -> Shape {
+    Rectangle(side, side)
+}
+
+fn main() {}
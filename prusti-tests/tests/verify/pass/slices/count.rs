@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[ensures(result == count(|i: usize| i < s.len() && s[i] > 0))]
+fn count_positive(s: &[i32]) -> usize {
+    let mut result = 0;
+    let mut i = 0;
+    while i < s.len() {
+        body_invariant!(i < s.len());
+        body_invariant!(result <= i);
+        body_invariant!(result == count(|j: usize| j < i && s[j] > 0));
+        if s[i] > 0 {
+            result += 1;
+        }
+        i += 1;
+    }
+    result
+}
+
+#[requires(s.len() == 2 && s[0] > 0 && s[1] > 0)]
+#[ensures(result == count(|i: usize| i < s.len() && s[i] > 0))]
+fn two_positive(s: &[i32]) -> usize {
+    2
+}
+
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(forall(|i: usize| i < s.len() ==> s[i] < 1000))]
+#[ensures(s.len() == old(s.len()))]
+#[ensures(forall(|i: usize| i < s.len() ==> s[i] == old(s[i]) + 1))]
+fn increment_all(s: &mut [i32]) {
+    let mut k = 0;
+    while k < s.len() {
+        body_invariant!(k < s.len());
+        body_invariant!(s.len() == old(s.len()));
+        body_invariant!(forall(|i: usize| i < k ==> s[i] == old(s[i]) + 1));
+        body_invariant!(forall(|i: usize| k <= i && i < s.len() ==> s[i] == old(s[i])));
+        s[k] += 1;
+        k += 1;
+    }
+}
+
+#[requires(s.len() == 3)]
+#[requires(forall(|i: usize| i < s.len() ==> s[i] < 1000))]
+fn client(s: &mut [i32]) {
+    let a = s[0];
+    let c = s[2];
+    increment_all(s);
+    assert!(s[0] == a + 1);
+    assert!(s[2] == c + 1);
+}
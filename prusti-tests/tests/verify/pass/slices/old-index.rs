@@ -0,0 +1,32 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[requires(s.len() > 0)]
+#[requires(s[0] < 100)]
+#[ensures(s.len() == old(s.len()))]
+#[ensures(s[0] == old(s[0]) + 1)]
+fn inc_first(s: &mut [i32]) {
+    s[0] += 1;
+}
+
+#[requires(i < s.len())]
+#[requires(s[i] < 100)]
+#[ensures(s.len() == old(s.len()))]
+#[ensures(s[i] == old(s[i]) + 1)]
+#[ensures(forall(|j: usize| (j < s.len() && j != i) ==> s[j] == old(s[j])))]
+fn inc_at(s: &mut [i32], i: usize) {
+    s[i] += 1;
+}
+
+#[requires(s.len() > 1)]
+#[requires(s[0] < 100 && s[1] < 100)]
+fn client(s: &mut [i32]) {
+    let a = s[0];
+    let b = s[1];
+    inc_first(s);
+    assert!(s[0] == a + 1);
+    inc_at(s, 1);
+    assert!(s[0] == a + 1);
+    assert!(s[1] == b + 1);
+}
@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+#[ensures(is_permutation(s, old(s)))]
+fn reverse(s: &mut [i32]) {
+    let len = s.len();
+    let mut i = 0;
+    while i < len / 2 {
+        body_invariant!(s.len() == len);
+        body_invariant!(i < len / 2);
+        body_invariant!(is_permutation(s, old(s)));
+        s.swap(i, len - 1 - i);
+        i += 1;
+    }
+}
+
+#[requires(s.len() == 2)]
+#[ensures(s[0] == old(s[1]) && s[1] == old(s[0]))]
+#[ensures(is_permutation(s, old(s)))]
+fn swap_pair(s: &mut [i32]) {
+    s.swap(0, 1);
+}
+
+#[requires(s.len() > 2)]
+#[ensures(s[2] == old(s[2]))]
+fn swap_keeps_others(s: &mut [i32]) {
+    s.swap(0, 1);
+}
@@ -0,0 +1,58 @@
+use prusti_contracts::*;
+
+fn main() {}
+
+predicate! {
+    fn sorted(s: &[i32]) -> bool {
+        forall(|i: usize, j: usize| (0 <= i && i < j && j < s.len()) ==> s[i] <= s[j])
+    }
+}
+
+#[ensures(sorted(s))]
+#[ensures(s.len() == old(s.len()))]
+fn sort(s: &mut [i32]) {
+    s.sort();
+}
+
+#[ensures(sorted(s))]
+fn sort_unstable(s: &mut [i32]) {
+    s.sort_unstable();
+}
+
+#[requires(s.len() > 3)]
+fn first_is_smallest(s: &mut [i32]) {
+    s.sort();
+    assert!(s[0] <= s[3]);
+}
+
+#[requires(s.len() > 0)]
+#[requires(forall(|i: usize| (0 <= i && i < s.len()) ==> s[i] >= 0))]
+fn elements_are_kept(s: &mut [i32]) {
+    s.sort();
+    assert!(s[0] >= 0);
+}
+
+#[requires(s.len() == 3 && s[0] == 2 && s[1] == 1 && s[2] == 1)]
+fn duplicates_are_kept(s: &mut [i32]) {
+    s.sort();
+    assert!(s[0] == 1 && s[1] == 1 && s[2] == 2);
+}
+
+struct VecWrapperI32 {
+    v: Vec<i32>,
+}
+
+impl VecWrapperI32 {
+    #[trusted]
+    fn as_mut_slice(&mut self) -> &mut [i32] {
+        self.v.as_mut_slice()
+    }
+}
+
+fn sort_vec(v: &mut VecWrapperI32) {
+    let s = v.as_mut_slice();
+    s.sort();
+    if s.len() > 1 {
+        assert!(s[0] <= s[1]);
+    }
+}
@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+
+use std::mem;
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+struct Config {
+    verbose: bool,
+    level: u32,
+}
+
+struct Machine {
+    config: Config,
+    counter: u64,
+}
+
+impl Machine {
+    #[ensures(self.config == Config::default())]
+    #[ensures(self.counter == Default::default())]
+    fn reset(&mut self) {
+        self.config = Config::default();
+        self.counter = Default::default();
+    }
+
+    #[ensures(result == old(self.config))]
+    #[ensures(self.config == Default::default())]
+    fn take_config(&mut self) -> Config {
+        mem::take(&mut self.config)
+    }
+}
+
+#[ensures(result == u32::default())]
+fn zero() -> u32 {
+    0
+}
+
+fn client(machine: &mut Machine) {
+    machine.reset();
+    let config = machine.take_config();
+    assert!(config == Config::default());
+    assert!(machine.counter == 0);
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+use prusti_contracts::*;
+
+use std::mem;
+
+#[ensures(result == old(*x))]
+#[ensures(*x == y)]
+fn replace_value(x: &mut i32, y: i32) -> i32 {
+    mem::replace(x, y)
+}
+
+#[ensures(*a == old(*b))]
+#[ensures(*b == old(*a))]
+fn swap_values(a: &mut u32, b: &mut u32) {
+    mem::swap(a, b)
+}
+
+#[ensures(result == old(*x))]
+#[ensures(*x == 0)]
+fn take_value(x: &mut i64) -> i64 {
+    mem::take(x)
+}
+
+#[ensures(result == old(*flag))]
+#[ensures(!*flag)]
+fn take_flag(flag: &mut bool) -> bool {
+    mem::take(flag)
+}
+
+fn client() {
+    let mut a = 1;
+    let mut b = 2;
+    mem::swap(&mut a, &mut b);
+    assert!(a == 2 && b == 1);
+    let old_a = mem::replace(&mut a, 5);
+    assert!(old_a == 2 && a == 5);
+}
+
+fn main() {}
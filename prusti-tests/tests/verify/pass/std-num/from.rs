@@ -0,0 +1,38 @@
+use prusti_contracts::*;
+
+#[extern_spec]
+impl From<i32> for i64 {
+    #[pure]
+    #[ensures(result == value as i64)]
+    fn from(value: i32) -> i64;
+}
+
+#[ensures(result == a as i64 + b as i64)]
+fn add(a: i32, b: i32) -> i64 {
+    i64::from(a) + i64::from(b)
+}
+
+#[ensures(result == a as i64 * b as i64)]
+fn mul(a: i32, b: i32) -> i64 {
+    let a: i64 = a.into();
+    a * i64::from(b)
+}
+
+#[pure]
+#[ensures(result == x as i64 + 1)]
+fn successor(x: i32) -> i64 {
+    i64::from(x) + 1
+}
+
+#[requires(x > 0)]
+#[ensures(result > i64::from(x))]
+fn double(x: i32) -> i64 {
+    2 * i64::from(x)
+}
+
+fn client() {
+    assert!(add(i32::MAX, 1) == 2147483648);
+    assert!(successor(i32::MAX) == i32::MAX as i64 + 1);
+}
+
+fn main() {}
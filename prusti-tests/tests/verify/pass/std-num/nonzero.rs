@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+
+use std::num::{NonZeroU32, NonZeroUsize};
+
+#[ensures(result > 0)]
+fn positive(n: NonZeroU32) -> u32 {
+    n.get()
+}
+
+fn divide(a: u32, b: NonZeroU32) -> u32 {
+    a / b.get()
+}
+
+#[requires(n > 0)]
+#[ensures(result == n)]
+fn round_trip(n: usize) -> usize {
+    let wrapped = unsafe { NonZeroUsize::new_unchecked(n) };
+    wrapped.get()
+}
+
+fn client() {
+    let two = unsafe { NonZeroU32::new_unchecked(2) };
+    assert!(divide(6, two) <= 6);
+}
+
+fn main() {}
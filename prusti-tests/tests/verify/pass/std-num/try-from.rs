@@ -0,0 +1,27 @@
+use prusti_contracts::*;
+
+use std::convert::TryFrom;
+
+#[extern_spec]
+impl TryFrom<i32> for u8 {
+    #[ensures(matches!(result, Ok(_)) == (0 <= value && value <= 255))]
+    #[ensures(match result { Ok(byte) => byte as i32 == value, Err(_) => true })]
+    fn try_from(value: i32) -> Result<Self, Self::Error>;
+}
+
+#[requires(0 <= x && x <= 255)]
+#[ensures(result as i32 == x)]
+fn to_byte(x: i32) -> u8 {
+    match u8::try_from(x) {
+        Ok(byte) => byte,
+        Err(_) => unreachable!(),
+    }
+}
+
+fn client() {
+    assert!(to_byte(200) == 200);
+    assert!(matches!(u8::try_from(-1i32), Err(_)));
+    assert!(matches!(u8::try_from(256i32), Err(_)));
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use prusti_contracts::*;
+
+#[ensures(matches!(a, Err(_)) ==> matches!(result, Err(_)))]
+#[ensures(matches!(a, Ok(_)) && matches!(b, Err(_)) ==> matches!(result, Err(_)))]
+#[ensures(matches!(a, Ok(_)) && matches!(b, Ok(_)) ==> matches!(result, Ok(_)))]
+fn add(a: Result<u32, u32>, b: Result<u32, u32>) -> Result<u32, u32> {
+    let x = a?;
+    let y = b?;
+    Ok(x + y)
+}
+
+#[ensures(match a {
+    Ok(x) => match result { Ok(r) => r == x, Err(_) => false },
+    Err(e) => match result { Err(r) => r == e, Ok(_) => false },
+})]
+fn forward(a: Result<u32, u32>) -> Result<u32, u32> {
+    let x = a?;
+    Ok(x)
+}
+
+fn main() {}
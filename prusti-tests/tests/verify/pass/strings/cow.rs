@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+use std::borrow::Cow;
+
+#[ensures(s.len() <= 3 ==> matches!(result, Cow::Borrowed(_)))]
+#[ensures(s.len() > 3 ==> matches!(result, Cow::Owned(_)))]
+#[ensures(if let Cow::Borrowed(b) = result { b.len() == s.len() } else { true })]
+fn shorten(s: &str) -> Cow<str> {
+    if s.len() <= 3 {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(String::new())
+    }
+}
+
+#[ensures(matches!(result, Cow::Borrowed(_)))]
+#[ensures(if let Cow::Borrowed(b) = result { b.len() == 5 } else { false })]
+fn borrowed() -> Cow<'static, str> {
+    Cow::Borrowed("hello")
+}
+
+fn client(s: &str) {
+    let c = shorten(s);
+    if s.len() <= 3 {
+        assert!(matches!(c, Cow::Borrowed(_)));
+    } else {
+        assert!(matches!(c, Cow::Owned(_)));
+    }
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+#[ensures(result.len() == 5)]
+fn greeting() -> &'static str {
+    "hello"
+}
+
+#[ensures(result.len() == 0)]
+fn empty() -> &'static str {
+    ""
+}
+
+#[requires(*x == 5)]
+#[ensures(*result == 5)]
+fn freeze(x: &'static mut u32) -> &'static u32 {
+    x
+}
+
+fn client(x: &'static mut u32) {
+    let s = greeting();
+    let t = greeting();
+    assert!(s.len() == t.len());
+    assert!(s.len() + empty().len() == 5);
+    *x = 5;
+    let y = freeze(x);
+    assert!(*y == 5);
+}
+
+fn main() {}
@@ -0,0 +1,67 @@
+use prusti_contracts::*;
+
+#[derive(Clone, Copy)]
+struct CounterModel {
+    position: u32,
+    end: u32,
+}
+
+struct Counter {
+    position: u32,
+    end: u32,
+}
+
+impl Counter {
+    #[pure]
+    fn model(&self) -> CounterModel {
+        CounterModel {
+            position: self.position,
+            end: self.end,
+        }
+    }
+
+    #[requires(start <= end)]
+    #[ensures(result.model().position == start)]
+    #[ensures(result.model().end == end)]
+    fn new(start: u32, end: u32) -> Self {
+        Counter { position: start, end }
+    }
+}
+
+#[refine_trait_spec]
+impl Iterator for Counter {
+    type Item = u32;
+
+    #[ensures(self.model().end == old(self.model().end))]
+    #[ensures(old(self.model().position) < old(self.model().end) ==>
+        self.model().position == old(self.model().position) + 1 &&
+        match result {
+            Some(value) => value == old(self.model().position),
+            None => false,
+        }
+    )]
+    #[ensures(old(self.model().position) >= old(self.model().end) ==>
+        self.model().position == old(self.model().position) && matches!(result, None)
+    )]
+    fn next(&mut self) -> Option<u32> {
+        if self.position < self.end {
+            let value = self.position;
+            self.position += 1;
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+fn client() {
+    let mut counter = Counter::new(3, 5);
+    let first = counter.next();
+    assert!(matches!(first, Some(3)));
+    let second = counter.next();
+    assert!(matches!(second, Some(4)));
+    let third = counter.next();
+    assert!(matches!(third, None));
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+// compile-flags: -Pexplain_errors=true
+
+use prusti_contracts::*;
+
+#[ensures(result > x)]
+fn identity(x: u32) -> u32 { x }
+
+fn main() {}
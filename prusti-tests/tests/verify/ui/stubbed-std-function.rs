@@ -0,0 +1,11 @@
+// compile-flags: -Pstub_unencodable_std_functions=true
+
+// Iterators of the standard library are not supported yet, so calls of their
+// `next` method are stubbed out.
+
+fn first(n: u32) -> Option<u32> {
+    let mut range = 0..n;
+    range.next()
+}
+
+fn main() {}
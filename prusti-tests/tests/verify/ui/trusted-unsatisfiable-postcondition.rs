@@ -0,0 +1,29 @@
+// compile-flags: -Pcheck_trusted_postconditions=true
+
+use prusti_contracts::*;
+
+#[trusted]
+#[requires(x > 0)]
+#[ensures(result > x && result < x)]
+fn contradictory(x: i32) -> i32 {
+    unimplemented!()
+}
+
+#[trusted]
+#[requires(x > 0)]
+#[ensures(result > x)]
+fn consistent(x: i32) -> i32 {
+    unimplemented!()
+}
+
+fn test_contradictory() {
+    let r = contradictory(1);
+    assert!(r == 0);
+}
+
+fn test_consistent() {
+    let r = consistent(1);
+    assert!(r > 1);
+}
+
+fn main() {}
@@ -0,0 +1,56 @@
+// compile-flags: -Preport_uncovered_result_variants=true
+
+use prusti_contracts::*;
+
+pub enum Ordering {
+    Less,
+    Equal,
+    Greater,
+}
+
+#[ensures(matches!(result, Ordering::Less) ==> a < b)]
+#[ensures(matches!(result, Ordering::Greater) ==> a > b)]
+fn compare(a: i32, b: i32) -> Ordering {
+    if a < b {
+        Ordering::Less
+    } else if a > b {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+#[ensures(matches!(result, Ordering::Less) ==> a < b)]
+fn compare_less(a: i32, b: i32) -> Ordering {
+    if a < b {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+#[ensures(matches!(result, Ordering::Less) ==> a < b)]
+#[ensures(matches!(result, Ordering::Equal | Ordering::Greater) ==> a >= b)]
+fn compare_covered(a: i32, b: i32) -> Ordering {
+    if a < b {
+        Ordering::Less
+    } else if a > b {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+#[ensures(match result {
+    Ordering::Less => a < b,
+    _ => a >= b,
+})]
+fn compare_wildcard(a: i32, b: i32) -> Ordering {
+    if a < b {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+// compile-flags: -Preport_unused_preconditions=true
+
+use prusti_contracts::*;
+
+#[requires(x > 0)]
+#[requires(y == 42)]
+#[ensures(result > 0)]
+fn first(x: i32, y: i32) -> i32 {
+    x
+}
+
+#[requires(x > 0)]
+#[ensures(result > 0)]
+fn all_needed(x: i32) -> i32 {
+    x
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+// compile-flags: -Punsafe_core_proof=true -Pcheck_panics=false
+
+use prusti_contracts::*;
+
+#[no_mangle]
+#[requires(start == end ==> len == 0)]
+#[ensures(result == len)]
+pub extern "C" fn range_len(start: *const u8, end: *const u8, len: usize) -> usize {
+    if start == end {
+        0
+    } else {
+        len
+    }
+}
+
+// A panic must not unwind out of an `extern "C"` function, so its absence is
+// checked even though `check_panics` is disabled.
+#[no_mangle]
+pub extern "C" fn unchecked_div(dividend: u32, divisor: u32) -> u32 {
+    dividend / divisor      //~ ERROR: assertion might fail with "attempt to divide by zero"
+}
+
+/// The first byte of the range `[start, end)`. The precondition only states
+/// that the range is not empty: it does not give access to the memory of the
+/// range, so the pointer cannot be dereferenced.
+#[no_mangle]
+#[requires(start != end)]
+pub extern "C" fn first_byte(start: *const u8, end: *const u8) -> u8 {
+    unsafe { *start }   //~ ERROR: dereferencing a raw pointer is not supported
+}
+
+fn test1() {
+    let a = 4u8;
+    let start = std::ptr::addr_of!(a);
+    range_len(start, start, 1);     //~ ERROR: precondition might not hold
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+
+#[requires(divisor != 0)]
+unsafe fn divide(dividend: u32, divisor: u32) -> u32 {
+    dividend / divisor
+}
+
+fn test1() {
+    let quotient = unsafe { divide(6, 3) };
+}
+
+fn test2(divisor: u32) {
+    let quotient = unsafe { divide(6, divisor) };   //~ ERROR: the safety precondition of the unsafe function might not hold.
+}
+
+#[requires(divisor > 0)]
+fn test3(divisor: u32) {
+    let quotient = unsafe { divide(6, divisor) };
+}
+
+fn main() {}
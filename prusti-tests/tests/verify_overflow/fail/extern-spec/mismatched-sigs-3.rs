@@ -0,0 +1,17 @@
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+#[extern_spec]
+impl<T> std::vec::Vec<T> {
+    /// Wrong return type
+    #[pure]
+    fn len(&self) -> u32; //~ ERROR Invalid external specification
+}
+
+#[extern_spec]
+impl<T> std::vec::Vec<T> {
+    /// Wrong argument type, which is coerced in the call of the method
+    fn is_empty(&mut self) -> bool; //~ ERROR Invalid external specification
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#![feature(unchecked_math)]
+
+use prusti_contracts::*;
+
+fn unbounded_sum(x: u8, y: u8) -> u8 {
+    unsafe { x.unchecked_add(y) } //~ ERROR the operation `unchecked_add` might overflow
+}
+
+#[requires(x > 0)]
+fn difference(x: u32, y: u32) -> u32 {
+    unsafe { x.unchecked_sub(y) } //~ ERROR the operation `unchecked_sub` might overflow
+}
+
+#[requires(x <= 1000)]
+#[ensures(result == x * x)]
+fn square(x: i32) -> i32 {
+    unsafe { x.unchecked_mul(x) } //~ ERROR the operation `unchecked_mul` might overflow
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+// compile-flags: -Punsafe_core_proof=true
+
+use prusti_contracts::*;
+
+/// The number of bytes of the range `[start, end)`, whose length `len` is
+/// passed along by the C caller.
+#[no_mangle]
+#[requires(start == end ==> len == 0)]
+#[ensures(result == len)]
+pub extern "C" fn range_len(start: *const u8, end: *const u8, len: usize) -> usize {
+    if start == end {
+        0
+    } else {
+        len
+    }
+}
+
+#[no_mangle]
+#[requires(divisor != 0)]
+#[ensures(result == dividend / divisor)]
+pub extern "C" fn checked_div(dividend: u32, divisor: u32) -> u32 {
+    dividend / divisor
+}
+
+fn test1() {
+    let a = 4u8;
+    let start = std::ptr::addr_of!(a);
+    assert!(range_len(start, start, 0) == 0);
+}
+
+fn test2() {
+    assert!(checked_div(6, 3) == 2);
+}
+
+fn main() {}
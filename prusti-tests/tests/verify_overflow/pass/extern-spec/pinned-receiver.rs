@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+use std::pin::Pin;
+
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    fn count(&self) -> u32 {
+        self.count
+    }
+
+    fn bump(self: Pin<&mut Self>) {
+        self.get_mut().count += 1;
+    }
+}
+
+#[extern_spec]
+impl Counter {
+    #[pure]
+    fn count(&self) -> u32;
+
+    #[requires(self.count() < 100)]
+    #[ensures(self.count() == old(self.count()) + 1)]
+    fn bump(self: Pin<&mut Self>);
+}
+
+#[requires(counter.count() < 100)]
+#[ensures(counter.count() == old(counter.count()) + 1)]
+fn client(counter: Pin<&mut Counter>) {
+    counter.bump();
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+#![feature(unchecked_math)]
+
+use prusti_contracts::*;
+
+#[requires(x < 100 && y < 100)]
+#[ensures(result == x + y)]
+fn sum(x: u8, y: u8) -> u8 {
+    unsafe { x.unchecked_add(y) }
+}
+
+#[requires(x >= y)]
+#[ensures(result == x - y)]
+fn difference(x: u32, y: u32) -> u32 {
+    unsafe { x.unchecked_sub(y) }
+}
+
+#[requires(-100 <= x && x <= 100)]
+#[ensures(result == x * x)]
+fn square(x: i32) -> i32 {
+    unsafe { x.unchecked_mul(x) }
+}
+
+fn main() {}
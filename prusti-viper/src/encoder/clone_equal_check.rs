@@ -0,0 +1,99 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Encoding of the check of the `#[prusti::clone_equal]` attribute of a type:
+//! the `clone` method of its `Clone` implementation returns a value that is
+//! snapshot-equal to `self`. The body of `clone` is treated as a pure
+//! function, so the check is `snap_eq(x.clone(), x)` for all `x`, with the
+//! body of `clone` inlined.
+
+use super::{
+    errors::{ErrorCtxt, SpannedEncodingError, SpannedEncodingResult, WithSpan},
+    mir::{pure::PureFunctionEncoderInterface, specifications::SpecificationsInterface},
+    mir_encoder::{MirEncoder, PlaceEncoder},
+    snapshot::interface::SnapshotEncoderInterface,
+    Encoder,
+};
+use prusti_interface::{data::ProcedureDefId, utils::has_prusti_attr};
+use rustc_middle::{mir, ty};
+use rustc_span::sym;
+use vir_crate::polymorphic as vir;
+
+/// Encode the check of the `#[prusti::clone_equal]` attribute of the type
+/// that implements `Clone` with the method `def_id`. Returns `None` if
+/// `def_id` is not the `clone` method of a `Clone` implementation for a type
+/// with this attribute.
+pub(super) fn encode_clone_equal_check<'v, 'tcx: 'v>(
+    encoder: &Encoder<'v, 'tcx>,
+    def_id: ProcedureDefId,
+) -> SpannedEncodingResult<Option<vir::Program>> {
+    let tcx = encoder.env().tcx();
+    let impl_def_id = match tcx.impl_of_method(def_id) {
+        Some(impl_def_id) => impl_def_id,
+        None => return Ok(None),
+    };
+    if tcx.trait_id_of_impl(impl_def_id) != tcx.lang_items().clone_trait()
+        || tcx.item_name(def_id) != sym::clone
+    {
+        return Ok(None);
+    }
+    let self_ty = tcx.type_of(impl_def_id);
+    let adt_def = match self_ty.kind() {
+        ty::TyKind::Adt(adt_def, _) if has_prusti_attr(tcx.get_attrs(adt_def.did()), "clone_equal") => {
+            *adt_def
+        }
+        _ => return Ok(None),
+    };
+    let span = encoder.env().get_def_span(def_id);
+    let type_name = tcx.def_path_str(adt_def.did());
+    if encoder.is_trusted(def_id, None) {
+        return Err(SpannedEncodingError::incorrect(
+            format!("`clone_equal` cannot check the trusted `clone` method of `{}`", type_name),
+            span,
+        ));
+    }
+
+    let substs = encoder.env().identity_substs(def_id);
+    let mir = encoder.env().local_mir(def_id.expect_local(), substs);
+    if mir.is_cfg_cyclic() {
+        return Err(SpannedEncodingError::unsupported(
+            "`clone_equal` only supports `clone` methods without loops",
+            span,
+        ));
+    }
+    let mir_encoder = MirEncoder::new_pure(encoder, &mir, def_id);
+    let self_local = vir::Expr::local(
+        mir_encoder.encode_local(mir::Local::from_usize(1))?,
+    );
+
+    // The value `x` to clone. References are transparent in snapshots, so
+    // `x` also stands for the `&self` argument of `clone`.
+    let snapshot_type = encoder.encode_snapshot_type(self_ty).with_span(span)?;
+    let x = vir::LocalVar::new("x", snapshot_type);
+    let clone = encoder
+        .encode_pure_expression(def_id, def_id, substs)?
+        .replace_place(&self_local, &vir::Expr::local(x.clone()));
+
+    let name = format!("{}$clone_equal_check", encoder.encode_item_name(def_id));
+    let mut method = vir::CfgMethod::new(name.clone(), 0, vec![], vec![], vec![]);
+    method.add_local_var(&x.name, x.typ.clone());
+    let pos = encoder.error_manager().register_error(
+        span,
+        ErrorCtxt::AssertCloneEqual(type_name),
+        def_id,
+    );
+    let expr = vir::Expr::eq_cmp(clone, vir::Expr::local(x));
+    let stmts = vec![
+        vir::Stmt::comment("Check that the clone of a value is snapshot-equal to the value"),
+        vir::Stmt::Assert(vir::Assert {
+            expr: encoder.patch_snapshots(expr).with_span(span)?,
+            position: pos,
+        }),
+    ];
+    let block = method.add_block("start", stmts);
+    method.set_successor(block, vir::Successor::Return);
+    super::definition_collector::collect_definitions(span, encoder, name, vec![method]).map(Some)
+}
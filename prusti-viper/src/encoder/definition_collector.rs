@@ -210,7 +210,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> Collector<'p, 'v, 'tcx> {
                     function.body = None;
                 }
             }
-            if self.method_names.contains(&function.name) {
+            // A pure function that mentions itself in its postcondition is
+            // used as a function by its own verification method. This is fine
+            // as long as the Viper identifiers do not clash.
+            if self.method_names.contains(&function.get_identifier()) {
                 return Err(SpannedEncodingError::internal(
                     format!(
                         "Rust function {} encoded both as a Viper method and function",
@@ -7,7 +7,10 @@
 use ::log::{info, debug, trace};
 use crate::encoder::builtin_encoder::BuiltinEncoder;
 use crate::encoder::builtin_encoder::BuiltinMethodKind;
-use crate::encoder::errors::{ErrorManager, SpannedEncodingError, EncodingError};
+use crate::encoder::errors::{
+    ErrorManager, ErrorCtxt, SpannedEncodingError, EncodingError, EncodingErrorKind, WithSpan,
+    UnsupportedFeatureCategory, MultiSpan,
+};
 use crate::encoder::foldunfold;
 use crate::encoder::procedure_encoder::ProcedureEncoder;
 use crate::encoder::SpecFunctionKind;
@@ -19,15 +22,20 @@ use prusti_interface::data::ProcedureDefId;
 use prusti_interface::environment::Environment;
 use prusti_interface::specs::typed;
 use prusti_interface::PrustiError;
+use prusti_interface::utils::{read_prusti_attr, read_prusti_attr_path};
+use prusti_common::report::call_graph::CallGraph;
 use vir_crate::polymorphic::{self as vir};
 use vir_crate::common::identifier::WithIdentifier;
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
-use rustc_middle::ty;
+use rustc_middle::ty::{self, TypeFoldable};
+use rustc_span::{Span, DUMMY_SP};
 use std::cell::{Cell, RefCell, RefMut, Ref};
 use rustc_hash::FxHashMap;
 use std::io::Write;
 use std::rc::Rc;
+use std::str::FromStr;
+use viper::VerificationBackend;
 use crate::encoder::stub_procedure_encoder::StubProcedureEncoder;
 use std::ops::AddAssign;
 use prusti_interface::specs::typed::ProcedureSpecificationKind;
@@ -47,7 +55,7 @@ use super::mir::{
     procedures::MirProcedureEncoderState,
     type_layouts::MirTypeLayoutsEncoderState,
     pure::{
-        PureFunctionEncoderState, PureFunctionEncoderInterface,
+        PureFunctionEncoderState, PureFunctionEncoderInterface, SpecificationEncoderInterface,
     },
     types::{
         compute_discriminant_bounds,
@@ -58,6 +66,7 @@ use super::mir::{
     }
 };
 use super::high::types::{HighTypeEncoderState, HighTypeEncoderInterface};
+use super::high::generics::HighGenericsEncoderInterface;
 
 pub struct Encoder<'v, 'tcx: 'v> {
     env: &'v Environment<'tcx>,
@@ -68,6 +77,25 @@ pub struct Encoder<'v, 'tcx: 'v> {
     pub(super) high_builtin_function_encoder_state: HighBuiltinFunctionEncoderState,
     procedures: RefCell<FxHashMap<ProcedureDefId, vir::CfgMethod>>,
     programs: Vec<vir::Program>,
+    /// The procedure that generated each program: program name → procedure.
+    program_procedures: FxHashMap<String, ProcedureDefId>,
+    /// The verification backends selected with `#[prusti::backend(...)]`: program name → backend.
+    program_backends: FxHashMap<String, VerificationBackend>,
+    /// The spans of the preconditions that are inhaled separately when
+    /// `REPORT_UNUSED_PRECONDITIONS` is enabled: procedure → spans.
+    precondition_spans: RefCell<FxHashMap<ProcedureDefId, Vec<Span>>>,
+    /// The same spans indexed by program name.
+    program_precondition_spans: FxHashMap<String, Vec<Span>>,
+    /// The quantifier instantiation limits selected with
+    /// `#[prusti::loop_options(qi_limit = ...)]`: procedure → limit.
+    loop_qi_limits: RefCell<FxHashMap<ProcedureDefId, u64>>,
+    /// The same limits indexed by program name.
+    program_qi_limits: FxHashMap<String, u64>,
+    /// The calls encoded when `DUMP_CALL_GRAPH` is enabled.
+    call_graph: RefCell<CallGraph>,
+    /// Programs that check whether the postcondition of a trusted function is
+    /// satisfiable, with the span of the function.
+    trusted_consistency_checks: Vec<(vir::Program, Span)>,
     pub(super) mir_sequences_encoder_state: MirSequencesEncoderState<'tcx>,
     pub(super) contracts_encoder_state: ContractsEncoderState<'tcx>,
     pub(super) mir_procedure_encoder_state: MirProcedureEncoderState,
@@ -80,12 +108,28 @@ pub struct Encoder<'v, 'tcx: 'v> {
     spec_functions: RefCell<FxHashMap<ProcedureDefId, Vec<vir::FunctionIdentifier>>>,
     type_discriminant_funcs: RefCell<FxHashMap<String, vir::FunctionIdentifier>>,
     type_cast_functions: RefCell<FxHashMap<(ty::Ty<'tcx>, ty::Ty<'tcx>), vir::FunctionIdentifier>>,
+    type_default_functions: RefCell<FxHashMap<String, vir::FunctionIdentifier>>,
+    /// The names of the functions that encode `count`, by their parameters and
+    /// the counted condition.
+    count_functions: RefCell<FxHashMap<String, String>>,
     pub(super) snapshot_encoder_state: SnapshotEncoderState,
     pub(super) mirror_encoder: RefCell<MirrorEncoder>,
     encoding_queue: RefCell<Vec<EncodingTask<'tcx>>>,
+    /// The substitutions with concrete const arguments with which the
+    /// procedures with const generic parameters are called, in the order in
+    /// which the calls were encoded.
+    procedure_instances: RefCell<Vec<(ProcedureDefId, ty::subst::SubstsRef<'tcx>)>>,
     vir_program_before_foldunfold_writer: Option<RefCell<Box<dyn Write>>>,
     vir_program_before_viper_writer: Option<RefCell<Box<dyn Write>>>,
     encoding_errors_counter: RefCell<usize>,
+    /// The usages of unsupported features, collected when
+    /// `report_unsupported_features` is enabled.
+    unsupported_features: RefCell<Vec<(UnsupportedFeatureCategory, MultiSpan, String)>>,
+    /// The procedure whose encoding is in progress.
+    current_procedure: Cell<Option<ProcedureDefId>>,
+    /// The messages and spans of the encoding errors of each procedure,
+    /// collected when `junit_report` is set.
+    procedure_encoding_errors: RefCell<FxHashMap<ProcedureDefId, Vec<(String, MultiSpan)>>>,
     name_interner: RefCell<NameInterner>,
     /// Maps locals to the local of their discriminant.
     discriminants_info: RefCell<FxHashMap<(ProcedureDefId, String), Vec<String>>>,
@@ -138,6 +182,14 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             builtin_methods: RefCell::new(FxHashMap::default()),
             high_builtin_function_encoder_state: Default::default(),
             programs: Vec::new(),
+            program_procedures: FxHashMap::default(),
+            program_backends: FxHashMap::default(),
+            precondition_spans: RefCell::new(FxHashMap::default()),
+            program_precondition_spans: FxHashMap::default(),
+            loop_qi_limits: RefCell::new(FxHashMap::default()),
+            program_qi_limits: FxHashMap::default(),
+            call_graph: RefCell::new(CallGraph::default()),
+            trusted_consistency_checks: Vec::new(),
             mir_sequences_encoder_state: Default::default(),
             mir_procedure_encoder_state: Default::default(),
             mir_type_layouts_encoder_state: Default::default(),
@@ -150,12 +202,18 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             spec_functions: RefCell::new(FxHashMap::default()),
             type_discriminant_funcs: RefCell::new(FxHashMap::default()),
             type_cast_functions: RefCell::new(FxHashMap::default()),
+            type_default_functions: RefCell::new(FxHashMap::default()),
+            count_functions: RefCell::new(FxHashMap::default()),
             encoding_queue: RefCell::new(vec![]),
+            procedure_instances: RefCell::new(vec![]),
             vir_program_before_foldunfold_writer,
             vir_program_before_viper_writer,
             snapshot_encoder_state: Default::default(),
             mirror_encoder: RefCell::new(MirrorEncoder::new()),
             encoding_errors_counter: RefCell::new(0),
+            unsupported_features: RefCell::new(vec![]),
+            current_procedure: Cell::new(None),
+            procedure_encoding_errors: RefCell::new(FxHashMap::default()),
             name_interner: RefCell::new(NameInterner::new()),
             discriminants_info: RefCell::new(FxHashMap::default()),
             is_encoding_trigger: Cell::new(false),
@@ -213,19 +271,110 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         super::definition_collector::collect_definitions(error_span, self, name, self.get_used_viper_methods())
     }
 
+    /// Combine the methods of the given programs into a single program that
+    /// contains the definitions used by any of them.
+    pub fn merge_viper_programs(
+        &self,
+        name: String,
+        programs: Vec<vir::Program>,
+    ) -> SpannedEncodingResult<vir::Program> {
+        let methods = programs.into_iter().flat_map(|program| program.methods).collect();
+        super::definition_collector::collect_definitions(DUMMY_SP, self, name, methods)
+    }
+
     pub fn get_viper_programs(&mut self) -> Vec<vir::Program> {
         std::mem::take(&mut self.programs)
     }
 
+    /// The procedure that generated the given program.
+    pub fn get_program_procedure(&self, program_name: &str) -> Option<ProcedureDefId> {
+        self.program_procedures.get(program_name).copied()
+    }
+
+    /// The verification backend selected for the given program, if the
+    /// procedure that generated it is annotated with `#[prusti::backend(...)]`.
+    pub fn get_program_backend(&self, program_name: &str) -> Option<VerificationBackend> {
+        self.program_backends.get(program_name).copied()
+    }
+
+    /// The spans of the preconditions of the procedure that generated the
+    /// given program, in the order of their marker comments.
+    pub fn get_program_precondition_spans(&self, program_name: &str) -> Option<&[Span]> {
+        self.program_precondition_spans.get(program_name).map(Vec::as_slice)
+    }
+
+    /// The quantifier instantiation limit selected for the loops of the
+    /// procedure that generated the given program, if any.
+    pub fn get_program_qi_limit(&self, program_name: &str) -> Option<u64> {
+        self.program_qi_limits.get(program_name).copied()
+    }
+
+    pub fn get_trusted_consistency_checks(&mut self) -> Vec<(vir::Program, Span)> {
+        std::mem::take(&mut self.trusted_consistency_checks)
+    }
+
+    pub(super) fn register_precondition_spans(&self, proc_def_id: ProcedureDefId, spans: Vec<Span>) {
+        self.precondition_spans.borrow_mut().insert(proc_def_id, spans);
+    }
+
+    /// Record a call of `callee` in `caller`, encoded with the given
+    /// specification items.
+    pub(super) fn register_call(&self, caller: String, callee: String, specs: Vec<String>) {
+        self.call_graph.borrow_mut().add_call(caller, callee, specs);
+    }
+
+    pub fn get_call_graph(&self) -> Ref<CallGraph> {
+        self.call_graph.borrow()
+    }
+
+    /// Register the quantifier instantiation limit of a loop of the procedure.
+    /// The procedure is verified with the largest limit of its loops.
+    pub(super) fn register_loop_qi_limit(&self, proc_def_id: ProcedureDefId, qi_limit: u64) {
+        let mut loop_qi_limits = self.loop_qi_limits.borrow_mut();
+        let limit = loop_qi_limits.entry(proc_def_id).or_insert(qi_limit);
+        *limit = (*limit).max(qi_limit);
+    }
+
+    /// Read the verification backend selected with `#[prusti::backend(...)]`.
+    fn get_procedure_backend(&self, proc_def_id: ProcedureDefId) -> SpannedEncodingResult<Option<VerificationBackend>> {
+        let attrs = self.env().tcx().get_attrs(proc_def_id);
+        if let Some(backend) = read_prusti_attr("backend", attrs) {
+            VerificationBackend::from_str(&backend).map(Some).map_err(|error| {
+                SpannedEncodingError::incorrect(
+                    error.to_string(),
+                    self.env().get_def_span(proc_def_id),
+                )
+            })
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn get_core_proof_programs(&mut self) -> Vec<prusti_common::vir::program::Program> {
         self.take_core_proof_programs().into_iter().map(prusti_common::vir::program::Program::Low).collect()
     }
 
     pub(in crate::encoder) fn register_encoding_error(&self, encoding_error: SpannedEncodingError) {
         debug!("Encoding error: {:?}", encoding_error);
+        if config::report_unsupported_features() {
+            if let EncodingErrorKind::Unsupported(message, category) = encoding_error.kind() {
+                self.unsupported_features.borrow_mut().push((
+                    *category,
+                    encoding_error.span().clone(),
+                    message.clone(),
+                ));
+            }
+        }
         let prusti_error: PrustiError = encoding_error.into();
         if prusti_error.is_error() {
             self.encoding_errors_counter.borrow_mut().add_assign(1);
+            if let (Some(proc_def_id), Some(_)) = (self.current_procedure.get(), config::junit_report()) {
+                self.procedure_encoding_errors
+                    .borrow_mut()
+                    .entry(proc_def_id)
+                    .or_default()
+                    .push((prusti_error.message().to_string(), prusti_error.span().clone()));
+            }
         }
         prusti_error.emit(self.env);
     }
@@ -234,6 +383,34 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         *self.encoding_errors_counter.borrow()
     }
 
+    /// The reason why the procedure is not verified, if it is not.
+    pub fn get_unverified_reason(&self, proc_def_id: ProcedureDefId) -> Option<&'static str> {
+        if self.is_trusted(proc_def_id, None) {
+            Some("trusted")
+        } else if matches!(self.get_proc_kind(proc_def_id, None), ProcedureSpecificationKind::Predicate(_)) {
+            Some("predicate")
+        } else {
+            None
+        }
+    }
+
+    /// The messages and spans of the encoding errors of the procedure.
+    pub fn get_procedure_encoding_errors(&self, proc_def_id: ProcedureDefId) -> Vec<(String, MultiSpan)> {
+        self.procedure_encoding_errors
+            .borrow()
+            .get(&proc_def_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The usages of unsupported features reported while encoding, sorted by
+    /// category.
+    pub fn get_unsupported_features(&self) -> Vec<(UnsupportedFeatureCategory, MultiSpan, String)> {
+        let mut features = self.unsupported_features.borrow().clone();
+        features.sort_by_key(|(category, _, _)| *category);
+        features
+    }
+
     pub(super) fn get_mirror_domain(&self) -> Option<vir::Domain> {
         self.mirror_encoder.borrow().get_domain().cloned()
     }
@@ -266,6 +443,16 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         self.procedures.borrow_mut().drain().map(|(_, value)| value).collect()
     }
 
+    /// The length in bytes of a string literal.
+    pub fn const_eval_str_len(&self, value: ty::ConstKind<'tcx>) -> Option<usize> {
+        match value {
+            ty::ConstKind::Value(mir::interpret::ConstValue::Slice { start, end, .. }) => {
+                Some(end - start)
+            }
+            _ => None,
+        }
+    }
+
     /// Extract scalar value, invoking const evaluation if necessary.
     pub fn const_eval_intlike(
         &self,
@@ -282,6 +469,13 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                     .ok()
                     .and_then(|const_value| const_value.try_to_scalar())
             }
+            ty::ConstKind::Param(param) => {
+                return Err(EncodingError::unsupported(format!(
+                    "the value of the const generic parameter `{}` is only known in the \
+                    instances of the procedure that are called with concrete arguments",
+                    param,
+                )));
+            }
             _ => unimplemented!("{:?}", value),
         };
 
@@ -429,6 +623,69 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         Ok(function_name)
     }
 
+    /// Encode the use of the function that counts the integers `qvar` in a
+    /// range `[lower, upper)` for which `condition` holds. The function takes
+    /// the given parameters, which stand for the free subexpressions of
+    /// `condition`, followed by the bounds of the range.
+    pub fn encode_count_function_use(
+        &self,
+        parameters: Vec<vir::LocalVar>,
+        qvar: vir::LocalVar,
+        condition: vir::Expr,
+    ) -> EncodingResult<String> {
+        trace!("encode_count_function_use(condition={})", condition);
+        // Use the same counted variable for all conditions, so that equal
+        // conditions share their function.
+        let index = vir_local!{ index: Int };
+        let condition = condition.replace_place(&qvar.into(), &index.clone().into());
+        let lower = vir_local!{ lower: Int };
+        let upper = vir_local!{ upper: Int };
+        let mut formal_args = parameters;
+        formal_args.push(lower.clone());
+        formal_args.push(upper.clone());
+        let key = format!("{:?} {}", formal_args, condition);
+        if let Some(function_name) = self.count_functions.borrow().get(&key) {
+            return Ok(function_name.clone());
+        }
+        let function_name = format!("builtin$count${}", self.count_functions.borrow().len());
+        // count(lower, upper) = count(lower, upper - 1) + (condition(upper - 1) ? 1 : 0)
+        let last = vir::Expr::sub(upper.clone().into(), 1.into());
+        let mut recursive_args: Vec<vir::Expr> = formal_args.iter().cloned().map(vir::Expr::local).collect();
+        *recursive_args.last_mut().unwrap() = last.clone();
+        let recursive_call = vir::Expr::func_app(
+            function_name.clone(),
+            vec![],
+            recursive_args,
+            formal_args.clone(),
+            vir::Type::Int,
+            vir::Position::default(),
+        );
+        let last_counted = vir::Expr::ite(
+            condition.replace_place(&index.into(), &last),
+            1.into(),
+            0.into(),
+        );
+        let body = vir::Expr::ite(
+            vir::Expr::le_cmp(upper.into(), lower.into()),
+            0.into(),
+            vir::Expr::add(recursive_call, last_counted),
+        );
+        let result = vir_local!{ __result: Int };
+        let function = vir::Function {
+            name: function_name.clone(),
+            type_arguments: vec![],
+            formal_args,
+            return_type: vir::Type::Int,
+            pres: vec![],
+            posts: vec![vir::Expr::le_cmp(0.into(), result.into())],
+            body: Some(body),
+        };
+        let function = self.patch_snapshots_function(function)?;
+        self.insert_function(function);
+        self.count_functions.borrow_mut().insert(key, function_name.clone());
+        Ok(function_name)
+    }
+
     pub fn encode_unsize_function_use(&self, src_ty: ty::Ty<'tcx>, dst_ty: ty::Ty<'tcx>)
         -> EncodingResult<String>
     {
@@ -478,6 +735,41 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         Ok(function_name)
     }
 
+    /// Encode the snapshot of `<ty as Default>::default()`. The default value
+    /// of primitive types is known; for all other types it is the result of
+    /// an abstract function, which is the same value for every use.
+    pub fn encode_default_value(&self, ty: ty::Ty<'tcx>) -> EncodingResult<vir::Expr> {
+        trace!("encode_default_value(ty={:?})", ty);
+        match ty.kind() {
+            ty::TyKind::Bool => return Ok(false.into()),
+            ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => return Ok(0.into()),
+            _ => {}
+        }
+        let return_type = self.encode_snapshot_type(ty)?;
+        let function_name = format!("builtin$default${}", return_type.name());
+        if !self.type_default_functions.borrow().contains_key(&function_name) {
+            let function = vir::Function {
+                name: function_name.clone(),
+                type_arguments: Vec::new(),
+                formal_args: vec![],
+                return_type: return_type.clone(),
+                pres: vec![],
+                posts: vec![],
+                body: None,
+            };
+            let identifier = self.insert_function(function);
+            self.type_default_functions.borrow_mut().insert(function_name.clone(), identifier);
+        }
+        Ok(vir::Expr::func_app(
+            function_name,
+            vec![],
+            vec![],
+            vec![],
+            return_type,
+            vir::Position::default(),
+        ))
+    }
+
     /// This encodes the Rust function as a Viper method for verification. It
     /// does this also for pure functions.
     pub fn encode_procedure(&self, def_id: ProcedureDefId) -> SpannedEncodingResult<()> {
@@ -651,6 +943,26 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         self.intern_viper_identifier(full_name, short_name)
     }
 
+    /// The name of the method of an instance of a generic procedure. The
+    /// instance with the identity substitutions is the procedure itself.
+    pub fn encode_item_instance_name(&self, def_id: DefId, substs: ty::subst::SubstsRef<'tcx>) -> String {
+        if substs == self.env.identity_substs(def_id) {
+            return self.encode_item_name(def_id);
+        }
+        let instance = encode_identifier(format!("{:?}", substs));
+        let full_name = format!(
+            "m_{}$inst${}",
+            encode_identifier(self.env.get_unique_item_name(def_id)),
+            instance,
+        );
+        let short_name = format!(
+            "m_{}$inst${}",
+            encode_identifier(self.env.get_item_name(def_id)),
+            instance,
+        );
+        self.intern_viper_identifier(full_name, short_name)
+    }
+
     pub fn encode_invariant_func_app(
         &self,
         ty: ty::Ty<'tcx>,
@@ -688,6 +1000,34 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         self.env.get_item_name(proc_def_id)
     }
 
+    /// Whether the procedure or its parent has const generic parameters.
+    fn has_const_generic_params(&self, def_id: ProcedureDefId) -> bool {
+        let tcx = self.env.tcx();
+        let mut generics = Some(tcx.generics_of(def_id));
+        while let Some(current) = generics {
+            if current.params.iter().any(|param| matches!(param.kind, ty::GenericParamDefKind::Const { .. })) {
+                return true;
+            }
+            generics = current.parent.map(|parent| tcx.generics_of(parent));
+        }
+        false
+    }
+
+    /// Record a call of a local procedure with the given substitutions, so that
+    /// a procedure with const generic parameters is verified for each of the
+    /// concrete arguments it is called with. Substitutions that still mention
+    /// generic parameters of the caller are ignored.
+    pub(super) fn register_procedure_instance(&self, def_id: ProcedureDefId, substs: ty::subst::SubstsRef<'tcx>) {
+        if !def_id.is_local() || substs.needs_subst() || !self.has_const_generic_params(def_id) {
+            return;
+        }
+        let substs = self.env.tcx().erase_regions(substs);
+        let mut instances = self.procedure_instances.borrow_mut();
+        if !instances.contains(&(def_id, substs)) {
+            instances.push((def_id, substs));
+        }
+    }
+
     pub fn queue_procedure_encoding(&self, proc_def_id: ProcedureDefId) {
         self.encoding_queue
             .borrow_mut()
@@ -696,8 +1036,10 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
 
     pub fn process_encoding_queue(&mut self) {
         self.initialize();
+        let mut const_generic_procedures = vec![];
         while !self.encoding_queue.borrow().is_empty() {
             let (proc_def_id, substs) = self.encoding_queue.borrow_mut().pop().unwrap();
+            self.current_procedure.set(Some(proc_def_id));
 
             let proc_name = self.env.get_unique_item_name(proc_def_id);
             let proc_def_path = self.env.get_item_def_path(proc_def_id);
@@ -730,12 +1072,49 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                 }
             }
 
+            // The properties of the procedure that are checked by separate
+            // programs.
+            let checks: [(
+                &str,
+                fn(&Self, ProcedureDefId) -> SpannedEncodingResult<Option<vir::Program>>,
+            ); 6] = [
+                ("implication check", Self::encode_implication_check),
+                ("opaque spec check", Self::encode_opaque_spec_check),
+                ("termination check", super::termination_check::encode_termination_check),
+                ("hash and eq consistency check", super::hash_eq_check::encode_hash_eq_check),
+                ("clone equality check", super::clone_equal_check::encode_clone_equal_check),
+                ("inline const check", super::inline_const_check::encode_inline_const_check),
+            ];
+            for (check_name, encode_check) in checks {
+                match encode_check(self, proc_def_id) {
+                    Ok(Some(program)) => self.programs.push(program),
+                    Ok(None) => {}
+                    Err(error) => {
+                        self.register_encoding_error(error);
+                        debug!("Error encoding {}: {:?}", check_name, proc_def_id);
+                    }
+                }
+            }
+
             match proc_kind {
                 _ if self.is_trusted(proc_def_id, None) => {
                     debug!(
                         "Trusted procedure will not be encoded or verified: {:?}",
                         proc_def_id
                     );
+                    if config::check_trusted_postconditions() {
+                        match self.encode_trusted_consistency_check(proc_def_id) {
+                            Ok(Some(program)) => {
+                                let span = self.env.get_def_span(proc_def_id);
+                                self.trusted_consistency_checks.push((program, span));
+                            }
+                            Ok(None) => {}
+                            Err(error) => {
+                                self.register_encoding_error(error);
+                                debug!("Error encoding consistency check: {:?}", proc_def_id);
+                            }
+                        }
+                    }
                 },
                 ProcedureSpecificationKind::Predicate(_) => {
                     debug!(
@@ -743,25 +1122,412 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                         proc_def_id
                     );
                 },
+                ProcedureSpecificationKind::Impure if self.has_const_generic_params(proc_def_id) => {
+                    debug!(
+                        "Procedure with const generic parameters will be verified generically and per instance: {:?}",
+                        proc_def_id
+                    );
+                    const_generic_procedures.push(proc_def_id);
+                }
                 ProcedureSpecificationKind::Pure |
                 ProcedureSpecificationKind::Impure => {
-                    if let Err(error) = self.encode_procedure(proc_def_id) {
-                        self.register_encoding_error(error);
-                        debug!("Error encoding function: {:?}", proc_def_id);
-                    } else {
-                        match self.finalize_viper_program(proc_name, proc_def_id) {
-                            Ok(program) => self.programs.push(program),
-                            Err(error) => {
-                                self.register_encoding_error(error);
-                                debug!("Error finalizing program: {:?}", proc_def_id);
-                            }
-                        }
-                    }
+                    self.encode_procedure_program(proc_def_id, proc_name);
+                }
+            }
+        }
+        self.encode_procedure_instances(const_generic_procedures);
+        self.current_procedure.set(None);
+    }
+
+    /// Encode the procedure as a Viper program to be verified.
+    fn encode_procedure_program(&mut self, proc_def_id: ProcedureDefId, proc_name: String) {
+        match self.try_encode_procedure_program(proc_def_id, proc_name) {
+            Ok(program) => self.programs.push(program),
+            Err(error) => {
+                self.register_encoding_error(error);
+                debug!("Error encoding function: {:?}", proc_def_id);
+            }
+        }
+    }
+
+    fn try_encode_procedure_program(
+        &mut self,
+        proc_def_id: ProcedureDefId,
+        proc_name: String,
+    ) -> SpannedEncodingResult<vir::Program> {
+        self.encode_procedure(proc_def_id)?;
+        self.register_program_settings(proc_def_id, &proc_name);
+        self.finalize_viper_program(proc_name, proc_def_id)
+    }
+
+    /// Associate the procedure, and the backend, the precondition spans and
+    /// the quantifier instantiation limit recorded for it, with its program.
+    fn register_program_settings(&mut self, proc_def_id: ProcedureDefId, program_name: &str) {
+        self.program_procedures.insert(program_name.to_string(), proc_def_id);
+        match self.get_procedure_backend(proc_def_id) {
+            Ok(Some(backend)) => {
+                self.program_backends.insert(program_name.to_string(), backend);
+            }
+            Ok(None) => {}
+            Err(error) => self.register_encoding_error(error),
+        }
+        if let Some(spans) = self.precondition_spans.borrow_mut().remove(&proc_def_id) {
+            self.program_precondition_spans.insert(program_name.to_string(), spans);
+        }
+        if let Some(qi_limit) = self.loop_qi_limits.borrow_mut().remove(&proc_def_id) {
+            self.program_qi_limits.insert(program_name.to_string(), qi_limit);
+        }
+    }
+
+    /// Verify each procedure with const generic parameters generically and,
+    /// in addition, once for every instance with concrete arguments it is
+    /// called with, in which the lengths of its arrays are known. If the
+    /// procedure uses features that are supported only in its instances, e.g.
+    /// arrays whose length is a const generic parameter, a warning reports
+    /// that the other instances are not verified.
+    fn encode_procedure_instances(&mut self, proc_def_ids: Vec<ProcedureDefId>) {
+        let mut generic_errors = vec![];
+        for &proc_def_id in &proc_def_ids {
+            self.current_procedure.set(Some(proc_def_id));
+            let proc_name = self.env.get_unique_item_name(proc_def_id);
+            match self.try_encode_procedure_program(proc_def_id, proc_name) {
+                Ok(program) => self.programs.push(program),
+                Err(error) => generic_errors.push((proc_def_id, error)),
+            }
+        }
+        let mut instantiated: FxHashMap<ProcedureDefId, Vec<String>> = FxHashMap::default();
+        // Encoding an instance can record further instances.
+        let mut index = 0;
+        loop {
+            let instance = self.procedure_instances.borrow().get(index).copied();
+            let (proc_def_id, substs) = match instance {
+                Some(instance) => instance,
+                None => break,
+            };
+            index += 1;
+            if !proc_def_ids.contains(&proc_def_id) {
+                continue;
+            }
+            instantiated
+                .entry(proc_def_id)
+                .or_default()
+                .push(self.env.tcx().def_path_str_with_substs(proc_def_id, substs));
+            self.current_procedure.set(Some(proc_def_id));
+            info!("Encoding instance: {} with {:?}", self.env.get_unique_item_name(proc_def_id), substs);
+            match self.encode_procedure_instance(proc_def_id, substs) {
+                Ok(program) => {
+                    self.register_program_settings(proc_def_id, &program.name);
+                    self.programs.push(program);
+                }
+                Err(error) => {
+                    self.register_encoding_error(error);
+                    debug!("Error encoding instance {:?} of function: {:?}", substs, proc_def_id);
+                }
+            }
+        }
+        for (proc_def_id, error) in generic_errors {
+            self.current_procedure.set(Some(proc_def_id));
+            match (error.kind(), instantiated.get(&proc_def_id)) {
+                (EncodingErrorKind::Unsupported(message, _), Some(instances)) => {
+                    let mut warning = PrustiError::unsupported(
+                        format!(
+                            "this function is verified only for the instances called in this \
+                            crate: {}",
+                            instances.join(", "),
+                        ),
+                        self.env.get_def_span(proc_def_id).into(),
+                    ).set_help("the contract of the other instances is assumed without being verified");
+                    warning.add_note_mut(
+                        format!("the function cannot be verified generically: {}", message),
+                        Some(error.span().clone()),
+                    );
+                    warning.set_warning();
+                    warning.emit(self.env);
+                }
+                _ => {
+                    self.register_encoding_error(error);
+                    debug!("Error encoding function: {:?}", proc_def_id);
                 }
             }
         }
     }
 
+    /// Encode the instance of a generic procedure with the given
+    /// substitutions as a Viper program named after its method.
+    fn encode_procedure_instance(
+        &self,
+        proc_def_id: ProcedureDefId,
+        substs: ty::subst::SubstsRef<'tcx>,
+    ) -> SpannedEncodingResult<vir::Program> {
+        let procedure = self.env.get_procedure_instance(proc_def_id, substs);
+        let mut method = ProcedureEncoder::new(self, &procedure)?.encode()?;
+        self.log_vir_program_before_viper(method.to_string());
+        if config::enable_purification_optimization() {
+            purifier::purify_method(self, &mut method);
+        }
+        let name = method.name();
+        let error_span = self.env.get_def_span(proc_def_id);
+        super::definition_collector::collect_definitions(error_span, self, name, vec![method])
+    }
+
+    /// Encode a program that verifies iff the postcondition of the trusted
+    /// procedure is unsatisfiable under its precondition. Returns `None` if the
+    /// procedure has no postcondition.
+    fn encode_trusted_consistency_check(
+        &self,
+        def_id: ProcedureDefId,
+    ) -> SpannedEncodingResult<Option<vir::Program>> {
+        let substs = self.env.identity_substs(def_id);
+        let has_postcondition = self.get_procedure_specs(def_id, substs)
+            .and_then(|specs| specs.posts.extract_with_selective_replacement().cloned())
+            .map_or(false, |posts| !posts.is_empty());
+        if !has_postcondition {
+            return Ok(None);
+        }
+        let spec_funcs = self.encode_spec_funcs(def_id)?;
+        let (pre_func, post_func) = match &spec_funcs[..] {
+            [pre, post] => (self.get_function(pre)?, self.get_function(post)?),
+            _ => return Ok(None),
+        };
+        let name = format!("{}$trusted_consistency", self.encode_item_name(def_id));
+        let mut method = vir::CfgMethod::new(name.clone(), 0, vec![], vec![], vec![]);
+        for arg in &post_func.formal_args {
+            method.add_local_var(&arg.name, arg.typ.clone());
+        }
+        let apply = |function: &vir::Function| vir::Expr::func_app(
+            function.name.clone(),
+            function.type_arguments.clone(),
+            function.formal_args.iter().cloned().map(vir::Expr::local).collect(),
+            function.formal_args.clone(),
+            function.return_type.clone(),
+            vir::Position::default(),
+        );
+        let span = self.env.get_def_span(def_id);
+        let pos = self.error_manager().register_error(span, ErrorCtxt::Unexpected, def_id);
+        let block = method.add_block("start", vec![
+            vir::Stmt::comment("Check that the postcondition of the trusted function is satisfiable"),
+            vir::Stmt::Inhale(vir::Inhale {
+                expr: apply(&*pre_func),
+            }),
+            vir::Stmt::Inhale(vir::Inhale {
+                expr: apply(&*post_func),
+            }),
+            vir::Stmt::Assert(vir::Assert {
+                expr: false.into(),
+                position: pos,
+            }),
+        ]);
+        method.set_successor(block, vir::Successor::Return);
+        super::definition_collector::collect_definitions(span, self, name, vec![method]).map(Some)
+    }
+
+    /// Encode the obligation of a `#[prusti::check_implies(p)]` attribute: on
+    /// all arguments that satisfy the precondition of the procedure, every
+    /// result that satisfies its postcondition also satisfies the pure
+    /// predicate `p(args..., result)`. The body of the procedure is not used.
+    /// Returns `None` if the procedure has no such attribute.
+    fn encode_implication_check(
+        &self,
+        def_id: ProcedureDefId,
+    ) -> SpannedEncodingResult<Option<vir::Program>> {
+        let tcx = self.env.tcx();
+        let span = self.env.get_def_span(def_id);
+        let path = match read_prusti_attr_path("check_implies", tcx.get_attrs(def_id)) {
+            Some(path) => path.map_err(|message| SpannedEncodingError::incorrect(message, span))?,
+            None => return Ok(None),
+        };
+        let property_def_id = self.env
+            .resolve_local_function_path(def_id, &path)
+            .ok_or_else(|| SpannedEncodingError::incorrect(
+                format!("cannot find the function `{}` of the `check_implies` attribute", path),
+                span,
+            ))?;
+        let property_substs = self.env.identity_substs(property_def_id);
+        if !self.is_pure(property_def_id, Some(property_substs)) {
+            return Err(SpannedEncodingError::incorrect(
+                format!("the function `{}` of the `check_implies` attribute must be pure", path),
+                span,
+            ));
+        }
+        let sig = tcx.erase_late_bound_regions(tcx.fn_sig(def_id));
+        let property_sig = tcx.erase_late_bound_regions(tcx.fn_sig(property_def_id));
+        let expected_inputs: Vec<_> = sig.inputs().iter().copied()
+            .chain(std::iter::once(sig.output()))
+            .collect();
+        if property_sig.inputs() != expected_inputs.as_slice() || !property_sig.output().is_bool() {
+            return Err(SpannedEncodingError::incorrect(
+                format!("the function `{}` of the `check_implies` attribute must take the \
+                    arguments of the annotated function followed by its result, and return a \
+                    `bool`", path),
+                span,
+            ));
+        }
+        if tcx.is_closure(def_id) || sig.inputs().iter().any(|ty| ty.is_mutable_ptr()) {
+            return Err(SpannedEncodingError::unsupported(
+                "`check_implies` is not supported for closures and for functions that take \
+                mutable references",
+                span,
+            ));
+        }
+
+        let spec_funcs = self.encode_spec_funcs(def_id)?;
+        let (pre_func, post_func) = match &spec_funcs[..] {
+            [pre, post] => (self.get_function(pre)?, self.get_function(post)?),
+            _ => return Err(SpannedEncodingError::incorrect(
+                "`check_implies` requires the annotated function to have a contract",
+                span,
+            )),
+        };
+        let apply = |function: &vir::Function| vir::Expr::func_app(
+            function.name.clone(),
+            function.type_arguments.clone(),
+            function.formal_args.iter().cloned().map(vir::Expr::local).collect(),
+            function.formal_args.clone(),
+            function.return_type.clone(),
+            vir::Position::default(),
+        );
+        let (property_name, property_return_type) = self
+            .encode_pure_function_use(property_def_id, def_id, property_substs)
+            .with_span(span)?;
+        let property_type_arguments = self
+            .encode_generic_arguments(property_def_id, property_substs)
+            .with_span(span)?;
+        // The formal arguments of the postcondition are the arguments of the
+        // procedure followed by its result, as for the property.
+        let property = vir::Expr::func_app(
+            property_name,
+            property_type_arguments,
+            post_func.formal_args.iter().cloned().map(vir::Expr::local).collect(),
+            post_func.formal_args.clone(),
+            property_return_type,
+            vir::Position::default(),
+        );
+
+        let name = format!("{}$implication_check", self.encode_item_name(def_id));
+        let mut method = vir::CfgMethod::new(name.clone(), 0, vec![], vec![], vec![]);
+        for arg in &post_func.formal_args {
+            method.add_local_var(&arg.name, arg.typ.clone());
+        }
+        let pos = self.error_manager().register_error(
+            span,
+            ErrorCtxt::AssertImpliedProperty(path),
+            def_id,
+        );
+        let block = method.add_block("start", vec![
+            vir::Stmt::comment("Check that the postcondition implies the property"),
+            vir::Stmt::Inhale(vir::Inhale {
+                expr: apply(&*pre_func),
+            }),
+            vir::Stmt::Inhale(vir::Inhale {
+                expr: apply(&*post_func),
+            }),
+            vir::Stmt::Assert(vir::Assert {
+                expr: property,
+                position: pos,
+            }),
+        ]);
+        method.set_successor(block, vir::Successor::Return);
+        super::definition_collector::collect_definitions(span, self, name, vec![method]).map(Some)
+    }
+
+    /// Encode the obligation of an `#[opaque_spec(...)]` attribute: on all
+    /// arguments that satisfy the precondition of the procedure, every result
+    /// that satisfies its full postcondition also satisfies the postcondition
+    /// exposed to callers outside of its module. Returns `None` if the
+    /// procedure has no such attribute or exposes no postcondition.
+    fn encode_opaque_spec_check(
+        &self,
+        def_id: ProcedureDefId,
+    ) -> SpannedEncodingResult<Option<vir::Program>> {
+        let tcx = self.env.tcx();
+        let span = self.env.get_def_span(def_id);
+        let substs = self.env.identity_substs(def_id);
+        let opaque_posts = match self.get_procedure_specs(def_id, substs)
+            .and_then(|spec| spec.opaque_posts)
+        {
+            Some(opaque_posts) => opaque_posts,
+            None => return Ok(None),
+        };
+        if self.is_pure(def_id, Some(substs)) {
+            return Err(SpannedEncodingError::incorrect(
+                "`opaque_spec` cannot be used on pure functions",
+                span,
+            ));
+        }
+        if opaque_posts.is_empty() {
+            return Ok(None);
+        }
+        let sig = tcx.erase_late_bound_regions(tcx.fn_sig(def_id));
+        if tcx.is_closure(def_id) || sig.inputs().iter().any(|ty| ty.is_mutable_ptr()) {
+            return Err(SpannedEncodingError::unsupported(
+                "`opaque_spec` is not supported for closures and for functions that take \
+                mutable references",
+                span,
+            ));
+        }
+
+        let spec_funcs = self.encode_spec_funcs(def_id)?;
+        let (pre_func, post_func) = match &spec_funcs[..] {
+            [pre, post] => (self.get_function(pre)?, self.get_function(post)?),
+            _ => return Ok(None),
+        };
+        let apply = |function: &vir::Function| vir::Expr::func_app(
+            function.name.clone(),
+            function.type_arguments.clone(),
+            function.formal_args.iter().cloned().map(vir::Expr::local).collect(),
+            function.formal_args.clone(),
+            function.return_type.clone(),
+            vir::Position::default(),
+        );
+        // The formal arguments of the postcondition are the arguments of the
+        // procedure followed by its result.
+        let (encoded_return, encoded_args) = post_func.formal_args
+            .split_last()
+            .map(|(ret, args)| (
+                vir::Expr::local(ret.clone()),
+                args.iter().cloned().map(vir::Expr::local).collect::<Vec<_>>(),
+            ))
+            .unwrap();
+
+        let name = format!("{}$opaque_spec_check", self.encode_item_name(def_id));
+        let mut method = vir::CfgMethod::new(name.clone(), 0, vec![], vec![], vec![]);
+        for arg in &post_func.formal_args {
+            method.add_local_var(&arg.name, arg.typ.clone());
+        }
+        let mut stmts = vec![
+            vir::Stmt::comment("Check that the postcondition implies the exposed postcondition"),
+            vir::Stmt::Inhale(vir::Inhale {
+                expr: apply(&*pre_func),
+            }),
+            vir::Stmt::Inhale(vir::Inhale {
+                expr: apply(&*post_func),
+            }),
+        ];
+        for opaque_post in &opaque_posts {
+            let assertion = self.encode_assertion(
+                opaque_post,
+                None,
+                &encoded_args,
+                Some(&encoded_return),
+                true,
+                def_id,
+                substs,
+            )?;
+            let pos = self.error_manager().register_error(
+                tcx.def_span(opaque_post.to_def_id()),
+                ErrorCtxt::AssertOpaquePostcondition,
+                def_id,
+            );
+            stmts.push(vir::Stmt::Assert(vir::Assert {
+                expr: self.patch_snapshots(assertion).with_span(span)?,
+                position: pos,
+            }));
+        }
+        let block = method.add_block("start", stmts);
+        method.set_successor(block, vir::Successor::Return);
+        super::definition_collector::collect_definitions(span, self, name, vec![method]).map(Some)
+    }
+
     pub fn encode_spec_func_name(&self, def_id: ProcedureDefId, kind: SpecFunctionKind) -> String {
         let kind_name = match kind {
             SpecFunctionKind::Pre => "pre",
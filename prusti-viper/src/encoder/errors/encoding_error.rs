@@ -8,6 +8,7 @@ use rustc_errors::MultiSpan;
 use log::trace;
 use crate::encoder::errors::SpannedEncodingError;
 use crate::encoder::errors::EncodingErrorKind;
+use crate::encoder::errors::UnsupportedFeatureCategory;
 use backtrace::Backtrace;
 
 /// An error in the encoding with *optional* information regarding the source code span.
@@ -26,6 +27,12 @@ impl EncodingError {
         EncodingError::Positionless(EncodingErrorKind::unsupported(message))
     }
 
+    /// Usage of an unsupported Rust feature of the given category
+    pub fn unsupported_feature<M: ToString>(category: UnsupportedFeatureCategory, message: M) -> Self {
+        trace!("Constructing unsupported error at:\n{:?}", Backtrace::new());
+        EncodingError::Positionless(EncodingErrorKind::unsupported_feature(category, message))
+    }
+
     /// An incorrect usage of Prusti (e.g. call an impure function in a contract)
     pub fn incorrect<M: ToString>(message: M) -> Self {
         trace!("Constructing incorrect error at:\n{:?}", Backtrace::new());
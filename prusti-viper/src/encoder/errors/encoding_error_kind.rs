@@ -4,11 +4,69 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use rustc_middle::ty;
+
+/// The category of an unsupported Rust feature, used to summarize the usages
+/// of unsupported features reported by `report_unsupported_features`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum UnsupportedFeatureCategory {
+    TraitObjects,
+    Async,
+    RawPointers,
+    FunctionPointers,
+    Unions,
+    Iterators,
+    Closures,
+    Slicing,
+    Casts,
+    Overflow,
+    BitwiseOperations,
+    ThreadLocals,
+    Other,
+}
+
+impl UnsupportedFeatureCategory {
+    /// The category of a type that is not supported.
+    pub fn of_type(ty: &ty::TyKind) -> Self {
+        match ty {
+            ty::TyKind::Dynamic(..) => UnsupportedFeatureCategory::TraitObjects,
+            ty::TyKind::Generator(..) | ty::TyKind::GeneratorWitness(..) => {
+                UnsupportedFeatureCategory::Async
+            }
+            ty::TyKind::RawPtr(..) => UnsupportedFeatureCategory::RawPointers,
+            ty::TyKind::FnPtr(..) => UnsupportedFeatureCategory::FunctionPointers,
+            ty::TyKind::Closure(..) => UnsupportedFeatureCategory::Closures,
+            _ => UnsupportedFeatureCategory::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for UnsupportedFeatureCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            UnsupportedFeatureCategory::TraitObjects => "trait objects",
+            UnsupportedFeatureCategory::Async => "async",
+            UnsupportedFeatureCategory::RawPointers => "raw pointers",
+            UnsupportedFeatureCategory::FunctionPointers => "function pointers",
+            UnsupportedFeatureCategory::Unions => "unions",
+            UnsupportedFeatureCategory::Iterators => "iterators",
+            UnsupportedFeatureCategory::Closures => "closures",
+            UnsupportedFeatureCategory::Slicing => "slicing",
+            UnsupportedFeatureCategory::Casts => "casts",
+            UnsupportedFeatureCategory::Overflow => "overflow checks",
+            UnsupportedFeatureCategory::BitwiseOperations => "bitwise operations",
+            UnsupportedFeatureCategory::ThreadLocals => "thread-local storage",
+            UnsupportedFeatureCategory::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// An error in the encoding with no information regarding the source code span.
 #[derive(Clone, Debug)]
 pub enum EncodingErrorKind {
     /// Usage of an unsupported Rust feature (e.g. dereferencing raw pointers)
-    Unsupported(String),
+    Unsupported(String, UnsupportedFeatureCategory),
     /// Report an incorrect usage of Prusti (e.g. call an impure function in a contract)
     Incorrect(String),
     /// An internal error of Prusti (e.g. failure of the fold-unfold)
@@ -18,7 +76,12 @@ pub enum EncodingErrorKind {
 impl EncodingErrorKind {
     /// Usage of an unsupported Rust feature (e.g. dereferencing raw pointers)
     pub fn unsupported<M: ToString>(message: M) -> Self {
-        EncodingErrorKind::Unsupported(message.to_string())
+        EncodingErrorKind::unsupported_feature(UnsupportedFeatureCategory::Other, message)
+    }
+
+    /// Usage of an unsupported Rust feature of the given category
+    pub fn unsupported_feature<M: ToString>(category: UnsupportedFeatureCategory, message: M) -> Self {
+        EncodingErrorKind::Unsupported(message.to_string(), category)
     }
 
     /// An incorrect usage of Prusti (e.g. call an impure function in a contract)
@@ -13,6 +13,7 @@ use prusti_interface::PrustiError;
 use log::{debug, trace};
 use super::PositionManager;
 use prusti_interface::data::ProcedureDefId;
+use prusti_common::report::lsp::DiagnosticSeverity;
 
 
 /// The cause of a panic!()
@@ -48,12 +49,18 @@ pub enum ErrorCtxt {
     Panic(PanicCause),
     /// A Viper `exhale expr` that encodes the call of a Rust procedure with precondition `expr`
     ExhaleMethodPrecondition,
+    /// A Viper `assert expr` that encodes the call of an `unsafe fn` with safety precondition
+    /// `expr`
+    AssertUnsafeFunctionSafetyPrecondition,
     /// An error when assuming method's functional specification.
     UnexpectedAssumeMethodPrecondition,
     /// An error when assuming method's functional specification.
     UnexpectedAssumeMethodPostcondition,
     /// A Viper `assert expr` that encodes the call of a Rust procedure with precondition `expr`
     AssertMethodPostcondition,
+    /// A Viper `assert expr` that encodes the postcondition of the named
+    /// `#[case(...)]` attribute at the end of a Rust procedure
+    AssertMethodPostconditionOfCase(String),
     /// A Viper `assert expr` that encodes the call of a Rust procedure with precondition `expr`
     AssertMethodPostconditionTypeInvariants,
     /// A Viper `exhale expr` that encodes the end of a Rust procedure with postcondition `expr`
@@ -64,6 +71,8 @@ pub enum ErrorCtxt {
     /// A Viper `assert expr` that asserts the functional specification of a loop invariant `expr`
     AssertLoopInvariantOnEntry,
     AssertLoopInvariantAfterIteration,
+    /// A Viper `assert expr` that asserts a `break_ensures!(expr)` when leaving a loop
+    AssertLoopBreakPostcondition,
     /// A Viper `assert false` that encodes the failure (panic) of an `assert` Rust terminator
     /// Arguments: the message of the Rust assertion
     AssertTerminator(String),
@@ -114,6 +123,40 @@ pub enum ErrorCtxt {
     /// A Viper `assert e1 ==> e2` that encodes a strengthening of the precondition
     /// of a method implementation of a trait.
     AssertMethodPostconditionStrengthening,
+    /// A Viper `assert pre ==> result == f(args)` that encodes the equivalence of
+    /// a method with the pure function `f` declared by `#[prusti::equivalent_to(f)]`.
+    AssertMethodEquivalence(String),
+    /// A Viper `assert forall args :: pre ==> pre_g && ...` that encodes that the
+    /// function passed for a function pointer satisfies the contract `f` declared
+    /// by `#[prusti::fn_contract(f)]`.
+    AssertFnPointerContract(String),
+    /// A Viper function call with `false` precondition that encodes the
+    /// projection `e.Variant.field` when `e` might be of another variant.
+    InactiveVariantField,
+    /// A Viper `assert p(args, result)` that encodes that the postcondition of
+    /// a method implies the property `p` declared by `#[prusti::check_implies(p)]`.
+    AssertImpliedProperty(String),
+    /// A Viper `assert e` that encodes that the postcondition of a method
+    /// implies the postcondition `e` exposed by its `#[opaque_spec(...)]`
+    /// attribute.
+    AssertOpaquePostcondition,
+    /// A Viper `assert cond ==> decreases(measure(args), measure(call_args))`
+    /// that encodes that the `#[terminates(...)]` measure of a pure function
+    /// decreases at a recursive call.
+    AssertTerminationMeasure,
+    /// A Viper `assert !cond` that encodes that the body of an inline
+    /// `const { ... }` block does not panic under the condition `cond`.
+    AssertInlineConst,
+    /// A Viper `assert eq(a, b) ==> a.f == b.f` that encodes that a field `f`
+    /// hashed by the `Hash` implementation of a `#[prusti::hash_eq_consistent]`
+    /// type is equal in values that are equal according to `PartialEq`.
+    AssertHashEqConsistency(String),
+    /// A Viper `assert clone(x) == x` that encodes that the `clone` method of
+    /// a `#[prusti::clone_equal]` type returns a value equal to `self`.
+    AssertCloneEqual(String),
+    /// A Viper `assert` that encodes that the result of an unchecked
+    /// arithmetic operation like `unchecked_add` is in the range of its type.
+    UncheckedArithmetic(String),
     /// A cast like `usize as u32`.
     TypeCast,
     /// A Viper `assert false` that encodes an unsupported feature.
@@ -144,12 +187,83 @@ pub enum ErrorCtxt {
     SetEnumVariant,
 }
 
+impl ErrorCtxt {
+    /// The category of the errors of this context, used as the diagnostic
+    /// code in the LSP diagnostics, and the severity of these errors.
+    pub fn diagnostic_category(&self) -> (&'static str, DiagnosticSeverity) {
+        use DiagnosticSeverity::*;
+        match self {
+            ErrorCtxt::Panic(_)
+            | ErrorCtxt::AssertTerminator(_)
+            | ErrorCtxt::BoundsCheckAssert
+            | ErrorCtxt::AbortTerminator
+            | ErrorCtxt::UnreachableTerminator
+            | ErrorCtxt::DivergingCallInPureFunction
+            | ErrorCtxt::PanicInPureFunction(_)
+            | ErrorCtxt::PureFunctionAssertTerminator(_) => ("panic", Error),
+            ErrorCtxt::ExhaleMethodPrecondition
+            | ErrorCtxt::AssertUnsafeFunctionSafetyPrecondition
+            | ErrorCtxt::PureFunctionCall
+            | ErrorCtxt::UncheckedArithmetic(_) => ("precondition", Error),
+            ErrorCtxt::AssertMethodPostcondition
+            | ErrorCtxt::AssertMethodPostconditionOfCase(_)
+            | ErrorCtxt::AssertMethodPostconditionTypeInvariants
+            | ErrorCtxt::ExhaleMethodPostcondition
+            | ErrorCtxt::PureFunctionDefinition
+            | ErrorCtxt::PureFunctionPostconditionValueRangeOfResult
+            | ErrorCtxt::PackageMagicWandForPostcondition
+            | ErrorCtxt::AssertOpaquePostcondition => ("postcondition", Error),
+            ErrorCtxt::ExhaleLoopInvariantOnEntry
+            | ErrorCtxt::ExhaleLoopInvariantAfterIteration
+            | ErrorCtxt::AssertLoopInvariantOnEntry
+            | ErrorCtxt::AssertLoopInvariantAfterIteration
+            | ErrorCtxt::AssertLoopBreakPostcondition => ("loop-invariant", Error),
+            ErrorCtxt::AssertMethodPreconditionWeakening
+            | ErrorCtxt::AssertMethodPostconditionStrengthening
+            | ErrorCtxt::AssertMethodEquivalence(_)
+            | ErrorCtxt::AssertFnPointerContract(_)
+            | ErrorCtxt::AssertImpliedProperty(_) => ("refinement", Error),
+            ErrorCtxt::AssertTerminationMeasure => ("termination", Error),
+            ErrorCtxt::AssertInlineConst => ("panic", Error),
+            ErrorCtxt::AssertHashEqConsistency(_)
+            | ErrorCtxt::AssertCloneEqual(_) => ("refinement", Error),
+            ErrorCtxt::Unsupported(_) => ("unsupported", Warning),
+            ErrorCtxt::ApplyMagicWandOnExpiry
+            | ErrorCtxt::InactiveVariantField
+            | ErrorCtxt::TypeCast
+            | ErrorCtxt::Unfold
+            | ErrorCtxt::UnfoldUnionVariant
+            | ErrorCtxt::ProcedureCall
+            | ErrorCtxt::DropCall
+            | ErrorCtxt::MovePlace
+            | ErrorCtxt::CopyPlace
+            | ErrorCtxt::WritePlace
+            | ErrorCtxt::Assign
+            | ErrorCtxt::SetEnumVariant
+            | ErrorCtxt::LifetimeEncoding
+            | ErrorCtxt::LifetimeTake
+            | ErrorCtxt::LifetimeReturn
+            | ErrorCtxt::OpenMutRef
+            | ErrorCtxt::OpenFracRef
+            | ErrorCtxt::CloseMutRef
+            | ErrorCtxt::CloseFracRef => ("memory-safety", Error),
+            ErrorCtxt::Unexpected
+            | ErrorCtxt::UnexpectedBuiltinMethod(_)
+            | ErrorCtxt::UnexpectedStorageLive
+            | ErrorCtxt::UnexpectedStorageDead
+            | ErrorCtxt::UnexpectedAssumeMethodPrecondition
+            | ErrorCtxt::UnexpectedAssumeMethodPostcondition => ("internal", Error),
+        }
+    }
+}
+
 /// The error manager
 #[derive(Clone)]
 pub struct ErrorManager<'tcx> {
     position_manager: PositionManager<'tcx>,
     error_contexts: FxHashMap<u64, ErrorCtxt>,
     inner_positions: FxHashMap<u64, Position>,
+    obligation_formulas: FxHashMap<u64, String>,
 }
 
 impl<'tcx> ErrorManager<'tcx> {
@@ -158,6 +272,7 @@ impl<'tcx> ErrorManager<'tcx> {
             position_manager: PositionManager::new(codemap),
             error_contexts: FxHashMap::default(),
             inner_positions: FxHashMap::default(),
+            obligation_formulas: FxHashMap::default(),
         }
     }
 
@@ -204,6 +319,25 @@ impl<'tcx> ErrorManager<'tcx> {
         surrounding_position
     }
 
+    /// Register a new VIR position for a part of the obligation at `pos`, e.g.
+    /// a conjunct of an assertion. The new position has the error context of
+    /// `pos` and the span of `part_pos`, or the one of `pos` if `part_pos` has
+    /// no span. Returns `pos` if it has no error context.
+    pub fn register_part_position(&mut self, pos: Position, part_pos: Position) -> Position {
+        let error_ctxt = match self.error_contexts.get(&pos.id()) {
+            Some(error_ctxt) => error_ctxt.clone(),
+            None => return pos,
+        };
+        let span = self.position_manager.get_span(part_pos)
+            .or_else(|| self.position_manager.get_span(pos))
+            .cloned();
+        let def_id = self.position_manager.get_def_id(pos);
+        match (span, def_id) {
+            (Some(span), Some(def_id)) => self.register_error(span, error_ctxt, def_id),
+            _ => pos,
+        }
+    }
+
     /// Register a new VIR position with the given ErrorCtxt.
     /// Equivalent to calling `set_error` on the output of `register_span`.
     pub fn register_error<T: Into<MultiSpan>>(&mut self, span: T, error_ctxt: ErrorCtxt, def_id: ProcedureDefId) -> Position {
@@ -212,12 +346,49 @@ impl<'tcx> ErrorManager<'tcx> {
         pos
     }
 
+    /// Register the formula of the obligation checked at an existing VIR
+    /// position, to be shown if the obligation fails.
+    pub fn set_obligation_formula(&mut self, pos: Position, formula: String) {
+        self.obligation_formulas.entry(pos.id()).or_insert(formula);
+    }
+
+    /// The source location and the error context of each position with an
+    /// error context, by position id.
+    pub fn describe_positions(&self) -> Vec<(u64, String)> {
+        let mut positions: Vec<_> = self.error_contexts.iter().map(|(&pos_id, error_ctxt)| {
+            let location = self.position_manager
+                .get_source_location(Position::new(0, 0, pos_id))
+                .unwrap_or_else(|| "unknown location".to_string());
+            (pos_id, format!("{}: {:?}", location, error_ctxt))
+        }).collect();
+        positions.sort();
+        positions
+    }
+
+    /// The span and the error context of each position with an error
+    /// context, by position id.
+    pub fn error_positions(&self) -> Vec<(u64, &MultiSpan, &ErrorCtxt)> {
+        let mut positions: Vec<_> = self.error_contexts.iter().filter_map(|(&pos_id, error_ctxt)| {
+            let span = self.position_manager.source_span.get(&pos_id)?;
+            Some((pos_id, span, error_ctxt))
+        }).collect();
+        positions.sort_by_key(|&(pos_id, _, _)| pos_id);
+        positions
+    }
+
     pub fn get_def_id(&self, ver_error: &VerificationError) -> Option<ProcedureDefId> {
         ver_error.pos_id.as_ref()
             .and_then(|id| id.parse().ok())
             .and_then(|id| self.position_manager.def_id.get(&id).copied())
     }
 
+    /// The error context registered at the position of the error, if any.
+    pub fn get_error_ctxt(&self, ver_error: &VerificationError) -> Option<&ErrorCtxt> {
+        ver_error.pos_id.as_ref()
+            .and_then(|id| id.parse().ok())
+            .and_then(|id: u64| self.error_contexts.get(&id))
+    }
+
     pub fn translate_verification_error(&self, ver_error: &VerificationError) -> PrustiError {
         debug!("Verification error: {:?}", ver_error);
         let opt_pos_id: Option<u64> = match ver_error.pos_id {
@@ -269,12 +440,19 @@ impl<'tcx> ErrorManager<'tcx> {
         if let Some(error_ctxt) = opt_error_ctxts {
             debug_assert!(opt_error_span.is_some());
             let error_span = opt_error_span.cloned().unwrap_or_else(MultiSpan::new);
-            self.translate_verification_error_with_context(
+            let prusti_error = self.translate_verification_error_with_context(
                 ver_error,
                 error_span,
                 opt_cause_span,
                 error_ctxt
-            )
+            );
+            match opt_pos_id.and_then(|pos_id| self.obligation_formulas.get(&pos_id)) {
+                Some(formula) => prusti_error.add_note(
+                    format!("the failed obligation is `{}`", formula),
+                    None,
+                ),
+                None => prusti_error,
+            }
         } else {
             debug!("Unregistered verification error: {:?}", ver_error);
             let error_span = if let Some(error_span) = opt_error_span {
@@ -371,6 +549,13 @@ impl<'tcx> ErrorManager<'tcx> {
                     .set_failing_assertion(opt_cause_span)
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::AssertUnsafeFunctionSafetyPrecondition) => {
+                PrustiError::verification(
+                    "the safety precondition of the unsafe function might not hold.",
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+            }
+
             ("fold.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition) => {
                 PrustiError::verification(
                     "implicit type invariant expected by the function call might not hold.",
@@ -414,6 +599,13 @@ impl<'tcx> ErrorManager<'tcx> {
                 ).push_primary_span(opt_cause_span)
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::AssertLoopBreakPostcondition) => {
+                PrustiError::verification(
+                    "the postcondition of the loop might not hold when leaving the loop.",
+                    error_span
+                ).push_primary_span(opt_cause_span)
+            }
+
             ("application.precondition:assertion.false", ErrorCtxt::PureFunctionCall) => {
                 PrustiError::verification(
                     "precondition of pure function call might not hold.",
@@ -502,6 +694,13 @@ impl<'tcx> ErrorManager<'tcx> {
                 ).set_failing_assertion(opt_cause_span)
             },
 
+            ("application.precondition:assertion.false", ErrorCtxt::InactiveVariantField) => {
+                PrustiError::verification(
+                    "the enum might not be of the variant whose field is accessed".to_string(),
+                    error_span,
+                ).set_help("The variant of the enum needs to be known before one of its fields is accessed, e.g. with `matches!`.")
+            }
+
             ("application.precondition:assertion.false", ErrorCtxt::TypeCast) => {
                 PrustiError::verification(
                     "value might not fit into the target type.",
@@ -519,6 +718,13 @@ impl<'tcx> ErrorManager<'tcx> {
                     .push_primary_span(opt_cause_span)
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::AssertMethodPostconditionOfCase(name)) => {
+                PrustiError::verification(
+                    format!("postcondition of case `{}` might not hold.", name),
+                    error_span
+                ).push_primary_span(opt_cause_span)
+            }
+
             (
                 "assert.failed:assertion.false",
                 ErrorCtxt::AssertMethodPostconditionTypeInvariants,
@@ -563,6 +769,56 @@ impl<'tcx> ErrorManager<'tcx> {
                     .set_help("The implemented method's postcondition should imply the trait's postcondition.")
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::AssertMethodEquivalence(ref function)) => {
+                PrustiError::verification(format!("the function might not be equivalent to `{}`.", function), error_span)
+                    .set_help("The function should return the same result as the equivalent function on all inputs that satisfy the preconditions of both.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertFnPointerContract(ref function)) => {
+                PrustiError::verification(format!("the function passed as argument might not satisfy the contract `{}`.", function), error_span)
+                    .set_help("The precondition of the contract should imply the precondition of the function, and the postcondition of the function should imply the postcondition of the contract.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertImpliedProperty(ref property)) => {
+                PrustiError::verification(format!("the postcondition might not imply `{}`.", property), error_span)
+                    .set_help("The property should hold for all results that satisfy the postcondition, assuming the precondition.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertOpaquePostcondition) => {
+                PrustiError::verification("the postcondition might not imply the exposed postcondition.", error_span)
+                    .set_help("The postcondition of `#[opaque_spec(...)]` should hold for all results that satisfy the postcondition, assuming the precondition.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertTerminationMeasure) => {
+                PrustiError::verification("the termination measure might not decrease.", error_span)
+                    .set_help("The measure of the called function should be lexicographically smaller than the measure of the caller, and not negative.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertHashEqConsistency(ref type_name)) => {
+                PrustiError::verification(
+                    format!("the `Hash` implementation of `{}` might not be consistent with its `PartialEq` implementation.", type_name),
+                    error_span
+                ).set_help("Values that are equal must have the same hash, so `hash` should only hash values that `eq` compares.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertCloneEqual(ref type_name)) => {
+                PrustiError::verification(
+                    format!("the `Clone` implementation of `{}` might not return a value equal to the original.", type_name),
+                    error_span
+                ).set_help("The clone of a `#[prusti::clone_equal]` type must have the same value in every field as the original.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::UncheckedArithmetic(ref operation)) => {
+                PrustiError::verification(format!("the operation `{}` might overflow.", operation), error_span)
+                    .set_help("The caller of an unchecked arithmetic operation must guarantee that the result is in the range of its type; otherwise the behavior is undefined.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertInlineConst) => {
+                PrustiError::verification("the inline const block might panic.", error_span)
+                    .set_failing_assertion(opt_cause_span)
+                    .set_help("An inline const block is evaluated at compile time, so its assertions should hold without assuming the precondition or the state of the function.")
+            }
+
             ("assert.failed:assertion.false", ErrorCtxt::BoundsCheckAssert) |
             ("application.precondition:assertion.false", ErrorCtxt::BoundsCheckAssert) => {
                 PrustiError::verification(
@@ -616,3 +872,26 @@ impl<'tcx> ErrorManager<'tcx> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_category() {
+        use DiagnosticSeverity::*;
+        assert_eq!(ErrorCtxt::Panic(PanicCause::Assert).diagnostic_category(), ("panic", Error));
+        assert_eq!(ErrorCtxt::ExhaleMethodPrecondition.diagnostic_category(), ("precondition", Error));
+        assert_eq!(ErrorCtxt::AssertMethodPostcondition.diagnostic_category(), ("postcondition", Error));
+        assert_eq!(ErrorCtxt::AssertLoopInvariantOnEntry.diagnostic_category(), ("loop-invariant", Error));
+        assert_eq!(ErrorCtxt::AssertMethodPreconditionWeakening.diagnostic_category(), ("refinement", Error));
+        assert_eq!(ErrorCtxt::AssertTerminationMeasure.diagnostic_category(), ("termination", Error));
+        assert_eq!(ErrorCtxt::WritePlace.diagnostic_category(), ("memory-safety", Error));
+        assert_eq!(ErrorCtxt::Unexpected.diagnostic_category(), ("internal", Error));
+        // Reachable unsupported features are warnings.
+        assert_eq!(
+            ErrorCtxt::Unsupported("trait objects".to_string()).diagnostic_category(),
+            ("unsupported", Warning),
+        );
+    }
+}
@@ -86,4 +86,12 @@ impl<'tcx> PositionManager<'tcx>
     pub fn get_span(&self, pos: Position) -> Option<&MultiSpan> {
         self.source_span.get(&pos.id())
     }
+
+    /// The source location (`file:line:column`) of the primary span of the
+    /// given VIR position.
+    pub fn get_source_location(&self, pos: Position) -> Option<String> {
+        let primary_span = self.get_span(pos)?.primary_span()?;
+        let loc = self.codemap.lookup_char_pos(primary_span.source_callsite().lo());
+        Some(format!("{}:{}:{}", loc.file.name.prefer_local(), loc.line, loc.col.0 + 1))
+    }
 }
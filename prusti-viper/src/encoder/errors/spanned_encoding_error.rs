@@ -8,7 +8,7 @@ use rustc_errors::MultiSpan;
 use log::trace;
 use prusti_interface::PrustiError;
 
-use crate::encoder::errors::EncodingErrorKind;
+use crate::encoder::errors::{EncodingErrorKind, UnsupportedFeatureCategory};
 use backtrace::Backtrace;
 
 /// An error in the encoding with information regarding the source code span that caused it.
@@ -25,7 +25,7 @@ pub type SpannedEncodingResult<T> = Result<T, SpannedEncodingError>;
 impl From<SpannedEncodingError> for PrustiError {
     fn from(other: SpannedEncodingError) -> Self {
         let mut error = match other.error {
-            EncodingErrorKind::Unsupported(msg) => {
+            EncodingErrorKind::Unsupported(msg, _) => {
                 PrustiError::unsupported(msg, other.span)
             }
             EncodingErrorKind::Incorrect(msg) => {
@@ -64,6 +64,19 @@ impl SpannedEncodingError {
         )
     }
 
+    /// Usage of an unsupported Rust feature of the given category
+    pub fn unsupported_feature<M: ToString, S: Into<MultiSpan>>(
+        category: UnsupportedFeatureCategory,
+        message: M,
+        span: S,
+    ) -> Self {
+        trace!("Constructing unsupported error at:\n{:?}", Backtrace::new());
+        SpannedEncodingError::new(
+            EncodingErrorKind::unsupported_feature(category, message),
+            span
+        )
+    }
+
     /// An incorrect usage of Prusti (e.g. call an impure function in a contract)
     pub fn incorrect<M: ToString, S: Into<MultiSpan>>(message: M, span: S) -> Self {
         trace!("Constructing incorrect error at:\n{:?}", Backtrace::new());
@@ -86,6 +99,10 @@ impl SpannedEncodingError {
         &self.error
     }
 
+    pub fn span(&self) -> &MultiSpan {
+        &self.span
+    }
+
     pub fn with_span<S: Into<MultiSpan>>(self, span: S) -> SpannedEncodingError {
         // TODO: Stack error spans
         SpannedEncodingError {
@@ -0,0 +1,269 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Encoding of the check of the `#[prusti::hash_eq_consistent]` attribute of
+//! a type: two values that are equal according to `PartialEq::eq` must have
+//! the same hash. The `hash` method of the `Hash` implementation is treated as
+//! the pure function that returns the sequence of the fields of `self` that it
+//! feeds to the hasher, so the check is that `a == b` implies that each of
+//! these fields is equal in `a` and `b`.
+
+use super::{
+    errors::{ErrorCtxt, SpannedEncodingError, SpannedEncodingResult, WithSpan},
+    high::generics::HighGenericsEncoderInterface,
+    mir::{pure::PureFunctionEncoderInterface, specifications::SpecificationsInterface},
+    mir_encoder::{MirEncoder, PlaceEncoder},
+    snapshot::interface::SnapshotEncoderInterface,
+    Encoder,
+};
+use prusti_interface::{data::ProcedureDefId, utils::has_prusti_attr};
+use rustc_middle::{mir, ty};
+use rustc_span::{sym, Span};
+use vir_crate::polymorphic as vir;
+
+/// The place whose value or reference is stored in the temporary `local`,
+/// following the references that are stored in other temporaries, e.g.
+/// `(*_1).0` for `_3 = &((*_1).0); _4 = &(*_3)` and the local `_4`.
+fn resolve_hashed_place<'tcx>(
+    tcx: ty::TyCtxt<'tcx>,
+    mir: &mir::Body<'tcx>,
+    local: mir::Local,
+) -> Option<mir::Place<'tcx>> {
+    let assigned_place = |local: mir::Local| {
+        mir.basic_blocks().iter().flat_map(|block| &block.statements).find_map(|stmt| {
+            match &stmt.kind {
+                mir::StatementKind::Assign(box (target, rvalue))
+                    if target.local == local && target.projection.is_empty() =>
+                {
+                    match rvalue {
+                        mir::Rvalue::Ref(_, _, place)
+                        | mir::Rvalue::Use(mir::Operand::Copy(place) | mir::Operand::Move(place)) => {
+                            Some((*place, matches!(rvalue, mir::Rvalue::Ref(..))))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        })
+    };
+    let (mut place, _) = assigned_place(local)?;
+    while place.local.index() > mir.arg_count {
+        let (base, is_ref) = assigned_place(place.local)?;
+        let projection = match place.projection.first() {
+            Some(mir::ProjectionElem::Deref) if is_ref => &place.projection[1..],
+            _ if !is_ref => &place.projection[..],
+            _ => return None,
+        };
+        let elems: Vec<_> = base.projection.iter().chain(projection.iter().copied()).collect();
+        place = mir::Place {
+            local: base.local,
+            projection: tcx.intern_place_elems(&elems),
+        };
+    }
+    Some(place)
+}
+
+/// The fields of `self` that are hashed by the `hash` method `mir`, with
+/// the span of the call that hashes them. Returns an error if the method does
+/// anything else than hashing fields of `self` in sequence.
+fn get_hashed_fields<'tcx>(
+    encoder: &Encoder<'_, 'tcx>,
+    mir: &mir::Body<'tcx>,
+) -> SpannedEncodingResult<Vec<(mir::Place<'tcx>, Span)>> {
+    let tcx = encoder.env().tcx();
+    let hash_trait = tcx.get_diagnostic_item(sym::Hash);
+    let hasher_trait = tcx.get_diagnostic_item(sym::Hasher);
+    let unsupported = |span| SpannedEncodingError::unsupported(
+        "`hash_eq_consistent` only supports `hash` methods that hash fields of `self` in \
+        sequence, without branches or other calls",
+        span,
+    );
+    let mut fields = vec![];
+    for block in mir.basic_blocks() {
+        let terminator = block.terminator();
+        let span = terminator.source_info.span;
+        match &terminator.kind {
+            mir::TerminatorKind::Call {
+                func: mir::Operand::Constant(box mir::Constant { literal, .. }),
+                args,
+                ..
+            } => {
+                let called_def_id = match literal.ty().kind() {
+                    ty::TyKind::FnDef(called_def_id, _) => *called_def_id,
+                    _ => return Err(unsupported(span)),
+                };
+                let called_trait = tcx.trait_of_item(called_def_id);
+                if called_trait.is_none()
+                    || (called_trait != hash_trait && called_trait != hasher_trait)
+                {
+                    return Err(unsupported(span));
+                }
+                // The hashed value is the first argument of `Hash::hash`, and
+                // the second argument of the `write` methods of `Hasher`.
+                let index = if called_trait == hash_trait { 0 } else { 1 };
+                let hashed_local = match args.get(index) {
+                    Some(mir::Operand::Copy(place) | mir::Operand::Move(place)) => place.local,
+                    _ => return Err(unsupported(span)),
+                };
+                let hashed_place = resolve_hashed_place(tcx, mir, hashed_local);
+                match hashed_place {
+                    Some(place)
+                        if place.local == mir::Local::from_usize(1)
+                            && place.projection.len() > 1
+                            && place.projection[0] == mir::ProjectionElem::Deref
+                            && place.projection[1..]
+                                .iter()
+                                .all(|elem| matches!(elem, mir::ProjectionElem::Field(..))) =>
+                    {
+                        fields.push((place, span));
+                    }
+                    _ => return Err(unsupported(span)),
+                }
+            }
+            mir::TerminatorKind::Goto { .. }
+            | mir::TerminatorKind::Return
+            | mir::TerminatorKind::Resume
+            | mir::TerminatorKind::Abort
+            | mir::TerminatorKind::Unreachable
+            | mir::TerminatorKind::Drop { .. } => {}
+            _ => return Err(unsupported(span)),
+        }
+    }
+    Ok(fields)
+}
+
+/// Encode the check of the `#[prusti::hash_eq_consistent]` attribute of the
+/// type that implements `Hash` with the method `def_id`. Returns `None` if
+/// `def_id` is not the `hash` method of a `Hash` implementation for a type
+/// with this attribute.
+pub(super) fn encode_hash_eq_check<'v, 'tcx: 'v>(
+    encoder: &Encoder<'v, 'tcx>,
+    def_id: ProcedureDefId,
+) -> SpannedEncodingResult<Option<vir::Program>> {
+    let tcx = encoder.env().tcx();
+    let impl_def_id = match tcx.impl_of_method(def_id) {
+        Some(impl_def_id) => impl_def_id,
+        None => return Ok(None),
+    };
+    if tcx.trait_id_of_impl(impl_def_id) != tcx.get_diagnostic_item(sym::Hash)
+        || tcx.item_name(def_id) != sym::hash
+    {
+        return Ok(None);
+    }
+    let self_ty = tcx.type_of(impl_def_id);
+    let adt_def = match self_ty.kind() {
+        ty::TyKind::Adt(adt_def, _) if has_prusti_attr(tcx.get_attrs(adt_def.did()), "hash_eq_consistent") => {
+            *adt_def
+        }
+        _ => return Ok(None),
+    };
+    let span = encoder.env().get_def_span(def_id);
+    let type_name = tcx.def_path_str(adt_def.did());
+    if !adt_def.is_struct() {
+        return Err(SpannedEncodingError::unsupported(
+            format!("`hash_eq_consistent` is only supported on structs, but `{}` is not a struct", type_name),
+            span,
+        ));
+    }
+
+    // The `eq` method of the `PartialEq` implementation of the type.
+    let eq_trait = tcx.lang_items().eq_trait().ok_or_else(|| SpannedEncodingError::internal(
+        "cannot find the `PartialEq` trait",
+        span,
+    ))?;
+    let eq_item = tcx
+        .associated_items(eq_trait)
+        .filter_by_name_unhygienic(sym::eq)
+        .next()
+        .ok_or_else(|| SpannedEncodingError::internal("cannot find `PartialEq::eq`", span))?;
+    let (eq_def_id, eq_substs) = encoder.env().resolve_method_call(
+        def_id,
+        eq_item.def_id,
+        tcx.mk_substs_trait(self_ty, &[self_ty.into()]),
+    );
+
+    let snapshot_type = encoder.encode_snapshot_type(self_ty).with_span(span)?;
+    let a = vir::LocalVar::new("a", snapshot_type.clone());
+    let b = vir::LocalVar::new("b", snapshot_type);
+    let equal = if encoder.is_pure(eq_def_id, Some(eq_substs)) {
+        let (eq_name, eq_return_type) = encoder.encode_pure_function_use(eq_def_id, def_id, eq_substs)?;
+        let eq_type_arguments = encoder
+            .encode_generic_arguments(eq_def_id, eq_substs)
+            .with_span(span)?;
+        let ref_snapshot_type = encoder
+            .encode_snapshot_type(tcx.mk_imm_ref(tcx.lifetimes.re_erased, self_ty))
+            .with_span(span)?;
+        vir::Expr::func_app(
+            eq_name,
+            eq_type_arguments,
+            vec![vir::Expr::local(a.clone()), vir::Expr::local(b.clone())],
+            vec![
+                vir::LocalVar::new("_1", ref_snapshot_type.clone()),
+                vir::LocalVar::new("_2", ref_snapshot_type),
+            ],
+            eq_return_type,
+            vir::Position::default(),
+        )
+    } else if encoder.has_structural_eq_impl(self_ty) {
+        vir::Expr::eq_cmp(vir::Expr::local(a.clone()), vir::Expr::local(b.clone()))
+    } else {
+        return Err(SpannedEncodingError::incorrect(
+            format!(
+                "`hash_eq_consistent` requires the `eq` method of the `PartialEq` implementation \
+                of `{}` to be pure",
+                type_name,
+            ),
+            span,
+        ));
+    };
+
+    let substs = encoder.env().identity_substs(def_id);
+    let mir = encoder.env().local_mir(def_id.expect_local(), substs);
+    let hashed_fields = get_hashed_fields(encoder, &mir)?;
+    let mir_encoder = MirEncoder::new_pure(encoder, &mir, def_id);
+    let self_local = vir::Expr::local(
+        mir_encoder.encode_local(mir::Local::from_usize(1))?,
+    );
+
+    let name = format!("{}$hash_eq_check", encoder.encode_item_name(def_id));
+    let mut method = vir::CfgMethod::new(name.clone(), 0, vec![], vec![], vec![]);
+    method.add_local_var(&a.name, a.typ.clone());
+    method.add_local_var(&b.name, b.typ.clone());
+    let mut stmts = vec![vir::Stmt::comment(
+        "Check that the hashed fields are equal in values that are equal according to `eq`",
+    )];
+    for (place, call_span) in hashed_fields {
+        let field_ty = place.ty(&*mir, tcx).ty;
+        let field = mir_encoder
+            .encode_operand_expr(&mir::Operand::Copy(place))
+            .with_span(call_span)?;
+        let field = if field_ty.is_scalar() {
+            field
+        } else {
+            vir::Expr::snap_app(field)
+        };
+        let field_of = |value: &vir::LocalVar| {
+            field.clone().replace_place(&self_local, &vir::Expr::local(value.clone()))
+        };
+        let pos = encoder.error_manager().register_error(
+            call_span,
+            ErrorCtxt::AssertHashEqConsistency(type_name.clone()),
+            def_id,
+        );
+        let expr = vir::Expr::implies(
+            equal.clone(),
+            vir::Expr::eq_cmp(field_of(&a), field_of(&b)),
+        );
+        stmts.push(vir::Stmt::Assert(vir::Assert {
+            expr: encoder.patch_snapshots(expr).with_span(call_span)?,
+            position: pos,
+        }));
+    }
+    let block = method.add_block("start", stmts);
+    method.set_successor(block, vir::Successor::Return);
+    super::definition_collector::collect_definitions(span, encoder, name, vec![method]).map(Some)
+}
@@ -0,0 +1,145 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Encoding of the check that the inline `const { ... }` blocks of a procedure
+//! do not panic. An inline const block is evaluated at compile time, whether
+//! or not it is reached, so its assertions must hold without assuming the
+//! precondition or the state of the procedure.
+
+use super::{
+    builtin_encoder::BuiltinFunctionKind,
+    errors::{ErrorCtxt, SpannedEncodingResult, WithSpan},
+    high::builtin_functions::HighBuiltinFunctionEncoderInterface,
+    mir::{pure::PureFunctionEncoderInterface, specifications::SpecificationsInterface},
+    Encoder,
+};
+use prusti_interface::data::ProcedureDefId;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::LocalDefId;
+use vir_crate::polymorphic::{self as vir, ExprIterator, ExprWalker};
+
+/// The calls to the builtin `unreachable` functions in an expression, which
+/// encode the panics of a pure body, with the condition under which they are
+/// evaluated.
+struct PanicCollector<'p, 'v: 'p, 'tcx: 'v> {
+    encoder: &'p Encoder<'v, 'tcx>,
+    path_condition: Vec<vir::Expr>,
+    panics: Vec<(vir::Expr, vir::Position)>,
+}
+
+impl<'p, 'v: 'p, 'tcx: 'v> PanicCollector<'p, 'v, 'tcx> {
+    fn walk_guarded(&mut self, condition: vir::Expr, expr: &vir::Expr) {
+        self.path_condition.push(condition);
+        ExprWalker::walk(self, expr);
+        self.path_condition.pop();
+    }
+}
+
+impl<'p, 'v: 'p, 'tcx: 'v> ExprWalker for PanicCollector<'p, 'v, 'tcx> {
+    fn walk_cond(&mut self, vir::Cond { guard, then_expr, else_expr, .. }: &vir::Cond) {
+        ExprWalker::walk(self, guard);
+        self.walk_guarded((**guard).clone(), then_expr);
+        self.walk_guarded(vir::Expr::not((**guard).clone()), else_expr);
+    }
+
+    fn walk_bin_op(&mut self, vir::BinOp { op_kind, left, right, .. }: &vir::BinOp) {
+        ExprWalker::walk(self, left);
+        match op_kind {
+            vir::BinaryOpKind::And | vir::BinaryOpKind::Implies => {
+                self.walk_guarded((**left).clone(), right)
+            }
+            vir::BinaryOpKind::Or => self.walk_guarded(vir::Expr::not((**left).clone()), right),
+            _ => ExprWalker::walk(self, right),
+        }
+    }
+
+    fn walk_let_expr(&mut self, vir::LetExpr { variable, def, body, .. }: &vir::LetExpr) {
+        ExprWalker::walk(self, def);
+        let body = (**body).clone().replace_place(&vir::Expr::local(variable.clone()), def);
+        ExprWalker::walk(self, &body);
+    }
+
+    fn walk_func_app(&mut self, vir::FuncApp { function_name, arguments, return_type, position, .. }: &vir::FuncApp) {
+        for arg in arguments {
+            ExprWalker::walk(self, arg);
+        }
+        let (unreachable_name, _) = self
+            .encoder
+            .encode_builtin_function_use(BuiltinFunctionKind::Unreachable(return_type.clone()));
+        if arguments.is_empty() && *function_name == unreachable_name {
+            self.panics.push((self.path_condition.iter().cloned().conjoin(), *position));
+        }
+    }
+}
+
+/// The inline const blocks in the body of the procedure `def_id`, including
+/// the ones in its closures.
+fn get_inline_consts(encoder: &Encoder, def_id: ProcedureDefId) -> Vec<LocalDefId> {
+    let tcx = encoder.env().tcx();
+    tcx.hir()
+        .body_owners()
+        .filter(|&owner| {
+            tcx.def_kind(owner) == DefKind::InlineConst
+                && tcx.typeck_root_def_id(owner.to_def_id()) == def_id
+        })
+        .collect()
+}
+
+/// Encode the check that the inline const blocks of the procedure `def_id` do
+/// not panic. Returns `None` if the procedure has no inline const blocks that
+/// might panic.
+pub(super) fn encode_inline_const_check<'v, 'tcx: 'v>(
+    encoder: &Encoder<'v, 'tcx>,
+    def_id: ProcedureDefId,
+) -> SpannedEncodingResult<Option<vir::Program>> {
+    if encoder.is_trusted(def_id, None) {
+        return Ok(None);
+    }
+    let inline_consts = get_inline_consts(encoder, def_id);
+    if inline_consts.is_empty() {
+        return Ok(None);
+    }
+    let span = encoder.env().get_def_span(def_id);
+    let name = format!("{}$inline_const_check", encoder.encode_item_name(def_id));
+    let mut methods = vec![];
+    for (index, const_def_id) in inline_consts.into_iter().enumerate() {
+        let const_def_id = const_def_id.to_def_id();
+        let const_span = encoder.env().get_def_span(const_def_id);
+        let substs = encoder.env().identity_substs(const_def_id);
+        let body = encoder.encode_pure_expression(const_def_id, def_id, substs)?;
+        let body = encoder.patch_snapshots(body).with_span(const_span)?;
+        let mut collector = PanicCollector {
+            encoder,
+            path_condition: vec![],
+            panics: vec![],
+        };
+        ExprWalker::walk(&mut collector, &body);
+        if collector.panics.is_empty() {
+            continue;
+        }
+
+        let mut method = vir::CfgMethod::new(format!("{}${}", name, index), 0, vec![], vec![], vec![]);
+        let mut stmts = vec![vir::Stmt::comment("Check that the inline const block does not panic")];
+        for (path_condition, panic_position) in collector.panics {
+            let pos = encoder.error_manager().register_error(
+                const_span,
+                ErrorCtxt::AssertInlineConst,
+                def_id,
+            );
+            stmts.push(vir::Stmt::Assert(vir::Assert {
+                expr: vir::Expr::not(path_condition).set_pos(panic_position),
+                position: pos,
+            }));
+        }
+        let block = method.add_block("start", stmts);
+        method.set_successor(block, vir::Successor::Return);
+        methods.push(method);
+    }
+    if methods.is_empty() {
+        return Ok(None);
+    }
+    super::definition_collector::collect_definitions(span, encoder, name, methods).map(Some)
+}
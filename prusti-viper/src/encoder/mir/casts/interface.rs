@@ -2,7 +2,7 @@ use log::{debug, trace};
 use rustc_middle::ty;
 use vir_crate::high::{self as vir_high};
 
-use crate::encoder::errors::{EncodingError, EncodingResult};
+use crate::encoder::errors::{EncodingError, EncodingResult, UnsupportedFeatureCategory};
 
 pub(crate) trait CastsEncoderInterface<'tcx> {
     fn encode_int_cast_high(
@@ -72,10 +72,13 @@ impl<'v, 'tcx: 'v> CastsEncoderInterface<'tcx> for super::super::super::Encoder<
             }
             ty::TyKind::Char => value.into(),
             kind => {
-                return Err(EncodingError::unsupported(format!(
-                    "unsupported integer cast: {:?}",
-                    kind
-                )));
+                return Err(EncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::Casts,
+                    format!(
+                        "unsupported integer cast: {:?}",
+                        kind
+                    ),
+                ));
             }
         };
         debug!("encode_int_cast {:?} as {:?} --> {:?}", value, ty, expr);
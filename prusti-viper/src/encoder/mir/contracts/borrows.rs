@@ -5,7 +5,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::{
-    encoder::errors::{EncodingError, EncodingResult},
+    encoder::errors::{EncodingError, EncodingResult, UnsupportedFeatureCategory},
     utils::type_visitor::{self, TypeVisitor},
 };
 use log::trace;
@@ -22,7 +22,8 @@ pub struct BorrowInfo<P>
 where
     P: fmt::Debug,
 {
-    /// Region of this borrow. None means static.
+    /// Region of this borrow. None means that the region is erased or
+    /// inferred; `'static` borrows have no borrow info.
     pub region: Option<ty::BoundRegionKind>,
     pub blocking_paths: Vec<(P, Mutability)>,
     pub blocked_paths: Vec<(P, Mutability)>,
@@ -42,7 +43,7 @@ impl<P: fmt::Debug> BorrowInfo<P> {
 impl<P: fmt::Debug> fmt::Display for BorrowInfo<P> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let lifetime = match self.region {
-            None => "static".to_string(),
+            None => "_".to_string(),
             Some(ty::BoundRegionKind::BrAnon(id)) => format!("#{}", id),
             Some(ty::BoundRegionKind::BrNamed(_, name)) => name.to_string(),
             _ => unimplemented!(),
@@ -138,10 +139,10 @@ impl<'tcx> TypeVisitor<'tcx> for BorrowInfoCollectingVisitor<'tcx> {
     type Error = EncodingError;
 
     fn visit_unsupported_sty(&mut self, sty: &TyKind<'tcx>) -> Result<(), Self::Error> {
-        Err(EncodingError::unsupported(format!(
-            "unsupported type {:?}",
-            sty,
-        )))
+        Err(EncodingError::unsupported_feature(
+            UnsupportedFeatureCategory::of_type(sty),
+            format!("unsupported type {:?}", sty),
+        ))
     }
 
     fn tcx(&self) -> TyCtxt<'tcx> {
@@ -183,10 +184,20 @@ impl<'tcx> TypeVisitor<'tcx> for BorrowInfoCollectingVisitor<'tcx> {
             mutability,
             self.current_path
         );
-        let bound_region = self.extract_bound_region(region);
         let is_path_blocking = self.is_path_blocking;
         let old_path = self.current_path.take().unwrap();
         let current_path = self.tcx.mk_place_deref(old_path);
+        if region.is_static() {
+            // A `'static` borrow never expires, so it does not block a path
+            // that is given back to the caller. A `&'static mut` argument is
+            // never given back, while a shared one can still be read.
+            if !is_path_blocking && mutability == Mutability::Not {
+                self.references_in.push((current_path, mutability));
+            }
+            self.current_path = Some(old_path);
+            return Ok(());
+        }
+        let bound_region = self.extract_bound_region(region);
         self.current_path = Some(current_path);
         let borrow_info = self.get_or_create_borrow_info(bound_region);
         if is_path_blocking {
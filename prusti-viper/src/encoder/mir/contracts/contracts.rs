@@ -97,6 +97,29 @@ impl<L: fmt::Debug, P: fmt::Debug> ProcedureContractGeneric<L, P> {
         }
     }
 
+    /// The assumptions of the module that contains the procedure. They are
+    /// only used when verifying the procedure itself.
+    pub fn functional_assumptions<'tcx>(
+        &self,
+        substs: SubstsRef<'tcx>,
+    ) -> Vec<(LocalDefId, SubstsRef<'tcx>)> {
+        self.specification
+            .assumptions
+            .extract_with_selective_replacement_iter()
+            .map(|assumption_def_id| (*assumption_def_id, substs))
+            .collect()
+    }
+
+    /// The name of the `#[case(...)]` attribute whose postcondition is
+    /// `post`, if that case is named.
+    pub fn case_name(&self, post: LocalDefId) -> Option<&str> {
+        self.specification
+            .cases
+            .extract_with_selective_replacement_iter()
+            .find(|case| case.post == post)
+            .and_then(|case| case.name.as_deref())
+    }
+
     pub fn pledges(&self) -> impl Iterator<Item = &typed::Pledge> + '_ {
         self.specification
             .pledges
@@ -56,7 +56,8 @@ pub(crate) trait ContractsEncoderInterface<'tcx> {
 
 #[derive(Default)]
 pub(crate) struct ContractsEncoderState<'tcx> {
-    encoded_contracts: RefCell<FxHashMap<DefId, EncodingResult<ProcedureContractMirDef<'tcx>>>>,
+    encoded_contracts:
+        RefCell<FxHashMap<(DefId, SubstsRef<'tcx>), EncodingResult<ProcedureContractMirDef<'tcx>>>>,
 }
 
 impl<'v, 'tcx: 'v> ContractsEncoderInterface<'tcx> for super::super::super::Encoder<'v, 'tcx> {
@@ -68,8 +69,8 @@ impl<'v, 'tcx: 'v> ContractsEncoderInterface<'tcx> for super::super::super::Enco
         self.contracts_encoder_state
             .encoded_contracts
             .borrow_mut()
-            .entry(proc_def_id)
-            .or_insert_with(|| get_procedure_contract(self, proc_def_id, substs))
+            .entry((proc_def_id, substs))
+            .or_insert_with(|| get_procedure_contract(self, proc_def_id, substs, None))
             .clone()
     }
 
@@ -82,10 +83,7 @@ impl<'v, 'tcx: 'v> ContractsEncoderInterface<'tcx> for super::super::super::Enco
         let (called_def_id, call_substs) =
             self.env()
                 .resolve_method_call(caller_def_id, called_def_id, call_substs);
-        // let spec = self.get_procedure_specs(called_def_id, call_substs)
-        //     .unwrap_or_else(typed::ProcedureSpecification::empty);
-        let contract = get_procedure_contract(self, called_def_id, call_substs)?;
-        Ok(contract)
+        get_procedure_contract(self, called_def_id, call_substs, Some(caller_def_id))
     }
 
     fn get_procedure_contract_for_def(
@@ -110,7 +108,8 @@ impl<'v, 'tcx: 'v> ContractsEncoderInterface<'tcx> for super::super::super::Enco
         let (called_def_id, call_substs) =
             self.env()
                 .resolve_method_call(caller_def_id, called_def_id, call_substs);
-        let contract = get_procedure_contract(self, called_def_id, call_substs)?;
+        let contract =
+            get_procedure_contract(self, called_def_id, call_substs, Some(caller_def_id))?;
         Ok(contract.to_call_site_contract(args, target))
     }
 }
@@ -119,14 +118,18 @@ fn get_procedure_contract<'p, 'v: 'p, 'tcx: 'v>(
     encoder: &'p Encoder<'v, 'tcx>,
     proc_def_id: DefId,
     substs: SubstsRef<'tcx>,
+    caller_def_id: Option<DefId>,
 ) -> EncodingResult<ProcedureContractMirDef<'tcx>> {
     use crate::rustc_middle::ty::subst::Subst;
 
     let env = encoder.env();
     let tcx = env.tcx();
-    let specification = encoder
-        .get_procedure_specs(proc_def_id, substs)
-        .unwrap_or_else(typed::ProcedureSpecification::empty);
+    let specification = if let Some(caller_def_id) = caller_def_id {
+        encoder.get_procedure_specs_for_call(proc_def_id, caller_def_id, substs)
+    } else {
+        encoder.get_procedure_specs(proc_def_id, substs)
+    }
+    .unwrap_or_else(typed::ProcedureSpecification::empty);
 
     trace!("[get_procedure_contract] enter name={:?}", proc_def_id);
 
@@ -1,7 +1,7 @@
 use crate::encoder::{
     errors::{
         EncodingError, EncodingResult, ErrorCtxt, SpannedEncodingError, SpannedEncodingResult,
-        WithSpan,
+        UnsupportedFeatureCategory, WithSpan,
     },
     high::pure_functions::HighPureFunctionEncoderInterface,
     mir::{constants::ConstantsEncoderInterface, types::MirTypeEncoderInterface},
@@ -318,7 +318,8 @@ impl<'v, 'tcx: 'v> PlacesEncoderInterface<'tcx> for super::super::super::Encoder
             mir::BinOp::BitOr if is_bool => vir_high::Expression::or(left, right),
             mir::BinOp::BitXor if is_bool => vir_high::Expression::xor(left, right),
             mir::BinOp::BitAnd | mir::BinOp::BitOr | mir::BinOp::BitXor => {
-                return Err(EncodingError::unsupported(
+                return Err(EncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::BitwiseOperations,
                     "bitwise operations on non-boolean types are not supported",
                 ))
             }
@@ -396,16 +397,20 @@ impl<'v, 'tcx: 'v> PlacesEncoderInterface<'tcx> for super::super::super::Encoder
                     ),
 
                     _ => {
-                        return Err(EncodingError::unsupported(format!(
-                            "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
-                            op, ty,
-                        )));
+                        return Err(EncodingError::unsupported_feature(
+                            UnsupportedFeatureCategory::Overflow,
+                            format!(
+                                "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
+                                op, ty,
+                            ),
+                        ));
                     }
                 },
 
                 mir::BinOp::Shl | mir::BinOp::Shr => {
                     if !config::encode_bitvectors() {
-                        return Err(EncodingError::unsupported(
+                        return Err(EncodingError::unsupported_feature(
+                            UnsupportedFeatureCategory::Overflow,
                             "overflow checks on a shift operation are unsupported",
                         ));
                     }
@@ -416,7 +421,8 @@ impl<'v, 'tcx: 'v> PlacesEncoderInterface<'tcx> for super::super::super::Encoder
                         vir_high::Type::Int(vir_high::ty::Int::U64) => 64,
                         vir_high::Type::Int(vir_high::ty::Int::U128) => 128,
                         vir_high::Type::Int(vir_high::ty::Int::Usize) => {
-                            return Err(EncodingError::unsupported(
+                            return Err(EncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Overflow,
                                 "unknown size of usize for the overflow check",
                             ));
                         }
@@ -426,15 +432,19 @@ impl<'v, 'tcx: 'v> PlacesEncoderInterface<'tcx> for super::super::super::Encoder
                         vir_high::Type::Int(vir_high::ty::Int::I64) => 64,
                         vir_high::Type::Int(vir_high::ty::Int::I128) => 128,
                         vir_high::Type::Int(vir_high::ty::Int::Isize) => {
-                            return Err(EncodingError::unsupported(
+                            return Err(EncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Overflow,
                                 "unknown size of isize for the overflow check",
                             ));
                         }
                         _ => {
-                            return Err(EncodingError::unsupported(format!(
-                                "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
-                                op, ty,
-                            )));
+                            return Err(EncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Overflow,
+                                format!(
+                                    "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
+                                    op, ty,
+                                ),
+                            ));
                         }
                     };
                     vir_high::Expression::or(
@@ -545,7 +555,8 @@ impl<'v, 'tcx: 'v> PlacesEncoderInterface<'tcx> for super::super::super::Encoder
             }
 
             _ => {
-                return Err(SpannedEncodingError::unsupported(
+                return Err(SpannedEncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::Casts,
                     format!(
                         "unsupported cast from type '{:?}' to type '{:?}'",
                         src_ty, dst_ty
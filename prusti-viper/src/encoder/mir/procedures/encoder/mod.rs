@@ -88,7 +88,13 @@ pub(super) fn encode_procedure<'v, 'tcx: 'v>(
         reachable_blocks: Default::default(),
         specification_blocks,
         specification_block_encoding: Default::default(),
-        check_panics: config::check_panics(),
+        check_panics: config::check_panics()
+            || encoder.env().has_prusti_module_attribute(def_id, "panic_free")
+            || encoder.env().has_foreign_abi(def_id),
+        check_functional_specifications: !config::only_memory_safety()
+            || encoder
+                .env()
+                .has_prusti_attribute(def_id, "functional_verification"),
         locals_without_explicit_allocation,
         fresh_id_generator: 0,
         rd_perm,
@@ -111,6 +117,10 @@ struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     /// Specifications to be inserted at the given point.
     specification_block_encoding: BTreeMap<mir::BasicBlock, Vec<vir_high::Statement>>,
     check_panics: bool,
+    /// Whether the preconditions and postconditions are used. When only
+    /// memory safety is checked, they are ignored unless the procedure has the
+    /// `#[prusti::functional_verification]` attribute.
+    check_functional_specifications: bool,
     /// Locals that are not explicitly allocated or deallocated with
     /// `StorageLive`/`StorageDead`. Such locals are assumed to be alive through
     /// the entire body of the function.
@@ -296,9 +306,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         for local in self.mir.args_iter() {
             arguments.push(self.encode_local(local)?.into());
         }
-        for expression in
-            self.encode_precondition_expressions(&procedure_contract, substs, &arguments)?
+        // The safety precondition of an `unsafe fn` is also assumed when only
+        // memory safety is checked, because it is asserted at its calls.
+        let precondition_expressions = if self.check_functional_specifications
+            || self.encoder.env().is_unsafe_function(self.def_id)
         {
+            self.encode_precondition_expressions(&procedure_contract, substs, &arguments)?
+        } else {
+            Vec::new()
+        };
+        for expression in precondition_expressions {
             let assume_statement = self.encoder.set_statement_error_ctxt(
                 vir_high::Statement::assume_no_pos(expression),
                 mir_span,
@@ -318,13 +335,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             "Assert functional postconditions.".to_string(),
         )];
         let result: vir_high::Expression = self.encode_local(mir::RETURN_PLACE)?.into();
-        for expression in self.encode_postcondition_expressions(
-            &procedure_contract,
-            substs,
-            arguments,
-            &result,
-            PRECONDITION_LABEL,
-        )? {
+        let postcondition_expressions = if self.check_functional_specifications {
+            self.encode_postcondition_expressions(
+                &procedure_contract,
+                substs,
+                arguments,
+                &result,
+                PRECONDITION_LABEL,
+            )?
+        } else {
+            Vec::new()
+        };
+        for expression in postcondition_expressions {
             let assert_statement = self.encoder.set_statement_error_ctxt(
                 vir_high::Statement::assert_no_pos(expression),
                 mir_span,
@@ -484,6 +506,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 )?);
             }
             mir::StatementKind::Assign(box (target, source)) => {
+                self.check_no_raw_pointer_dereference(location, statement)?;
                 let position = self.register_error(location, ErrorCtxt::Unexpected);
                 let encoded_target = self
                     .encoder
@@ -498,6 +521,51 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(())
     }
 
+    /// Report an unsupported feature if the statement dereferences a raw
+    /// pointer. The validity of a pointer cannot be specified yet, so a
+    /// pointer (e.g. one received from C) never gives access to the memory it
+    /// points to.
+    fn check_no_raw_pointer_dereference(
+        &self,
+        location: mir::Location,
+        statement: &mir::Statement<'tcx>,
+    ) -> SpannedEncodingResult<()> {
+        struct RawPointerDereferenceVisitor<'p, 'tcx> {
+            mir: &'p mir::Body<'tcx>,
+            tcx: ty::TyCtxt<'tcx>,
+            found: bool,
+        }
+        impl<'p, 'tcx> mir::visit::Visitor<'tcx> for RawPointerDereferenceVisitor<'p, 'tcx> {
+            fn visit_place(
+                &mut self,
+                place: &mir::Place<'tcx>,
+                _context: mir::visit::PlaceContext,
+                _location: mir::Location,
+            ) {
+                for (base, element) in place.iter_projections() {
+                    if matches!(element, mir::ProjectionElem::Deref)
+                        && base.ty(self.mir, self.tcx).ty.is_unsafe_ptr()
+                    {
+                        self.found = true;
+                    }
+                }
+            }
+        }
+        let mut visitor = RawPointerDereferenceVisitor {
+            mir: self.mir,
+            tcx: self.encoder.env().tcx(),
+            found: false,
+        };
+        mir::visit::Visitor::visit_statement(&mut visitor, statement, location);
+        if visitor.found {
+            return Err(SpannedEncodingError::unsupported(
+                "dereferencing a raw pointer is not supported",
+                self.encoder.get_mir_location_span(self.mir, location),
+            ));
+        }
+        Ok(())
+    }
+
     fn encode_statement_assign(
         &mut self,
         block_builder: &mut BasicBlockBuilder,
@@ -1336,13 +1404,24 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .get_mir_procedure_contract_for_call(self.def_id, called_def_id, call_substs)
             .with_span(span)?;
 
-        for expression in
+        // The precondition of an `unsafe fn` is the safety contract that the
+        // caller has to uphold, also when only memory safety is checked.
+        let is_unsafe_function = self.encoder.env().is_unsafe_function(called_def_id);
+        let precondition_error_ctxt = if is_unsafe_function {
+            ErrorCtxt::AssertUnsafeFunctionSafetyPrecondition
+        } else {
+            ErrorCtxt::ExhaleMethodPrecondition
+        };
+        let precondition_expressions = if self.check_functional_specifications || is_unsafe_function {
             self.encode_precondition_expressions(&procedure_contract, call_substs, &arguments)?
-        {
+        } else {
+            Vec::new()
+        };
+        for expression in precondition_expressions {
             let assert_statement = self.encoder.set_statement_error_ctxt(
                 vir_high::Statement::assert_no_pos(expression),
                 span,
-                ErrorCtxt::ExhaleMethodPrecondition,
+                precondition_error_ctxt.clone(),
                 self.def_id,
             )?;
             block_builder.add_statement(assert_statement);
@@ -1361,13 +1440,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 .encoder
                 .encode_place_high(self.mir, *target_place)?
                 .set_default_position(position);
-            let postcondition_expressions = self.encode_postcondition_expressions(
-                &procedure_contract,
-                call_substs,
-                arguments.clone(),
-                &encoded_target_place,
-                &old_label,
-            )?;
+            let postcondition_expressions = if self.check_functional_specifications {
+                self.encode_postcondition_expressions(
+                    &procedure_contract,
+                    call_substs,
+                    arguments.clone(),
+                    &encoded_target_place,
+                    &old_label,
+                )?
+            } else {
+                Vec::new()
+            };
             if let Some(target_place_local) = target_place.as_local() {
                 let size = self.encoder.encode_type_size_expression(
                     self.encoder.get_local_type(self.mir, target_place_local)?,
@@ -5,7 +5,10 @@ pub(super) mod state;
 use self::state::ExprBackwardInterpreterState;
 use super::PureEncodingContext;
 use crate::encoder::{
-    errors::{EncodingResult, ErrorCtxt, SpannedEncodingError, SpannedEncodingResult, WithSpan},
+    errors::{
+        EncodingResult, ErrorCtxt, SpannedEncodingError, SpannedEncodingResult,
+        UnsupportedFeatureCategory, WithSpan,
+    },
     high::{
         builtin_functions::{BuiltinFunctionHighKind, HighBuiltinFunctionEncoderInterface},
         pure_functions::HighPureFunctionEncoderInterface,
@@ -233,7 +236,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ExpressionBackwardInterpreter<'p, 'v, 'tcx> {
             }
             mir::Rvalue::Ref(_, mir::BorrowKind::Unique, place)
             | mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, place)
-            | mir::Rvalue::Ref(_, mir::BorrowKind::Shared, place) => {
+            | mir::Rvalue::Ref(_, mir::BorrowKind::Shared, place)
+            // The fake borrows of the scrutinee of a `match` with guards, e.g. of
+            // `matches!(&self.state, State::Closed(n) if *n > 0)`.
+            | mir::Rvalue::Ref(_, mir::BorrowKind::Shallow, place) => {
                 let encoded_place = self.encoder.encode_place_high(self.mir, *place)?;
                 let ty = self
                     .encoder
@@ -280,14 +286,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> ExpressionBackwardInterpreter<'p, 'v, 'tcx> {
                         .with_span(span)?;
                     state.substitute_value(&encoded_lhs, expr);
                 } else {
-                    return Err(SpannedEncodingError::unsupported(
+                    return Err(SpannedEncodingError::unsupported_feature(
+                        UnsupportedFeatureCategory::Slicing,
                         format!("unsizing a {} into a {} is not supported", rhs_ty, cast_ty),
                         span,
                     ));
                 }
             }
             mir::Rvalue::Cast(kind, _, _) => {
-                return Err(SpannedEncodingError::unsupported(
+                return Err(SpannedEncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::Casts,
                     format!("unsupported kind of cast: {:?}", kind),
                     span,
                 ));
@@ -506,6 +514,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ExpressionBackwardInterpreter<'p, 'v, 'tcx> {
                     // TODO: check we are in a spec function
                     "prusti_contracts::exists"
                     | "prusti_contracts::forall"
+                    | "prusti_contracts::count"
                     | "prusti_contracts::specification_entailment"
                     | "prusti_contracts::call_description" => {
                         let expr = self.encoder.encode_prusti_operation_high(
@@ -0,0 +1,96 @@
+//! Check that the result of a pure function depends only on its arguments.
+//!
+//! Calls of impure functions are rejected while interpreting the body, so here
+//! we only look for reads of mutable statics and for values with interior
+//! mutability.
+
+use crate::encoder::errors::{SpannedEncodingError, SpannedEncodingResult};
+use rustc_hash::FxHashSet;
+use rustc_middle::{
+    mir::{self, visit::Visitor},
+    ty::{self, Ty, TyCtxt},
+};
+
+/// Report the first source of non-determinism of the pure function `body`.
+pub(super) fn check_determinism<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mir::Body<'tcx>,
+) -> SpannedEncodingResult<()> {
+    let mut checker = DeterminismChecker { tcx, error: None };
+    checker.visit_body(body);
+    match checker.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+struct DeterminismChecker<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    error: Option<SpannedEncodingError>,
+}
+
+impl<'tcx> Visitor<'tcx> for DeterminismChecker<'tcx> {
+    fn visit_constant(&mut self, constant: &mir::Constant<'tcx>, location: mir::Location) {
+        self.super_constant(constant, location);
+        if self.error.is_some() {
+            return;
+        }
+        if let Some(def_id) = constant.check_static_ptr(self.tcx) {
+            if self.tcx.is_mutable_static(def_id) {
+                self.error = Some(SpannedEncodingError::incorrect(
+                    format!(
+                        "pure functions cannot read the mutable static `{}`",
+                        self.tcx.def_path_str(def_id),
+                    ),
+                    constant.span,
+                ));
+            }
+        }
+    }
+
+    fn visit_local_decl(&mut self, local: mir::Local, local_decl: &mir::LocalDecl<'tcx>) {
+        self.super_local_decl(local, local_decl);
+        if self.error.is_some() {
+            return;
+        }
+        if has_interior_mutability(self.tcx, local_decl.ty, &mut FxHashSet::default()) {
+            self.error = Some(SpannedEncodingError::incorrect(
+                format!(
+                    "pure functions cannot use values with interior mutability, such as `{}`",
+                    local_decl.ty,
+                ),
+                local_decl.source_info.span,
+            ));
+        }
+    }
+}
+
+/// Whether a value of type `ty` contains an `UnsafeCell`, possibly behind
+/// references or boxes. Raw pointers and type parameters are not followed.
+fn has_interior_mutability<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ty: Ty<'tcx>,
+    visited: &mut FxHashSet<Ty<'tcx>>,
+) -> bool {
+    if !visited.insert(ty) {
+        return false;
+    }
+    match ty.kind() {
+        ty::TyKind::Adt(adt_def, _) if tcx.lang_items().unsafe_cell_type() == Some(adt_def.did()) => {
+            true
+        }
+        ty::TyKind::Adt(adt_def, _) if adt_def.is_box() => {
+            has_interior_mutability(tcx, ty.boxed_ty(), visited)
+        }
+        ty::TyKind::Adt(adt_def, substs) => adt_def
+            .all_fields()
+            .any(|field| has_interior_mutability(tcx, field.ty(tcx, substs), visited)),
+        ty::TyKind::Ref(_, ty, _) | ty::TyKind::Array(ty, _) | ty::TyKind::Slice(ty) => {
+            has_interior_mutability(tcx, *ty, visited)
+        }
+        ty::TyKind::Tuple(elems) => elems
+            .iter()
+            .any(|ty| has_interior_mutability(tcx, ty, visited)),
+        _ => false,
+    }
+}
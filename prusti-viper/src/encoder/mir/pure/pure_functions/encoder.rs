@@ -4,7 +4,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use super::interpreter::PureFunctionBackwardInterpreter;
+use super::{determinism::check_determinism, interpreter::PureFunctionBackwardInterpreter};
 use crate::encoder::{
     errors::{ErrorCtxt, SpannedEncodingError, SpannedEncodingResult, WithSpan},
     high::{generics::HighGenericsEncoderInterface, types::HighTypeEncoderInterface},
@@ -58,7 +58,9 @@ pub(super) fn encode_body<'p, 'v: 'p, 'tcx: 'v>(
     parent_def_id: DefId,
     substs: SubstsRef<'tcx>,
 ) -> SpannedEncodingResult<vir::Expr> {
-    let mir = encoder.env().local_mir(proc_def_id.expect_local(), substs);
+    let mir = encoder
+        .env()
+        .local_mir_in_caller_env(proc_def_id.expect_local(), substs, parent_def_id);
     let interpreter = PureFunctionBackwardInterpreter::new(
         encoder,
         &mir,
@@ -128,6 +130,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionEncoder<'p, 'v, 'tcx> {
             .encoder
             .env()
             .local_mir(self.proc_def_id.expect_local(), self.substs);
+        check_determinism(self.encoder.env().tcx(), &mir)?;
         let interpreter = PureFunctionBackwardInterpreter::new(
             self.encoder,
             &mir,
@@ -1,7 +1,10 @@
 use super::interface::PureFunctionEncoderInterface;
 use crate::encoder::{
     builtin_encoder::BuiltinFunctionKind,
-    errors::{EncodingResult, ErrorCtxt, SpannedEncodingError, SpannedEncodingResult, WithSpan},
+    errors::{
+        EncodingResult, ErrorCtxt, SpannedEncodingError, SpannedEncodingResult,
+        UnsupportedFeatureCategory, WithSpan,
+    },
     high::{
         builtin_functions::HighBuiltinFunctionEncoderInterface,
         generics::HighGenericsEncoderInterface, types::HighTypeEncoderInterface,
@@ -12,11 +15,15 @@ use crate::encoder::{
         specifications::SpecificationsInterface,
         types::MirTypeEncoderInterface,
     },
-    mir_encoder::{MirEncoder, PlaceEncoder, PlaceEncoding, PRECONDITION_LABEL, WAND_LHS_LABEL},
+    mir_encoder::{
+        MirEncoder, PlaceEncoder, PlaceEncoding, LOOP_ITERATIONS_PLACEHOLDER, PRECONDITION_LABEL,
+        WAND_LHS_LABEL,
+    },
     mir_interpreter::{BackwardMirInterpreter, ExprBackwardInterpreterState},
     snapshot::interface::SnapshotEncoderInterface,
     Encoder,
 };
+use crate::utils::{is_sequence_ty, pinned_reference_ty};
 use log::{debug, trace};
 use prusti_common::vir_local;
 use prusti_interface::environment::mir_utils::SliceOrArrayRef;
@@ -53,7 +60,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionBackwardInterpreter<'p, 'v, 'tcx> {
         PureFunctionBackwardInterpreter {
             encoder,
             mir,
-            mir_encoder: MirEncoder::new(encoder, mir, def_id),
+            mir_encoder: MirEncoder::new_pure(encoder, mir, def_id),
             pure_encoding_context,
             caller_def_id,
             def_id,
@@ -469,6 +476,23 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state
                             }
 
+                            "std::default::Default::default" | "core::default::Default::default"
+                                if !self.encoder.is_pure(
+                                    self.encoder
+                                        .env()
+                                        .resolve_method_call(self.def_id, def_id, call_substs)
+                                        .0,
+                                    Some(call_substs),
+                                ) =>
+                            {
+                                assert!(args.is_empty());
+                                let encoded_rhs =
+                                    self.encoder.encode_default_value(ty).with_span(span)?;
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, encoded_rhs);
+                                state
+                            }
+
                             "core::slice::<impl [T]>::len" => {
                                 assert_eq!(args.len(), 1);
                                 let slice_ty = self.mir_encoder.get_operand_ty(&args[0]);
@@ -482,7 +506,52 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state
                             }
 
-                            "std::ops::Index::index" | "core::ops::Index::index" => {
+                            "core::str::<impl str>::len" => {
+                                assert_eq!(args.len(), 1);
+                                let str_ty = self.mir_encoder.get_operand_ty(&args[0]);
+                                let len = self
+                                    .encoder
+                                    .encode_snapshot_str_len(str_ty, encoded_args[0].clone())
+                                    .with_span(span)?;
+
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, len);
+                                state
+                            }
+
+                            "std::ops::Deref::deref" | "core::ops::Deref::deref"
+                                if pinned_reference_ty(
+                                    tcx,
+                                    self.mir_encoder.get_operand_ty(&args[0]).peel_refs(),
+                                )
+                                .is_some() =>
+                            {
+                                // A `Pin<&mut T>` is encoded as a struct that wraps the
+                                // mutable reference, so dereferencing it reads through the
+                                // wrapped reference. The methods that give access to the
+                                // pointee by value (e.g. `Pin::get_mut`) are not pure and
+                                // thus cannot move the pointee in a specification.
+                                assert_eq!(args.len(), 1);
+                                let pin_ty = self.mir_encoder.get_operand_ty(&args[0]).peel_refs();
+                                let reference_ty = pinned_reference_ty(tcx, pin_ty).unwrap();
+                                let pin = encoded_args[0].clone().field(
+                                    self.encoder.encode_dereference_field(pin_ty).with_span(span)?,
+                                );
+                                let encoded_rhs = pin.field(
+                                    self.encoder
+                                        .encode_struct_field("pointer", reference_ty)
+                                        .with_span(span)?,
+                                );
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, encoded_rhs);
+                                state
+                            }
+
+                            "std::ops::Index::index" | "core::ops::Index::index"
+                                if is_sequence_ty(
+                                    self.mir_encoder.get_operand_ty(&args[0]).peel_refs(),
+                                ) =>
+                            {
                                 assert_eq!(args.len(), 2);
                                 trace!("slice::index(args={:?}, encoded_args={:?}, ty={:?}, encoded_lhs={:?})", args, encoded_args, ty, encoded_lhs);
 
@@ -511,7 +580,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                         self.encoder.encode_struct_field_value(encoded_idx.clone(), "start", usize_ty).with_span(span)?,
                                     // See procedure_encoder.rs
                                     "std::ops::RangeInclusive" | "core::ops::RangeInclusive" => return Err(
-                                        SpannedEncodingError::unsupported("slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string(), span)
+                                        SpannedEncodingError::unsupported_feature(UnsupportedFeatureCategory::Slicing, "slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string(), span)
                                     ),
                                     "std::ops::RangeTo" | "core::ops::RangeTo" |
                                     "std::ops::RangeFull" | "core::ops::RangeFull" |
@@ -523,7 +592,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                     "std::ops::RangeTo" | "core::ops::RangeTo" =>
                                         self.encoder.encode_struct_field_value(encoded_idx.clone(), "end", usize_ty).with_span(span)?,
                                     "std::ops::RangeInclusive" | "core::ops::RangeInclusive" => return Err(
-                                        SpannedEncodingError::unsupported("slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string(), span)
+                                        SpannedEncodingError::unsupported_feature(UnsupportedFeatureCategory::Slicing, "slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string(), span)
                                     ),
                                     "std::ops::RangeToInclusive" | "core::ops::RangeToInclusive" => {
                                         let end_expr = self.encoder.encode_struct_field_value(encoded_idx.clone(), "end", usize_ty).with_span(span)?;
@@ -560,11 +629,109 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state
                             }
 
+                            "prusti_contracts::is_permutation" => {
+                                assert_eq!(args.len(), 2);
+                                let seq_ty = self.mir_encoder.get_operand_ty(&args[0]);
+                                let expr = self
+                                    .encoder
+                                    .encode_snapshot_permutation(
+                                        seq_ty,
+                                        encoded_args[0].clone(),
+                                        encoded_args[1].clone(),
+                                    )
+                                    .with_span(span)?;
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, expr);
+                                state
+                            }
+
+                            "prusti_contracts::snap_eq" => {
+                                assert_eq!(args.len(), 2);
+                                let encoded_rhs = vir::Expr::eq_cmp(
+                                    vir::Expr::snap_app(encoded_args[0].clone()),
+                                    vir::Expr::snap_app(encoded_args[1].clone()),
+                                );
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, encoded_rhs);
+                                state
+                            }
+
+                            "prusti_contracts::has_flag" => {
+                                assert_eq!(args.len(), 2);
+                                let expr = self
+                                    .mir_encoder
+                                    .encode_has_flag(
+                                        encoded_args[0].clone(),
+                                        self.mir_encoder.get_operand_ty(&args[0]),
+                                        encoded_args[1].clone(),
+                                        self.mir_encoder.get_operand_ty(&args[1]),
+                                    )
+                                    .with_span(span)?;
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, expr);
+                                state
+                            }
+
+                            "prusti_contracts::borrows_from" => {
+                                // The lifetimes of the result and of the
+                                // argument are checked by the specification
+                                // checker.
+                                assert_eq!(args.len(), 2);
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, true.into());
+                                state
+                            }
+
+                            "prusti_contracts::loop_iterations" => {
+                                // Replaced by the counter of the enclosing loop
+                                // when the loop specification is encoded.
+                                assert!(args.is_empty());
+                                let counter = vir::LocalVar::new(
+                                    LOOP_ITERATIONS_PLACEHOLDER,
+                                    vir::Type::Int,
+                                );
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, vir::Expr::local(counter));
+                                state
+                            }
+
+                            "prusti_contracts::inactive_variant_field" => {
+                                // The projection `e.Variant.field` is only
+                                // defined if `e` is of the variant.
+                                assert!(args.is_empty());
+                                let encoded_type = self
+                                    .encoder
+                                    .encode_snapshot_type(ty)
+                                    .with_span(span)?;
+                                let (function_name, type_arguments) = self
+                                    .encoder
+                                    .encode_builtin_function_use(BuiltinFunctionKind::Unreachable(
+                                        encoded_type.clone(),
+                                    ));
+                                let pos = self.encoder.error_manager().register_error(
+                                    span,
+                                    ErrorCtxt::InactiveVariantField,
+                                    self.caller_def_id,
+                                );
+                                let encoded_rhs = vir::Expr::func_app(
+                                    function_name,
+                                    type_arguments,
+                                    vec![],
+                                    vec![],
+                                    encoded_type,
+                                    pos,
+                                );
+                                let mut state = states[target_block].clone();
+                                state.substitute_value(&encoded_lhs, encoded_rhs);
+                                state
+                            }
+
                             // Prusti-specific syntax
                             // TODO: check we are in a spec function
                             "prusti_contracts::implication"
                             | "prusti_contracts::exists"
                             | "prusti_contracts::forall"
+                            | "prusti_contracts::count"
                             | "prusti_contracts::specification_entailment"
                             | "prusti_contracts::call_description" => {
                                 let expr = self.encoder.encode_prusti_operation(
@@ -597,6 +764,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                             call_substs,
                                         )
                                         .with_span(term.source_info.span)?
+                                } else if tcx.trait_of_item(def_id).is_some()
+                                    && tcx.trait_of_item(def_id) == tcx.lang_items().deref_trait()
+                                {
+                                    // An auto-deref in a specification, e.g. `wrapper.field`,
+                                    // calls the `Deref` implementation of the wrapper.
+                                    let self_ty = self.mir_encoder.get_operand_ty(&args[0]).peel_refs();
+                                    return Err(SpannedEncodingError::incorrect(
+                                        format!(
+                                            "the `Deref` implementation of `{}` must be `#[pure]` \
+                                            to dereference it in pure code",
+                                            self_ty,
+                                        ),
+                                        term.source_info.span,
+                                    ));
                                 } else {
                                     return Err(SpannedEncodingError::incorrect(
                                         format!(
@@ -910,7 +1091,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 for (field_index, field) in variant_def.fields.iter().enumerate() {
                                     let operand = &operands[field_index];
                                     let field_name = field.ident(tcx).to_string();
-                                    let field_ty = field.ty(tcx, subst);
+                                    let field_ty = self.encoder.env().field_ty(field, subst);
                                     let encoded_field = self.encoder
                                         .encode_struct_field(&field_name, field_ty)
                                         .with_span(span)?;
@@ -1120,7 +1301,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
 
                     mir::Rvalue::Ref(_, mir::BorrowKind::Unique, ref place)
                     | mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, ref place)
-                    | mir::Rvalue::Ref(_, mir::BorrowKind::Shared, ref place) => {
+                    | mir::Rvalue::Ref(_, mir::BorrowKind::Shared, ref place)
+                    // The fake borrows of the scrutinee of a `match` with guards, e.g. of
+                    // `matches!(&self.state, State::Closed(n) if *n > 0)`.
+                    | mir::Rvalue::Ref(_, mir::BorrowKind::Shallow, ref place) => {
                         let (encoded_place, _, _) = self.encode_place(place).with_span(span)?;
                         // TODO: Instead of generating an `AddrOf(..)` expression, here we could
                         // generate a shapshot representing a reference. If we do so, we should
@@ -1192,7 +1376,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             );
                             state.substitute_value(&opt_lhs_value_place.unwrap(), unsize_func);
                         } else {
-                            return Err(SpannedEncodingError::unsupported(
+                            return Err(SpannedEncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Slicing,
                                 format!("unsizing a {} into a {} is not supported", rhs_ref_ty, lhs_ref_ty),
                                 span,
                             ));
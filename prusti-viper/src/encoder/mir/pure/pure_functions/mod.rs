@@ -1,5 +1,6 @@
 //! Encoder of pure functions.
 
+mod determinism;
 mod encoder;
 mod interface;
 mod interpreter;
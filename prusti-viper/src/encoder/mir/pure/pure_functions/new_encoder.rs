@@ -39,10 +39,18 @@ pub(super) fn encode_function_decl<'p, 'v: 'p, 'tcx: 'v>(
     );
     let function_decl = pure_encoder.encode_function_decl()?;
     if function_decl.body.is_some() {
-        // Check that function does not call itself in its contract.
+        // Check that function does not call itself in its contract. The
+        // precondition must not mention the function at all, and the
+        // postcondition may only call it with other arguments than its own
+        // parameters, e.g. to state a recursive definition. Such calls are
+        // checked against the body when verifying the function, while a call
+        // with its own parameters would define the function circularly.
         let span = encoder.env().get_def_span(proc_def_id);
         struct CallFinder<'a> {
             function_name: &'a str,
+            /// The parameters of the function if only the calls with these
+            /// arguments are forbidden, or `None` if all calls are forbidden.
+            forbidden_arguments: Option<&'a [vir_high::VariableDecl]>,
             span: Span,
         }
         impl<'a> vir_high::visitors::ExpressionFallibleWalker for CallFinder<'a> {
@@ -52,10 +60,29 @@ pub(super) fn encode_function_decl<'p, 'v: 'p, 'tcx: 'v>(
                 func_app: &vir_high::FuncApp,
             ) -> Result<(), Self::Error> {
                 if func_app.function_name == self.function_name {
-                    return Err(SpannedEncodingError::incorrect(
-                        "only trusted functions can call themselves in their contracts".to_string(),
-                        self.span,
-                    ));
+                    let is_forbidden = match self.forbidden_arguments {
+                        None => true,
+                        Some(parameters) => {
+                            func_app.arguments.len() == parameters.len()
+                                && func_app.arguments.iter().zip(parameters).all(
+                                    |(argument, parameter)| {
+                                        matches!(
+                                            argument,
+                                            vir_high::Expression::Local(vir_high::Local { variable, .. })
+                                                if variable == parameter
+                                        )
+                                    },
+                                )
+                        }
+                    };
+                    if is_forbidden {
+                        return Err(SpannedEncodingError::incorrect(
+                            "only trusted functions can call themselves in their preconditions, \
+                            or with their own arguments in their postconditions"
+                                .to_string(),
+                            self.span,
+                        ));
+                    }
                 }
                 vir_high::visitors::default_fallible_walk_func_app(self, func_app)
             }
@@ -63,8 +90,16 @@ pub(super) fn encode_function_decl<'p, 'v: 'p, 'tcx: 'v>(
         let mut finder = CallFinder {
             span,
             function_name: &function_decl.name,
+            forbidden_arguments: None,
         };
-        for expr in function_decl.pres.iter().chain(&function_decl.posts) {
+        for expr in &function_decl.pres {
+            vir_high::visitors::ExpressionFallibleWalker::fallible_walk_expression(
+                &mut finder,
+                expr,
+            )?;
+        }
+        finder.forbidden_arguments = Some(&function_decl.parameters);
+        for expr in &function_decl.posts {
             vir_high::visitors::ExpressionFallibleWalker::fallible_walk_expression(
                 &mut finder,
                 expr,
@@ -5,7 +5,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::encoder::{
-    errors::{EncodingError, EncodingResult, SpannedEncodingResult, WithSpan},
+    errors::{EncodingError, EncodingResult, SpannedEncodingError, SpannedEncodingResult, WithSpan},
     high::types::HighTypeEncoderInterface,
     mir::{
         pure::{specifications::utils::extract_closure_from_ty, PureFunctionEncoderInterface},
@@ -34,7 +34,9 @@ pub(super) fn inline_closure<'tcx>(
     parent_def_id: DefId,
     substs: SubstsRef<'tcx>,
 ) -> SpannedEncodingResult<vir_crate::polymorphic::Expr> {
-    let mir = encoder.env().local_mir(def_id.expect_local(), substs);
+    let mir = encoder
+        .env()
+        .local_mir_in_caller_env(def_id.expect_local(), substs, parent_def_id);
     assert_eq!(mir.arg_count, args.len() + 1);
     let mir_encoder = MirEncoder::new(encoder, &mir, def_id);
     let mut body_replacements = vec![];
@@ -71,7 +73,9 @@ pub(super) fn inline_spec_item<'tcx>(
 ) -> SpannedEncodingResult<vir_crate::polymorphic::Expr> {
     assert_eq!(substs.len(), encoder.env().identity_substs(def_id).len());
 
-    let mir = encoder.env().local_mir(def_id.expect_local(), substs);
+    let mir = encoder
+        .env()
+        .local_mir_in_caller_env(def_id.expect_local(), substs, parent_def_id);
     assert_eq!(
         mir.arg_count,
         target_args.len() + if target_return.is_some() { 1 } else { 0 }
@@ -259,6 +263,289 @@ pub(super) fn encode_quantifier<'tcx>(
     }
 }
 
+pub(super) fn encode_count<'tcx>(
+    encoder: &Encoder<'_, 'tcx>,
+    _span: Span,
+    encoded_args: Vec<vir_crate::polymorphic::Expr>,
+    parent_def_id: DefId,
+    substs: ty::subst::SubstsRef<'tcx>,
+) -> SpannedEncodingResult<vir_crate::polymorphic::Expr> {
+    use vir_crate::polymorphic as vir;
+    let tcx = encoder.env().tcx();
+
+    // Counts are encoded as:
+    //   count(|qvar| -> bool { <body expr> })
+    // The body must bound the counted integer, e.g. `0 <= i && i < n && ...`,
+    // and the count is the result of a recursive function over that range.
+
+    let (body_def_id, body_substs, body_span, args, _) =
+        extract_closure_from_ty(tcx, substs.type_at(0));
+    let arg_ty = match args.as_slice() {
+        [arg_ty] if arg_ty.is_integral() => *arg_ty,
+        _ => {
+            return Err(SpannedEncodingError::incorrect(
+                "`count` must have exactly one argument of an integer type",
+                body_span,
+            ))
+        }
+    };
+    let qvar = vir::LocalVar::new(
+        format!("_0_count_{}", encoder.encode_item_name(body_def_id)),
+        encoder.encode_snapshot_type(arg_ty).with_span(body_span)?,
+    );
+    let encoded_body = inline_closure(
+        encoder,
+        body_def_id,
+        encoded_args[0].clone(),
+        vec![qvar.clone()],
+        parent_def_id,
+        body_substs,
+    )?;
+    // Patch the snapshots first, so that the parameters have snapshot types.
+    let encoded_body = encoder
+        .patch_snapshots(encoded_body)
+        .with_span(body_span)?;
+
+    let qvar_expr = vir::Expr::local(qvar.clone());
+    let (lower, upper, encoded_body) = split_count_range(&qvar_expr, encoded_body);
+    let lower = match lower {
+        Some(lower) => lower,
+        None if matches!(arg_ty.kind(), ty::TyKind::Uint(_)) => 0.into(),
+        None => {
+            return Err(SpannedEncodingError::incorrect(
+                "the condition of `count` must bound its argument from below, e.g. with `0 <= i`",
+                body_span,
+            ))
+        }
+    };
+    let upper = upper.ok_or_else(|| {
+        SpannedEncodingError::incorrect(
+            "the condition of `count` must bound its argument from above, e.g. with `i < n`",
+            body_span,
+        )
+    })?;
+
+    // The function that counts cannot refer to the variables of the
+    // specification, so the subexpressions that do not depend on the counted
+    // integer become its parameters.
+    let mut abstractor = FreeExpressionAbstractor {
+        bound: vec![qvar_expr],
+        parameters: vec![],
+    };
+    let condition = vir::ExprFolder::fold(&mut abstractor, encoded_body);
+    if abstractor
+        .parameters
+        .iter()
+        .any(|(_, parameter)| parameter.typ.is_typed_ref())
+    {
+        return Err(SpannedEncodingError::unsupported(
+            "the condition of `count` can only access the memory through snapshots, e.g. of slices",
+            body_span,
+        ));
+    }
+    let (mut arguments, parameters): (Vec<_>, Vec<_>) = abstractor.parameters.into_iter().unzip();
+    let function_name = encoder
+        .encode_count_function_use(parameters.clone(), qvar, condition)
+        .with_span(body_span)?;
+    let mut formal_arguments = parameters;
+    formal_arguments.push(vir::LocalVar::new("lower", vir::Type::Int));
+    formal_arguments.push(vir::LocalVar::new("upper", vir::Type::Int));
+    arguments.push(lower);
+    arguments.push(upper);
+    Ok(vir::Expr::func_app(
+        function_name,
+        vec![],
+        arguments,
+        formal_arguments,
+        vir::Type::Int,
+        vir::Position::default(),
+    ))
+}
+
+/// Find the bounds `lower <= qvar` and `qvar < upper` among the conjuncts of
+/// the condition of `count`, and remove them from the condition. If there are
+/// several bounds on the same side, the first one is used and the others are
+/// kept in the condition. This way,
+/// the counts of ranges with different bounds use the same function.
+fn split_count_range(
+    qvar: &vir_crate::polymorphic::Expr,
+    condition: vir_crate::polymorphic::Expr,
+) -> (
+    Option<vir_crate::polymorphic::Expr>,
+    Option<vir_crate::polymorphic::Expr>,
+    vir_crate::polymorphic::Expr,
+) {
+    use vir_crate::polymorphic::{self as vir, BinaryOpKind, ExprFolder};
+    fn conjuncts<'a>(expr: &'a vir::Expr, result: &mut Vec<&'a vir::Expr>) {
+        match expr {
+            vir::Expr::BinOp(vir::BinOp {
+                op_kind: BinaryOpKind::And,
+                left,
+                right,
+                ..
+            }) => {
+                conjuncts(left, result);
+                conjuncts(right, result);
+            }
+            // Lazy conjunctions are encoded as `a ? b : false`.
+            vir::Expr::Cond(vir::Cond {
+                guard,
+                then_expr,
+                else_expr: box vir::Expr::Const(vir::ConstExpr {
+                    value: vir::Const::Bool(false),
+                    ..
+                }),
+                ..
+            }) => {
+                conjuncts(guard, result);
+                conjuncts(then_expr, result);
+            }
+            _ => result.push(expr),
+        }
+    }
+    struct BoundsRemover<'a> {
+        bounds: &'a [vir::Expr],
+    }
+    impl<'a> ExprFolder for BoundsRemover<'a> {
+        fn fold(&mut self, expr: vir::Expr) -> vir::Expr {
+            if self.bounds.contains(&expr) {
+                true.into()
+            } else {
+                vir::default_fold_expr(self, expr)
+            }
+        }
+    }
+    let plus_one = |expr: &vir::Expr| vir::Expr::add(expr.clone(), 1.into());
+    let mut exprs = vec![];
+    conjuncts(&condition, &mut exprs);
+    let (mut lower, mut upper) = (None, None);
+    let mut bounds = vec![];
+    for expr in exprs {
+        if let vir::Expr::BinOp(vir::BinOp {
+            op_kind,
+            left,
+            right,
+            ..
+        }) = expr
+        {
+            let (left, right) = (left.as_ref(), right.as_ref());
+            let (is_lower, bound) = match op_kind {
+                BinaryOpKind::LeCmp if right == qvar && !left.find(qvar) => (true, left.clone()),
+                BinaryOpKind::GeCmp if left == qvar && !right.find(qvar) => (true, right.clone()),
+                BinaryOpKind::LtCmp if right == qvar && !left.find(qvar) => (true, plus_one(left)),
+                BinaryOpKind::GtCmp if left == qvar && !right.find(qvar) => (true, plus_one(right)),
+                BinaryOpKind::LtCmp if left == qvar && !right.find(qvar) => (false, right.clone()),
+                BinaryOpKind::GtCmp if right == qvar && !left.find(qvar) => (false, left.clone()),
+                BinaryOpKind::LeCmp if left == qvar && !right.find(qvar) => (false, plus_one(right)),
+                BinaryOpKind::GeCmp if right == qvar && !left.find(qvar) => (false, plus_one(left)),
+                _ => continue,
+            };
+            // Only the first bound of each side delimits the range. Any other
+            // bound stays in the condition, which still restricts the count.
+            let range_bound = if is_lower { &mut lower } else { &mut upper };
+            if range_bound.is_none() {
+                *range_bound = Some(bound);
+                bounds.push(expr.clone());
+            }
+        }
+    }
+    let condition = BoundsRemover { bounds: &bounds }.fold(condition);
+    (lower, upper, condition)
+}
+
+/// Replaces the maximal subexpressions that do not mention the bound
+/// variables, except for constants, by parameters.
+struct FreeExpressionAbstractor {
+    bound: Vec<vir_crate::polymorphic::Expr>,
+    parameters: Vec<(vir_crate::polymorphic::Expr, vir_crate::polymorphic::LocalVar)>,
+}
+
+impl vir_crate::polymorphic::ExprFolder for FreeExpressionAbstractor {
+    fn fold(&mut self, expr: vir_crate::polymorphic::Expr) -> vir_crate::polymorphic::Expr {
+        use vir_crate::polymorphic as vir;
+        if expr.is_constant() || self.bound.iter().any(|bound| expr.find(bound)) {
+            return vir::default_fold_expr(self, expr);
+        }
+        if let Some((_, parameter)) = self.parameters.iter().find(|(free, _)| free == &expr) {
+            return vir::Expr::local(parameter.clone());
+        }
+        let parameter = vir::LocalVar::new(
+            format!("_count_arg_{}", self.parameters.len()),
+            expr.get_type().clone(),
+        );
+        self.parameters.push((expr, parameter.clone()));
+        vir::Expr::local(parameter)
+    }
+    fn fold_forall(
+        &mut self,
+        vir_crate::polymorphic::ForAll {
+            variables,
+            triggers,
+            body,
+            position,
+        }: vir_crate::polymorphic::ForAll,
+    ) -> vir_crate::polymorphic::Expr {
+        let (triggers, body) = self.fold_quantifier(&variables, triggers, body);
+        vir_crate::polymorphic::Expr::ForAll(vir_crate::polymorphic::ForAll {
+            variables,
+            triggers,
+            body,
+            position,
+        })
+    }
+    fn fold_exists(
+        &mut self,
+        vir_crate::polymorphic::Exists {
+            variables,
+            triggers,
+            body,
+            position,
+        }: vir_crate::polymorphic::Exists,
+    ) -> vir_crate::polymorphic::Expr {
+        let (triggers, body) = self.fold_quantifier(&variables, triggers, body);
+        vir_crate::polymorphic::Expr::Exists(vir_crate::polymorphic::Exists {
+            variables,
+            triggers,
+            body,
+            position,
+        })
+    }
+}
+
+impl FreeExpressionAbstractor {
+    /// Fold the triggers and the body of a quantifier, in which its variables
+    /// are bound.
+    fn fold_quantifier(
+        &mut self,
+        variables: &[vir_crate::polymorphic::LocalVar],
+        triggers: Vec<vir_crate::polymorphic::Trigger>,
+        body: Box<vir_crate::polymorphic::Expr>,
+    ) -> (
+        Vec<vir_crate::polymorphic::Trigger>,
+        Box<vir_crate::polymorphic::Expr>,
+    ) {
+        use vir_crate::polymorphic::{self as vir, ExprFolder};
+        let bound_count = self.bound.len();
+        self.bound
+            .extend(variables.iter().cloned().map(vir::Expr::local));
+        let triggers = triggers
+            .into_iter()
+            .map(|trigger| {
+                vir::Trigger::new(
+                    trigger
+                        .elements()
+                        .iter()
+                        .map(|element| self.fold(element.clone()))
+                        .collect(),
+                )
+            })
+            .collect();
+        let body = self.fold_boxed(body);
+        self.bound.truncate(bound_count);
+        (triggers, body)
+    }
+}
+
 fn find_quantifier_depth(expr: &vir_crate::polymorphic::Expr) -> usize {
     use vir_crate::polymorphic::ExprWalker;
     struct DepthChecker {
@@ -341,3 +628,137 @@ fn check_trigger_set(
     }
     Ok(())
 }
+
+/// Move the indexing of arrays and slices out of `old` expressions, so that
+/// `old[l](read(seq, idx))` becomes `read(old[l](seq), idx)`. Only the
+/// sequence is snapshotted in the old state; the index is evaluated where the
+/// `old` expression is written, which matters when it mentions variables
+/// bound in that context (e.g. quantified variables). Indices that read from
+/// the heap are still evaluated in the old state.
+pub(super) fn hoist_indices_out_of_old(
+    expr: vir_crate::polymorphic::Expr,
+) -> vir_crate::polymorphic::Expr {
+    use vir_crate::polymorphic::{self as vir, ExprFolder};
+    fn is_sequence_read(app: &vir::DomainFuncApp) -> bool {
+        app.domain_function.name.starts_with("read$") && app.arguments.len() == 2
+    }
+    fn hoist(label: &str, mut app: vir::DomainFuncApp, position: vir::Position) -> vir::Expr {
+        let idx = app.arguments.pop().unwrap();
+        let seq = match app.arguments.pop().unwrap() {
+            vir::Expr::DomainFuncApp(inner) if is_sequence_read(&inner) => {
+                hoist(label, inner, position)
+            }
+            seq => vir::Expr::labelled_old(label, seq).set_pos(position),
+        };
+        let idx = if idx.is_heap_dependent() {
+            vir::Expr::labelled_old(label, idx).set_pos(position)
+        } else {
+            idx
+        };
+        app.arguments = vec![seq, idx];
+        vir::Expr::DomainFuncApp(app)
+    }
+    struct OldIndexHoister;
+    impl ExprFolder for OldIndexHoister {
+        fn fold_labelled_old(
+            &mut self,
+            vir::LabelledOld {
+                label,
+                base,
+                position,
+            }: vir::LabelledOld,
+        ) -> vir::Expr {
+            match self.fold(*base) {
+                vir::Expr::DomainFuncApp(app) if is_sequence_read(&app) => {
+                    hoist(&label, app, position)
+                }
+                base => vir::Expr::LabelledOld(vir::LabelledOld {
+                    label,
+                    base: box base,
+                    position,
+                }),
+            }
+        }
+    }
+    OldIndexHoister.fold(expr)
+}
+
+/// Trigger the quantifiers without user-provided triggers that relate the
+/// elements of a sequence to its elements in an old state, such as
+/// `forall(|i: usize| i < s.len() ==> s[i] == old(s[i]) + 1)`, on the element
+/// of the current sequence, `read(seq, i)`. Otherwise, the backend might pick
+/// the old element as the trigger, which is not mentioned by the assertions
+/// that read the sequence in the current state. This expects the indices to be
+/// already hoisted out of `old` by `hoist_indices_out_of_old`.
+pub(super) fn trigger_old_element_quantifiers(
+    expr: vir_crate::polymorphic::Expr,
+) -> vir_crate::polymorphic::Expr {
+    use vir_crate::polymorphic::{self as vir, ExprFolder, ExprWalker};
+    /// Collects the reads `read(seq, qvar)` of sequences indexed by `qvar`.
+    struct ElementReadsCollector<'a> {
+        qvar: &'a vir::Expr,
+        reads: Vec<vir::DomainFuncApp>,
+    }
+    impl<'a> ExprWalker for ElementReadsCollector<'a> {
+        fn walk_domain_func_app(&mut self, app: &vir::DomainFuncApp) {
+            if app.domain_function.name.starts_with("read$")
+                && app.arguments.len() == 2
+                && &app.arguments[1] == self.qvar
+            {
+                self.reads.push(app.clone());
+            }
+            for arg in &app.arguments {
+                self.walk(arg);
+            }
+        }
+    }
+    fn find_current_element_read(qvar: &vir::LocalVar, body: &vir::Expr) -> Option<vir::Expr> {
+        let qvar = vir::Expr::local(qvar.clone());
+        let mut collector = ElementReadsCollector {
+            qvar: &qvar,
+            reads: vec![],
+        };
+        collector.walk(body);
+        let old_sequences: Vec<&vir::Expr> = collector
+            .reads
+            .iter()
+            .filter_map(|read| match &read.arguments[0] {
+                vir::Expr::LabelledOld(vir::LabelledOld { base, .. }) => Some(base.as_ref()),
+                _ => None,
+            })
+            .collect();
+        collector
+            .reads
+            .iter()
+            .find(|read| old_sequences.contains(&&read.arguments[0]))
+            .map(|read| vir::Expr::DomainFuncApp(read.clone()))
+    }
+    struct OldElementTriggerer;
+    impl ExprFolder for OldElementTriggerer {
+        fn fold_forall(
+            &mut self,
+            vir::ForAll {
+                variables,
+                triggers,
+                body,
+                position,
+            }: vir::ForAll,
+        ) -> vir::Expr {
+            let body = self.fold_boxed(body);
+            let triggers = if triggers.is_empty() && variables.len() == 1 {
+                find_current_element_read(&variables[0], &body)
+                    .map(|read| vec![vir::Trigger::new(vec![read])])
+                    .unwrap_or_default()
+            } else {
+                triggers
+            };
+            vir::Expr::ForAll(vir::ForAll {
+                variables,
+                triggers,
+                body,
+                position,
+            })
+        }
+    }
+    OldElementTriggerer.fold(expr)
+}
@@ -5,11 +5,14 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::encoder::{
-    errors::{SpannedEncodingResult, WithSpan},
+    errors::{SpannedEncodingError, SpannedEncodingResult, UnsupportedFeatureCategory, WithSpan},
     mir::pure::{
         specifications::{
             encoder_high::{encode_quantifier_high, inline_spec_item_high},
-            encoder_poly::{encode_quantifier, inline_closure, inline_spec_item},
+            encoder_poly::{
+                encode_count, encode_quantifier, hoist_indices_out_of_old, inline_closure,
+                inline_spec_item, trigger_old_element_quantifiers,
+            },
         },
         PureEncodingContext, PureFunctionBackwardInterpreter,
     },
@@ -91,6 +94,15 @@ impl<'v, 'tcx: 'v> SpecificationEncoderInterface<'tcx> for crate::encoder::Encod
                 parent_def_id,
                 substs,
             ),
+            "prusti_contracts::count" => Err(SpannedEncodingError::unsupported(
+                "`count` is not supported yet",
+                span,
+            )),
+            "prusti_contracts::specification_entailment" => Err(SpannedEncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::Closures,
+                "specification entailments (e.g. contracts of returned closures) are not supported yet",
+                span,
+            )),
             _ => unimplemented!(),
         }
     }
@@ -137,6 +149,14 @@ impl<'v, 'tcx: 'v> SpecificationEncoderInterface<'tcx> for crate::encoder::Encod
                 parent_def_id,
                 substs,
             ),
+            "prusti_contracts::count" => {
+                encode_count(self, span, encoded_args, parent_def_id, substs)
+            }
+            "prusti_contracts::specification_entailment" => Err(SpannedEncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::Closures,
+                "specification entailments (e.g. contracts of returned closures) are not supported yet",
+                span,
+            )),
             _ => unimplemented!(),
         }
     }
@@ -174,6 +194,8 @@ impl<'v, 'tcx: 'v> SpecificationEncoderInterface<'tcx> for crate::encoder::Encod
 
         let span = self.env().tcx().def_span(assertion.to_def_id());
         encoded_assertion = self.patch_snapshots(encoded_assertion).with_span(span)?;
+        encoded_assertion = hoist_indices_out_of_old(encoded_assertion);
+        encoded_assertion = trigger_old_element_quantifiers(encoded_assertion);
 
         Ok(encoded_assertion
             .set_default_pos(self.error_manager().register_span(parent_def_id, span)))
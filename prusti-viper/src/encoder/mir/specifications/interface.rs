@@ -3,7 +3,10 @@ use log::trace;
 use prusti_interface::{
     specs::{
         typed,
-        typed::{DefSpecificationMap, ProcedureSpecification, ProcedureSpecificationKind},
+        typed::{
+            DefSpecificationMap, ProcedureSpecification, ProcedureSpecificationKind,
+            SpecificationItem,
+        },
     },
     utils::has_spec_only_attr,
 };
@@ -85,8 +88,9 @@ pub(crate) trait SpecificationsInterface<'tcx> {
 
     fn get_predicate_body(&self, def_id: DefId, substs: SubstsRef<'tcx>) -> Option<LocalDefId>;
 
-    /// Get the loop invariant attached to a function with a
-    /// `prusti::loop_body_invariant_spec` attribute.
+    /// Get the loop specification attached to a function with a
+    /// `prusti::loop_body_invariant_spec` or `prusti::loop_break_ensures_spec`
+    /// attribute.
     fn get_loop_specs(&self, def_id: DefId) -> Option<typed::LoopSpecification>;
 
     /// Get the specifications attached to a function.
@@ -97,6 +101,8 @@ pub(crate) trait SpecificationsInterface<'tcx> {
     ) -> Option<typed::ProcedureSpecification>;
 
     /// Get the specifications attached to a function for a function call.
+    /// If the function has an `#[opaque_spec(...)]` attribute and the caller
+    /// is outside of its module, the postconditions are the opaque ones.
     fn get_procedure_specs_for_call(
         &self,
         called_def_id: DefId,
@@ -148,7 +154,8 @@ impl<'v, 'tcx: 'v> SpecificationsInterface<'tcx> for super::super::super::Encode
             .borrow_mut()
             .get_and_refine_proc_spec(self.env(), query)
             .and_then(|spec| spec.trusted.extract_with_selective_replacement().copied())
-            .unwrap_or(false);
+            .unwrap_or(false)
+            || self.env().has_prusti_attribute(def_id, "trusted_boundary");
         trace!("is_trusted {:?} = {}", query, result);
         result
     }
@@ -196,8 +203,17 @@ impl<'v, 'tcx: 'v> SpecificationsInterface<'tcx> for super::super::super::Encode
             call_substs,
         });
         let mut specs = self.specifications_state.specs.borrow_mut();
-        let spec = specs.get_and_refine_proc_spec(self.env(), query)?;
-        Some(spec.clone())
+        let mut spec = specs.get_and_refine_proc_spec(self.env(), query)?.clone();
+        if let Some(opaque_posts) = spec.opaque_posts.take() {
+            if self.env().is_outside_module_of(caller_def_id, called_def_id) {
+                spec.posts = if opaque_posts.is_empty() {
+                    SpecificationItem::Empty
+                } else {
+                    SpecificationItem::Inherent(opaque_posts)
+                };
+            }
+        }
+        Some(spec)
     }
 
     fn is_spec_closure(&self, def_id: DefId) -> bool {
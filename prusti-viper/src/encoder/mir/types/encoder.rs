@@ -6,7 +6,9 @@
 
 use super::{helpers::compute_discriminant_values, interface::MirTypeEncoderInterface};
 use crate::encoder::{
-    errors::{EncodingResult, SpannedEncodingError, SpannedEncodingResult},
+    errors::{
+        EncodingResult, SpannedEncodingError, SpannedEncodingResult, UnsupportedFeatureCategory,
+    },
     high::types::HighTypeEncoderInterface,
     mir::{generics::MirGenericsEncoderInterface, types::helpers::compute_discriminant_ranges},
     Encoder,
@@ -739,7 +741,7 @@ fn encode_variant<'v, 'tcx: 'v>(
     let mut fields = Vec::new();
     for field in &variant.fields {
         let field_name = crate::encoder::encoder::encode_field_name(field.ident(tcx).as_str());
-        let field_ty = field.ty(tcx, substs);
+        let field_ty = encoder.env().field_ty(field, substs);
         let field = vir::FieldDecl::new(field_name, encoder.encode_type_high(field_ty)?);
         fields.push(field);
     }
@@ -771,7 +773,8 @@ pub(super) fn encode_adt_def<'v, 'tcx>(
     } else if adt_def.is_union() {
         debug!("ADT {:?} is a union", adt_def);
         if !config::unsafe_core_proof() {
-            return Err(SpannedEncodingError::unsupported(
+            return Err(SpannedEncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::Unions,
                 "unions are not supported",
                 encoder.env().get_def_span(adt_def.did()),
             ));
@@ -786,7 +789,7 @@ pub(super) fn encode_adt_def<'v, 'tcx>(
         let mut variants = Vec::new();
         for field in &variant.fields {
             let field_name = field.ident(tcx).as_str().to_string();
-            let field_ty = field.ty(tcx, substs);
+            let field_ty = encoder.env().field_ty(field, substs);
             let encoded_field = vir::FieldDecl::new("value", encoder.encode_type_high(field_ty)?);
             let variant = vir::type_decl::Struct::new(field_name, vec![encoded_field]);
             variants.push(variant);
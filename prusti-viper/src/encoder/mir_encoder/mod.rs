@@ -6,7 +6,7 @@
 
 use crate::encoder::errors::{
     ErrorCtxt, PanicCause, SpannedEncodingError, EncodingError, WithSpan,
-    SpannedEncodingResult, EncodingResult
+    SpannedEncodingResult, EncodingResult, UnsupportedFeatureCategory
 };
 use crate::encoder::Encoder;
 use crate::encoder::snapshot::interface::SnapshotEncoderInterface;
@@ -35,6 +35,9 @@ pub use place_encoding::{PlaceEncoding, ExprOrArrayBase};
 
 pub static PRECONDITION_LABEL: &str = "pre";
 pub static WAND_LHS_LABEL: &str = "lhs";
+/// The variable that stands for `loop_iterations()` in an encoded loop
+/// specification, until it is replaced by the counter of the loop.
+pub static LOOP_ITERATIONS_PLACEHOLDER: &str = "loop_iterations$placeholder";
 
 pub trait PlaceEncoder<'v, 'tcx: 'v> {
 
@@ -44,6 +47,13 @@ pub trait PlaceEncoder<'v, 'tcx: 'v> {
 
     fn get_local_span(&self, local: mir::Local) -> Span;
 
+    /// Whether fields of shared reference types, e.g. the payload of
+    /// `Cow::Borrowed`, can be read. This is only the case in pure code, which
+    /// never creates borrows of such fields.
+    fn can_access_shared_reference_fields(&self) -> bool {
+        false
+    }
+
     fn encode_local_var_name(&self, local: mir::Local) -> String {
         format!("{:?}", local)
     }
@@ -144,8 +154,12 @@ pub trait PlaceEncoder<'v, 'tcx: 'v> {
                             encoded_base
                         };
                         let field = &variant_def.fields[field.index()];
-                        let field_ty = field.ty(tcx, subst);
-                        if utils::is_reference(field_ty) {
+                        let field_ty = self.encoder().env().field_ty(field, subst);
+                        let is_shared_reference =
+                            matches!(field_ty.kind(), ty::TyKind::Ref(_, _, mir::Mutability::Not));
+                        if utils::is_reference(field_ty)
+                            && !(is_shared_reference && self.can_access_shared_reference_fields())
+                        {
                             return Err(EncodingError::unsupported(
                                 "access to reference-typed fields is not supported",
                             ));
@@ -296,7 +310,8 @@ pub trait PlaceEncoder<'v, 'tcx: 'v> {
                 }
             }
 
-            mir::ProjectionElem::Subslice { .. } => return Err(EncodingError::unsupported(
+            mir::ProjectionElem::Subslice { .. } => return Err(EncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::Slicing,
                 "slice patterns are not supported",
             )),
         })
@@ -406,6 +421,7 @@ pub struct MirEncoder<'p, 'v: 'p, 'tcx: 'v> {
     encoder: &'p Encoder<'v, 'tcx>,
     mir: &'p mir::Body<'tcx>,
     def_id: DefId,
+    is_pure: bool,
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> PlaceEncoder<'v, 'tcx> for MirEncoder<'p, 'v, 'tcx> {
@@ -421,6 +437,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> PlaceEncoder<'v, 'tcx> for MirEncoder<'p, 'v, 'tcx> {
     fn get_local_span(&self, local: mir::Local) -> Span {
         self.mir.local_decls[local].source_info.span
     }
+
+    fn can_access_shared_reference_fields(&self) -> bool {
+        self.is_pure
+    }
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
@@ -434,6 +454,19 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
             encoder,
             mir,
             def_id,
+            is_pure: false,
+        }
+    }
+
+    /// A `MirEncoder` for the body of a pure function or of a specification.
+    pub fn new_pure(
+        encoder: &'p Encoder<'v, 'tcx>,
+        mir: &'p mir::Body<'tcx>,
+        def_id: DefId,
+    ) -> Self {
+        MirEncoder {
+            is_pure: true,
+            ..Self::new(encoder, mir, def_id)
         }
     }
 
@@ -551,7 +584,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
             mir::BinOp::BitAnd |
             mir::BinOp::BitOr |
             mir::BinOp::BitXor if !config::encode_bitvectors() => {
-                return Err(EncodingError::unsupported(
+                return Err(EncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::BitwiseOperations,
                     "bitwise operations on non-boolean types are experimental and disabled by default; use `encode_bitvectors` to enable"
                 ))
             }
@@ -578,6 +612,96 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         })
     }
 
+    /// Encode `has_flag(value, flag)`, that is `value & flag != 0`. The value
+    /// is either an integer or a struct whose only field is an integer.
+    ///
+    /// Without `encode_bitvectors` the bitwise operation is not available, so
+    /// the flag needs to be a constant with a single bit set and the check is
+    /// encoded as `(value / flag) % 2 != 0`.
+    pub fn encode_has_flag(
+        &self,
+        value: vir::Expr,
+        value_ty: ty::Ty<'tcx>,
+        flag: vir::Expr,
+        flag_ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let (bits, bits_ty) = self.encode_flag_bits(value, value_ty)?;
+        if !matches!(flag_ty.kind(), ty::TyKind::Int(_) | ty::TyKind::Uint(_)) {
+            return Err(EncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::BitwiseOperations,
+                format!(
+                    "the flag of `has_flag` must be an integer, not {:?}",
+                    flag_ty
+                ),
+            ));
+        }
+        if config::encode_bitvectors() {
+            return Ok(vir::Expr::ne_cmp(
+                vir::Expr::bin_op(vir::BinaryOpKind::BitAnd, bits, flag),
+                0.into(),
+            ));
+        }
+        let is_single_bit = match &flag {
+            vir::Expr::Const(vir::ConstExpr { value: vir::Const::Int(value), .. }) => {
+                *value > 0 && (*value as u64).is_power_of_two()
+            }
+            vir::Expr::Const(vir::ConstExpr { value: vir::Const::BigInt(value), .. }) => {
+                value.parse::<u128>().map_or(false, u128::is_power_of_two)
+            }
+            _ => false,
+        };
+        if !is_single_bit || !matches!(bits_ty.kind(), ty::TyKind::Uint(_)) {
+            return Err(EncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::BitwiseOperations,
+                "`has_flag` requires an unsigned value and a constant single-bit flag; use `encode_bitvectors` to support other flags"
+            ));
+        }
+        Ok(vir::Expr::ne_cmp(
+            vir::Expr::rem(vir::Expr::div(bits, flag), 2.into()),
+            0.into(),
+        ))
+    }
+
+    /// The integer that stores the flags of a `has_flag` value, and its type.
+    fn encode_flag_bits(
+        &self,
+        value: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<(vir::Expr, ty::Ty<'tcx>)> {
+        let tcx = self.encoder.env().tcx();
+        match ty.kind() {
+            ty::TyKind::Int(_) | ty::TyKind::Uint(_) => Ok((value, ty)),
+            ty::TyKind::Adt(adt_def, substs) if adt_def.is_struct()
+                && adt_def.all_fields().count() == 1 =>
+            {
+                let field = adt_def.all_fields().next().unwrap();
+                let field_ty = field.ty(tcx, substs);
+                if !matches!(field_ty.kind(), ty::TyKind::Int(_) | ty::TyKind::Uint(_)) {
+                    return Err(EncodingError::unsupported_feature(
+                        UnsupportedFeatureCategory::BitwiseOperations,
+                        format!(
+                            "the only field of the `has_flag` value of type {:?} must be an integer",
+                            ty
+                        ),
+                    ));
+                }
+                let bits = self.encoder.encode_struct_field_value(
+                    value,
+                    field.ident(tcx).as_str(),
+                    field_ty,
+                )?;
+                Ok((bits, field_ty))
+            }
+            _ => Err(EncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::BitwiseOperations,
+                format!(
+                    "`has_flag` is only supported for integers and structs with a single integer field, not {:?}",
+                    ty
+                ),
+            )),
+        }
+    }
+
     pub fn encode_unary_op_expr(&self, op: mir::UnOp, expr: vir::Expr) -> vir::Expr {
         match op {
             mir::UnOp::Not => vir::Expr::not(expr),
@@ -660,11 +784,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
                         vir::Expr::gt_cmp(result, std::f64::MAX.into()),
                     ),
                     _ => {
-                        return Err(EncodingError::unsupported(format!(
-                            "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
-                            op,
-                            ty,
-                        )));
+                        return Err(EncodingError::unsupported_feature(
+                            UnsupportedFeatureCategory::Overflow,
+                            format!(
+                                "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
+                                op,
+                                ty,
+                            ),
+                        ));
                     }
                 },
 
@@ -676,7 +803,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
                         ty::TyKind::Uint(ty::UintTy::U64) => 64,
                         ty::TyKind::Uint(ty::UintTy::U128) => 128,
                         ty::TyKind::Uint(ty::UintTy::Usize) => {
-                            return Err(EncodingError::unsupported(
+                            return Err(EncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Overflow,
                                 "unknown size of usize for the overflow check",
                             ));
                         }
@@ -686,15 +814,19 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
                         ty::TyKind::Int(ty::IntTy::I64) => 64,
                         ty::TyKind::Int(ty::IntTy::I128) => 128,
                         ty::TyKind::Int(ty::IntTy::Isize) => {
-                            return Err(EncodingError::unsupported(
+                            return Err(EncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Overflow,
                                 "unknown size of isize for the overflow check",
                             ));
                         },
                         _ => {
-                            return Err(EncodingError::unsupported(format!(
-                                "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
-                                op, ty,
-                            )));
+                            return Err(EncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Overflow,
+                                format!(
+                                    "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
+                                    op, ty,
+                                ),
+                            ));
                         }
                     };
                     vir::Expr::or(
@@ -796,7 +928,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
             }
 
             _ => {
-                return Err(SpannedEncodingError::unsupported(
+                return Err(SpannedEncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::Casts,
                     format!(
                         "unsupported cast from type '{:?}' to type '{:?}'",
                         src_ty,
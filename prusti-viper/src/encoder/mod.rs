@@ -7,11 +7,14 @@
 pub use self::encoder::Encoder;
 
 mod builtin_encoder;
+mod clone_equal_check;
 #[allow(clippy::module_inception)]
 mod encoder;
 mod errors;
 mod foldunfold;
+mod hash_eq_check;
 mod initialisation;
+mod inline_const_check;
 mod loop_encoder;
 mod mir_encoder;
 mod mir_successor;
@@ -23,6 +26,7 @@ mod spec_function_encoder;
 pub use spec_function_encoder::SpecFunctionKind;
 mod stub_function_encoder;
 mod stub_procedure_encoder;
+mod termination_check;
 mod utils;
 mod snapshot;
 mod mirror_function_encoder;
@@ -7,14 +7,14 @@
 use crate::encoder::builtin_encoder::{BuiltinMethodKind};
 use crate::encoder::errors::{
     SpannedEncodingError, ErrorCtxt, EncodingError, WithSpan,
-    EncodingResult, SpannedEncodingResult
+    EncodingResult, SpannedEncodingResult, PanicCause, UnsupportedFeatureCategory
 };
 use crate::encoder::foldunfold;
 use crate::encoder::high::types::HighTypeEncoderInterface;
 use crate::encoder::initialisation::InitInfo;
 use crate::encoder::loop_encoder::{LoopEncoder, LoopEncoderError};
 use crate::encoder::mir_encoder::{MirEncoder, FakeMirEncoder, PlaceEncoder, PlaceEncoding, ExprOrArrayBase};
-use crate::encoder::mir_encoder::PRECONDITION_LABEL;
+use crate::encoder::mir_encoder::{PRECONDITION_LABEL, LOOP_ITERATIONS_PLACEHOLDER};
 use crate::encoder::mir_successor::MirSuccessor;
 use crate::encoder::places::{Local, LocalVariableManager, Place};
 use crate::encoder::Encoder;
@@ -22,7 +22,10 @@ use crate::encoder::snapshot::interface::SnapshotEncoderInterface;
 use prusti_common::{
     config,
     utils::to_string::ToString,
-    vir::{ToGraphViz, fixes::fix_ghost_vars},
+    vir::{ToGraphViz, fixes::fix_ghost_vars, source_comments::add_source_comments,
+        precondition_conjuncts::precondition_conjunct_marker,
+        obligation_formulas::obligation_formulas,
+        split_assertions::split_conjunctive_assertions},
     vir_local, vir_expr, vir_stmt
 };
 use vir_crate::{
@@ -44,6 +47,7 @@ use prusti_interface::{
     },
 };
 use prusti_interface::utils;
+use prusti_interface::PrustiError;
 use rustc_middle::mir::Mutability;
 use rustc_middle::mir;
 use rustc_middle::mir::{TerminatorKind};
@@ -52,6 +56,7 @@ use rustc_target::abi::Integer;
 use rustc_hash::{FxHashMap, FxHashSet};
 use rustc_attr::IntType::SignedInt;
 use rustc_span::Span;
+use rustc_hir::def_id::LocalDefId;
 use rustc_errors::MultiSpan;
 use prusti_interface::specs::typed;
 use ::log::{trace, debug};
@@ -60,7 +65,7 @@ use crate::encoder::errors::EncodingErrorKind;
 use std::convert::TryInto;
 use prusti_interface::specs::typed::{Pledge, SpecificationItem};
 use vir_crate::polymorphic::Float;
-use crate::utils::is_reference;
+use crate::utils::{is_reference, is_sequence_ty, is_unpinning_method};
 use crate::encoder::mir::{
     sequences::MirSequencesEncoderInterface,
     contracts::{
@@ -122,6 +127,12 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     /// Type substitutions inside this procedure. Most likely identity for the
     /// given proc_def_id.
     substs: SubstsRef<'tcx>,
+    /// Conditions of proven `assert!`s, keyed by the MIR edge from the branch
+    /// of the assertion to the block that continues after it.
+    proven_assertions: FxHashMap<(BasicBlockIndex, BasicBlockIndex), vir::Expr>,
+    /// Names and call sites of the `std` functions whose calls could not be
+    /// encoded and were stubbed out.
+    stubbed_functions: Vec<(String, Span)>,
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
@@ -140,7 +151,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
         let cfg_method = vir::CfgMethod::new(
             // method name
-            encoder.encode_item_name(def_id),
+            encoder.encode_item_instance_name(def_id, procedure.get_substs()),
             // formal args
             mir.arg_count,
             // formal returns
@@ -161,7 +172,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             loop_encoder: LoopEncoder::new(procedure, tcx),
             auxiliary_local_vars: FxHashMap::default(),
             mir_encoder,
-            check_panics: config::check_panics(),
+            check_panics: config::check_panics()
+                || encoder.env().has_prusti_module_attribute(def_id, "panic_free")
+                || encoder.env().has_foreign_abi(def_id),
             check_foldunfold_state: config::check_foldunfold_state(),
             polonius_info: None,
             procedure_contract: None,
@@ -178,7 +191,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             old_to_ghost_var: FxHashMap::default(),
             old_ghost_vars: FxHashMap::default(),
             cached_loop_invariant_block: FxHashMap::default(),
-            substs: encoder.env().identity_substs(def_id),
+            substs: procedure.get_substs(),
+            proven_assertions: FxHashMap::default(),
+            stubbed_functions: vec![],
         })
     }
 
@@ -284,6 +299,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             }
         }
 
+        self.encode_loop_options()?;
+
         // Load Polonius info
         self.polonius_info = Some(
             PoloniusInfo::new(self.encoder.env(), self.procedure, &self.cached_loop_invariant_block)
@@ -376,6 +393,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
 
         self.check_vir()?;
+        self.report_stubbed_functions();
         let method_name = self.cfg_method.name();
         let source_filename = self.encoder.env().source_file_name();
 
@@ -432,6 +450,38 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         // Fix variable declarations.
         let method_with_fold_unfold = fix_ghost_vars(method_with_fold_unfold);
 
+        // Check the conjuncts of asserted conjunctions separately.
+        let method_with_fold_unfold = if config::split_conjunctive_assertions() {
+            let mut error_manager = self.encoder.error_manager();
+            split_conjunctive_assertions(method_with_fold_unfold, |pos, conjunct_pos| {
+                error_manager.register_part_position(pos, conjunct_pos)
+            })
+        } else {
+            method_with_fold_unfold
+        };
+
+        // Remember the formulas of the obligations to explain failures.
+        if config::explain_errors() {
+            let source_names = self.source_local_names();
+            let mut error_manager = self.encoder.error_manager();
+            for (pos, formula) in obligation_formulas(&method_with_fold_unfold, &source_names) {
+                error_manager.set_obligation_formula(pos, formula);
+            }
+        }
+
+        // Annotate statements with the source location they come from.
+        let method_with_fold_unfold = if config::annotate_viper_with_source()
+            || config::keep_viper_files().is_some()
+        {
+            let error_manager = self.encoder.error_manager();
+            let position_manager = error_manager.position_manager();
+            add_source_comments(method_with_fold_unfold, |pos| {
+                position_manager.get_source_location(pos)
+            })
+        } else {
+            method_with_fold_unfold
+        };
+
         // Dump final CFG
         if config::dump_debug_info() {
             prusti_common::report::log::report_with_writer(
@@ -444,6 +494,26 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(method_with_fold_unfold)
     }
 
+    /// The names that the local variables of the procedure have in the source
+    /// code, keyed by the name of their encoding. The return value is called
+    /// `result`, as in specifications.
+    fn source_local_names(&self) -> FxHashMap<String, String> {
+        let mut source_names = FxHashMap::default();
+        let return_local = Local::from(mir::RETURN_PLACE);
+        source_names.insert(self.locals.get_name(return_local), "result".to_string());
+        for var_debug_info in &self.mir.var_debug_info {
+            if let mir::VarDebugInfoContents::Place(place) = var_debug_info.value {
+                if let Some(local) = place.as_local() {
+                    source_names.insert(
+                        self.locals.get_name(local.into()),
+                        var_debug_info.name.to_ident_string(),
+                    );
+                }
+            }
+        }
+        source_names
+    }
+
     /// Encodes a topologically ordered group of blocks.
     ///
     /// Returns:
@@ -679,9 +749,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         heads.push(Some(inv_pre_block));
         self.cfg_method
             .set_successor(inv_pre_block, vir::Successor::Goto(inv_post_block));
+        let iterations: vir::Expr = self.loop_iterations_var(loop_head).into();
+        let mut uses_iterations = false;
         {
             let stmts =
                 self.encode_loop_invariant_exhale_stmts(loop_head, before_invariant_block, false)?;
+            uses_iterations |= stmts.iter().any(|stmt| matches!(
+                stmt,
+                vir::Stmt::Assert(vir::Assert { expr, .. }) if expr.find(&iterations)
+            ));
             self.cfg_method.add_stmts(inv_pre_block, stmts);
         }
         // We'll add later more statements at the end of inv_pre_block, to havoc local variables
@@ -809,6 +885,67 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         self.cfg_method
             .set_successor(end_body_block, vir::Successor::Return);
 
+        // Check the `break_ensures!(..)` specifications on the edges that leave the loop
+        let (break_specs, break_specs_span) = self.encode_loop_break_specs(loop_head)?;
+        uses_iterations |= break_specs.iter().any(|spec| spec.find(&iterations));
+        if !break_specs.is_empty() {
+            let break_pos = self.register_error(
+                break_specs_span,
+                ErrorCtxt::AssertLoopBreakPostcondition,
+            );
+            let break_spec = break_specs.into_iter().conjoin();
+            let mut exit_edges = vec![];
+            for (index, (curr_block, target)) in still_unresolved_edges.into_iter().enumerate() {
+                let exit_block = self.cfg_method.add_block(
+                    &format!("{}_exit{}", loop_label_prefix, index),
+                    vec![
+                        vir::Stmt::comment(format!(
+                            "========== {}_exit{} ==========",
+                            loop_label_prefix, index
+                        )),
+                        vir::Stmt::Assert( vir::Assert {
+                            expr: break_spec.clone(),
+                            position: break_pos,
+                        }),
+                    ],
+                );
+                self.cfg_method
+                    .set_successor(curr_block, vir::Successor::Goto(exit_block));
+                exit_edges.push((exit_block, target));
+            }
+            still_unresolved_edges = exit_edges;
+        }
+
+        // Count the completed iterations if `loop_iterations()` is used in the specifications
+        if uses_iterations {
+            let iterations_var = self.loop_iterations_var(loop_head);
+            if !self.cfg_method.get_all_vars().contains(&iterations_var) {
+                self.cfg_method.add_local_var(&iterations_var.name, iterations_var.typ.clone());
+            }
+            self.cfg_method.add_stmt(
+                start_block,
+                vir::Stmt::Assign( vir::Assign {
+                    target: iterations.clone(),
+                    source: 0.into(),
+                    kind: vir::AssignKind::Copy,
+                }),
+            );
+            self.cfg_method.add_stmt(
+                inv_post_block,
+                vir::Stmt::Inhale( vir::Inhale {
+                    expr: vir::Expr::ge_cmp(iterations.clone(), 0.into()),
+                }),
+            );
+            self.cfg_method.add_stmt(
+                inv_post_block,
+                vir::Stmt::Assign( vir::Assign {
+                    target: iterations.clone(),
+                    source: vir::Expr::add(iterations, 1.into()),
+                    kind: vir::AssignKind::Copy,
+                }),
+            );
+        }
+
         // Final step: havoc Viper local variables assigned in the encoding of the loop body
         let vars = collect_assigned_vars(&self.cfg_method, end_body_block, inv_pre_block);
         for var in vars {
@@ -1003,7 +1140,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             Ok(stmts_succ) => stmts_succ,
             Err(err) => {
                 let unsupported_msg = match err.kind() {
-                    EncodingErrorKind::Unsupported(msg)
+                    EncodingErrorKind::Unsupported(msg, _)
+                        if config::report_unsupported_features() => {
+                        // Report the unsupported feature and continue encoding
+                        let msg = msg.to_string();
+                        self.encoder.register_encoding_error(err);
+                        msg
+                    },
+                    EncodingErrorKind::Unsupported(msg, _)
                         if config::allow_unreachable_unsupported_code() => {
                         msg.to_string()
                     },
@@ -1216,24 +1360,28 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         location,
                     )?
                 } else {
-                    return Err(SpannedEncodingError::unsupported(
+                    return Err(SpannedEncodingError::unsupported_feature(
+                        UnsupportedFeatureCategory::Slicing,
                         format!("unsizing a {} into a {} is not supported", rhs_ty, cast_ty),
                         span,
                     ));
                 }
             }
             mir::Rvalue::Cast(mir::CastKind::Pointer(_), _, _) => {
-                return Err(EncodingError::unsupported(
+                return Err(EncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::RawPointers,
                     "raw pointers are not supported"
                 )).with_span(span);
             }
             mir::Rvalue::AddressOf(_, _) => {
-                return Err(EncodingError::unsupported(
+                return Err(EncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::RawPointers,
                     "raw addresses of expressions or casting a reference to a raw pointer are not supported"
                 )).with_span(span);
             }
             mir::Rvalue::ThreadLocalRef(_) => {
-                return Err(EncodingError::unsupported(
+                return Err(EncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::ThreadLocals,
                     "references to thread-local storage are not supported"
                 )).with_span(span);
             }
@@ -1909,6 +2057,29 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
                 let guard_is_bool = matches!(switch_ty.kind(), ty::TyKind::Bool);
 
+                // Remember the asserted expression of an `assert!`, to assume
+                // it once the failing branch has been proven unreachable. The
+                // guard itself is already on the path, so only an expression
+                // over the variables that the guard was computed from is
+                // worth assuming.
+                if guard_is_bool && self.check_panics && config::assume_proven_assertions()
+                    && targets.all_targets().len() == 2
+                {
+                    let (value, target) = targets.iter().next().unwrap();
+                    let otherwise = targets.otherwise();
+                    let source = location.block;
+                    let asserted_expr = self.encode_asserted_expr(source, discr).map(|expr| {
+                        if value == 0 { vir::Expr::not(expr) } else { expr }
+                    });
+                    if let Some(expr) = asserted_expr {
+                        if self.is_failing_assertion_block(target) {
+                            self.proven_assertions.insert((source, otherwise), expr.negate());
+                        } else if self.is_failing_assertion_block(otherwise) {
+                            self.proven_assertions.insert((source, target), expr);
+                        }
+                    }
+                }
+
                 for (value, target) in targets.iter() {
                     // Convert int to bool, if required
                     let viper_guard = match switch_ty.kind() {
@@ -2047,6 +2218,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     let full_func_proc_name: &str =
                         &self.encoder.env().tcx().def_path_str(called_def_id);
 
+                    // A `Pin<&mut T>` is encoded as a mutable reference, which
+                    // does not forbid moving the pointee.
+                    if is_unpinning_method(self.encoder.env().tcx(), called_def_id) {
+                        return Err(SpannedEncodingError::unsupported(
+                            "accessing the pointee of a `Pin` without pinning it is not supported",
+                            term.source_info.span,
+                        ));
+                    }
+
                     match full_func_proc_name {
                         "std::rt::begin_panic"
                         | "core::panicking::panic"
@@ -2178,7 +2358,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                                 }
 
                                 _ => {
-                                    return Err(SpannedEncodingError::unsupported(
+                                    return Err(SpannedEncodingError::unsupported_feature(
+                                        UnsupportedFeatureCategory::Closures,
                                         format!("only calls to closures are supported. The term is a {:?}, not a closure.", cl_type.kind()),
                                         term.source_info.span,
                                     ));
@@ -2186,8 +2367,24 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             }
                         }
 
-                        "core::slice::<impl [T]>::len" => {
-                            debug!("Encoding call of slice::len");
+                        name if config::check_unchecked_arithmetic()
+                            && unchecked_arithmetic_op(name).is_some() =>
+                        {
+                            debug!("Encoding call of {}", name);
+                            stmts.extend(
+                                self.encode_unchecked_arithmetic_call(
+                                    name,
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                )?
+                            );
+                        }
+
+                        "core::slice::<impl [T]>::len"
+                        | "core::str::<impl str>::len" => {
+                            debug!("Encoding call of slice::len or str::len");
                             stmts.extend(
                                 self.encode_slice_len_call(
                                     destination,
@@ -2198,18 +2395,57 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             );
                         }
 
+                        "std::iter::IntoIterator::into_iter" |
+                        "core::iter::IntoIterator::into_iter"
+                            if self.is_iterator_into_iter_call(called_def_id, call_substs) =>
+                        {
+                            // As in the desugaring of `for` loops over iterators
+                            debug!("Encoding call of IntoIterator::into_iter on an iterator");
+                            stmts.extend(
+                                self.encode_iterator_into_iter_call(
+                                    destination,
+                                    args,
+                                    location,
+                                    span,
+                                )?
+                            );
+                        }
+
                         "std::iter::Iterator::next" |
-                        "core::iter::Iterator::next" => {
-                            return Err(SpannedEncodingError::unsupported(
+                        "core::iter::Iterator::next"
+                            if !self.encoder.env().resolve_method_call(
+                                self.proc_def_id,
+                                called_def_id,
+                                call_substs,
+                            ).0.is_local() =>
+                        {
+                            // Calls of `next` on an iterator of the crate are
+                            // encoded with the contract of its implementation.
+                            let error = SpannedEncodingError::unsupported_feature(
+                                UnsupportedFeatureCategory::Iterators,
                                 "iterators are not fully supported yet",
                                 term.source_info.span,
-                            ));
+                            );
+                            if !self.can_stub_function_call(called_def_id, call_substs, &error) {
+                                return Err(error);
+                            }
+                            stmts.extend(
+                                self.encode_stubbed_function_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    called_def_id,
+                                )?
+                            );
                         }
 
                         "core::ops::IndexMut::index_mut" |
                         "std::ops::IndexMut::index_mut" |
                         "core::ops::Index::index" |
-                        "std::ops::Index::index" => {
+                        "std::ops::Index::index"
+                            if self.is_sequence_index_call(args) =>
+                        {
                             debug!("Encoding call of array/slice index call");
                             stmts.extend(
                                 self.encode_sequence_index_call(
@@ -2256,16 +2492,32 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                                     )?
                                 );
                             } else {
-                                stmts.extend(
-                                    self.encode_impure_function_call(
-                                        location,
-                                        term.source_info.span,
-                                        args,
-                                        destination,
+                                let call_stmts = self.encode_impure_function_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    called_def_id,
+                                    call_substs,
+                                );
+                                match call_stmts {
+                                    Err(error) if self.can_stub_function_call(
                                         called_def_id,
                                         call_substs,
-                                    )?
-                                );
+                                        &error,
+                                    ) => {
+                                        stmts.extend(
+                                            self.encode_stubbed_function_call(
+                                                location,
+                                                term.source_info.span,
+                                                args,
+                                                destination,
+                                                called_def_id,
+                                            )?
+                                        );
+                                    }
+                                    call_stmts => stmts.extend(call_stmts?),
+                                }
                             }
                         }
                     }
@@ -2285,9 +2537,41 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 }
             }
 
-            TerminatorKind::Call { .. } => {
-                // Other kind of calls?
-                unimplemented!();
+            TerminatorKind::Call {
+                ref args,
+                ref destination,
+                func: mir::Operand::Copy(ref func_place) | mir::Operand::Move(ref func_place),
+                ..
+            } => {
+                // A call through a function pointer is encoded as a call of the
+                // function that the pointer was created from or, for parameters,
+                // of the contract declared by `#[prusti::fn_contract(f)]`.
+                let span = term.source_info.span;
+                let source = match func_place.as_local() {
+                    Some(local) => self.fn_pointer_source(local)?,
+                    None => None,
+                };
+                let (called_def_id, call_substs) = source.ok_or_else(|| SpannedEncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::FunctionPointers,
+                    "calls of function pointers are supported only if the pointer is assigned \
+                    exactly once or is a parameter that declares a contract with \
+                    `#[prusti::fn_contract(f)]`",
+                    span,
+                ))?;
+                debug!("Encoding call of function pointer as a call of {:?}", called_def_id);
+                stmts.extend(self.encode_impure_function_call(
+                    location,
+                    span,
+                    args,
+                    destination,
+                    called_def_id,
+                    call_substs,
+                )?);
+                if let Some((_, target)) = destination {
+                    (stmts, MirSuccessor::Goto(*target))
+                } else {
+                    (stmts, MirSuccessor::Kill)
+                }
             }
 
             TerminatorKind::Assert {
@@ -2353,6 +2637,42 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(result)
     }
 
+    /// Whether the call of `IntoIterator::into_iter` resolves to the impl of
+    /// the standard library for all iterators, which returns its argument.
+    fn is_iterator_into_iter_call(
+        &self,
+        called_def_id: ProcedureDefId,
+        call_substs: SubstsRef<'tcx>,
+    ) -> bool {
+        let tcx = self.encoder.env().tcx();
+        let (resolved_def_id, _) = self.encoder.env()
+            .resolve_method_call(self.proc_def_id, called_def_id, call_substs);
+        let crate_name = tcx.crate_name(resolved_def_id.krate);
+        matches!(crate_name.as_str(), "core" | "std")
+            && tcx.impl_of_method(resolved_def_id).map_or(false, |impl_def_id| {
+                matches!(tcx.type_of(impl_def_id).kind(), ty::TyKind::Param(_))
+            })
+    }
+
+    /// Encode a call of `IntoIterator::into_iter` on an iterator as a move of
+    /// the iterator into the destination.
+    fn encode_iterator_into_iter_call(
+        &mut self,
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        args: &[mir::Operand<'tcx>],
+        location: mir::Location,
+        span: Span,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        assert!(args.len() == 1, "unexpected args to IntoIterator::into_iter(): {:?}", args);
+        let (encoded_lhs, mut stmts, _, _) = self.encode_place(
+            &destination.as_ref().unwrap().0,
+            ArrayAccessKind::Mutable(None, location),
+            location
+        ).with_span(span)?;
+        stmts.extend(self.encode_assign_operand(&encoded_lhs, &args[0], location)?);
+        Ok(stmts)
+    }
+
     fn encode_slice_len_call(
         &mut self,
         destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
@@ -2372,9 +2692,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
         let slice_ty_ref = self.mir_encoder.get_operand_ty(&args[0]);
         let slice_ty = if let ty::TyKind::Ref(_, slice_ty, _) = slice_ty_ref.kind() { slice_ty } else { unreachable!() };
-        let slice_types = self.encoder.encode_sequence_types(*slice_ty).with_span(span)?;
-
-        let rhs = slice_types.len(self.encoder, slice_operand);
+        let rhs = if slice_ty.is_str() {
+            // Only the length of strings is modelled, by their snapshot
+            let (string, _, _) = self.mir_encoder.encode_deref(slice_operand, slice_ty_ref)
+                .with_span(span)?;
+            self.encoder.encode_snapshot_str_len(*slice_ty, vir::Expr::snap_app(string))
+                .with_span(span)?
+        } else {
+            let slice_types = self.encoder.encode_sequence_types(*slice_ty).with_span(span)?;
+            slice_types.len(self.encoder, slice_operand)
+        };
 
         let (encoded_lhs, encode_stmts, ty, _) = self.encode_place(
             &destination.as_ref().unwrap().0,
@@ -2404,6 +2731,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(stmts)
     }
 
+    /// Whether the value indexed by a call of `Index::index` or
+    /// `IndexMut::index_mut` is an array, a slice or a string, which are
+    /// indexed with the sequence model. Other types are indexed by calling
+    /// their implementation of the trait.
+    fn is_sequence_index_call(&self, args: &[mir::Operand<'tcx>]) -> bool {
+        is_sequence_ty(self.mir_encoder.get_operand_ty(&args[0]).peel_refs())
+    }
+
     fn encode_sequence_index_call(
         &mut self,
         destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
@@ -2450,7 +2785,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let base_seq_ty = self.mir_encoder.get_operand_ty(&args[0]);
 
         if !base_seq_ty.is_slice_or_ref() && !base_seq_ty.is_array_or_ref() {
-            return Err(EncodingError::unsupported(
+            return Err(EncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::Slicing,
                 format!("Slicing is only supported for arrays/slices currently, not '{:?}'", base_seq_ty)
             ));
         }
@@ -2490,7 +2826,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             // and it is created with a new() fn and start/end are accessed with getter fns
             // See https://github.com/rust-lang/rust/issues/67371 for why this is the case...
             "std::ops::RangeInclusive" | "core::ops::RangeInclusive" => return Err(
-                EncodingError::unsupported("slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string())
+                EncodingError::unsupported_feature(UnsupportedFeatureCategory::Slicing, "slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string())
             ),
             "std::ops::RangeTo" | "core::ops::RangeTo" |
             "std::ops::RangeFull" | "core::ops::RangeFull" |
@@ -2502,7 +2838,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             "std::ops::RangeTo" | "core::ops::RangeTo" =>
                 self.encoder.encode_struct_field_value(encoded_idx, "end", usize_ty)?,
             "std::ops::RangeInclusive" | "core::ops::RangeInclusive" => return Err(
-                EncodingError::unsupported("slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string())
+                EncodingError::unsupported_feature(UnsupportedFeatureCategory::Slicing, "slicing with RangeInclusive (e.g. [x..=y]) currently not supported".to_string())
             ),
             "std::ops::RangeToInclusive" | "core::ops::RangeToInclusive" => {
                 let end_expr = self.encoder.encode_struct_field_value(encoded_idx, "end", usize_ty)?;
@@ -2646,6 +2982,50 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
     }
 
+    /// Encode a call of an unchecked arithmetic operation like
+    /// `u32::unchecked_add`, whose caller must guarantee that the result does
+    /// not overflow. The result is the mathematical result of the operation,
+    /// which is asserted to be in the range of the type.
+    fn encode_unchecked_arithmetic_call(
+        &mut self,
+        name: &str,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        assert_eq!(args.len(), 2);
+        let op_kind = unchecked_arithmetic_op(name).unwrap();
+        let arg_ty = self.mir_encoder.get_operand_ty(&args[0]);
+        let lhs = self.mir_encoder.encode_operand_expr(&args[0])
+            .with_span(call_site_span)?;
+        let rhs = self.mir_encoder.encode_operand_expr(&args[1])
+            .with_span(call_site_span)?;
+        let result = vir::Expr::bin_op(op_kind, lhs, rhs);
+
+        let operation = name.rsplit("::").next().unwrap().to_string();
+        let pos = self.register_error(call_site_span, ErrorCtxt::UncheckedArithmetic(operation));
+        let mut stmts = vec![
+            vir::Stmt::comment(format!("Check that {} does not overflow", name)),
+            vir::Stmt::Assert(vir::Assert {
+                expr: self.encoder.encode_type_bounds(&result, arg_ty).into_iter().conjoin(),
+                position: pos,
+            }),
+        ];
+
+        let (target_value, lhs_stmts) = self.encode_pure_function_call_lhs_value(destination, location)
+            .with_span(call_site_span)?;
+        stmts.extend(lhs_stmts);
+        let (call_stmts, label) = self.encode_pure_function_call_site(
+            location,
+            destination,
+            vir::Expr::eq_cmp(target_value, result),
+        )?;
+        stmts.extend(call_stmts);
+        self.encode_transfer_args_permissions(location, args, &mut stmts, label, false)?;
+        Ok(stmts)
+    }
+
     /// Encode an edge of the MIR graph
     fn encode_edge_block(
         &mut self,
@@ -2662,8 +3042,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             statement_index: 0,
         };
         let stmts = self.encode_expiring_borrows_between(source_loc, destination_loc)?;
+        let proven_assertion = self.proven_assertions.get(&(source, destination)).cloned();
 
-        if force_block || !stmts.is_empty() {
+        if force_block || !stmts.is_empty() || proven_assertion.is_some() {
             let edge_label = self.cfg_method.get_fresh_label_name();
             let edge_block = self.cfg_method.add_block(
                 &edge_label,
@@ -2677,12 +3058,156 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     .add_stmt(edge_block, vir::Stmt::comment("Expire borrows"));
                 self.cfg_method.add_stmts(edge_block, stmts);
             }
+            if let Some(expr) = proven_assertion {
+                self.cfg_method
+                    .add_stmt(edge_block, vir::Stmt::comment("Assume the proven assertion"));
+                self.cfg_method.add_stmt(edge_block, vir::Stmt::Inhale(vir::Inhale { expr }));
+            }
             Ok(Some(edge_block))
         } else {
             Ok(None)
         }
     }
 
+    /// The expression that the temporary `operand` was computed from by the
+    /// statements of `block`, e.g. `x > 10` for the guard `_2` of
+    /// `_3 = Gt(copy _1, const 10_i32); _2 = Not(move _3)`. Returns `None` if
+    /// the operand is not computed from comparisons and negations of
+    /// variables and constants.
+    fn encode_asserted_expr(
+        &self,
+        block: BasicBlockIndex,
+        operand: &mir::Operand<'tcx>,
+    ) -> Option<vir::Expr> {
+        let place = match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place)
+                if place.projection.is_empty()
+                    && self.mir.local_kind(place.local) == mir::LocalKind::Temp => place,
+            mir::Operand::Constant(_) => return None,
+            _ => return self.mir_encoder.encode_operand_expr(operand).ok(),
+        };
+        let rvalue = self.mir[block].statements.iter().rev().find_map(|stmt| match &stmt.kind {
+            mir::StatementKind::Assign(box (target, rvalue)) if target == place => Some(rvalue),
+            _ => None,
+        })?;
+        match rvalue {
+            mir::Rvalue::Use(operand) => self.encode_asserted_expr(block, operand),
+            mir::Rvalue::UnaryOp(mir::UnOp::Not, operand) => self
+                .encode_asserted_expr(block, operand)
+                .map(vir::Expr::not),
+            mir::Rvalue::BinaryOp(op, box (left, right)) => {
+                let ty = left.ty(self.mir, self.encoder.env().tcx());
+                let encode = |operand: &mir::Operand<'tcx>| match operand {
+                    mir::Operand::Constant(_) => self.mir_encoder.encode_operand_expr(operand).ok(),
+                    _ => self.encode_asserted_expr(block, operand),
+                };
+                let (left, right) = (encode(left)?, encode(right)?);
+                self.mir_encoder.encode_bin_op_expr(*op, left, right, ty).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the block only panics because an `assert!` failed.
+    fn is_failing_assertion_block(&self, bbi: BasicBlockIndex) -> bool {
+        let term = self.mir[bbi].terminator();
+        if let TerminatorKind::Call {
+            func: mir::Operand::Constant(box mir::Constant { literal, .. }),
+            ..
+        } = &term.kind {
+            if let ty::TyKind::FnDef(called_def_id, _) = literal.ty().kind() {
+                let is_panic = matches!(
+                    self.encoder.env().tcx().def_path_str(*called_def_id).as_str(),
+                    "std::rt::begin_panic" | "core::panicking::panic" | "core::panicking::panic_fmt"
+                );
+                return is_panic && matches!(
+                    self.mir_encoder.encode_panic_cause(term.source_info.span),
+                    PanicCause::Assert | PanicCause::DebugAssert
+                );
+            }
+        }
+        false
+    }
+
+    /// Whether a call that failed to be encoded with `error` can be replaced
+    /// by a stub. This is the case for calls to `std` functions that have no
+    /// specification, if `STUB_UNENCODABLE_STD_FUNCTIONS` is enabled. Incorrect
+    /// usages of Prusti are always reported.
+    fn can_stub_function_call(
+        &self,
+        called_def_id: ProcedureDefId,
+        substs: SubstsRef<'tcx>,
+        error: &SpannedEncodingError,
+    ) -> bool {
+        if !config::stub_unencodable_std_functions()
+            || matches!(error.kind(), EncodingErrorKind::Incorrect(_))
+        {
+            return false;
+        }
+        let tcx = self.encoder.env().tcx();
+        let crate_name = tcx.crate_name(called_def_id.krate);
+        matches!(crate_name.as_str(), "std" | "core" | "alloc")
+            && self.encoder.get_procedure_specs(called_def_id, substs).is_none()
+    }
+
+    /// Encode a call to a function that could not be encoded as a call to a
+    /// trusted function without specification: the result is havocked.
+    fn encode_stubbed_function_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let function_name = self.encoder.env().tcx().def_path_str(called_def_id);
+        debug!("Stubbing out the call of '{}'", function_name);
+        self.stubbed_functions.push((function_name.clone(), call_site_span));
+        let mut stmts = vec![
+            vir::Stmt::comment(format!("Stubbed call of {}", function_name)),
+        ];
+        if destination.is_some() {
+            let (call_stmts, label) = self.encode_pure_function_call_site(
+                location,
+                destination,
+                true.into(),
+            )?;
+            stmts.extend(call_stmts);
+            self.encode_transfer_args_permissions(location, args, &mut stmts, label, false)?;
+        } else {
+            // The function never returns.
+            stmts.push(vir::Stmt::Inhale( vir::Inhale {
+                expr: false.into()
+            }));
+        }
+        Ok(stmts)
+    }
+
+    /// Emit a warning that lists the functions whose calls were stubbed out.
+    fn report_stubbed_functions(&self) {
+        if self.stubbed_functions.is_empty() {
+            return;
+        }
+        let mut names: Vec<_> = self.stubbed_functions.iter()
+            .map(|(name, _)| format!("`{}`", name))
+            .collect();
+        names.sort();
+        names.dedup();
+        let mut warning = PrustiError::unsupported(
+            format!(
+                "calls to the following functions could not be encoded and were stubbed \
+                out: {}",
+                names.join(", "),
+            ),
+            self.mir.span.into(),
+        ).set_help("add extern specifications for these functions to verify their calls");
+        for (name, span) in &self.stubbed_functions {
+            warning.add_note_mut(format!("stubbed call of `{}`", name), Some((*span).into()));
+        }
+        warning.set_warning();
+        warning.emit(self.encoder.env());
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn encode_impure_function_call(
         &mut self,
@@ -2701,6 +3226,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             // .absolute_item_path_str(called_def_id);
         debug!("Encoding non-pure function call '{}' with args {:?} and substs {:?}", full_func_proc_name, mir_args, substs);
 
+        if self.encoder.env().is_ignored(called_def_id) {
+            return Err(SpannedEncodingError::incorrect(
+                format!(
+                    "the function `{}` is defined in an ignored module and can only be \
+                    used through the trusted boundary of the module",
+                    full_func_proc_name,
+                ),
+                call_site_span,
+            ));
+        }
+
         // First we construct the "operands" vector. This construction differs
         // for closure calls, where we need to unpack a tuple into the actual
         // call arguments. The components of the operands tuples are:
@@ -2786,6 +3322,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let mut stmts = vec![];
         let mut stmts_after: Vec<vir::Stmt> = vec![];
 
+        if !self.encoder.env().tcx().is_closure(called_def_id) {
+            stmts.extend(self.encode_fn_pointer_contract_checks(called_def_id, mir_args, call_site_span)?);
+        }
+
         for (mir_arg, arg, arg_ty, encoded_operand) in operands {
             arguments.push(arg);
 
@@ -2922,6 +3462,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         };
         assert_one_magic_wand(procedure_contract.borrow_infos.len()).with_span(call_site_span)?;
 
+        if config::dump_call_graph() {
+            self.register_call_in_call_graph(called_def_id, substs);
+        }
+        self.encoder.register_procedure_instance(called_def_id, substs);
+
         // Store a label for the pre state
         let pre_label = self.cfg_method.get_fresh_label_name();
         stmts.push(vir::Stmt::label(pre_label.clone()));
@@ -2940,13 +3485,33 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         ) = self.encode_precondition_expr(&procedure_contract, substs, fake_expr_spans)?;
         let pos = self.register_error(call_site_span, ErrorCtxt::ExhaleMethodPrecondition);
         stmts.push(vir::Stmt::Assert( vir::Assert {
-            expr: replace_fake_exprs(pre_func_spec),
+            expr: replace_fake_exprs(pre_func_spec.into_iter().conjoin()),
             position: pos,
         }));
         stmts.push(vir::Stmt::Assert( vir::Assert {
             expr: replace_fake_exprs(pre_invs_spec),
             position: pos,
         }));
+        let encoded_args: Vec<vir::Expr> = procedure_contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        if let Some((no_panic, error_ctxt)) = self.encode_builtin_panic_check(&procedure_contract, &encoded_args)
+            .with_span(call_site_span)?
+        {
+            let no_panic = replace_fake_exprs(no_panic);
+            if self.check_panics {
+                stmts.push(vir::Stmt::Assert( vir::Assert {
+                    expr: no_panic,
+                    position: self.register_error(call_site_span, error_ctxt),
+                }));
+            } else {
+                stmts.push(vir::Stmt::Inhale( vir::Inhale {
+                    expr: no_panic,
+                }));
+            }
+        }
         let pre_perm_spec = replace_fake_exprs(pre_type_spec);
         assert!(!pos.is_default());
         stmts.push(vir::Stmt::Exhale( vir::Exhale {
@@ -3338,10 +3903,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         substs: SubstsRef<'tcx>,
         override_spans: FxHashMap<Local, Span> // spans for fake locals
     ) -> SpannedEncodingResult<(
-        vir::Expr,
-        Vec<vir::Expr>,
-        vir::Expr,
-        vir::Expr,
+        vir::Expr,      // Permissions from types.
+        Vec<vir::Expr>, // Read permissions that must be exhaled.
+        vir::Expr,      // Invariants.
+        Vec<vir::Expr>, // Functional specification, one per precondition.
     )> {
         let borrow_infos = &contract.borrow_infos;
         let maybe_blocked_paths = if !borrow_infos.is_empty() {
@@ -3400,7 +3965,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .map(|local| self.encode_prusti_local(*local).into())
             .collect();
 
-        let func_spec: Vec<vir::Expr> = contract.functional_precondition(
+        let mut func_spec: Vec<vir::Expr> = contract.functional_precondition(
                 self.encoder.env(),
                 substs,
             ).iter()
@@ -3414,6 +3979,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 assertion_substs,
             ))
             .collect::<Result<Vec<_>, _>>()?;
+        func_spec.extend(self.encode_builtin_precondition(contract, &encoded_args));
 
         // TODO(tymap): do this with the previous step ...
         let precondition_spans = MultiSpan::from_spans(
@@ -3441,7 +4007,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             type_spec.into_iter().conjoin(),
             mandatory_type_spec,
             invs_spec.into_iter().conjoin(),
-            func_spec.into_iter().conjoin(),
+            func_spec,
         ))
     }
 
@@ -3619,19 +4185,139 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 }),
             );
         }
-        self.cfg_method.add_stmt(
-            start_cfg_block,
-            vir::Stmt::Inhale( vir::Inhale {
-                expr: func_spec
-            }),
-        );
-        self.cfg_method.add_stmt(
-            start_cfg_block,
-            vir::Stmt::label(PRECONDITION_LABEL),
-        );
+        if config::report_unused_preconditions() {
+            // Inhale every precondition separately, so that the verifier can
+            // check whether the proof still succeeds without it.
+            let precondition_spans = self.procedure_contract()
+                .functional_precondition(self.encoder.env(), self.substs)
+                .iter()
+                .map(|(assertion, _)| self.encoder.env().tcx().def_span(assertion.to_def_id()))
+                .collect();
+            for (index, conjunct) in func_spec.into_iter().enumerate() {
+                self.cfg_method.add_stmt(
+                    start_cfg_block,
+                    vir::Stmt::comment(precondition_conjunct_marker(index)),
+                );
+                self.cfg_method.add_stmt(
+                    start_cfg_block,
+                    vir::Stmt::Inhale( vir::Inhale {
+                        expr: conjunct
+                    }),
+                );
+            }
+            self.encoder.register_precondition_spans(self.proc_def_id, precondition_spans);
+        } else {
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::Inhale( vir::Inhale {
+                    expr: func_spec.into_iter().conjoin()
+                }),
+            );
+        }
+        let assumptions = self.encode_module_assumptions()?;
+        if !assumptions.is_empty() {
+            self.cfg_method
+                .add_stmt(start_cfg_block, vir::Stmt::comment("Module assumptions:"));
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::Inhale( vir::Inhale {
+                    expr: assumptions.into_iter().conjoin()
+                }),
+            );
+        }
+        let enclosing_preconditions = self.encode_enclosing_preconditions()?;
+        if !enclosing_preconditions.is_empty() {
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::comment("Preconditions of the enclosing procedure:"),
+            );
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::Inhale( vir::Inhale {
+                    expr: enclosing_preconditions.into_iter().conjoin()
+                }),
+            );
+        }
+        self.cfg_method.add_stmt(
+            start_cfg_block,
+            vir::Stmt::label(PRECONDITION_LABEL),
+        );
         Ok(())
     }
 
+    /// Encode the assertions of the `#[assumptions(...)]` attribute of the
+    /// module that contains the procedure.
+    fn encode_module_assumptions(&self) -> SpannedEncodingResult<Vec<vir::Expr>> {
+        let contract = self.procedure_contract();
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        contract.functional_assumptions(self.substs)
+            .iter()
+            .map(|(assertion, assertion_substs)| self.encoder.encode_assertion(
+                assertion,
+                None,
+                &encoded_args,
+                None,
+                false,
+                self.proc_def_id,
+                assertion_substs,
+            ))
+            .collect()
+    }
+
+    /// Encode the preconditions of the procedure in which the closure that is
+    /// being verified is immediately invoked (e.g. `(|| { ... })()`). The
+    /// parameters of that procedure are replaced by the closure fields that
+    /// capture them.
+    fn encode_enclosing_preconditions(&self) -> SpannedEncodingResult<Vec<vir::Expr>> {
+        let (caller_def_id, captures) = match self.encoder.env().get_iife_caller(self.proc_def_id) {
+            Some(iife_caller) => iife_caller,
+            None => return Ok(vec![]),
+        };
+        let tcx = self.encoder.env().tcx();
+        let span = self.mir.span;
+        // The closure is passed by reference, unless it is a `FnOnce` closure.
+        let env_local = mir::Local::new(1);
+        let mut env_place: mir::Place<'tcx> = env_local.into();
+        let mut env_ty = self.mir.local_decls[env_local].ty;
+        if let ty::TyKind::Ref(_, ty, _) = env_ty.kind() {
+            env_place = tcx.mk_place_deref(env_place);
+            env_ty = *ty;
+        }
+        let upvar_tys: Vec<_> = match env_ty.kind() {
+            ty::TyKind::Closure(_, substs) => substs.as_closure().upvar_tys().collect(),
+            _ => return Ok(vec![]),
+        };
+        let mut encoded_args = vec![];
+        for (field, by_ref) in captures {
+            let mut place = tcx.mk_place_field(env_place, field, upvar_tys[field.index()]);
+            if by_ref {
+                place = tcx.mk_place_deref(place);
+            }
+            let (encoded_place, _, _) = self.mir_encoder.encode_place(&place).with_span(span)?;
+            encoded_args.push(encoded_place.try_into_expr().with_span(span)?);
+        }
+        let caller_substs = self.encoder.env().identity_substs(caller_def_id);
+        let caller_contract = self.encoder
+            .get_procedure_contract_for_def(caller_def_id, caller_substs)
+            .with_span(span)?;
+        caller_contract.functional_precondition(self.encoder.env(), caller_substs)
+            .iter()
+            .map(|(assertion, assertion_substs)| self.encoder.encode_assertion(
+                assertion,
+                None,
+                &encoded_args,
+                None,
+                false,
+                self.proc_def_id,
+                assertion_substs,
+            ))
+            .collect()
+    }
+
     /// Encode the magic wand used in the postcondition with its
     /// functional specification. Returns (lhs, rhs).
     fn encode_postcondition_magic_wand(
@@ -3937,25 +4623,37 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let mut func_spec_spans = vec![];
         let func_postcondition = contract.functional_postcondition(self.encoder.env(), substs);
         for (typed_assertion, assertion_substs) in func_postcondition {
-            let mut assertion = self.encoder.encode_assertion(
-                &typed_assertion,
-                Some(pre_label),
-                &encoded_args,
-                Some(&encoded_return),
-                false,
-                self.proc_def_id,
+            // At the end of the method, the postconditions of named cases are
+            // asserted separately by `encode_case_postconditions`.
+            if function_end && contract.case_name(typed_assertion).is_some() {
+                continue;
+            }
+            let (assertion, assertion_span) = self.encode_postcondition_assertion(
+                typed_assertion,
                 assertion_substs,
+                contract,
+                pre_label,
+                &encoded_args,
+                &encoded_return,
             )?;
-            let assertion_span = self.encoder.env().tcx().def_span(typed_assertion.to_def_id());
             func_spec_spans.push(assertion_span);
-            let assertion_pos = self.mir_encoder.register_span(assertion_span);
-            assertion = self.wrap_arguments_into_old(
-                assertion,
-                pre_label,
+            func_spec.push(assertion);
+        }
+        if !function_end {
+            let builtin_postcondition = self.encode_builtin_postcondition(
                 contract,
+                pre_label,
                 &encoded_args,
-            )?;
-            func_spec.push(assertion.set_default_pos(assertion_pos));
+                &encoded_return,
+            ).with_span(span)?;
+            if let Some(assertion) = builtin_postcondition {
+                func_spec.push(self.wrap_arguments_into_old(
+                    assertion,
+                    pre_label,
+                    contract,
+                    &encoded_args,
+                )?);
+            }
         }
         let postcondition_span = MultiSpan::from_spans(func_spec_spans);
         let func_spec_pos = self.mir_encoder.register_span(postcondition_span.clone());
@@ -3981,6 +4679,896 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         ))
     }
 
+    /// Encode the postcondition `typed_assertion` of the procedure, with the
+    /// arguments wrapped into `old`. Also return the span of the assertion.
+    fn encode_postcondition_assertion(
+        &mut self,
+        typed_assertion: LocalDefId,
+        assertion_substs: SubstsRef<'tcx>,
+        contract: &ProcedureContract<'tcx>,
+        pre_label: &str,
+        encoded_args: &[vir::Expr],
+        encoded_return: &vir::Expr,
+    ) -> SpannedEncodingResult<(vir::Expr, Span)> {
+        let assertion = self.encoder.encode_assertion(
+            &typed_assertion,
+            Some(pre_label),
+            encoded_args,
+            Some(encoded_return),
+            false,
+            self.proc_def_id,
+            assertion_substs,
+        )?;
+        let assertion_span = self.encoder.env().tcx().def_span(typed_assertion.to_def_id());
+        let assertion_pos = self.mir_encoder.register_span(assertion_span);
+        let assertion = self.wrap_arguments_into_old(
+            assertion,
+            pre_label,
+            contract,
+            encoded_args,
+        )?;
+        Ok((assertion.set_default_pos(assertion_pos), assertion_span))
+    }
+
+    /// Encode the postconditions of the named `#[case(...)]` attributes of
+    /// the procedure, to be asserted at the end of the method. Each of them is
+    /// returned with its position, so that a failure names the case.
+    fn encode_case_postconditions(
+        &mut self,
+        contract: &ProcedureContract<'tcx>,
+    ) -> SpannedEncodingResult<Vec<(vir::Expr, vir::Position)>> {
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        let encoded_return: vir::Expr = self.encode_prusti_local(contract.returned_value).into();
+        let mut case_posts = vec![];
+        for (typed_assertion, assertion_substs) in contract.functional_postcondition(self.encoder.env(), self.substs) {
+            let case_name = if let Some(case_name) = contract.case_name(typed_assertion) {
+                case_name.to_string()
+            } else {
+                continue;
+            };
+            let (assertion, _) = self.encode_postcondition_assertion(
+                typed_assertion,
+                assertion_substs,
+                contract,
+                PRECONDITION_LABEL,
+                &encoded_args,
+                &encoded_return,
+            )?;
+            let pos = self.register_error(self.mir.span, ErrorCtxt::AssertMethodPostconditionOfCase(case_name));
+            case_posts.push((assertion, pos));
+        }
+        Ok(case_posts)
+    }
+
+    /// Encode the obligation of a `#[prusti::equivalent_to(f)]` attribute: on
+    /// all inputs that satisfy the precondition of the pure function `f`, the
+    /// procedure returns the same value as `f`. Since `f` is pure, it cannot
+    /// mutate its arguments; its signature must be the same as the one of the
+    /// procedure.
+    fn encode_equivalence_check(
+        &self,
+        contract: &ProcedureContract<'tcx>,
+    ) -> SpannedEncodingResult<Option<(vir::Expr, vir::Position)>> {
+        let tcx = self.encoder.env().tcx();
+        let span = self.mir.span;
+        let path = match utils::read_prusti_attr_path("equivalent_to", tcx.get_attrs(self.proc_def_id)) {
+            Some(path) => path.map_err(|message| SpannedEncodingError::incorrect(message, span))?,
+            None => return Ok(None),
+        };
+        let other_def_id = self.encoder.env()
+            .resolve_local_function_path(self.proc_def_id, &path)
+            .ok_or_else(|| SpannedEncodingError::incorrect(
+                format!("cannot find the function `{}` of the `equivalent_to` attribute", path),
+                span,
+            ))?;
+        let sig = tcx.erase_late_bound_regions(tcx.fn_sig(self.proc_def_id));
+        // Only the results are compared, so the final values of mutably
+        // borrowed arguments would be left unchecked.
+        if sig.inputs().iter().any(|ty| ty.walk().any(|arg| matches!(
+            arg.unpack(),
+            ty::subst::GenericArgKind::Type(ty) if matches!(ty.kind(), ty::TyKind::Ref(_, _, Mutability::Mut))
+        ))) {
+            return Err(SpannedEncodingError::unsupported(
+                "the `equivalent_to` attribute is supported only on functions whose arguments \
+                do not contain mutable references",
+                span,
+            ));
+        }
+        let other_substs = self.encoder.env().identity_substs(other_def_id);
+        if !self.encoder.is_pure(other_def_id, Some(other_substs)) {
+            return Err(SpannedEncodingError::incorrect(
+                format!("the function `{}` of the `equivalent_to` attribute must be pure", path),
+                span,
+            ));
+        }
+        let other_sig = tcx.erase_late_bound_regions(tcx.fn_sig(other_def_id));
+        if sig.inputs() != other_sig.inputs() || sig.output() != other_sig.output() {
+            return Err(SpannedEncodingError::incorrect(
+                format!("the function `{}` of the `equivalent_to` attribute must have the same \
+                    signature as the annotated function", path),
+                span,
+            ));
+        }
+
+        let (function_name, return_type) = self.encoder
+            .encode_pure_function_use(other_def_id, self.proc_def_id, other_substs)
+            .with_span(span)?;
+        let type_arguments = self.encoder
+            .encode_generic_arguments(other_def_id, other_substs)
+            .with_span(span)?;
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        let formal_args: Vec<vir::LocalVar> = contract
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, &arg)| {
+                self.encoder.encode_snapshot_type(self.locals.get_type(arg))
+                    .map(|ty| vir::LocalVar::new(format!("x{}", i), ty))
+            })
+            .collect::<Result<_, _>>()
+            .with_span(span)?;
+        let func_call = vir::Expr::func_app(
+            function_name,
+            type_arguments,
+            encoded_args.iter().cloned().map(vir::Expr::snap_app).collect(),
+            formal_args,
+            return_type,
+            vir::Position::default(),
+        );
+        let encoded_return: vir::Expr = self.encode_prusti_local(contract.returned_value).into();
+        let same_result = vir::Expr::eq_cmp(vir::Expr::snap_app(encoded_return), func_call);
+
+        // The equivalence only needs to hold on the inputs that satisfy the
+        // precondition of both functions.
+        let other_contract = self.encoder
+            .get_procedure_contract_for_def(other_def_id, other_substs)
+            .with_span(span)?;
+        let other_precondition = other_contract
+            .functional_precondition(self.encoder.env(), other_substs)
+            .iter()
+            .map(|(assertion, assertion_substs)| self.encoder.encode_assertion(
+                assertion,
+                None,
+                &encoded_args,
+                None,
+                false,
+                self.proc_def_id,
+                assertion_substs,
+            ))
+            .collect::<Result<Vec<_>, _>>()?;
+        let equivalence = self.wrap_arguments_into_old(
+            vir::Expr::implies(other_precondition.into_iter().conjoin(), same_result),
+            PRECONDITION_LABEL,
+            contract,
+            &encoded_args,
+        )?;
+        let pos = self.register_error(span, ErrorCtxt::AssertMethodEquivalence(path));
+        Ok(Some((equivalence, pos)))
+    }
+
+    /// The contract functions declared with `#[prusti::fn_contract(f)]` on the
+    /// function pointer parameters of `def_id`, with the index of the parameter.
+    fn fn_pointer_contracts(
+        &self,
+        def_id: ProcedureDefId,
+    ) -> SpannedEncodingResult<Vec<(usize, ProcedureDefId)>> {
+        let tcx = self.encoder.env().tcx();
+        let inputs = tcx.erase_late_bound_regions(tcx.fn_sig(def_id)).inputs().to_vec();
+        let mut contracts = vec![];
+        for (index, span, path) in self.encoder.env().get_fn_pointer_contracts(def_id) {
+            let path = path.map_err(|message| SpannedEncodingError::incorrect(message, span))?;
+            let contract_def_id = self.encoder.env()
+                .resolve_local_function_path(def_id, &path)
+                .ok_or_else(|| SpannedEncodingError::incorrect(
+                    format!("cannot find the function `{}` of the `fn_contract` attribute", path),
+                    span,
+                ))?;
+            let fn_ptr_sig = match inputs[index].kind() {
+                ty::TyKind::FnPtr(sig) => tcx.erase_late_bound_regions(*sig),
+                _ => return Err(SpannedEncodingError::incorrect(
+                    "the `fn_contract` attribute can only be used on parameters of function pointer type",
+                    span,
+                )),
+            };
+            let contract_sig = tcx.erase_late_bound_regions(tcx.fn_sig(contract_def_id));
+            if contract_sig.inputs() != fn_ptr_sig.inputs() || contract_sig.output() != fn_ptr_sig.output() {
+                return Err(SpannedEncodingError::incorrect(
+                    format!("the function `{}` of the `fn_contract` attribute must have the \
+                        signature of the function pointer", path),
+                    span,
+                ));
+            }
+            contracts.push((index, contract_def_id));
+        }
+        Ok(contracts)
+    }
+
+    /// The function from which the function pointer stored in `local` was
+    /// created, following the copies between locals. A parameter of the
+    /// procedure stands for the contract declared by its
+    /// `#[prusti::fn_contract(f)]` attribute. The source is unknown if one of
+    /// the locals is assigned more than once, e.g. on two branches.
+    fn fn_pointer_source(
+        &self,
+        mut local: mir::Local,
+    ) -> SpannedEncodingResult<Option<(ProcedureDefId, SubstsRef<'tcx>)>> {
+        loop {
+            let assignments = match self.local_assignments(local) {
+                Some(assignments) => assignments,
+                None => return Ok(None),
+            };
+            if local.index() >= 1 && local.index() <= self.mir.arg_count {
+                if !assignments.is_empty() {
+                    return Ok(None);
+                }
+                let contract = self.fn_pointer_contracts(self.proc_def_id)?
+                    .into_iter()
+                    .find(|&(index, _)| index + 1 == local.index())
+                    .map(|(_, def_id)| (def_id, self.encoder.env().identity_substs(def_id)));
+                return Ok(contract);
+            }
+            let rvalue = match assignments[..] {
+                [rvalue] => rvalue,
+                _ => return Ok(None),
+            };
+            match rvalue {
+                mir::Rvalue::Use(mir::Operand::Copy(place) | mir::Operand::Move(place))
+                    if place.as_local().is_some() =>
+                {
+                    local = place.local;
+                }
+                mir::Rvalue::Cast(
+                    mir::CastKind::Pointer(ty::adjustment::PointerCast::ReifyFnPointer),
+                    mir::Operand::Constant(constant),
+                    _,
+                ) => {
+                    return Ok(match constant.ty().kind() {
+                        ty::TyKind::FnDef(def_id, substs) => Some((*def_id, substs)),
+                        _ => None,
+                    });
+                }
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// The right-hand sides of all assignments to `local` in the body, or
+    /// `None` if `local` is also written in another way, i.e. as the
+    /// destination of a call or through a mutable borrow or a raw pointer.
+    fn local_assignments(&self, local: mir::Local) -> Option<Vec<&mir::Rvalue<'tcx>>> {
+        let mut assignments = vec![];
+        for block in self.mir.basic_blocks() {
+            for stmt in &block.statements {
+                if let mir::StatementKind::Assign(box (place, rvalue)) = &stmt.kind {
+                    if place.local == local {
+                        if place.as_local().is_none() {
+                            return None;
+                        }
+                        assignments.push(rvalue);
+                    }
+                    match rvalue {
+                        mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, place)
+                        | mir::Rvalue::AddressOf(_, place) if place.local == local => {
+                            return None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if let mir::TerminatorKind::Call { destination: Some((place, _)), .. } = &block.terminator().kind {
+                if place.local == local {
+                    return None;
+                }
+            }
+        }
+        Some(assignments)
+    }
+
+    /// Encode the checks that the functions passed for the function pointer
+    /// parameters of `called_def_id` satisfy the contracts declared by their
+    /// `#[prusti::fn_contract(f)]` attributes.
+    fn encode_fn_pointer_contract_checks(
+        &self,
+        called_def_id: ProcedureDefId,
+        mir_args: &[mir::Operand<'tcx>],
+        call_site_span: Span,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let mut stmts = vec![];
+        for (index, contract_def_id) in self.fn_pointer_contracts(called_def_id)? {
+            let source = match mir_args[index].place().and_then(|place| place.as_local()) {
+                Some(local) => self.fn_pointer_source(local)?,
+                None => None,
+            };
+            let (source_def_id, source_substs) = source.ok_or_else(|| SpannedEncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::FunctionPointers,
+                "cannot determine the function passed for a function pointer with a contract",
+                call_site_span,
+            ))?;
+            if source_def_id == contract_def_id {
+                continue;
+            }
+            let check = self.encode_fn_contract_satisfaction(
+                contract_def_id,
+                source_def_id,
+                source_substs,
+                call_site_span,
+            )?;
+            let contract_name = self.encoder.env().tcx().def_path_str(contract_def_id);
+            let pos = self.register_error(call_site_span, ErrorCtxt::AssertFnPointerContract(contract_name));
+            stmts.push(vir::Stmt::comment(format!(
+                "Check the contract of the function pointer argument {}", index,
+            )));
+            stmts.push(vir::Stmt::Assert( vir::Assert {
+                expr: check,
+                position: pos,
+            }));
+        }
+        Ok(stmts)
+    }
+
+    /// Encode that the function `def_id` satisfies the contract of the function
+    /// `contract_def_id`, i.e. that on all arguments its precondition is weaker
+    /// and its postcondition is stronger. Since the arguments and the result
+    /// are compared by value, their types cannot contain references.
+    fn encode_fn_contract_satisfaction(
+        &self,
+        contract_def_id: ProcedureDefId,
+        def_id: ProcedureDefId,
+        substs: SubstsRef<'tcx>,
+        span: Span,
+    ) -> SpannedEncodingResult<vir::Expr> {
+        let tcx = self.encoder.env().tcx();
+        let sig = tcx.erase_late_bound_regions(tcx.fn_sig(contract_def_id));
+        if sig.inputs_and_output.iter().any(|ty| ty.walk().any(|arg| {
+            matches!(arg.unpack(), ty::subst::GenericArgKind::Type(ty) if ty.is_any_ptr())
+        })) {
+            return Err(SpannedEncodingError::unsupported_feature(
+                UnsupportedFeatureCategory::FunctionPointers,
+                "contracts of function pointers are supported only if the arguments and the \
+                result do not contain references",
+                span,
+            ));
+        }
+        let vars = sig.inputs()
+            .iter()
+            .enumerate()
+            .map(|(index, ty)| {
+                self.encoder.encode_snapshot_type(*ty)
+                    .map(|ty| vir::LocalVar::new(format!("x{}", index), ty))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .with_span(span)?;
+        let result_var = vir::LocalVar::new(
+            "result",
+            self.encoder.encode_snapshot_type(sig.output()).with_span(span)?,
+        );
+        let args: Vec<vir::Expr> = vars.iter().cloned().map(vir::Expr::local).collect();
+        let result: vir::Expr = result_var.clone().into();
+        let encode_specs = |def_id: ProcedureDefId, substs: SubstsRef<'tcx>, postcondition: bool| {
+            let contract = self.encoder
+                .get_procedure_contract_for_def(def_id, substs)
+                .with_span(span)?;
+            let specs = if postcondition {
+                contract.functional_postcondition(self.encoder.env(), substs)
+            } else {
+                contract.functional_precondition(self.encoder.env(), substs)
+            };
+            specs.iter()
+                .map(|(assertion, assertion_substs)| self.encoder.encode_assertion(
+                    assertion,
+                    None,
+                    &args,
+                    if postcondition { Some(&result) } else { None },
+                    true,
+                    self.proc_def_id,
+                    assertion_substs,
+                ))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|specs| specs.into_iter().conjoin())
+        };
+        let contract_substs = self.encoder.env().identity_substs(contract_def_id);
+        let contract_pre = encode_specs(contract_def_id, contract_substs, false)?;
+        let contract_post = encode_specs(contract_def_id, contract_substs, true)?;
+        let pre = encode_specs(def_id, substs, false)?;
+        let post = encode_specs(def_id, substs, true)?;
+
+        let mut post_vars = vars.clone();
+        post_vars.push(result_var);
+        Ok(vir::Expr::and(
+            vir::Expr::forall(vars, vec![], vir::Expr::implies(contract_pre.clone(), pre)),
+            vir::Expr::forall(
+                post_vars,
+                vec![],
+                vir::Expr::implies(vir::Expr::and(contract_pre, post), contract_post),
+            ),
+        ))
+    }
+
+    /// Encode the postcondition of standard library functions whose effect
+    /// is modelled precisely by the encoder instead of being havocked:
+    ///
+    /// - `mem::swap(a, b)` exchanges `*a` and `*b`;
+    /// - `mem::replace(dest, src)` returns `old(*dest)` and stores `src`;
+    /// - `mem::take(dest)` returns `old(*dest)` and, for primitive types and
+    ///   structs that derive `Default`, leaves their default value in `*dest`;
+    /// - `slice::sort(s)` and `slice::sort_unstable(s)` leave the integers of
+    ///   `*s` sorted and a permutation of the elements before;
+    /// - `slice::swap(s, a, b)` exchanges `s[a]` and `s[b]`, which are checked
+    ///   to be in bounds by `encode_builtin_panic_check`, and keeps the
+    ///   multiset of the elements of `*s`;
+    /// - `Try::branch(r)` and `FromResidual::from_residual(r)` on `Result`,
+    ///   which implement the `?` operator, continue with the `Ok` value and
+    ///   return early with the `Err` value;
+    /// - `Default::default()` of primitive types and of structs that derive
+    ///   `Default` returns the value of `Default::default()` in specifications.
+    ///
+    /// The returned assertion still needs to be wrapped into `old` by
+    /// `wrap_arguments_into_old`.
+    fn encode_builtin_postcondition(
+        &self,
+        contract: &ProcedureContract<'tcx>,
+        pre_label: &str,
+        encoded_args: &[vir::Expr],
+        encoded_return: &vir::Expr,
+    ) -> EncodingResult<Option<vir::Expr>> {
+        let full_func_proc_name = self.encoder.env().tcx().def_path_str(contract.def_id);
+        let encode_deref_arg = |index: usize| {
+            let arg_ty = self.locals.get_type(contract.args[index]);
+            self.mir_encoder
+                .encode_deref(encoded_args[index].clone(), arg_ty)
+                .map(|(place, ty, _)| (place, ty))
+        };
+        let snap_eq_old = |place: vir::Expr, old_place: vir::Expr| {
+            vir::Expr::eq_cmp(
+                vir::Expr::snap_app(place),
+                vir::Expr::snap_app(old_place).old(pre_label),
+            )
+        };
+        let assertion = match full_func_proc_name.as_str() {
+            "std::mem::swap" | "core::mem::swap" => {
+                let (left, ty) = encode_deref_arg(0)?;
+                let (right, _) = encode_deref_arg(1)?;
+                if !self.encoder.supports_snapshot_equality(ty)? {
+                    return Ok(None);
+                }
+                vir::Expr::and(
+                    snap_eq_old(left.clone(), right.clone()),
+                    snap_eq_old(right, left),
+                )
+            }
+            "std::mem::replace" | "core::mem::replace" => {
+                let (dest, ty) = encode_deref_arg(0)?;
+                if !self.encoder.supports_snapshot_equality(ty)? {
+                    return Ok(None);
+                }
+                vir::Expr::and(
+                    snap_eq_old(encoded_return.clone(), dest.clone()),
+                    snap_eq_old(dest, encoded_args[1].clone()),
+                )
+            }
+            "std::mem::take" | "core::mem::take" => {
+                let (dest, ty) = encode_deref_arg(0)?;
+                if !self.encoder.supports_snapshot_equality(ty)? {
+                    return Ok(None);
+                }
+                let mut conjuncts = vec![snap_eq_old(encoded_return.clone(), dest.clone())];
+                if self.has_derived_default(ty) {
+                    conjuncts.push(vir::Expr::eq_cmp(
+                        vir::Expr::snap_app(dest),
+                        self.encoder.encode_default_value(ty)?,
+                    ));
+                }
+                conjuncts.into_iter().conjoin()
+            }
+            "alloc::slice::<impl [T]>::sort" | "std::slice::<impl [T]>::sort" |
+            "core::slice::<impl [T]>::sort_unstable" => {
+                let (slice, slice_ty) = encode_deref_arg(0)?;
+                let sequence_types = self.encoder.encode_sequence_types(slice_ty)?;
+                // Only the order of primitive integers is known to the verifier.
+                if !matches!(
+                    sequence_types.elem_ty_rs.kind(),
+                    ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char
+                ) {
+                    return Ok(None);
+                }
+                let elem_snap_ty = self.encoder.encode_snapshot_type(sequence_types.elem_ty_rs)?;
+                let lookup = |seq: vir::Expr, idx: &vir::LocalVar| {
+                    sequence_types.encode_lookup_pure_call(
+                        self.encoder,
+                        seq,
+                        idx.clone().into(),
+                        elem_snap_ty.clone(),
+                    )
+                };
+                let in_bounds = |idx: &vir::LocalVar, len: vir::Expr| {
+                    vir::Expr::and(
+                        vir::Expr::le_cmp(0.into(), idx.clone().into()),
+                        vir::Expr::lt_cmp(idx.clone().into(), len),
+                    )
+                };
+                let len = sequence_types.len(self.encoder, slice.clone());
+                let old_len = len.clone().old(pre_label);
+                let lookup_new = |idx: &vir::LocalVar| lookup(slice.clone(), idx);
+                let lookup_old = |idx: &vir::LocalVar| lookup(slice.clone(), idx).old(pre_label);
+                let i = vir_local!{ i: Int };
+                let j = vir_local!{ j: Int };
+
+                // forall i, j :: 0 <= i && i < j && j < len(s) ==> s[i] <= s[j]
+                let sorted = vir::Expr::forall(
+                    vec![i.clone(), j.clone()],
+                    vec![vir::Trigger::new(vec![
+                        lookup_new(&i),
+                        lookup_new(&j),
+                    ])],
+                    vir::Expr::implies(
+                        vec![
+                            vir::Expr::le_cmp(0.into(), i.clone().into()),
+                            vir::Expr::lt_cmp(i.clone().into(), j.clone().into()),
+                            vir::Expr::lt_cmp(j.clone().into(), len.clone()),
+                        ].into_iter().conjoin(),
+                        vir::Expr::le_cmp(lookup_new(&i), lookup_new(&j)),
+                    ),
+                );
+
+                // The sorted slice is a permutation of the original one. That
+                // every element of the sorted slice comes from the original
+                // one and vice versa follows from it, but is stated as well
+                // to give the verifier the indices of the elements.
+                let permutation = self.encoder.encode_snapshot_permutation(
+                    slice_ty,
+                    vir::Expr::snap_app(slice.clone()),
+                    vir::Expr::snap_app(slice.clone()).old(pre_label),
+                )?;
+                let contained_in = |
+                    from: &dyn Fn(&vir::LocalVar) -> vir::Expr,
+                    from_len: &vir::Expr,
+                    to: &dyn Fn(&vir::LocalVar) -> vir::Expr,
+                    to_len: &vir::Expr,
+                | {
+                    vir::Expr::forall(
+                        vec![i.clone()],
+                        vec![vir::Trigger::new(vec![from(&i)])],
+                        vir::Expr::implies(
+                            in_bounds(&i, from_len.clone()),
+                            vir::Expr::exists(
+                                vec![j.clone()],
+                                vec![vir::Trigger::new(vec![to(&j)])],
+                                vir::Expr::and(
+                                    in_bounds(&j, to_len.clone()),
+                                    vir::Expr::eq_cmp(from(&i), to(&j)),
+                                ),
+                            ),
+                        ),
+                    )
+                };
+
+                vec![
+                    vir::Expr::eq_cmp(len.clone(), old_len.clone()),
+                    sorted,
+                    permutation,
+                    contained_in(&lookup_new, &len, &lookup_old, &old_len),
+                    contained_in(&lookup_old, &old_len, &lookup_new, &len),
+                ].into_iter().conjoin()
+            }
+            "core::slice::<impl [T]>::swap" => {
+                let (slice, slice_ty) = encode_deref_arg(0)?;
+                let sequence_types = self.encoder.encode_sequence_types(slice_ty)?;
+                if !self.encoder.supports_snapshot_equality(sequence_types.elem_ty_rs)? {
+                    return Ok(None);
+                }
+                let elem_snap_ty = self.encoder.encode_snapshot_type(sequence_types.elem_ty_rs)?;
+                let lookup = |idx: vir::Expr| {
+                    sequence_types.encode_lookup_pure_call(
+                        self.encoder,
+                        slice.clone(),
+                        idx,
+                        elem_snap_ty.clone(),
+                    )
+                };
+                let a = vir::Expr::snap_app(encoded_args[1].clone());
+                let b = vir::Expr::snap_app(encoded_args[2].clone());
+                let len = sequence_types.len(self.encoder, slice.clone());
+                let k = vir_local!{ k: Int };
+                let k_expr: vir::Expr = k.clone().into();
+
+                // The other elements are unchanged:
+                // forall k :: 0 <= k < len(s) && k != a && k != b ==> s[k] == old(s[k])
+                let unchanged = vir::Expr::forall(
+                    vec![k.clone()],
+                    vec![vir::Trigger::new(vec![lookup(k_expr.clone())])],
+                    vir::Expr::implies(
+                        vec![
+                            vir::Expr::le_cmp(0.into(), k_expr.clone()),
+                            vir::Expr::lt_cmp(k_expr.clone(), len.clone()),
+                            vir::Expr::ne_cmp(k_expr.clone(), a.clone()),
+                            vir::Expr::ne_cmp(k_expr.clone(), b.clone()),
+                        ].into_iter().conjoin(),
+                        vir::Expr::eq_cmp(
+                            lookup(k_expr.clone()),
+                            lookup(k_expr).old(pre_label),
+                        ),
+                    ),
+                );
+                // The multiset of the elements is unchanged, which is what
+                // allows a sequence of swaps to maintain `is_permutation`.
+                let permutation = self.encoder.encode_snapshot_permutation(
+                    slice_ty,
+                    vir::Expr::snap_app(slice.clone()),
+                    vir::Expr::snap_app(slice.clone()).old(pre_label),
+                )?;
+                vec![
+                    vir::Expr::eq_cmp(len.clone(), len.clone().old(pre_label)),
+                    vir::Expr::eq_cmp(lookup(a.clone()), lookup(b.clone()).old(pre_label)),
+                    vir::Expr::eq_cmp(lookup(b), lookup(a).old(pre_label)),
+                    unchanged,
+                    permutation,
+                ].into_iter().conjoin()
+            }
+            _ if self.result_try_method(contract.def_id) == Some("branch") => {
+                // `r?` continues with `v` if `r` is `Ok(v)`, and breaks with
+                // `Err(e)` if `r` is `Err(e)`.
+                let arg_ty = self.locals.get_type(contract.args[0]);
+                let return_ty = self.locals.get_type(contract.returned_value);
+                let (is_ok, ok_value) =
+                    self.encode_enum_variant_guard(encoded_args[0].clone(), arg_ty, "Ok")?;
+                let (is_err, err_value) =
+                    self.encode_enum_variant_guard(encoded_args[0].clone(), arg_ty, "Err")?;
+                let (is_continue, continue_value) =
+                    self.encode_enum_variant_guard(encoded_return.clone(), return_ty, "Continue")?;
+                let (is_break, residual) =
+                    self.encode_enum_variant_guard(encoded_return.clone(), return_ty, "Break")?;
+                let mut conjuncts = vec![
+                    vir::Expr::implies(is_ok.clone(), is_continue),
+                    vir::Expr::implies(is_err.clone(), is_break),
+                ];
+                if let (Some((ok_value, ty)), Some((continue_value, _))) = (ok_value, continue_value) {
+                    if self.encoder.supports_snapshot_equality(ty)? {
+                        conjuncts.push(vir::Expr::implies(
+                            is_ok,
+                            vir::Expr::eq_cmp(
+                                vir::Expr::snap_app(continue_value),
+                                vir::Expr::snap_app(ok_value),
+                            ),
+                        ));
+                    }
+                }
+                if let Some((residual, residual_ty)) = residual {
+                    let (is_residual_err, residual_value) =
+                        self.encode_enum_variant_guard(residual, residual_ty, "Err")?;
+                    conjuncts.push(vir::Expr::implies(is_err.clone(), is_residual_err));
+                    if let (Some((err_value, ty)), Some((residual_value, _))) = (err_value, residual_value) {
+                        if self.encoder.supports_snapshot_equality(ty)? {
+                            conjuncts.push(vir::Expr::implies(
+                                is_err,
+                                vir::Expr::eq_cmp(
+                                    vir::Expr::snap_app(residual_value),
+                                    vir::Expr::snap_app(err_value),
+                                ),
+                            ));
+                        }
+                    }
+                }
+                conjuncts.into_iter().conjoin()
+            }
+            _ if self.result_try_method(contract.def_id) == Some("from_residual") => {
+                // The early return of `r?` is always an `Err`. Its value is
+                // only known if no conversion of the error type is needed.
+                let arg_ty = self.locals.get_type(contract.args[0]);
+                let return_ty = self.locals.get_type(contract.returned_value);
+                let (is_residual_err, residual_value) =
+                    self.encode_enum_variant_guard(encoded_args[0].clone(), arg_ty, "Err")?;
+                let (is_err, err_value) =
+                    self.encode_enum_variant_guard(encoded_return.clone(), return_ty, "Err")?;
+                let mut conjuncts = vec![is_err];
+                if let (Some((residual_value, residual_ty)), Some((err_value, ty))) = (residual_value, err_value) {
+                    if residual_ty == ty && self.encoder.supports_snapshot_equality(ty)? {
+                        conjuncts.push(vir::Expr::implies(
+                            is_residual_err,
+                            vir::Expr::eq_cmp(
+                                vir::Expr::snap_app(err_value),
+                                vir::Expr::snap_app(residual_value),
+                            ),
+                        ));
+                    }
+                }
+                conjuncts.into_iter().conjoin()
+            }
+            _ if self.is_default_method(contract.def_id) => {
+                // The value returned by a derived `Default` impl is the one
+                // used for `Default::default()` in specifications.
+                let return_ty = self.locals.get_type(contract.returned_value);
+                if !self.has_derived_default(return_ty)
+                    || !self.encoder.supports_snapshot_equality(return_ty)?
+                {
+                    return Ok(None);
+                }
+                vir::Expr::eq_cmp(
+                    vir::Expr::snap_app(encoded_return.clone()),
+                    self.encoder.encode_default_value(return_ty)?,
+                )
+            }
+            name if nonzero_integer_method(name) == Some("get") => {
+                // The wrapped integer of a `NonZero*` value is never zero.
+                let inner_ty = self.locals.get_type(contract.returned_value);
+                let inner_field = self.encoder.encode_struct_field("0", inner_ty)?;
+                let result = vir::Expr::snap_app(encoded_return.clone());
+                vir::Expr::and(
+                    vir::Expr::eq_cmp(
+                        result.clone(),
+                        vir::Expr::snap_app(encoded_args[0].clone().field(inner_field)),
+                    ),
+                    vir::Expr::gt_cmp(result, 0.into()),
+                )
+            }
+            name if nonzero_integer_method(name) == Some("new_unchecked") => {
+                let inner_ty = self.locals.get_type(contract.args[0]);
+                let inner_field = self.encoder.encode_struct_field("0", inner_ty)?;
+                vir::Expr::eq_cmp(
+                    vir::Expr::snap_app(encoded_return.clone().field(inner_field)),
+                    vir::Expr::snap_app(encoded_args[0].clone()),
+                )
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(assertion))
+    }
+
+    /// Whether the procedure implements `Default::default`.
+    fn is_default_method(&self, def_id: ProcedureDefId) -> bool {
+        let env = self.encoder.env();
+        let trait_method = env.find_trait_method(def_id).unwrap_or(def_id);
+        matches!(
+            env.tcx().def_path_str(trait_method).as_str(),
+            "std::default::Default::default" | "core::default::Default::default"
+        )
+    }
+
+    /// Whether the default value of the type is built without side effects,
+    /// that is, it is a primitive type or a struct that derives `Default` and
+    /// whose fields all have such a type.
+    fn has_derived_default(&self, ty: ty::Ty<'tcx>) -> bool {
+        let tcx = self.encoder.env().tcx();
+        match ty.kind() {
+            ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => true,
+            ty::TyKind::Tuple(elems) => elems.iter().all(|elem_ty| self.has_derived_default(elem_ty)),
+            ty::TyKind::Adt(adt_def, substs) if adt_def.is_struct() => {
+                let default_trait = match tcx.get_diagnostic_item(rustc_span::sym::Default) {
+                    Some(default_trait) => default_trait,
+                    None => return false,
+                };
+                let mut is_derived = false;
+                tcx.for_each_relevant_impl(default_trait, ty, |impl_def_id| {
+                    if tcx.has_attr(impl_def_id, rustc_span::sym::automatically_derived) {
+                        is_derived = true;
+                    }
+                });
+                is_derived && adt_def.all_fields().all(|field| {
+                    self.has_derived_default(field.ty(tcx, substs))
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// If the procedure implements the `?` operator for `Result`, the name of
+    /// the implemented method of the `Try` traits.
+    fn result_try_method(&self, def_id: ProcedureDefId) -> Option<&'static str> {
+        let env = self.encoder.env();
+        let tcx = env.tcx();
+        let trait_method = env.find_trait_method(def_id).unwrap_or(def_id);
+        let method = if Some(trait_method) == tcx.lang_items().branch_fn() {
+            "branch"
+        } else if Some(trait_method) == tcx.lang_items().from_residual_fn() {
+            "from_residual"
+        } else {
+            return None;
+        };
+        let impl_def_id = tcx.impl_of_method(def_id)?;
+        match tcx.type_of(impl_def_id).kind() {
+            ty::TyKind::Adt(adt_def, _) if matches!(
+                tcx.def_path_str(adt_def.did()).as_str(),
+                "std::result::Result" | "core::result::Result"
+            ) => Some(method),
+            _ => None,
+        }
+    }
+
+    /// Encode the guard `discriminant(place) == variant` of an enum, together
+    /// with the place and type of the first field of the variant (if any).
+    fn encode_enum_variant_guard(
+        &self,
+        place: vir::Expr,
+        ty: ty::Ty<'tcx>,
+        variant_name: &str,
+    ) -> EncodingResult<(vir::Expr, Option<(vir::Expr, ty::Ty<'tcx>)>)> {
+        let tcx = self.encoder.env().tcx();
+        let (adt_def, substs) = match ty.kind() {
+            ty::TyKind::Adt(adt_def, substs) if adt_def.is_enum() => (*adt_def, *substs),
+            _ => return Err(EncodingError::internal(format!("{:?} is not an enum", ty))),
+        };
+        let (variant_index, variant_def) = adt_def.variants()
+            .iter_enumerated()
+            .find(|(_, variant_def)| variant_def.ident(tcx).as_str() == variant_name)
+            .ok_or_else(|| EncodingError::internal(
+                format!("{:?} has no variant {}", ty, variant_name)
+            ))?;
+        let discr_value: vir::Expr = adt_def
+            .discriminant_for_variant(tcx, variant_index)
+            .val
+            .into();
+        let guard = vir::Expr::eq_cmp(
+            self.encoder.encode_discriminant_func_app(place.clone(), adt_def)?,
+            discr_value,
+        );
+        let field = match variant_def.fields.first() {
+            Some(field) => {
+                let field_ty = self.encoder.env().field_ty(field, substs);
+                let encoded_field = self.encoder
+                    .encode_struct_field(&field.ident(tcx).to_string(), field_ty)?;
+                Some((place.variant(variant_name).field(encoded_field), field_ty))
+            }
+            None => None,
+        };
+        Ok((guard, field))
+    }
+
+    /// Preconditions of library functions that are known to the verifier
+    /// without an external specification.
+    /// Encode the condition under which a call of a standard library function
+    /// whose effect is modelled by `encode_builtin_postcondition` does not
+    /// panic, with the error context of a violation:
+    ///
+    /// - `slice::swap(s, a, b)` panics if `a` or `b` is out of bounds.
+    fn encode_builtin_panic_check(
+        &self,
+        contract: &ProcedureContract<'tcx>,
+        encoded_args: &[vir::Expr],
+    ) -> EncodingResult<Option<(vir::Expr, ErrorCtxt)>> {
+        let full_func_proc_name = self.encoder.env().tcx().def_path_str(contract.def_id);
+        match full_func_proc_name.as_str() {
+            "core::slice::<impl [T]>::swap" => {
+                let arg_ty = self.locals.get_type(contract.args[0]);
+                let (slice, slice_ty, _) = self.mir_encoder
+                    .encode_deref(encoded_args[0].clone(), arg_ty)?;
+                let sequence_types = self.encoder.encode_sequence_types(slice_ty)?;
+                let len = sequence_types.len(self.encoder, slice);
+                let in_bounds = |index: &vir::Expr| vir::Expr::and(
+                    vir::Expr::le_cmp(0.into(), vir::Expr::snap_app(index.clone())),
+                    vir::Expr::lt_cmp(vir::Expr::snap_app(index.clone()), len.clone()),
+                );
+                Ok(Some((
+                    vir::Expr::and(in_bounds(&encoded_args[1]), in_bounds(&encoded_args[2])),
+                    ErrorCtxt::BoundsCheckAssert,
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn encode_builtin_precondition(
+        &self,
+        contract: &ProcedureContract<'tcx>,
+        encoded_args: &[vir::Expr],
+    ) -> Option<vir::Expr> {
+        let full_func_proc_name = self.encoder.env().tcx().def_path_str(contract.def_id);
+        match nonzero_integer_method(&full_func_proc_name) {
+            // Constructing a `NonZero*` value from zero is undefined behaviour.
+            Some("new_unchecked") => Some(vir::Expr::ne_cmp(
+                vir::Expr::snap_app(encoded_args[0].clone()),
+                0.into(),
+            )),
+            _ => None,
+        }
+    }
+
     /// Modelling move as simple assignment on Viper level has a consequence
     /// that the assigned place changes. Therefore, if some value is
     /// moved into a borrow, the borrow starts pointing to a different
@@ -4364,6 +5952,34 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             }),
         );
 
+        // Assert postconditions of the named cases
+        for (case_post, case_pos) in self.encode_case_postconditions(&contract)? {
+            let patched_case_post = self.replace_old_places_with_ghost_vars(None, case_post);
+            self.cfg_method.add_stmt(
+                return_cfg_block,
+                vir::Stmt::Assert( vir::Assert {
+                    expr: patched_case_post,
+                    position: case_pos,
+                }),
+            );
+        }
+
+        // Assert equivalence with the function of `#[prusti::equivalent_to]`
+        if let Some((equivalence, equivalence_pos)) = self.encode_equivalence_check(&contract)? {
+            self.cfg_method.add_stmt(
+                return_cfg_block,
+                vir::Stmt::comment("Assert equivalence with the equivalent function"),
+            );
+            let patched_equivalence = self.replace_old_places_with_ghost_vars(None, equivalence);
+            self.cfg_method.add_stmt(
+                return_cfg_block,
+                vir::Stmt::Assert( vir::Assert {
+                    expr: patched_equivalence,
+                    position: equivalence_pos,
+                }),
+            );
+        }
+
         // Assert type invariants
         self.cfg_method.add_stmt(
             return_cfg_block,
@@ -4728,6 +6344,81 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok((permissions, equalities, invs_spec))
     }
 
+    /// Record the call and the specification items used to encode it in the
+    /// call graph.
+    fn register_call_in_call_graph(
+        &self,
+        called_def_id: ProcedureDefId,
+        call_substs: SubstsRef<'tcx>,
+    ) {
+        let (called_def_id, call_substs) = self.encoder.env()
+            .resolve_method_call(self.proc_def_id, called_def_id, call_substs);
+        let mut specs = vec![];
+        if let Some(spec) = self.encoder.get_procedure_specs_for_call(
+            called_def_id,
+            self.proc_def_id,
+            call_substs,
+        ) {
+            for item in [&spec.pres, &spec.posts] {
+                if let Some(def_ids) = item.extract_with_selective_replacement() {
+                    specs.extend(def_ids.iter().map(|def_id| format!("{:?}", def_id.to_def_id())));
+                }
+            }
+        }
+        let tcx = self.encoder.env().tcx();
+        self.encoder.register_call(
+            tcx.def_path_str(self.proc_def_id),
+            tcx.def_path_str(called_def_id),
+            specs,
+        );
+    }
+
+    /// Read the `#[prusti::loop_options(...)]` attributes attached to the loops
+    /// of the procedure. The backend can only be configured per program, so the
+    /// options apply to the verification of the whole procedure.
+    fn encode_loop_options(&self) -> SpannedEncodingResult<()> {
+        let local_def_id = if let Some(local_def_id) = self.proc_def_id.as_local() {
+            local_def_id
+        } else {
+            return Ok(());
+        };
+        let loop_head_spans: Vec<Span> = self.cached_loop_invariant_block
+            .keys()
+            .map(|&loop_head| self.mir_encoder.get_span_of_basic_block(loop_head))
+            .collect();
+        let attributed_code = self.encoder.env().get_attributed_code(local_def_id, "loop_options");
+        for (span, attrs) in attributed_code {
+            if !loop_head_spans.iter().any(|&loop_head_span| span.contains(loop_head_span)) {
+                return Err(SpannedEncodingError::incorrect(
+                    "the `loop_options` attribute must be attached to a loop or to a block \
+                    containing a loop",
+                    span,
+                ));
+            }
+            let options = utils::read_prusti_attr_options("loop_options", attrs)
+                .unwrap()
+                .map_err(|message| SpannedEncodingError::incorrect(message, span))?;
+            for (key, value) in options {
+                match key.as_str() {
+                    "qi_limit" => {
+                        let qi_limit = value.parse().map_err(|_| SpannedEncodingError::incorrect(
+                            "the `qi_limit` loop option must be a non-negative integer",
+                            span,
+                        ))?;
+                        self.encoder.register_loop_qi_limit(self.proc_def_id, qi_limit);
+                    }
+                    _ => {
+                        return Err(SpannedEncodingError::incorrect(
+                            format!("unknown loop option `{}`", key),
+                            span,
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Get the basic blocks that encode the specification of a loop invariant
     fn get_loop_spec_blocks(&self, loop_head: BasicBlockIndex) -> Vec<BasicBlockIndex> {
         let mut res = vec![];
@@ -4748,6 +6439,21 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         res
     }
 
+    /// The counter of the completed iterations of the loop, which
+    /// `loop_iterations()` refers to in the specifications of the loop.
+    fn loop_iterations_var(&self, loop_head: BasicBlockIndex) -> vir::LocalVar {
+        vir::LocalVar::new(format!("loop{}$iterations", loop_head.index()), vir::Type::Int)
+    }
+
+    /// Replace `loop_iterations()` in a specification of the loop by the
+    /// counter of its completed iterations.
+    fn replace_loop_iterations(&self, loop_head: BasicBlockIndex, spec: vir::Expr) -> vir::Expr {
+        let placeholder = vir::Expr::local(
+            vir::LocalVar::new(LOOP_ITERATIONS_PLACEHOLDER, vir::Type::Int)
+        );
+        spec.replace_place(&placeholder, &self.loop_iterations_var(loop_head).into())
+    }
+
     /// Encode the functional specification of a loop
     fn encode_loop_invariant_specs(
         &self,
@@ -4771,14 +6477,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     _,
                     mir::Rvalue::Aggregate(box mir::AggregateKind::Closure(cl_def_id, cl_substs), _),
                 )) = stmt.kind {
-                    if let Some(spec) = self.encoder.get_loop_specs(cl_def_id) {
-                        encoded_specs.push(self.encoder.encode_invariant(
+                    if let Some(typed::LoopSpecification::Invariant(invariant)) =
+                        self.encoder.get_loop_specs(cl_def_id)
+                    {
+                        let encoded_spec = self.encoder.encode_invariant(
                             self.mir,
                             bbi,
                             self.proc_def_id,
                             cl_substs,
-                        )?);
-                        encoded_spec_spans.push(self.encoder.env().tcx().def_span(spec.invariant.to_def_id()));
+                        )?;
+                        encoded_specs.push(self.replace_loop_iterations(loop_head, encoded_spec));
+                        encoded_spec_spans.push(self.encoder.env().tcx().def_span(invariant.to_def_id()));
                     }
                 }
             }
@@ -4788,6 +6497,42 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok((encoded_specs, MultiSpan::from_spans(encoded_spec_spans)))
     }
 
+    /// Encode the `break_ensures!(..)` specifications of a loop, which have to
+    /// hold on every edge that leaves the loop.
+    fn encode_loop_break_specs(
+        &self,
+        loop_head: BasicBlockIndex,
+    ) -> SpannedEncodingResult<(Vec<vir::Expr>, MultiSpan)> {
+        let mut encoded_specs = vec![];
+        let mut encoded_spec_spans = vec![];
+        for bbi in self.get_loop_spec_blocks(loop_head) {
+            for stmt in &self.mir.basic_blocks()[bbi].statements {
+                if let mir::StatementKind::Assign(box (
+                    _,
+                    mir::Rvalue::Aggregate(box mir::AggregateKind::Closure(cl_def_id, cl_substs), _),
+                )) = stmt.kind {
+                    if let Some(typed::LoopSpecification::BreakEnsures(postcondition)) =
+                        self.encoder.get_loop_specs(cl_def_id)
+                    {
+                        let encoded_spec = self.encoder.encode_invariant(
+                            self.mir,
+                            bbi,
+                            self.proc_def_id,
+                            cl_substs,
+                        )?;
+                        encoded_specs.push(self.replace_loop_iterations(loop_head, encoded_spec));
+                        encoded_spec_spans.push(
+                            self.encoder.env().tcx().def_span(postcondition.to_def_id())
+                        );
+                    }
+                }
+            }
+        }
+        trace!("encoded break specs: {:?}", encoded_specs);
+
+        Ok((encoded_specs, MultiSpan::from_spans(encoded_spec_spans)))
+    }
+
     fn encode_loop_invariant_exhale_stmts(
         &mut self,
         loop_head: BasicBlockIndex,
@@ -5211,7 +6956,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 let (src, mut stmts, ty, _) = self.encode_place(place, ArrayAccessKind::Shared, location)?;
                 let encode_stmts = match ty.kind() {
                     ty::TyKind::RawPtr(..) => {
-                        return Err(SpannedEncodingError::unsupported(
+                        return Err(SpannedEncodingError::unsupported_feature(
+                            UnsupportedFeatureCategory::RawPointers,
                             "raw pointers are not supported",
                             span,
                         ));
@@ -5253,6 +6999,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     ty::TyKind::Tuple(elements) if elements.is_empty() => Vec::new(),
                     _ => {
                         let field = self.encoder.encode_value_field(ty).with_span(span)?;
+                        let is_fresh_target = !self.init_info.is_vir_place_accessible(&lhs, location);
                         let mut stmts = self.prepare_assign_target(
                             lhs.clone(),
                             field.clone(),
@@ -5261,7 +7008,23 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             true
                         )?;
                         // TODO Encoding of string literals is not yet supported,
-                        // so do not encode an assignment if the RHS is a string
+                        // so do not encode an assignment if the RHS is a string.
+                        // Only the length of a literal is known, if the target
+                        // has just been allocated for it.
+                        if let ty::TyKind::Ref(_, str_ty, _) = ty.kind() {
+                            match self.encoder.const_eval_str_len(val) {
+                                Some(len) if str_ty.is_str() && is_fresh_target => {
+                                    let encoded_len = self.encoder.encode_snapshot_str_len(
+                                        *str_ty,
+                                        vir::Expr::snap_app(lhs.clone().field(field.clone())),
+                                    ).with_span(span)?;
+                                    stmts.push(vir::Stmt::Inhale(vir::Inhale {
+                                        expr: vir::Expr::eq_cmp(encoded_len, len.into()),
+                                    }));
+                                }
+                                _ => {}
+                            }
+                        }
                         if !is_str(ty) {
                             // Initialize the constant
                             let const_val = self.encoder
@@ -5627,7 +7390,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             mir::BorrowKind::Shared =>
                 (vir::AssignKind::SharedBorrow(loan.index().into()), ArrayAccessKind::Shared),
             mir::BorrowKind::Unique => {
-                return Err(EncodingError::unsupported(
+                return Err(EncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::Closures,
                     "unsuported creation of unique borrows (implicitly created in closure bindings)"
                 )).with_span(span);
             }
@@ -6133,7 +7897,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 let tcx = self.encoder.env().tcx();
                 let adt_def = tcx.adt_def(adt_did);
                 if adt_def.is_union() {
-                    return Err(SpannedEncodingError::unsupported(
+                    return Err(SpannedEncodingError::unsupported_feature(
+                        UnsupportedFeatureCategory::Unions,
                         "unions are not supported",
                         span
                     ));
@@ -6189,7 +7954,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 for (field_index, field) in variant_def.fields.iter().enumerate() {
                     let operand = &operands[field_index];
                     let field_name = field.ident(tcx).to_string();
-                    let field_ty = field.ty(tcx, subst);
+                    let field_ty = self.encoder.env().field_ty(field, subst);
                     let encoded_field = self.encoder
                         .encode_struct_field(&field_name, field_ty)
                         .with_span(span)?;
@@ -6240,7 +8005,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             }
 
             mir::AggregateKind::Generator(..) => {
-                return Err(SpannedEncodingError::unsupported(
+                return Err(SpannedEncodingError::unsupported_feature(
+                    UnsupportedFeatureCategory::Async,
                     "construction of generators is not supported",
                     span
                 ));
@@ -6501,6 +8267,20 @@ fn mir_constantkind_to_ty_val(literal: mir::ConstantKind) -> (ty::Ty, ty::ConstK
     }
 }
 
+/// If `def_path` is a method of one of the `NonZero*` integer types, returns
+/// the name of the method.
+fn nonzero_integer_method(def_path: &str) -> Option<&str> {
+    let path = def_path
+        .strip_prefix("core::num::")
+        .or_else(|| def_path.strip_prefix("std::num::"))?;
+    let (type_name, method_name) = path.split_once("::")?;
+    if type_name.starts_with("NonZero") {
+        Some(method_name)
+    } else {
+        None
+    }
+}
+
 // Checks if a type is a reference to a string, or a reference to a reference to a string, etc.
 fn is_str(ty: ty::Ty<'_>) -> bool {
     match ty.kind() {
@@ -6514,4 +8294,18 @@ type PostconditionStrengthening = RefinementCheckExpr;
 struct RefinementCheckExpr {
     spec_functions_span: MultiSpan,
     refinement_check_expr: vir::Expr,
-}
\ No newline at end of file
+}
+
+/// The operation of an unchecked arithmetic method or intrinsic like
+/// `core::num::<impl u32>::unchecked_add`, given its path.
+fn unchecked_arithmetic_op(name: &str) -> Option<vir::BinaryOpKind> {
+    if !name.starts_with("core::num::<impl ") && !name.starts_with("core::intrinsics::") {
+        return None;
+    }
+    match name.rsplit("::").next() {
+        Some("unchecked_add") => Some(vir::BinaryOpKind::Add),
+        Some("unchecked_sub") => Some(vir::BinaryOpKind::Sub),
+        Some("unchecked_mul") => Some(vir::BinaryOpKind::Mul),
+        _ => None,
+    }
+}
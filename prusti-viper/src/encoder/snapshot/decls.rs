@@ -40,6 +40,9 @@ pub(super) enum Snapshot {
         cons: vir::DomainFunc,
         uncons: vir::DomainFunc,
         read: vir::DomainFunc,
+        /// The number of occurrences of an element in the sequence of the
+        /// snapshot (the multiset model).
+        count: vir::DomainFunc,
     },
     /// Slices
     Slice {
@@ -53,9 +56,20 @@ pub(super) enum Snapshot {
         /// result Seq[elem_ty]
         slice_helper: vir::FunctionIdentifier,
         cons: vir::DomainFunc,
-        _uncons: vir::DomainFunc,
+        uncons: vir::DomainFunc,
         read: vir::DomainFunc,
         len: vir::DomainFunc,
+        /// The number of occurrences of an element in the sequence of the
+        /// snapshot (the multiset model).
+        count: vir::DomainFunc,
+    },
+    /// String slices, of which only the length is modelled.
+    Str {
+        predicate_type: Type,
+        _domain: String,
+        _snap_func: vir::FunctionIdentifier,
+        /// The length of the string in bytes.
+        len: vir::DomainFunc,
     },
     /// Type cannot be encoded: type parameters, unsupported types.
     Abstract {
@@ -76,7 +90,8 @@ impl Snapshot {
             Self::Complex { predicate_type, .. }
             | Self::Abstract { predicate_type, .. }
             | Self::Array { predicate_type, .. }
-            | Self::Slice { predicate_type, .. } => {
+            | Self::Slice { predicate_type, .. }
+            | Self::Str { predicate_type, .. } => {
                 assert!(predicate_type.is_snapshot());
                 predicate_type.clone()
             }
@@ -93,7 +108,7 @@ impl Snapshot {
         use Snapshot::*;
         matches!(
             self,
-            Primitive(_) | Complex { .. } | Array { .. } | Slice { .. }
+            Primitive(_) | Complex { .. } | Array { .. } | Slice { .. } | Str { .. }
         )
     }
 }
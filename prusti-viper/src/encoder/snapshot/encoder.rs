@@ -103,6 +103,243 @@ fn forall_or_body(vars: Vec<vir::LocalVar>, triggers: Vec<vir::Trigger>, body: E
     }
 }
 
+/// Encodes the multiset model of a sequence of snapshots: a domain function
+/// `count(data, elem)` returning how often `elem` occurs in `data`, the helper
+/// function `count_from(data, idx, elem)` counting the occurrences from the
+/// index `idx` on, and the axioms defining them. The axioms about `count_from`
+/// unfold it once for every index of `data` that is read, so that they also
+/// apply to sequences that are not built by prepending elements.
+fn encode_multiset_count(
+    domain_name: &str,
+    elem_snap_ty: &Type,
+) -> (vir::DomainFunc, vir::DomainFunc, Vec<vir::DomainAxiom>) {
+    let seq_type = Type::Seq(vir::SeqType {
+        typ: box elem_snap_ty.clone(),
+    });
+    let count = vir::DomainFunc {
+        name: format!("count${}$", domain_name),
+        type_arguments: Vec::new(),
+        formal_args: vec![
+            vir_local! { data: {seq_type.clone()} },
+            vir_local! { elem: {elem_snap_ty.clone()} },
+        ],
+        return_type: Type::Int,
+        unique: false,
+        domain_name: domain_name.to_string(),
+    };
+
+    let data = vir_local! { data: {seq_type.clone()} };
+    let head = vir_local! { head: {elem_snap_ty.clone()} };
+    let elem = vir_local! { elem: {elem_snap_ty.clone()} };
+    let data_expr: Expr = data.clone().into();
+    let head_expr: Expr = head.clone().into();
+    let elem_expr: Expr = elem.clone().into();
+
+    // forall elem :: count(Seq(), elem) == 0
+    let count_empty = {
+        let empty_seq = Expr::Seq(vir::Seq {
+            typ: seq_type.clone(),
+            elements: vec![],
+            position: vir::Position::default(),
+        });
+        let count_call = count.apply(vec![empty_seq, elem_expr.clone()]);
+        vir::DomainAxiom {
+            name: format!("{}$count_empty", domain_name),
+            expr: Expr::forall(
+                vec![elem.clone()],
+                vec![vir::Trigger::new(vec![count_call.clone()])],
+                vir_expr! { [count_call] == [Expr::from(0)] },
+            ),
+            domain_name: domain_name.to_string(),
+        }
+    };
+
+    // forall head, data, elem :: count(Seq(head) ++ data, elem) ==
+    //     count(data, elem) + (head == elem ? 1 : 0)
+    let count_prepend = {
+        let prepended = Expr::ContainerOp(vir::ContainerOp {
+            op_kind: ContainerOpKind::SeqConcat,
+            left: box Expr::Seq(vir::Seq {
+                typ: seq_type.clone(),
+                elements: vec![head_expr.clone()],
+                position: vir::Position::default(),
+            }),
+            right: box data_expr.clone(),
+            position: vir::Position::default(),
+        });
+        let count_call = count.apply(vec![prepended, elem_expr.clone()]);
+        let count_rest = count.apply(vec![data_expr.clone(), elem_expr.clone()]);
+        let head_count = Expr::ite(
+            vir_expr! { [head_expr] == [elem_expr] },
+            Expr::from(1),
+            Expr::from(0),
+        );
+        vir::DomainAxiom {
+            name: format!("{}$count_prepend", domain_name),
+            expr: Expr::forall(
+                vec![head.clone(), data.clone(), elem.clone()],
+                vec![vir::Trigger::new(vec![count_call.clone()])],
+                vir_expr! { [count_call] == ([count_rest] + [head_count]) },
+            ),
+            domain_name: domain_name.to_string(),
+        }
+    };
+
+    // forall data, elem :: count(data, elem) >= 0
+    let count_positive = {
+        let count_call = count.apply(vec![data_expr.clone(), elem_expr.clone()]);
+        vir::DomainAxiom {
+            name: format!("{}$count_positive", domain_name),
+            expr: Expr::forall(
+                vec![data.clone(), elem.clone()],
+                vec![vir::Trigger::new(vec![count_call.clone()])],
+                vir_expr! { [count_call] >= [Expr::from(0)] },
+            ),
+            domain_name: domain_name.to_string(),
+        }
+    };
+
+    let count_from = vir::DomainFunc {
+        name: format!("count_from${}$", domain_name),
+        type_arguments: Vec::new(),
+        formal_args: vec![
+            vir_local! { data: {seq_type.clone()} },
+            vir_local! { idx: Int },
+            vir_local! { elem: {elem_snap_ty.clone()} },
+        ],
+        return_type: Type::Int,
+        unique: false,
+        domain_name: domain_name.to_string(),
+    };
+    let idx = vir_local! { idx: Int };
+    let idx_expr: Expr = idx.clone().into();
+    let seq_len = Expr::ContainerOp(vir::ContainerOp {
+        op_kind: ContainerOpKind::SeqLen,
+        left: box data_expr.clone(),
+        right: box true.into(), // unused
+        position: vir::Position::default(),
+    });
+
+    // forall elem :: count(Seq(head), elem) == (head == elem ? 1 : 0)
+    let count_singleton = {
+        let singleton = Expr::Seq(vir::Seq {
+            typ: seq_type,
+            elements: vec![head_expr.clone()],
+            position: vir::Position::default(),
+        });
+        let count_call = count.apply(vec![singleton, elem_expr.clone()]);
+        let head_count = Expr::ite(
+            vir_expr! { [head_expr] == [elem_expr] },
+            Expr::from(1),
+            Expr::from(0),
+        );
+        vir::DomainAxiom {
+            name: format!("{}$count_singleton", domain_name),
+            expr: Expr::forall(
+                vec![head.clone(), elem.clone()],
+                vec![vir::Trigger::new(vec![count_call.clone()])],
+                vir_expr! { [count_call] == [head_count] },
+            ),
+            domain_name: domain_name.to_string(),
+        }
+    };
+
+    // forall data, elem :: count(data, elem) == count_from(data, 0, elem)
+    let count_from_start = {
+        let count_call = count.apply(vec![data_expr.clone(), elem_expr.clone()]);
+        let count_from_call = count_from.apply(vec![
+            data_expr.clone(),
+            Expr::from(0),
+            elem_expr.clone(),
+        ]);
+        vir::DomainAxiom {
+            name: format!("{}$count_from_start", domain_name),
+            expr: Expr::forall(
+                vec![data.clone(), elem.clone()],
+                vec![vir::Trigger::new(vec![count_call.clone()])],
+                vir_expr! { [count_call] == [count_from_call] },
+            ),
+            domain_name: domain_name.to_string(),
+        }
+    };
+
+    // forall data, idx, elem :: 0 <= idx && idx < |data| ==>
+    //     count_from(data, idx, elem) ==
+    //     count_from(data, idx + 1, elem) + (data[idx] == elem ? 1 : 0)
+    // The trigger requires `data[idx]`, so that the unfolding stops at the
+    // indices that are read.
+    let count_from_index = {
+        let lookup = Expr::ContainerOp(vir::ContainerOp {
+            op_kind: ContainerOpKind::SeqIndex,
+            left: box data_expr.clone(),
+            right: box idx_expr.clone(),
+            position: vir::Position::default(),
+        });
+        let count_from_call = count_from.apply(vec![
+            data_expr.clone(),
+            idx_expr.clone(),
+            elem_expr.clone(),
+        ]);
+        let count_from_next = count_from.apply(vec![
+            data_expr.clone(),
+            vir_expr! { [idx_expr] + [Expr::from(1)] },
+            elem_expr.clone(),
+        ]);
+        let lookup_count = Expr::ite(
+            vir_expr! { [lookup] == [elem_expr] },
+            Expr::from(1),
+            Expr::from(0),
+        );
+        vir::DomainAxiom {
+            name: format!("{}$count_from_index", domain_name),
+            expr: Expr::forall(
+                vec![data.clone(), idx.clone(), elem.clone()],
+                vec![vir::Trigger::new(vec![count_from_call.clone(), lookup])],
+                Expr::implies(
+                    vir_expr! { ([Expr::from(0)] <= [idx_expr]) && ([idx_expr] < [seq_len]) },
+                    vir_expr! { [count_from_call] == ([count_from_next] + [lookup_count]) },
+                ),
+            ),
+            domain_name: domain_name.to_string(),
+        }
+    };
+
+    // forall data, idx, elem :: idx >= |data| ==> count_from(data, idx, elem) == 0
+    let count_from_end = {
+        let count_from_call = count_from.apply(vec![
+            data_expr,
+            idx_expr.clone(),
+            elem_expr,
+        ]);
+        vir::DomainAxiom {
+            name: format!("{}$count_from_end", domain_name),
+            expr: Expr::forall(
+                vec![data, idx, elem],
+                vec![vir::Trigger::new(vec![count_from_call.clone()])],
+                Expr::implies(
+                    vir_expr! { [idx_expr] >= [seq_len] },
+                    vir_expr! { [count_from_call] == [Expr::from(0)] },
+                ),
+            ),
+            domain_name: domain_name.to_string(),
+        }
+    };
+
+    (
+        count,
+        count_from,
+        vec![
+            count_empty,
+            count_prepend,
+            count_positive,
+            count_singleton,
+            count_from_start,
+            count_from_index,
+            count_from_end,
+        ],
+    )
+}
+
 impl SnapshotEncoder {
     pub fn get_domain(&self, name: &str) -> Option<&vir::Domain> {
         self.domains.get(name)
@@ -465,9 +702,8 @@ impl SnapshotEncoder {
             Snapshot::Complex { .. } => {
                 unimplemented!();
             }
-            Snapshot::Array { uncons, .. } => Ok(uncons.apply(args)),
-            Snapshot::Slice { .. } => {
-                unimplemented!()
+            Snapshot::Array { uncons, .. } | Snapshot::Slice { uncons, .. } => {
+                Ok(uncons.apply(args))
             }
             _ => Err(EncodingError::internal(format!(
                 "invalid constructor (not Complex): {}",
@@ -537,6 +773,70 @@ impl SnapshotEncoder {
         Ok(len_func.apply(vec![slice]))
     }
 
+    pub fn encode_str_len<'p, 'v: 'p, 'tcx: 'v>(
+        &mut self,
+        encoder: &'p Encoder<'v, 'tcx>,
+        str_ty: ty::Ty<'tcx>,
+        string: Expr,
+    ) -> EncodingResult<Expr> {
+        let len_func =
+            if let Snapshot::Str { len, .. } = self.encode_snapshot(encoder, str_ty)? {
+                len
+            } else {
+                return Err(EncodingError::internal(format!(
+                    "called encode_str_len on non-str-type {:?}",
+                    str_ty
+                )));
+            };
+
+        Ok(len_func.apply(vec![string]))
+    }
+
+    /// Encode `is_permutation(a, b)` for arrays and slices: both sequences
+    /// have the same length and every element occurs equally often in them.
+    pub fn encode_permutation<'p, 'v: 'p, 'tcx: 'v>(
+        &mut self,
+        encoder: &'p Encoder<'v, 'tcx>,
+        ty: ty::Ty<'tcx>,
+        a: Expr,
+        b: Expr,
+    ) -> EncodingResult<Expr> {
+        let (uncons, count) = match self.encode_snapshot(encoder, ty)? {
+            Snapshot::Array { uncons, count, .. } | Snapshot::Slice { uncons, count, .. } => {
+                (uncons, count)
+            }
+            _ => {
+                return Err(EncodingError::unsupported(format!(
+                    "is_permutation is only supported for arrays and slices, not {:?}",
+                    ty
+                )))
+            }
+        };
+        let elem = count.formal_args[1].clone();
+        let a_data = uncons.apply(vec![a]);
+        let b_data = uncons.apply(vec![b]);
+        let seq_len = |data: &Expr| {
+            Expr::ContainerOp(vir::ContainerOp {
+                op_kind: ContainerOpKind::SeqLen,
+                left: box data.clone(),
+                right: box true.into(), // unused
+                position: vir::Position::default(),
+            })
+        };
+        let same_len = vir_expr! { [seq_len(&a_data)] == [seq_len(&b_data)] };
+        let a_count = count.apply(vec![a_data, elem.clone().into()]);
+        let b_count = count.apply(vec![b_data, elem.clone().into()]);
+        let same_counts = Expr::forall(
+            vec![elem],
+            vec![
+                vir::Trigger::new(vec![a_count.clone()]),
+                vir::Trigger::new(vec![b_count.clone()]),
+            ],
+            vir_expr! { [a_count] == [b_count] },
+        );
+        Ok(vir_expr! { [same_len] && [same_counts] })
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn encode_slicing<'p, 'v: 'p, 'tcx: 'v>(
         &mut self,
@@ -730,7 +1030,7 @@ impl SnapshotEncoder {
                 let mut fields = vec![];
                 for field in adt_def.all_fields() {
                     // or adt_def.variants[0].fields ?
-                    let field_ty = field.ty(tcx, substs);
+                    let field_ty = encoder.env().field_ty(field, substs);
                     fields.push(SnapshotField {
                         name: encode_field_name(&field.ident(tcx).to_string()),
                         access: self.snap_app(
@@ -778,7 +1078,7 @@ impl SnapshotEncoder {
                         }
                     };
                     for field in &variant.fields {
-                        let field_ty = field.ty(tcx, substs);
+                        let field_ty = encoder.env().field_ty(field, substs);
                         fields.push(SnapshotField {
                             name: encode_field_name(&field.ident(tcx).to_string()),
                             access: self.snap_app(
@@ -824,6 +1124,8 @@ impl SnapshotEncoder {
                 let seq_type = Type::Seq(vir::SeqType {
                     typ: box elem_snap_ty.clone(),
                 });
+                let (count, count_from, count_axioms) =
+                    encode_multiset_count(&domain_name, &elem_snap_ty);
 
                 let cons = vir::DomainFunc {
                     name: format!("cons${}$", domain_name),
@@ -1000,6 +1302,10 @@ impl SnapshotEncoder {
                     type_vars: vec![],
                 };
 
+                domain.functions.push(count.clone());
+                domain.functions.push(count_from);
+                domain.axioms.extend(count_axioms);
+
                 // encode type validity axiom for array element
                 // TODO: encode type invariants rather than just integer bounds
                 match elem_ty.kind() {
@@ -1038,6 +1344,7 @@ impl SnapshotEncoder {
                     cons,
                     uncons,
                     read,
+                    count,
                 })
             }
 
@@ -1049,6 +1356,8 @@ impl SnapshotEncoder {
                 let seq_type = Type::Seq(vir::SeqType {
                     typ: box elem_snap_ty.clone(),
                 });
+                let (count, count_from, count_axioms) =
+                    encode_multiset_count(&domain_name, &elem_snap_ty);
 
                 let cons = vir::DomainFunc {
                     name: format!("cons${}$", domain_name),
@@ -1304,6 +1613,10 @@ impl SnapshotEncoder {
                     type_vars: vec![],
                 };
 
+                domain.functions.push(count.clone());
+                domain.functions.push(count_from);
+                domain.axioms.extend(count_axioms);
+
                 // encode type validity axiom for slice element
                 // TODO: encode type invariants rather than just integer bounds
                 match elem_ty.kind() {
@@ -1340,12 +1653,15 @@ impl SnapshotEncoder {
                     _slice_collect_func: self.insert_function(slice_collect_func),
                     slice_helper: self.insert_function(slice_helper),
                     cons,
-                    _uncons: uncons,
+                    uncons,
                     read,
                     len,
+                    count,
                 })
             }
 
+            ty::TyKind::Str => self.encode_str(predicate_type),
+
             // Param(_) and unsupported types
             _ => self.encode_abstract(predicate_type),
         }
@@ -1620,6 +1936,61 @@ impl SnapshotEncoder {
         })
     }
 
+    /// Encodes the snapshot of `str`. The contents of strings are not modelled,
+    /// only their length, so that specifications can refer to `s.len()`.
+    fn encode_str(&mut self, predicate_type: &Type) -> EncodingResult<Snapshot> {
+        let domain_name = format!("Snap${}", predicate_type.name());
+        let snapshot_type = predicate_type.convert_to_snapshot();
+
+        let arg_ref_local = vir::LocalVar::new("self", predicate_type.clone());
+        let arg_ref_expr = Expr::local(arg_ref_local.clone());
+
+        // encode snap function
+        let snap_func = vir::Function {
+            name: SNAP_FUNC_NAME.to_string(),
+            type_arguments: vec![snapshot_type.clone()],
+            formal_args: vec![arg_ref_local],
+            return_type: snapshot_type.clone(),
+            pres: vec![Expr::predicate_access_predicate(
+                predicate_type.clone(),
+                arg_ref_expr,
+                PermAmount::Read,
+            )],
+            posts: vec![],
+            body: None,
+        };
+
+        let len = vir::DomainFunc {
+            name: format!("len${}$", domain_name),
+            type_arguments: Vec::new(),
+            formal_args: vec![vir_local! { self: {snapshot_type.clone()} }],
+            return_type: Type::Int,
+            unique: false,
+            domain_name: domain_name.clone(),
+        };
+        let len_bounds = {
+            let len_call = len.apply(vec![vir_local! { string: {snapshot_type.clone()} }.into()]);
+            vir::DomainAxiom {
+                name: format!("{}$len_bounds", predicate_type.name()),
+                expr: vir_expr! { forall string: {snapshot_type} :: { [len_call] }
+                    (([len_call] >= [Expr::from(0)]) && ([len_call] <= [Expr::from(isize::MAX)])) },
+                domain_name: domain_name.clone(),
+            }
+        };
+
+        Ok(Snapshot::Str {
+            predicate_type: predicate_type.convert_to_snapshot(),
+            _domain: self.insert_domain(vir::Domain {
+                name: domain_name,
+                functions: vec![len.clone()],
+                axioms: vec![len_bounds],
+                type_vars: vec![],
+            }),
+            _snap_func: self.insert_function(snap_func),
+            len,
+        })
+    }
+
     /// Encodes the snapshot for a complex data structure (tuple, struct, enum, or closure).
     /// The returned snapshot will be of the [Snapshot::Complex] variant.
     fn encode_complex<'p, 'v: 'p, 'tcx: 'v>(
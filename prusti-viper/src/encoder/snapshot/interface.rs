@@ -63,6 +63,11 @@ pub(crate) trait SnapshotEncoderInterface<'tcx> {
         ty: ty::Ty<'tcx>,
         slice: vir_poly::Expr,
     ) -> EncodingResult<vir_poly::Expr>;
+    fn encode_snapshot_str_len(
+        &self,
+        ty: ty::Ty<'tcx>,
+        string: vir_poly::Expr,
+    ) -> EncodingResult<vir_poly::Expr>;
     fn encode_snapshot_slicing(
         &self,
         base_ty: ty::Ty<'tcx>,
@@ -71,6 +76,12 @@ pub(crate) trait SnapshotEncoderInterface<'tcx> {
         lo: vir_poly::Expr,
         hi: vir_poly::Expr,
     ) -> EncodingResult<vir_poly::Expr>;
+    fn encode_snapshot_permutation(
+        &self,
+        ty: ty::Ty<'tcx>,
+        a: vir_poly::Expr,
+        b: vir_poly::Expr,
+    ) -> EncodingResult<vir_poly::Expr>;
     fn supports_snapshot_equality(&self, ty: ty::Ty<'tcx>) -> EncodingResult<bool>;
     fn is_quantifiable(&self, ty: ty::Ty<'tcx>) -> EncodingResult<bool>;
 }
@@ -221,6 +232,29 @@ impl<'v, 'tcx: 'v> SnapshotEncoderInterface<'tcx> for super::super::Encoder<'v,
             .encode_slice_len(self, ty, slice)
     }
 
+    fn encode_snapshot_str_len(
+        &self,
+        ty: ty::Ty<'tcx>,
+        string: vir_poly::Expr,
+    ) -> EncodingResult<vir_poly::Expr> {
+        self.snapshot_encoder_state
+            .encoder
+            .borrow_mut()
+            .encode_str_len(self, ty, string)
+    }
+
+    fn encode_snapshot_permutation(
+        &self,
+        ty: ty::Ty<'tcx>,
+        a: vir_poly::Expr,
+        b: vir_poly::Expr,
+    ) -> EncodingResult<vir_poly::Expr> {
+        self.snapshot_encoder_state
+            .encoder
+            .borrow_mut()
+            .encode_permutation(self, ty, a, b)
+    }
+
     fn encode_snapshot_slicing(
         &self,
         base_ty: ty::Ty<'tcx>,
@@ -0,0 +1,270 @@
+// © 2022, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Encoding of the termination check of a pure function with a
+//! `#[terminates(...)]` measure. At every call to a function of the same
+//! termination group, which is the group of mutually recursive functions
+//! declared by `#[terminates(..., group = "name")]`, the measure of the called
+//! function must be lexicographically smaller than the measure of the caller,
+//! and not negative.
+
+use super::{
+    errors::{ErrorCtxt, SpannedEncodingError, SpannedEncodingResult, WithSpan},
+    mir::{
+        pure::{PureFunctionEncoderInterface, SpecificationEncoderInterface},
+        specifications::SpecificationsInterface,
+    },
+    snapshot::interface::SnapshotEncoderInterface,
+    Encoder,
+};
+use prusti_interface::{data::ProcedureDefId, utils::read_prusti_attr};
+use rustc_hash::FxHashMap;
+use rustc_hir::def_id::LocalDefId;
+use vir_crate::polymorphic::{self as vir, ExprIterator, ExprWalker};
+
+/// The calls to the functions of the termination group in an expression, with
+/// the condition under which they are evaluated.
+struct GroupCallCollector<'a> {
+    functions: &'a FxHashMap<String, ProcedureDefId>,
+    path_condition: Vec<vir::Expr>,
+    quantifier_depth: usize,
+    calls: Vec<(vir::Expr, ProcedureDefId, Vec<vir::Expr>, vir::Position)>,
+    has_quantified_calls: bool,
+}
+
+impl<'a> GroupCallCollector<'a> {
+    fn walk_guarded(&mut self, condition: vir::Expr, expr: &vir::Expr) {
+        self.path_condition.push(condition);
+        ExprWalker::walk(self, expr);
+        self.path_condition.pop();
+    }
+}
+
+impl<'a> ExprWalker for GroupCallCollector<'a> {
+    fn walk_cond(&mut self, vir::Cond { guard, then_expr, else_expr, .. }: &vir::Cond) {
+        ExprWalker::walk(self, guard);
+        self.walk_guarded((**guard).clone(), then_expr);
+        self.walk_guarded(vir::Expr::not((**guard).clone()), else_expr);
+    }
+
+    fn walk_bin_op(&mut self, vir::BinOp { op_kind, left, right, .. }: &vir::BinOp) {
+        ExprWalker::walk(self, left);
+        match op_kind {
+            vir::BinaryOpKind::And | vir::BinaryOpKind::Implies => {
+                self.walk_guarded((**left).clone(), right)
+            }
+            vir::BinaryOpKind::Or => self.walk_guarded(vir::Expr::not((**left).clone()), right),
+            _ => ExprWalker::walk(self, right),
+        }
+    }
+
+    fn walk_let_expr(&mut self, vir::LetExpr { variable, def, body, .. }: &vir::LetExpr) {
+        ExprWalker::walk(self, def);
+        let body = (**body).clone().replace_place(&vir::Expr::local(variable.clone()), def);
+        ExprWalker::walk(self, &body);
+    }
+
+    fn walk_forall(&mut self, vir::ForAll { body, .. }: &vir::ForAll) {
+        self.quantifier_depth += 1;
+        ExprWalker::walk(self, body);
+        self.quantifier_depth -= 1;
+    }
+
+    fn walk_exists(&mut self, vir::Exists { body, .. }: &vir::Exists) {
+        self.quantifier_depth += 1;
+        ExprWalker::walk(self, body);
+        self.quantifier_depth -= 1;
+    }
+
+    fn walk_func_app(&mut self, vir::FuncApp { function_name, arguments, position, .. }: &vir::FuncApp) {
+        for arg in arguments {
+            ExprWalker::walk(self, arg);
+        }
+        if let Some(&called_def_id) = self.functions.get(function_name) {
+            if self.quantifier_depth > 0 {
+                self.has_quantified_calls = true;
+            } else {
+                self.calls.push((
+                    self.path_condition.iter().cloned().conjoin(),
+                    called_def_id,
+                    arguments.clone(),
+                    *position,
+                ));
+            }
+        }
+    }
+}
+
+fn get_measures(encoder: &Encoder, def_id: ProcedureDefId) -> Vec<LocalDefId> {
+    let substs = encoder.env().identity_substs(def_id);
+    encoder
+        .get_procedure_specs(def_id, substs)
+        .and_then(|spec| spec.measures.extract_with_selective_replacement().cloned())
+        .unwrap_or_default()
+}
+
+/// `callee <_lex caller`, where every component of `callee` up to the first
+/// one that decreases must not be negative.
+fn lexicographically_decreases(callee: &[vir::Expr], caller: &[vir::Expr]) -> vir::Expr {
+    callee
+        .iter()
+        .zip(caller)
+        .rev()
+        .fold(None, |rest, (callee_component, caller_component)| {
+            let decreases = vir::Expr::and(
+                vir::Expr::le_cmp(vir::Expr::from(0), callee_component.clone()),
+                vir::Expr::lt_cmp(callee_component.clone(), caller_component.clone()),
+            );
+            Some(match rest {
+                None => decreases,
+                Some(rest) => vir::Expr::or(
+                    decreases,
+                    vir::Expr::and(
+                        vir::Expr::eq_cmp(callee_component.clone(), caller_component.clone()),
+                        rest,
+                    ),
+                ),
+            })
+        })
+        .unwrap()
+}
+
+/// Encode the termination check of the pure function `def_id`. Returns `None`
+/// if the function has no termination measure.
+pub(super) fn encode_termination_check<'v, 'tcx: 'v>(
+    encoder: &Encoder<'v, 'tcx>,
+    def_id: ProcedureDefId,
+) -> SpannedEncodingResult<Option<vir::Program>> {
+    let tcx = encoder.env().tcx();
+    let span = encoder.env().get_def_span(def_id);
+    let substs = encoder.env().identity_substs(def_id);
+    let measures = get_measures(encoder, def_id);
+    if measures.is_empty() {
+        return Ok(None);
+    }
+    if !encoder.is_pure(def_id, Some(substs)) {
+        return Err(SpannedEncodingError::incorrect(
+            "`terminates` can only be used on pure functions",
+            span,
+        ));
+    }
+    if encoder.is_trusted(def_id, Some(substs)) {
+        return Ok(None);
+    }
+
+    let group = read_prusti_attr("termination_group", tcx.get_attrs(def_id));
+    let members: Vec<ProcedureDefId> = match &group {
+        Some(group) => tcx
+            .hir()
+            .body_owners()
+            .map(LocalDefId::to_def_id)
+            .filter(|&other| {
+                read_prusti_attr("termination_group", tcx.get_attrs(other)).as_ref() == Some(group)
+            })
+            .collect(),
+        None => vec![def_id],
+    };
+    let mut functions = FxHashMap::default();
+    for &member in &members {
+        if get_measures(encoder, member).len() != measures.len() {
+            return Err(SpannedEncodingError::incorrect(
+                format!(
+                    "the measures of the functions of the termination group `{}` must have the \
+                    same number of components",
+                    group.as_deref().unwrap_or_default(),
+                ),
+                span,
+            ));
+        }
+        let member_substs = encoder.env().identity_substs(member);
+        if encoder.is_pure(member, Some(member_substs)) {
+            let (function_name, _) = encoder.encode_pure_function_use(member, def_id, member_substs)?;
+            functions.insert(function_name, member);
+        }
+    }
+
+    let spec_funcs = encoder.encode_spec_funcs(def_id)?;
+    let pre_func = match spec_funcs.first() {
+        Some(pre) => encoder.get_function(pre)?,
+        None => return Ok(None),
+    };
+    let encoded_args: Vec<vir::Expr> = pre_func
+        .formal_args
+        .iter()
+        .cloned()
+        .map(vir::Expr::local)
+        .collect();
+    let encode_measures = |measured_def_id: ProcedureDefId, args: &[vir::Expr]| {
+        let measured_substs = encoder.env().identity_substs(measured_def_id);
+        get_measures(encoder, measured_def_id)
+            .iter()
+            .map(|measure| {
+                encoder.encode_assertion(measure, None, args, None, true, def_id, measured_substs)
+            })
+            .collect::<SpannedEncodingResult<Vec<_>>>()
+    };
+    let caller_measures = encode_measures(def_id, &encoded_args)?;
+
+    let body = encoder.encode_pure_expression(def_id, def_id, substs)?;
+    let body = encoder.patch_snapshots(body).with_span(span)?;
+    let mut collector = GroupCallCollector {
+        functions: &functions,
+        path_condition: vec![],
+        quantifier_depth: 0,
+        calls: vec![],
+        has_quantified_calls: false,
+    };
+    ExprWalker::walk(&mut collector, &body);
+    if collector.has_quantified_calls {
+        return Err(SpannedEncodingError::unsupported(
+            "recursive calls in quantifiers are not supported by the termination check",
+            span,
+        ));
+    }
+
+    let name = format!("{}$termination_check", encoder.encode_item_name(def_id));
+    let mut method = vir::CfgMethod::new(name.clone(), 0, vec![], vec![], vec![]);
+    for arg in &pre_func.formal_args {
+        method.add_local_var(&arg.name, arg.typ.clone());
+    }
+    let mut stmts = vec![
+        vir::Stmt::comment("Check that the termination measure decreases at every recursive call"),
+        vir::Stmt::Inhale(vir::Inhale {
+            expr: vir::Expr::func_app(
+                pre_func.name.clone(),
+                pre_func.type_arguments.clone(),
+                encoded_args.clone(),
+                pre_func.formal_args.clone(),
+                pre_func.return_type.clone(),
+                vir::Position::default(),
+            ),
+        }),
+    ];
+    for (path_condition, called_def_id, args, call_position) in collector.calls {
+        let callee_measures = encode_measures(called_def_id, &args)?;
+        let pos = if call_position.is_default() {
+            encoder.error_manager().register_error(
+                span,
+                ErrorCtxt::AssertTerminationMeasure,
+                def_id,
+            )
+        } else {
+            let pos = encoder.error_manager().duplicate_position(call_position);
+            encoder.error_manager().set_error(pos, ErrorCtxt::AssertTerminationMeasure);
+            pos
+        };
+        stmts.push(vir::Stmt::Assert(vir::Assert {
+            expr: vir::Expr::implies(
+                path_condition,
+                lexicographically_decreases(&callee_measures, &caller_measures),
+            ),
+            position: pos,
+        }));
+    }
+    let block = method.add_block("start", stmts);
+    method.set_successor(block, vir::Successor::Return);
+    super::definition_collector::collect_definitions(span, encoder, name, vec![method]).map(Some)
+}
@@ -4,6 +4,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use rustc_hir::def_id::DefId;
 use rustc_middle::ty;
 
 
@@ -43,4 +44,39 @@ pub fn ty_to_string(typ: &ty::TyKind) -> String {
 
 pub fn is_reference(base_ty: ty::Ty) -> bool {
     matches!(base_ty.kind(), ty::TyKind::RawPtr(..) | ty::TyKind::Ref(..))
-}
\ No newline at end of file
+}
+
+/// Whether the type is an array, a slice or a string, which are indexed with
+/// the sequence model instead of their `Index` implementation.
+pub fn is_sequence_ty(ty: ty::Ty) -> bool {
+    matches!(ty.kind(), ty::TyKind::Array(..) | ty::TyKind::Slice(..) | ty::TyKind::Str)
+}
+
+/// If the type is a pinned mutable reference `Pin<&mut T>`, returns the type
+/// `&mut T` of the wrapped reference.
+pub fn pinned_reference_ty<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> Option<ty::Ty<'tcx>> {
+    match ty.kind() {
+        ty::TyKind::Adt(adt_def, substs) if Some(adt_def.did()) == tcx.lang_items().pin_type() => {
+            let pointer_ty = substs.type_at(0);
+            if matches!(pointer_ty.kind(), ty::TyKind::Ref(_, _, rustc_hir::Mutability::Mut)) {
+                Some(pointer_ty)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether the function is one of the unsafe methods of `Pin` that give
+/// unpinned access to the pointee (e.g. `Pin::get_unchecked_mut`), through
+/// which the pointee can be moved even if it is not `Unpin`.
+pub fn is_unpinning_method(tcx: ty::TyCtxt, def_id: DefId) -> bool {
+    let is_pin_method = tcx.impl_of_method(def_id)
+        .and_then(|impl_def_id| tcx.type_of(impl_def_id).ty_adt_def())
+        .map_or(false, |adt_def| Some(adt_def.did()) == tcx.lang_items().pin_type());
+    is_pin_method && matches!(
+        tcx.item_name(def_id).as_str(),
+        "get_unchecked_mut" | "into_inner_unchecked" | "map_unchecked_mut"
+    )
+}
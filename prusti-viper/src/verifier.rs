@@ -4,9 +4,22 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use prusti_common::vir::{optimizations::optimize_program};
+use prusti_common::vir::{
+    optimizations::optimize_program,
+    polymorphic_vir,
+    precondition_conjuncts::drop_precondition_conjunct,
+    obligation_dependencies::{format_dependencies_json, obligation_dependencies},
+    obligation_profiling::{format_profile_csv, isolate_obligation, obligation_positions},
+};
 use prusti_common::{
-    config, report::log, Stopwatch, vir::program::Program,
+    config,
+    report::{
+        junit::{format_junit_xml, TestCase, TestOutcome},
+        log,
+        lsp::{self, format_publish_diagnostics, DiagnosticSeverity},
+    },
+    Stopwatch,
+    vir::program::Program,
 };
 use crate::encoder::Encoder;
 use crate::encoder::counterexample_translation;
@@ -14,17 +27,26 @@ use crate::encoder::counterexample_translation;
 use prusti_interface::data::VerificationResult;
 use prusti_interface::data::VerificationTask;
 use prusti_interface::environment::Environment;
+use prusti_interface::data::ProcedureDefId;
 use prusti_interface::PrustiError;
 // use prusti_interface::specifications::TypedSpecificationMap;
 
-use viper::{self, PersistentCache, Viper};
+use viper::{self, PersistentCache, Viper, VerificationBackend};
 
 
 
 use prusti_interface::specs::typed;
 use ::log::{info, debug, error};
-use prusti_server::{VerificationRequest, PrustiClient, process_verification_request, spawn_server_thread};
-use rustc_span::DUMMY_SP;
+use prusti_server::{
+    VerificationRequest, ViperBackendConfig, PrustiClient, SavedVerificationRequest, NoCache,
+    process_verification_request_with_time, spawn_server_thread,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_errors::MultiSpan;
+use rustc_span::{Span, DUMMY_SP};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 use prusti_server::tokio::runtime::Builder;
 
 // /// A verifier builder is an object that lives entire program's
@@ -160,6 +182,14 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             task.procedures.len()
         );
 
+        let invalid_settings = config::check_settings();
+        for message in &invalid_settings {
+            PrustiError::incorrect(message, DUMMY_SP.into()).emit(self.env);
+        }
+        if !invalid_settings.is_empty() {
+            return VerificationResult::Failure;
+        }
+
         let mut stopwatch = Stopwatch::start("prusti-viper", "encoding to Viper");
 
         // Dump the configuration
@@ -246,22 +276,82 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         }
         self.encoder.process_encoding_queue();
 
+        if config::dump_call_graph() {
+            log::report(
+                "call_graph",
+                format!("{}.dot", self.env.crate_name()),
+                self.encoder.get_call_graph().to_dot(),
+            );
+        }
+
         let encoding_errors_count = self.encoder.count_encoding_errors();
 
+        if config::report_unsupported_features() {
+            self.report_unsupported_features();
+            stopwatch.finish();
+            return if encoding_errors_count == 0 {
+                VerificationResult::Success
+            } else {
+                VerificationResult::Failure
+            };
+        }
+
         let polymorphic_programs = self.encoder.get_viper_programs();
+        let trusted_consistency_checks = self.encoder.get_trusted_consistency_checks();
 
-        let mut programs: Vec<Program> = if config::simplify_encoding() {
-            stopwatch.start_next("optimizing Viper program");
-            let source_file_name = self.encoder.env().source_file_name();
-            polymorphic_programs.into_iter().map(
-                |program| Program::Legacy(optimize_program(program, &source_file_name).into())
-            ).collect()
+        // Keep the programs whose preconditions are checked after verification.
+        let precondition_programs: Vec<_> = if config::report_unused_preconditions() {
+            polymorphic_programs.iter().filter(|program| {
+                self.encoder.get_program_precondition_spans(&program.name).is_some()
+            }).cloned().collect()
+        } else {
+            Vec::new()
+        };
+
+        if config::dump_obligation_dependencies() {
+            self.dump_obligation_dependencies(&polymorphic_programs);
+        }
+
+        // Keep the programs whose assertions are profiled after verification.
+        let profiled_programs = if config::profile_backend() {
+            polymorphic_programs.clone()
         } else {
-            polymorphic_programs.into_iter().map(
-                |program| Program::Legacy(program.into())
-            ).collect()
+            Vec::new()
+        };
+
+        if config::simplify_encoding() {
+            stopwatch.start_next("optimizing Viper program");
+        }
+        let source_file_name = self.encoder.env().source_file_name();
+        let polymorphic_programs: Vec<_> = polymorphic_programs.into_iter().map(|program| {
+            let backend_config = self.get_backend_config(&program.name);
+            (program, backend_config)
+        }).collect();
+        let (polymorphic_programs, batches) = match config::verification_batching().as_str() {
+            "none" => (polymorphic_programs, FxHashMap::default()),
+            "by_backend" => self.batch_programs(polymorphic_programs),
+            strategy => {
+                PrustiError::incorrect(
+                    format!("unknown verification batching strategy '{}'", strategy),
+                    DUMMY_SP.into(),
+                ).set_help(
+                    "the allowed values of VERIFICATION_BATCHING are 'none' and 'by_backend'"
+                ).emit(self.env);
+                stopwatch.finish();
+                return VerificationResult::Failure;
+            }
         };
-        programs.extend(self.encoder.get_core_proof_programs());
+        let mut programs: Vec<_> = polymorphic_programs.into_iter().map(|(program, backend_config)| {
+            (into_legacy_program(program, &source_file_name), backend_config)
+        }).collect();
+        programs.extend(self.encoder.get_core_proof_programs().into_iter().map(|program| {
+            let backend_config = self.get_backend_config(program.get_name());
+            (program, backend_config)
+        }));
+
+        if let Some(dir) = config::save_verification_requests() {
+            self.save_verification_requests(&dir, &programs);
+        }
 
         stopwatch.start_next("verifying Viper program");
         let verification_results = verify_programs(self.env, programs);
@@ -271,18 +361,36 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         let mut verification_errors : Vec<_> = vec![];
         let mut consistency_errors : Vec<_> = vec![];
         let mut java_exceptions : Vec<_> = vec![];
+        let mut verified_programs = FxHashSet::default();
         for (method_name, result) in verification_results.into_iter() {
+            let program_names = batches.get(&method_name)
+                .cloned()
+                .unwrap_or_else(|| vec![method_name.clone()]);
             match result {
-                viper::VerificationResult::Success => {}
+                viper::VerificationResult::Success => {
+                    verified_programs.extend(program_names);
+                }
                 viper::VerificationResult::ConsistencyErrors(errors) => {
                     for error in errors.into_iter() {
                         consistency_errors.push((method_name.clone(), error));
                     }
                 }
                 viper::VerificationResult::Failure(errors) => {
+                    // The other programs of a batch are verified.
+                    let mut failed_programs = FxHashSet::default();
                     for error in errors.into_iter() {
-                        verification_errors.push((method_name.clone(), error));
+                        let error_programs = self.programs_of_error(&program_names, &error);
+                        let program_name = if error_programs.len() == 1 {
+                            error_programs[0].clone()
+                        } else {
+                            method_name.clone()
+                        };
+                        failed_programs.extend(error_programs);
+                        verification_errors.push((program_name, error));
                     }
+                    verified_programs.extend(
+                        program_names.into_iter().filter(|name| !failed_programs.contains(name))
+                    );
                 }
                 viper::VerificationResult::JavaException(exception) => {
                     java_exceptions.push((method_name, exception));
@@ -311,9 +419,33 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
 
         // Report verification errors
         let mut prusti_errors: Vec<_> = vec![];
+        let mut procedure_errors: FxHashMap<ProcedureDefId, Vec<(String, MultiSpan)>> = FxHashMap::default();
+        let mut lsp_diagnostics = vec![];
         for (method, verification_error) in verification_errors.into_iter() {
             debug!("Verification error in {}: {:?}", method, verification_error);
             let mut prusti_error = error_manager.translate_verification_error(&verification_error);
+            if config::junit_report().is_some() && !prusti_error.is_disabled() {
+                if let Some(def_id) = error_manager.get_def_id(&verification_error) {
+                    procedure_errors.entry(def_id).or_default().push((
+                        prusti_error.message().to_string(),
+                        prusti_error.span().clone(),
+                    ));
+                }
+            }
+            if config::lsp_diagnostics().is_some() && !prusti_error.is_disabled() {
+                let (code, mut severity) = error_manager.get_error_ctxt(&verification_error)
+                    .map(|error_ctxt| error_ctxt.diagnostic_category())
+                    .unwrap_or(("internal", DiagnosticSeverity::Error));
+                if !prusti_error.is_error() {
+                    severity = DiagnosticSeverity::Warning;
+                }
+                lsp_diagnostics.extend(self.lsp_diagnostic(
+                    prusti_error.span(),
+                    severity,
+                    code,
+                    prusti_error.message(),
+                ));
+            }
 
             // annotate with counterexample, if requested
             if config::counterexample() {
@@ -355,15 +487,451 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             result = VerificationResult::Failure;
         }
 
+        // The profiling needs the error manager to map positions to the source.
+        drop(error_manager);
+
+        if !precondition_programs.is_empty() {
+            self.report_unused_preconditions(precondition_programs, &verified_programs);
+        }
+
+        if !trusted_consistency_checks.is_empty() {
+            self.report_inconsistent_trusted_postconditions(trusted_consistency_checks);
+        }
+
+        if !profiled_programs.is_empty() {
+            self.profile_backend(profiled_programs, &verified_programs);
+        }
+
+        if let Some(path) = config::junit_report() {
+            self.write_junit_report(&path, task, procedure_errors);
+        }
+
+        if let Some(path) = config::lsp_diagnostics() {
+            self.write_lsp_diagnostics(&path, task, lsp_diagnostics);
+        }
+
         result
     }
+
+    /// Write the outcome of each procedure of the task as a JUnit XML report
+    /// to `path`. The errors of a procedure are its encoding errors and the
+    /// verification errors reported at its positions.
+    fn write_junit_report(
+        &self,
+        path: &str,
+        task: &VerificationTask,
+        mut procedure_errors: FxHashMap<ProcedureDefId, Vec<(String, MultiSpan)>>,
+    ) {
+        let test_cases: Vec<_> = task.procedures.iter().map(|&proc_id| {
+            let mut errors = self.encoder.get_procedure_encoding_errors(proc_id);
+            errors.extend(procedure_errors.remove(&proc_id).unwrap_or_default());
+            let outcome = if let Some(reason) = self.encoder.get_unverified_reason(proc_id) {
+                TestOutcome::Skipped(reason.to_string())
+            } else if errors.is_empty() {
+                TestOutcome::Success
+            } else {
+                TestOutcome::Failure(errors.into_iter().map(|(message, span)| {
+                    (message, self.span_location(&span))
+                }).collect())
+            };
+            TestCase {
+                name: self.env.get_absolute_item_name(proc_id),
+                outcome,
+            }
+        }).collect();
+        let xml = format_junit_xml(&self.env.crate_name(), &test_cases);
+        if let Err(error) = std::fs::write(path, xml) {
+            error!("Could not write the JUnit report to {}: {}", path, error);
+        }
+    }
+
+    /// Write the verification errors and the encoding errors of the
+    /// procedures of the task to `path` as LSP `publishDiagnostics`
+    /// parameters.
+    fn write_lsp_diagnostics(
+        &self,
+        path: &str,
+        task: &VerificationTask,
+        mut diagnostics: Vec<(String, lsp::Diagnostic)>,
+    ) {
+        for &proc_id in &task.procedures {
+            for (message, span) in self.encoder.get_procedure_encoding_errors(proc_id) {
+                diagnostics.extend(
+                    self.lsp_diagnostic(&span, DiagnosticSeverity::Error, "encoding", &message)
+                );
+            }
+        }
+        let json = format_publish_diagnostics(diagnostics);
+        if let Err(error) = std::fs::write(path, json) {
+            error!("Could not write the LSP diagnostics to {}: {}", path, error);
+        }
+    }
+
+    /// The URI of the file of the primary span and the LSP diagnostic at
+    /// that span. Returns `None` if the error has no span.
+    fn lsp_diagnostic(
+        &self,
+        span: &MultiSpan,
+        severity: DiagnosticSeverity,
+        code: &str,
+        message: &str,
+    ) -> Option<(String, lsp::Diagnostic)> {
+        let span = span.primary_span()?.source_callsite();
+        let codemap = self.env.codemap();
+        let position = |pos| {
+            let loc = codemap.lookup_char_pos(pos);
+            let column = (pos - loc.file.line_begin_pos(pos)).to_usize();
+            let line_text = loc.file.get_line(loc.line - 1).unwrap_or_default();
+            lsp::Position::new(loc.line, &line_text, column)
+        };
+        let range = lsp::Range {
+            start: position(span.lo()),
+            end: position(span.hi()),
+        };
+        let file = codemap.lookup_source_file(span.lo());
+        Some((
+            lsp::file_uri(&file.name.prefer_local().to_string()),
+            lsp::Diagnostic::new(range, severity, code, message),
+        ))
+    }
+
+    /// The source location `file:line:column` of the primary span.
+    fn span_location(&self, span: &MultiSpan) -> String {
+        span.primary_span().map(|span| {
+            let loc = self.env.codemap().lookup_char_pos(span.source_callsite().lo());
+            format!("{}:{}:{}", loc.file.name.prefer_local(), loc.line, loc.col.0 + 1)
+        }).unwrap_or_default()
+    }
+
+    /// Verify every successfully verified program again without each of its
+    /// preconditions and warn about the preconditions that are not needed.
+    fn report_unused_preconditions(
+        &self,
+        programs: Vec<polymorphic_vir::Program>,
+        verified_programs: &FxHashSet<String>,
+    ) {
+        let stopwatch = Stopwatch::start("prusti-viper", "checking unused preconditions");
+        let source_file_name = self.encoder.env().source_file_name();
+        let mut precondition_spans = vec![];
+        let mut variants = vec![];
+        for program in programs {
+            if !verified_programs.contains(&program.name) {
+                continue;
+            }
+            let spans = self.encoder.get_program_precondition_spans(&program.name)
+                .unwrap_or_default();
+            let backend_config = self.get_backend_config(&program.name);
+            for (index, &span) in spans.iter().enumerate() {
+                let mut variant = drop_precondition_conjunct(program.clone(), index);
+                variant.name = format!("{}$without_precondition_{}", program.name, index);
+                variants.push((
+                    into_legacy_program(variant, &source_file_name),
+                    backend_config.clone(),
+                ));
+                precondition_spans.push(span);
+            }
+        }
+        let results = verify_programs(self.env, variants);
+        stopwatch.finish();
+        for (span, (_, result)) in precondition_spans.into_iter().zip(results) {
+            if matches!(result, viper::VerificationResult::Success) {
+                let mut warning = PrustiError::incorrect(
+                    "this precondition is not needed to verify the function",
+                    span.into(),
+                ).set_help("the precondition might be wrong, or it can be removed");
+                warning.set_warning();
+                warning.emit(self.env);
+            }
+        }
+    }
+
+    /// Measure the backend time spent on each assertion and exhale of the
+    /// successfully verified programs and report it as CSV. Each of them is
+    /// verified in a variant of its program in which the other ones are
+    /// assumed.
+    fn profile_backend(
+        &self,
+        programs: Vec<polymorphic_vir::Program>,
+        verified_programs: &FxHashSet<String>,
+    ) {
+        let stopwatch = Stopwatch::start("prusti-viper", "profiling the backend");
+        let source_file_name = self.encoder.env().source_file_name();
+        let mut positions = vec![];
+        let mut variants = vec![];
+        for program in programs {
+            if !verified_programs.contains(&program.name) {
+                continue;
+            }
+            let backend_config = self.get_backend_config(&program.name);
+            // The baseline, in which all assertions and exhales are assumed.
+            let mut baseline = isolate_obligation(program.clone(), None);
+            baseline.name = format!("{}$profile_baseline", program.name);
+            variants.push((into_legacy_program(baseline, &source_file_name), backend_config.clone()));
+            positions.push(None);
+            for (index, position) in obligation_positions(&program).into_iter().enumerate() {
+                let mut variant = isolate_obligation(program.clone(), Some(position));
+                variant.name = format!("{}$profile_{}", program.name, index);
+                variants.push((into_legacy_program(variant, &source_file_name), backend_config.clone()));
+                positions.push(Some(position));
+            }
+        }
+        // Cached results would be reported as taking no time.
+        let results = verify_programs_with_times(self.env, variants, false);
+        let mut rows = vec![];
+        let mut baseline_time = Duration::ZERO;
+        for (position, (_, _, time)) in positions.into_iter().zip(results) {
+            if let Some(position) = position {
+                let location = self.encoder.error_manager()
+                    .position_manager()
+                    .get_source_location(position)
+                    .unwrap_or_else(|| format!("position {}", position.id()));
+                rows.push((location, time.saturating_sub(baseline_time)));
+            } else {
+                baseline_time = time;
+            }
+        }
+        stopwatch.finish();
+        log::report(
+            "backend_profile",
+            format!("{}.csv", self.env.crate_name()),
+            format_profile_csv(rows),
+        );
+    }
+
+    /// Write the dependencies between the obligations of each method as JSON
+    /// to the log directory.
+    fn dump_obligation_dependencies(&self, programs: &[polymorphic_vir::Program]) {
+        let methods = programs.iter()
+            .flat_map(|program| program.methods.iter())
+            .map(|method| (method.name(), obligation_dependencies(method)))
+            .collect();
+        let error_manager = self.encoder.error_manager();
+        let json = format_dependencies_json(methods, |position| {
+            error_manager
+                .position_manager()
+                .get_source_location(position)
+                .unwrap_or_default()
+        });
+        log::report(
+            "obligation_dependencies",
+            format!("{}.json", self.env.crate_name()),
+            json,
+        );
+    }
+
+    /// Save the verification request of each program to `dir`, together with
+    /// the description of its positions, so that it can be replayed with
+    /// `prusti-server-driver --replay`.
+    fn save_verification_requests(&self, dir: &str, programs: &[(Program, ViperBackendConfig)]) {
+        let rust_program_name = self.env.source_file_name();
+        let positions = self.encoder.error_manager().describe_positions();
+        let diagnostics: Vec<_> = self.encoder.error_manager().error_positions().into_iter()
+            .filter_map(|(pos_id, span, error_ctxt)| {
+                let (code, severity) = error_ctxt.diagnostic_category();
+                let (uri, diagnostic) = self.lsp_diagnostic(span, severity, code, "")?;
+                Some((pos_id, uri, diagnostic))
+            })
+            .collect();
+        let configuration = config::serialize_settings();
+        for (program, backend_config) in programs {
+            let mut program = program.clone();
+            // Name the program as in `verify_programs_with_times`.
+            let program_name = format!("{}_{}", rust_program_name, program.get_name());
+            program.set_name(program_name.clone());
+            let saved = SavedVerificationRequest {
+                request: VerificationRequest {
+                    program,
+                    backend_config: backend_config.clone(),
+                },
+                positions: positions.clone(),
+                diagnostics: diagnostics.clone(),
+                configuration: configuration.clone(),
+            };
+            let path = PathBuf::from(dir).join(format!("{}.request", program_name));
+            if let Err(error) = saved.save(&path) {
+                error!("Could not save the verification request to {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    /// Summarize the usages of unsupported features by category, and write
+    /// the list of usages as CSV to the log directory.
+    fn report_unsupported_features(&self) {
+        let features = self.encoder.get_unsupported_features();
+        let mut csv = String::from("category,location,message\n");
+        let mut counts: Vec<(String, usize)> = vec![];
+        for (category, span, message) in &features {
+            let location = self.span_location(span);
+            csv.push_str(&format!(
+                "\"{}\",\"{}\",\"{}\"\n",
+                category,
+                location,
+                message.replace('"', "\"\""),
+            ));
+            let category = category.to_string();
+            match counts.last_mut() {
+                Some((last, count)) if *last == category => *count += 1,
+                _ => counts.push((category, 1)),
+            }
+        }
+        log::report(
+            "unsupported_features",
+            format!("{}.csv", self.env.crate_name()),
+            csv,
+        );
+        let summary = counts.iter()
+            .map(|(category, count)| format!("{} ({})", category, count))
+            .collect::<Vec<_>>();
+        let mut warning = PrustiError::unsupported(
+            if features.is_empty() {
+                "found no usages of unsupported features".to_string()
+            } else {
+                format!(
+                    "found {} usages of unsupported features: {}",
+                    features.len(),
+                    summary.join(", "),
+                )
+            },
+            DUMMY_SP.into(),
+        );
+        warning.set_warning();
+        warning.emit(self.env);
+    }
+
+    /// Merge the programs that use the same backend configuration, so that
+    /// each group is verified with a single backend query. The verification
+    /// errors are still mapped to the individual obligations by their
+    /// positions. Returns the programs to verify and the names of the programs
+    /// contained in each batch.
+    #[allow(clippy::type_complexity)]
+    fn batch_programs(
+        &self,
+        programs: Vec<(polymorphic_vir::Program, ViperBackendConfig)>,
+    ) -> (
+        Vec<(polymorphic_vir::Program, ViperBackendConfig)>,
+        FxHashMap<String, Vec<String>>,
+    ) {
+        let mut groups: Vec<(ViperBackendConfig, Vec<polymorphic_vir::Program>)> = vec![];
+        for (program, backend_config) in programs {
+            if let Some((_, group)) = groups.iter_mut().find(|(config, _)| config == &backend_config) {
+                group.push(program);
+            } else {
+                groups.push((backend_config, vec![program]));
+            }
+        }
+        let mut batched_programs = vec![];
+        let mut batches = FxHashMap::default();
+        for (index, (backend_config, group)) in groups.into_iter().enumerate() {
+            if group.len() == 1 {
+                batched_programs.extend(group.into_iter().map(|program| (program, backend_config.clone())));
+                continue;
+            }
+            let name = format!("batch${}", index);
+            let program_names: Vec<_> = group.iter().map(|program| program.name.clone()).collect();
+            match self.encoder.merge_viper_programs(name.clone(), group.clone()) {
+                Ok(program) => {
+                    debug!("Batched programs {:?} into {}", program_names, name);
+                    batched_programs.push((program, backend_config));
+                    batches.insert(name, program_names);
+                }
+                Err(error) => {
+                    // Fall back to verifying the programs separately.
+                    debug!("Could not batch programs {:?}: {:?}", program_names, error);
+                    batched_programs.extend(group.into_iter().map(|program| (program, backend_config.clone())));
+                }
+            }
+        }
+        (batched_programs, batches)
+    }
+
+    /// The programs among `program_names`, e.g. the ones of a batch, that
+    /// contain the failing obligation of the error, i.e. the ones generated
+    /// by the procedure of its position. All of them if the error cannot be
+    /// attributed to any of them, e.g. if it is in a pure function.
+    fn programs_of_error(
+        &self,
+        program_names: &[String],
+        error: &viper::VerificationError,
+    ) -> Vec<String> {
+        let def_id = self.encoder.error_manager().get_def_id(error);
+        let error_programs: Vec<String> = program_names.iter()
+            .filter(|name| def_id.is_some() && self.encoder.get_program_procedure(name) == def_id)
+            .cloned()
+            .collect();
+        if error_programs.is_empty() {
+            program_names.to_vec()
+        } else {
+            error_programs
+        }
+    }
+
+    /// The backend configuration of the given program, using the backend and
+    /// the quantifier instantiation limit selected in the source code, if any.
+    fn get_backend_config(&self, program_name: &str) -> ViperBackendConfig {
+        let backend = self.encoder.get_program_backend(program_name)
+            .unwrap_or_else(|| VerificationBackend::from_str(&config::viper_backend()).unwrap());
+        let qi_limit = self.encoder.get_program_qi_limit(program_name)
+            .or_else(config::qi_limit);
+        ViperBackendConfig::with_qi_limit(backend, qi_limit)
+    }
+
+    /// Warn about the trusted functions whose consistency check verifies, i.e.
+    /// whose postcondition implies `false`.
+    fn report_inconsistent_trusted_postconditions(
+        &self,
+        checks: Vec<(polymorphic_vir::Program, Span)>,
+    ) {
+        let stopwatch = Stopwatch::start("prusti-viper", "checking trusted postconditions");
+        let source_file_name = self.encoder.env().source_file_name();
+        let (programs, spans): (Vec<_>, Vec<_>) = checks.into_iter().map(|(program, span)| {
+            ((into_legacy_program(program, &source_file_name), ViperBackendConfig::default()), span)
+        }).unzip();
+        let results = verify_programs(self.env, programs);
+        stopwatch.finish();
+        for (span, (_, result)) in spans.into_iter().zip(results) {
+            if matches!(result, viper::VerificationResult::Success) {
+                let mut warning = PrustiError::incorrect(
+                    "the postcondition of this trusted function is unsatisfiable",
+                    span.into(),
+                ).set_help("every call to this function is assumed to be unreachable");
+                warning.set_warning();
+                warning.emit(self.env);
+            }
+        }
+    }
 }
 
-/// Verify a list of programs.
+/// Convert an encoded program to the legacy VIR that is sent to the
+/// verifier, optimizing it if `SIMPLIFY_ENCODING` is enabled.
+fn into_legacy_program(program: polymorphic_vir::Program, source_file_name: &str) -> Program {
+    if config::simplify_encoding() {
+        Program::Legacy(optimize_program(program, source_file_name).into())
+    } else {
+        Program::Legacy(program.into())
+    }
+}
+
+/// Verify a list of programs, each with its backend configuration.
 /// Returns a list of (program_name, verification_result) tuples.
-fn verify_programs(env: &Environment, programs: Vec<Program>)
+fn verify_programs(env: &Environment, programs: Vec<(Program, ViperBackendConfig)>)
     -> Vec<(String, viper::VerificationResult)>
 {
+    verify_programs_with_times(env, programs, true)
+        .into_iter()
+        .map(|(program_name, result, _)| (program_name, result))
+        .collect()
+}
+
+/// Verify a list of programs, each with its backend configuration, using the
+/// cache only if `use_cache` is set.
+/// Returns a list of (program_name, verification_result, backend_time) tuples.
+/// Without a server, the backend time is the one spent by the backend on the
+/// program; with a server, it is the time until the server responded.
+fn verify_programs_with_times(
+    env: &Environment,
+    programs: Vec<(Program, ViperBackendConfig)>,
+    use_cache: bool,
+) -> Vec<(String, viper::VerificationResult, Duration)> {
     let source_path = env.source_path();
     let rust_program_name = source_path
         .file_name()
@@ -371,13 +939,13 @@ fn verify_programs(env: &Environment, programs: Vec<Program>)
         .to_str()
         .unwrap()
         .to_owned();
-    let verification_requests = programs.into_iter().map(|mut program| {
+    let verification_requests = programs.into_iter().map(|(mut program, backend_config)| {
         let program_name = program.get_name().to_string();
         // Prepend the Rust file name to the program.
         program.set_name(format!("{}_{}", rust_program_name, program_name));
         let request = VerificationRequest {
             program,
-            backend_config: Default::default(),
+            backend_config,
         };
         (program_name, request)
     });
@@ -404,7 +972,9 @@ fn verify_programs(env: &Environment, programs: Vec<Program>)
             .build()
             .expect("failed to construct Tokio runtime");
         verification_requests.map(|(program_name, request)| {
+            let start = Instant::now();
             let remote_result = runtime.block_on(client.verify(request));
+            let time = start.elapsed();
             let result = remote_result.unwrap_or_else(|error| {
                 panic!(
                     "Verification request of program {} failed: {:?}",
@@ -412,7 +982,7 @@ fn verify_programs(env: &Environment, programs: Vec<Program>)
                     error
                 )
             });
-            (program_name, result)
+            (program_name, result, time)
         }).collect()
     } else {
         let mut stopwatch = Stopwatch::start("prusti-viper", "JVM startup");
@@ -422,8 +992,12 @@ fn verify_programs(env: &Environment, programs: Vec<Program>)
         stopwatch.finish();
         let mut cache = PersistentCache::load_cache(config::cache_path());
         verification_requests.map(|(program_name, request)| {
-            let result = process_verification_request(&viper_thread, request, &mut cache);
-            (program_name, result)
+            let (result, time) = if use_cache {
+                process_verification_request_with_time(&viper_thread, request, &mut cache)
+            } else {
+                process_verification_request_with_time(&viper_thread, request, NoCache)
+            };
+            (program_name, result, time)
         }).collect()
     }
 }
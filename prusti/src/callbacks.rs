@@ -1,5 +1,5 @@
 use crate::verifier::verify;
-use prusti_common::config;
+use prusti_common::{config, report::user};
 use prusti_interface::{
     environment::{mir_storage, Environment},
     specs,
@@ -112,7 +112,10 @@ impl rustc_driver::Callbacks for PrustiCompilerCalls {
                     println!("{}", value);
                 }
             }
-            if !config::no_verify() {
+            if config::spec_lint() {
+                compiler.session().abort_if_errors();
+                user::message("The specifications are well-formed.");
+            } else if !config::no_verify() {
                 verify(env, def_spec);
             }
         });
@@ -38,7 +38,7 @@ use arg_value::arg_value;
 use callbacks::PrustiCompilerCalls;
 use lazy_static::lazy_static;
 use log::{info, warn};
-use prusti_common::{config, report::user, Stopwatch};
+use prusti_common::{config, format_duration, report::user, Stopwatch};
 use rustc_interface::interface::try_print_query_stack;
 use std::{borrow::Cow, env, panic};
 
@@ -233,9 +233,9 @@ fn main() {
         // Check that we met the deadline.
         assert!(
             duration < std::time::Duration::from_secs(deadline),
-            "Prusti failed to finish within {} seconds. It finished in {:?}.",
+            "Prusti failed to finish within {} seconds. It finished in {}.",
             deadline,
-            duration,
+            format_duration(duration),
         );
     }
     std::process::exit(exit_code)